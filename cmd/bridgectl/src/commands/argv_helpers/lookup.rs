@@ -0,0 +1,224 @@
+//! Looking up the IP of a single bridge from the common bridge arguments.
+
+use crate::{
+	commands::argv_helpers::{coalesce_bridge_arguments, get_default_bridge},
+	knobs::env::{BRIDGE_CURRENT_IP_ADDRESS, BRIDGE_CURRENT_NAME},
+	utils::add_context_to,
+};
+use cat_dev::{
+	errors::CatBridgeError,
+	mion::discovery::{find_mion, MIONFindBy},
+};
+use miette::miette;
+use std::{net::Ipv4Addr, path::PathBuf, time::Duration};
+use tracing::{error, field::valuable};
+
+/// The bits that differ between each command when looking up a bridge.
+pub struct BridgeLookup {
+	/// The name of the command used in log ids, e.g. `snapshot_parameters`.
+	pub id: &'static str,
+	/// The name of the command as typed on the command line.
+	pub command: &'static str,
+	/// The exit code to use when no bridge was specified.
+	pub no_filters_exit_code: i32,
+	/// The exit code to use when we could not find the bridge.
+	pub no_bridge_exit_code: i32,
+}
+
+/// Get the IP of the one bridge a command should talk to, exiting with the
+/// exit codes in `lookup` if we can't.
+///
+/// `did_specify_cli_arg` should be true if the command was passed a
+/// positional argument after the bridge, so a lone bridge argument is not
+/// treated as something else.
+#[allow(
+	// This is barely over, and I don't think it's worth it to lower the count.
+	clippy::too_many_lines,
+	// Every command that looks up a bridge takes all of these.
+	clippy::too_many_arguments,
+)]
+pub async fn get_a_bridge_ip(
+	use_json: bool,
+	lookup: &BridgeLookup,
+	just_fetch_default: bool,
+	bridge_flag_arguments: (Option<Ipv4Addr>, Option<String>, Option<String>),
+	bridge_argument: Option<String>,
+	did_specify_cli_arg: bool,
+	find_by_args: (Duration, u16),
+	host_state_path: Option<PathBuf>,
+) -> Ipv4Addr {
+	if let Some((filter_ip, filter_mac, filter_name)) = coalesce_bridge_arguments(
+		use_json,
+		just_fetch_default,
+		bridge_flag_arguments,
+		bridge_argument,
+		did_specify_cli_arg,
+	) {
+		if filter_ip.is_none() && filter_mac.is_none() && filter_name.is_none() {
+			if let Some(ip_address) = *BRIDGE_CURRENT_IP_ADDRESS {
+				return ip_address;
+			} else if let Some(name) = BRIDGE_CURRENT_NAME.as_deref() {
+				return find_bridge_ip_by_name(use_json, lookup, name, find_by_args, false).await;
+			} else if use_json {
+				error!(
+					id = format!("bridgectl::{}::no_bridge_filters", lookup.id),
+					help = "You didn't specify any bridge to use!",
+				);
+			} else {
+				error!(
+					"\n{:?}",
+					miette!(
+						help = format!(
+							"See `bridgectl {} --help` for more information!",
+							lookup.command
+						),
+						"You didn't specify any bridge to use!",
+					),
+				);
+			}
+			std::process::exit(lookup.no_filters_exit_code);
+		}
+
+		if let Some(ip) = filter_ip {
+			return ip;
+		}
+
+		match find_mion(
+			if let Some(mac) = filter_mac {
+				MIONFindBy::MacAddress(mac)
+			} else {
+				MIONFindBy::Name(filter_name.clone().unwrap_or_default())
+			},
+			false,
+			Some(find_by_args.0),
+			Some(find_by_args.1),
+		)
+		.await
+		{
+			Ok(Some(identity)) => identity.ip_address(),
+			Ok(None) => {
+				if use_json {
+					error!(
+					  id = format!("bridgectl::{}::failed_to_find_a_device", lookup.id),
+					  filter.ip = ?filter_ip,
+					  filter.mac = ?filter_mac,
+					  filter.name = ?filter_name,
+					  suggestions = valuable(&[
+						  "Please ensure the CAT-DEV you're trying to find is powered on, and running.",
+						  "Make sure you are on the same Local Network, Subnet, and VLAN as the CAT-DEV device.",
+						  "If you're not on the same VLAN, Subnet you can use something like: <https://github.com/udp-redux/udp-broadcast-relay-redux> to forward between the subnets & vlans.",
+						  "Ensure your filters line up with a single CAT-DEV device.",
+					  ]),
+					);
+				} else {
+					error!(
+						"\n{:?}",
+						add_context_to(
+							miette!("Failed to find bridge that matched the series of filters."),
+							[
+								miette!("Please ensure the CAT-DEV you're trying to find is powered on, and running."),
+								miette!("Make sure you are on the same Local Network, Subnet, and VLAN as the CAT-DEV device."),
+								miette!("If you're not on the same VLAN, Subnet you can use something like: <https://github.com/udp-redux/udp-broadcast-relay-redux> to forward between the subnets & vlans."),
+								miette!(
+									help = format!("Current Filter State: Bridge Filter IP: {filter_ip:?} / Bridge Filter Mac: {filter_mac:?} / Bridge Filter Name: {filter_name:?}"),
+									"Ensure your filters line up with a single CAT-DEV device.",
+								),
+							].into_iter(),
+						),
+					);
+				}
+				std::process::exit(lookup.no_bridge_exit_code);
+			}
+			Err(cause) => {
+				broadcast_error(use_json, lookup, cause);
+				std::process::exit(lookup.no_bridge_exit_code);
+			}
+		}
+	} else {
+		let (default_bridge_name, opt_ip) = get_default_bridge(use_json, host_state_path).await;
+		if let Some(ip) = opt_ip {
+			ip
+		} else {
+			find_bridge_ip_by_name(use_json, lookup, &default_bridge_name, find_by_args, true).await
+		}
+	}
+}
+
+/// Find the IP of a bridge by name, which is either the default bridge
+/// (`is_default`), or the one from `cafe`/`cafex`/`mochiato`.
+async fn find_bridge_ip_by_name(
+	use_json: bool,
+	lookup: &BridgeLookup,
+	bridge_name: &str,
+	find_by_args: (Duration, u16),
+	is_default: bool,
+) -> Ipv4Addr {
+	match find_mion(
+		MIONFindBy::Name(bridge_name.to_owned()),
+		false,
+		Some(find_by_args.0),
+		Some(find_by_args.1),
+	)
+	.await
+	{
+		Ok(Some(identity)) => identity.ip_address(),
+		Ok(None) => {
+			let source = if is_default {
+				"default"
+			} else {
+				"`cafe`/`cafex`/`mochiato`"
+			};
+			if use_json {
+				error!(
+					id = format!("bridgectl::{}::failed_to_find_ip_of_bridge", lookup.id),
+					bridge.name = bridge_name,
+					bridge.source = source,
+					suggestions = valuable(&[
+						"Please ensure the default CAT-DEV you're trying to find is powered on, and running.",
+						"Make sure you are on the same Local Network, Subnet, and VLAN as the CAT-DEV device.",
+						"If you're not on the same VLAN, Subnet you can use something like: <https://github.com/udp-redux/udp-broadcast-relay-redux> to forward between the subnets & vlans.",
+					]),
+				);
+			} else {
+				error!(
+					"\n{:?}",
+					add_context_to(
+						miette!(
+							help = format!("Bridge Name: {bridge_name}"),
+							"Failed to find the {source} bridge's ip by broadcasting.",
+						),
+						[
+							miette!("Please ensure the default CAT-DEV you're trying to find is powered on, and running."),
+							miette!("Make sure you are on the same Local Network, Subnet, and VLAN as the CAT-DEV device."),
+							miette!("If you're not on the same VLAN, Subnet you can use something like: <https://github.com/udp-redux/udp-broadcast-relay-redux> to forward between the subnets & vlans."),
+						].into_iter(),
+					),
+				);
+			}
+			std::process::exit(lookup.no_bridge_exit_code);
+		}
+		Err(cause) => {
+			broadcast_error(use_json, lookup, cause);
+			std::process::exit(lookup.no_bridge_exit_code);
+		}
+	}
+}
+
+fn broadcast_error(use_json: bool, lookup: &BridgeLookup, cause: CatBridgeError) {
+	if use_json {
+		error!(
+			id = format!("bridgectl::{}::failed_to_execute_broadcast", lookup.id),
+			?cause,
+			help = "Could not setup sockets to broadcast and search for the MION; perhaps another program is already using the single MION port?",
+		);
+	} else {
+		error!(
+			"\n{:?}",
+			miette!(
+				help = "Perhaps another program is already using the single MION port?",
+				"Could not setup sockets to broadcast and search for the MION.",
+			)
+			.wrap_err(cause),
+		);
+	}
+}
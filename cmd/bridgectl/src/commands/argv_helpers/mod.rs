@@ -1,10 +1,14 @@
 //! Argument helpers for commands that take a lot of the exact same arguments.
 
 mod bridge;
+mod lookup;
+mod parameters;
 #[macro_use]
 mod serial;
 mod strings;
 
 pub use bridge::*;
+pub use lookup::*;
+pub use parameters::*;
 pub use serial::{coalesce_serial_ports, spawn_serial_log_task};
 pub use strings::*;
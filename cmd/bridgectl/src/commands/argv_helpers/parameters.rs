@@ -0,0 +1,96 @@
+//! Helpers for commands that write to the parameter space.
+
+use crate::utils::add_context_to;
+use cat_dev::mion::proto::parameter::well_known::{definitions_at_index, ParameterDefinition};
+use miette::miette;
+use tracing::{error, field::valuable, warn};
+
+/// Make sure the user has opted in to writing any risky parameters.
+///
+/// Every field covering one of the indexes that is `caution`, or `dangerous`
+/// has it's known consequences shown. If `force` wasn't passed this exits with
+/// `exit_code`, otherwise it just warns.
+///
+/// Read-only fields are refused by `cat_dev` itself, so they aren't checked
+/// here.
+pub fn ensure_parameters_safe_to_write(
+	use_json: bool,
+	command: &str,
+	indexes: impl Iterator<Item = usize>,
+	force: bool,
+	exit_code: i32,
+) {
+	let mut risky_fields: Vec<&'static ParameterDefinition> = Vec::new();
+	for index in indexes {
+		for definition in definitions_at_index(index) {
+			if definition.safety().requires_force()
+				&& !risky_fields
+					.iter()
+					.any(|known| known.name() == definition.name())
+			{
+				risky_fields.push(definition);
+			}
+		}
+	}
+	if risky_fields.is_empty() {
+		return;
+	}
+
+	if force {
+		for definition in risky_fields {
+			warn!(
+				id = format!("bridgectl::{}::forced_risky_parameter", command.replace('-', "_")),
+				parameter.name = definition.name(),
+				parameter.safety = %definition.safety(),
+				parameter.consequences = definition.consequences().unwrap_or_default(),
+				"Writing a risky parameter because `--force` was passed.",
+			);
+		}
+		return;
+	}
+
+	if use_json {
+		error!(
+			id = format!(
+				"bridgectl::{}::risky_parameters_need_force",
+				command.replace('-', "_")
+			),
+			parameters = valuable(
+				&risky_fields
+					.iter()
+					.map(|definition| definition.name())
+					.collect::<Vec<_>>()
+			),
+			consequences = valuable(
+				&risky_fields
+					.iter()
+					.map(|definition| definition.consequences().unwrap_or_default())
+					.collect::<Vec<_>>()
+			),
+			help = format!("If you're sure, run `bridgectl {command}` again with `--force`."),
+			"Refusing to write parameters that can cause problems without `--force`.",
+		);
+	} else {
+		error!(
+			"\n{:?}",
+			add_context_to(
+				miette!(
+					help =
+						format!("If you're sure, run `bridgectl {command}` again with `--force`."),
+					"Refusing to write parameters that can cause problems without `--force`.",
+				),
+				risky_fields.iter().map(|definition| {
+					miette!(
+						"{} is {}: {}",
+						definition.name(),
+						definition.safety(),
+						definition
+							.consequences()
+							.unwrap_or("no known consequences."),
+					)
+				}),
+			),
+		);
+	}
+	std::process::exit(exit_code);
+}
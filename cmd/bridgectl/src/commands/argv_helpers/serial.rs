@@ -119,17 +119,20 @@ pub fn coalesce_serial_ports(
 }
 
 /// Spawn a task that reads from a serial port over, and over again.
+///
+/// This takes an already buffered port, so anything that was read but not
+/// yet logged (e.g. while booting) isn't lost.
 #[allow(clippy::blocks_in_conditions)]
 pub fn spawn_serial_log_task(
 	use_json: bool,
-	port: AsyncSerialPort,
+	reader: BufReader<AsyncSerialPort>,
 	port_path: PathBuf,
 ) -> JoinHandle<()> {
 	let handle = match TaskBuilder::new()
 		.name("bridgectl::serial_log::watcher")
 		.spawn(async move {
 			let mut reader = SerialLines {
-				reader: BufReader::new(port),
+				reader,
 				buf: String::new(),
 				bytes: Vec::new(),
 				read: 0,
@@ -1,25 +1,43 @@
 //! Perform booting of a cat-dev bridge.
 //!
-//! TODO(mythra): not fully implemented yet.
+//! Booting itself is done by [`BootPipeline`], this just renders it's
+//! progress. By default this also serves a title directory over PCFS while
+//! the device is running, so the device can boot it as if it were a disc.
+//!
+//! Serving over PCFS is experimental, and has to be opted into with
+//! `--experimental-pcfs`.
 
 use crate::{
 	commands::argv_helpers::{
 		coalesce_bridge_arguments, coalesce_serial_ports, get_default_bridge, spawn_serial_log_task,
 	},
 	exit_codes::{
-		BOOT_CGI_FAILURE, BOOT_NO_AVAILABLE_BRIDGE, BOOT_NO_BRIDGE_FILTERS, NOT_YET_IMPLEMENTED,
+		BOOT_BRIDGE_IN_USE, BOOT_CGI_FAILURE, BOOT_CONFLICTING_FLAGS, BOOT_INVALID_BOOT_MODE,
+		BOOT_INVALID_CAFE_ROOT, BOOT_NEVER_READY, BOOT_NO_AVAILABLE_BRIDGE, BOOT_NO_BRIDGE_FILTERS,
+		BOOT_NO_PCFS_ROOT, BOOT_PCFS_SERVER_FAILED, BOOT_SET_BOOT_MODE_FAILED,
+		BOOT_SET_SDK_VERSION_FAILED, NOT_YET_IMPLEMENTED,
 	},
-	knobs::env::{BRIDGE_CURRENT_IP_ADDRESS, BRIDGE_CURRENT_NAME},
+	knobs::env::{BRIDGE_CURRENT_IP_ADDRESS, BRIDGE_CURRENT_NAME, CAFE_ROOT},
 	utils::add_context_to,
 };
-use cat_dev::mion::{
-	cgis::very_hacky_will_break_dont_use_power_on,
-	discovery::{find_mion, MIONFindBy},
+use cat_dev::{
+	boot::{BootEvent, BootPipeline, BootStage},
+	errors::{BootError, CatBridgeError, NetworkError},
+	fsemul::{PcfsMode, PcfsServer},
+	mion::{
+		discovery::{find_mion, MIONFindBy},
+		proto::control::MIONBootType,
+	},
 };
 use mac_address::MacAddress;
 use miette::miette;
-use std::{net::Ipv4Addr, path::PathBuf, time::Duration};
-use tracing::{error, field::valuable, info, warn};
+use std::{
+	net::Ipv4Addr,
+	path::{Path, PathBuf},
+	time::Duration,
+};
+use tokio::{io::BufReader, signal::ctrl_c};
+use tracing::{error, field::valuable, info};
 
 #[allow(
 	// It's a CLI parameter, we got a lot of flags.
@@ -33,8 +51,31 @@ pub async fn handle_boot(
 	find_by_args: (Duration, u16),
 	host_state_path: Option<PathBuf>,
 	no_pcfs: bool,
+	pcfs_args: (bool, Option<PathBuf>, bool, u16),
+	pipeline_args: (Option<String>, Option<PathBuf>, Option<String>, Option<u64>),
 	serial_port_args: (Option<PathBuf>, Option<PathBuf>),
 ) {
+	let (boot_mode, cafe_root, ready_marker, ready_timeout) = pipeline_args;
+	if no_pcfs {
+		for (flag, is_set) in [
+			("--boot-mode", boot_mode.is_some()),
+			("--cafe-root", cafe_root.is_some()),
+			("--ready-marker", ready_marker.is_some()),
+			("--ready-timeout-seconds", ready_timeout.is_some()),
+		] {
+			if is_set {
+				conflicting_flags_error(use_json, flag);
+			}
+		}
+	}
+	let boot_mode = boot_mode
+		.as_deref()
+		.map(|boot_mode| parse_boot_mode(use_json, boot_mode));
+	let serves_pcfs = !no_pcfs && !matches!(boot_mode, Some(MIONBootType::NAND));
+	let (experimental_pcfs, pcfs_root, pcfs_read_write, pcfs_port) = pcfs_args;
+	if serves_pcfs && !experimental_pcfs {
+		not_experimental_pcfs_error(use_json);
+	}
 	let bridge_ip = get_bridge_ip(
 		use_json,
 		just_fetch_default,
@@ -45,55 +86,336 @@ pub async fn handle_boot(
 	)
 	.await;
 
+	// Start serving files before the device has a chance to ask for them.
+	let server = if serves_pcfs {
+		Some(spawn_pcfs_server(use_json, pcfs_root, pcfs_read_write, pcfs_port).await)
+	} else {
+		None
+	};
+	let (mut serial_reader, serial_path) = match coalesce_serial_ports(
+		use_json,
+		serial_port_args.0.as_ref(),
+		serial_port_args.1.as_ref(),
+	) {
+		Some((port, path)) => (Some(BufReader::new(port)), Some(path)),
+		None => (None, None),
+	};
+
+	let mut pipeline = BootPipeline::new(bridge_ip);
 	if no_pcfs {
-		let optional_serial_port = coalesce_serial_ports(
-			use_json,
-			serial_port_args.0.as_ref(),
-			serial_port_args.1.as_ref(),
-		);
-		boot_without_pcfs(use_json, bridge_ip).await;
-		if let Some((port, path)) = optional_serial_port {
-			_ = spawn_serial_log_task(use_json, port, path).await;
+		// Just power on, everything else on the bridge is left alone.
+		pipeline = pipeline.with_emulation(false);
+	} else {
+		if let Some(boot_mode) = boot_mode {
+			pipeline = pipeline.with_boot_mode(boot_mode);
 		}
+		if let Some(cafe_root) = cafe_root.or_else(|| CAFE_ROOT.clone()) {
+			pipeline = pipeline.with_cafe_root(cafe_root);
+		}
+	}
+	if let Some(marker) = ready_marker {
+		pipeline = pipeline.with_ready_marker(marker);
+	}
+	if let Some(seconds) = ready_timeout {
+		pipeline =
+			pipeline.with_stage_timeout(BootStage::WaitForReady, Duration::from_secs(seconds));
+	}
+	// Without PCFS the serial port is only logged once the device is on.
+	let wait_on = if no_pcfs {
+		None
 	} else {
-		error!("Sorry! THIS HAS NOT YET BEEN IMPLEMENTED! FSEMUL IS MAKING ME CRY!");
-		std::process::exit(NOT_YET_IMPLEMENTED);
+		serial_reader.as_mut()
+	};
+	if let Err(cause) = pipeline
+		.run(wait_on, |event| {
+			render_boot_event(use_json, serial_path.as_deref(), event);
+		})
+		.await
+	{
+		exit_with_boot_failure(use_json, bridge_ip, cause);
+	}
+
+	let serial_task = serial_reader
+		.zip(serial_path)
+		.map(|(reader, path)| spawn_serial_log_task(use_json, reader, path));
+	if let Some(server) = server {
+		if use_json {
+			info!(
+				id = "bridgectl::boot::serving_pcfs",
+				pcfs.root = %server.root().display(),
+				pcfs.address = %server.address(),
+				pcfs.mode = %server.mode(),
+			);
+		} else {
+			info!(
+				"Serving {} over PCFS on {} ({}), press Ctrl-C to stop.",
+				server.root().display(),
+				server.address(),
+				server.mode(),
+			);
+		}
+		_ = ctrl_c().await;
+		if let Some(task) = serial_task {
+			_ = task.await;
+		}
+		// Stop serving files only once everything else has shut down.
+		drop(server);
+	} else if let Some(task) = serial_task {
+		_ = task.await;
 	}
 }
 
-async fn boot_without_pcfs(use_json: bool, bridge_ip: Ipv4Addr) {
-	warn!("NOTE: this will work if the cat-dev hasn't been taken control of by another host, but does not have good error handling yet!");
+fn parse_boot_mode(use_json: bool, boot_mode: &str) -> MIONBootType {
+	match MIONBootType::try_from(boot_mode) {
+		Ok(MIONBootType::Unk(_)) | Err(_) => {
+			if use_json {
+				error!(
+					id = "bridgectl::boot::invalid_boot_mode",
+					boot_mode,
+					help = "The boot mode must be one of: `NAND`, `PCFS`, `DUAL`, or `HDD`.",
+				);
+			} else {
+				error!(
+					"\n{:?}",
+					miette!(
+						help = "The boot mode must be one of: `NAND`, `PCFS`, `DUAL`, or `HDD`.",
+						"`{boot_mode}` is not a boot mode we know how to boot with!",
+					),
+				);
+			}
+			std::process::exit(BOOT_INVALID_BOOT_MODE);
+		}
+		Ok(boot_mode) => boot_mode,
+	}
+}
+
+fn conflicting_flags_error(use_json: bool, flag: &str) -> ! {
+	if use_json {
+		error!(
+			id = "bridgectl::boot::conflicting_flags",
+			flag,
+			help = "`--boot-without-pcfs` only powers on the device, leaving everything else on the bridge as is. Remove one of the flags.",
+		);
+	} else {
+		error!(
+			"\n{:?}",
+			miette!(
+				help = "`--boot-without-pcfs` only powers on the device, leaving everything else on the bridge as is. Remove one of the flags.",
+				"`{flag}` can't be used with `--boot-without-pcfs`!",
+			),
+		);
+	}
+	std::process::exit(BOOT_CONFLICTING_FLAGS);
+}
 
-	match very_hacky_will_break_dont_use_power_on(bridge_ip).await {
-		Ok(result_code) => {
-			if result_code {
-				info!("Successfully powered on MION!");
+fn not_experimental_pcfs_error(use_json: bool) -> ! {
+	if use_json {
+		error!(
+			id = "bridgectl::boot::pcfs_not_experimental",
+			suggestions = valuable(&[
+				"If you want to try it anyway, run `bridgectl boot --experimental-pcfs ...`.",
+				"To just power on the device, run `bridgectl boot --boot-without-pcfs ...`.",
+			]),
+			"Sorry! Booting over PCFS has not been verified against a real device yet!",
+		);
+	} else {
+		error!(
+			"\n{:?}",
+			miette!(
+				help = "If you want to try it anyway pass `--experimental-pcfs`, or pass `--boot-without-pcfs` to just power on the device.",
+				"Sorry! Booting over PCFS has not been verified against a real device yet!",
+			),
+		);
+	}
+	std::process::exit(NOT_YET_IMPLEMENTED);
+}
+
+fn render_boot_event(use_json: bool, serial_path: Option<&Path>, event: BootEvent) {
+	match event {
+		BootEvent::StageStarted(stage) => {
+			if use_json {
+				info!(id = "bridgectl::boot::stage_started", %stage);
 			} else {
-				error!("Failed to boot cat-dev bridge! Please reach out for support!");
-				std::process::exit(BOOT_CGI_FAILURE);
+				info!("Starting: {stage}...");
 			}
 		}
+		BootEvent::StageSkipped(stage) => {
+			if use_json {
+				info!(id = "bridgectl::boot::stage_skipped", %stage);
+			} else {
+				info!("Skipping: {stage}, nothing to do.");
+			}
+		}
+		BootEvent::StageFinished(stage, took) => {
+			if use_json {
+				info!(
+					id = "bridgectl::boot::stage_finished",
+					%stage,
+					took_ms = u64::try_from(took.as_millis()).unwrap_or(u64::MAX),
+				);
+			} else {
+				info!("Finished: {stage} in {took:.2?}.");
+			}
+		}
+		BootEvent::SdkVersion([major, minor, misc]) => {
+			if use_json {
+				info!(
+					id = "bridgectl::boot::sdk_version",
+					sdk.major = major,
+					sdk.minor = minor,
+					sdk.misc = misc,
+				);
+			} else {
+				info!("Setting the bridges SDK version to: {major}.{minor:02}.{misc:02}");
+			}
+		}
+		BootEvent::SerialLine(line) => {
+			let port = serial_path.map(Path::display);
+			if use_json {
+				info!(
+					id = "bridgectl::serial_log::watcher::line",
+					port = %port.map(|path| path.to_string()).unwrap_or_default(),
+					%line,
+					"received log line from serial port",
+				);
+			} else {
+				info!(port = %port.map(|path| path.to_string()).unwrap_or_default(), line);
+			}
+		}
+	}
+}
+
+fn exit_with_boot_failure(use_json: bool, bridge_ip: Ipv4Addr, cause: CatBridgeError) -> ! {
+	let stage = match &cause {
+		CatBridgeError::BootError(BootError::StageFailed(stage, inner)) => {
+			if let CatBridgeError::NetworkError(NetworkError::MionInUseByAnotherHost(host)) =
+				inner.as_ref()
+			{
+				if use_json {
+					error!(
+						id = "bridgectl::boot::bridge_in_use",
+						bridge.ip = %bridge_ip,
+						bridge.owner = %host,
+						help = "Another host currently has control of this cat-dev, it needs to release it before you can boot it, or you can take it with `bridgectl claim --force`.",
+					);
+				} else {
+					error!(
+						"\n{:?}",
+						miette!(
+							help = "Another host currently has control of this cat-dev, it needs to release it before you can boot it, or you can take it with `bridgectl claim --force`.",
+							"The cat-dev bridge at {bridge_ip} is in use by: {host}",
+						),
+					);
+				}
+				std::process::exit(BOOT_BRIDGE_IN_USE);
+			}
+
+			*stage
+		}
+		CatBridgeError::BootError(BootError::StageTimedOut(stage, _)) => *stage,
+		// The pipeline tags every error with a stage, but just in case.
+		_ => BootStage::PowerOn,
+	};
+
+	let (help, exit_code) = match stage {
+		BootStage::DetectSdkVersion => (
+			"Make sure `--cafe-root` (or `CAFE_ROOT`) points at the root of an SDK.",
+			BOOT_INVALID_CAFE_ROOT,
+		),
+		BootStage::SetSdkVersion => (
+			"Make sure the cat-dev bridge is still reachable, and nothing else is using it's parameter port.",
+			BOOT_SET_SDK_VERSION_FAILED,
+		),
+		BootStage::SetBootMode => (
+			"Make sure the cat-dev bridge is still reachable, and it's firmware supports the boot mode.",
+			BOOT_SET_BOOT_MODE_FAILED,
+		),
+		BootStage::PowerOn => (
+			"Make sure the cat-dev bridge is still reachable, and if this keeps happening please file an issue.",
+			BOOT_CGI_FAILURE,
+		),
+		BootStage::WaitForReady => (
+			"The device may still be booting, try a longer `--ready-timeout-seconds`, or a different `--ready-marker`.",
+			BOOT_NEVER_READY,
+		),
+	};
+
+	if use_json {
+		error!(
+			id = "bridgectl::boot::failed_to_boot_device",
+			bridge.ip = %bridge_ip,
+			%stage,
+			?cause,
+			help,
+		);
+	} else {
+		error!(
+			"\n{:?}",
+			miette!(
+				help = help,
+				"Failed to boot the cat-dev bridge at {bridge_ip}.",
+			)
+			.wrap_err(cause),
+		);
+	}
+	std::process::exit(exit_code);
+}
+
+async fn spawn_pcfs_server(
+	use_json: bool,
+	pcfs_root: Option<PathBuf>,
+	read_write: bool,
+	port: u16,
+) -> PcfsServer {
+	let Some(pcfs_root) = pcfs_root else {
+		if use_json {
+			error!(
+				id = "bridgectl::boot::no_pcfs_root",
+				help = "Pass the title directory to serve with `--pcfs-root`, or boot with `--boot-without-pcfs`.",
+			);
+		} else {
+			error!(
+				"\n{:?}",
+				miette!(
+					help = "Pass the title directory to serve with `--pcfs-root`, or boot with `--boot-without-pcfs`.",
+					"You didn't specify a directory to serve to the device over PCFS!",
+				),
+			);
+		}
+		std::process::exit(BOOT_NO_PCFS_ROOT);
+	};
+
+	let mode = if read_write {
+		PcfsMode::ReadWrite
+	} else {
+		PcfsMode::ReadOnly
+	};
+	match PcfsServer::spawn_on(&pcfs_root, mode, Ipv4Addr::UNSPECIFIED, port).await {
+		Ok(server) => server,
 		Err(cause) => {
 			if use_json {
 				error!(
-					id = "bridgectl::boot::failed_to_boot_device",
-					bridge.ip = %bridge_ip,
+					id = "bridgectl::boot::failed_to_serve_pcfs",
 					?cause,
+					pcfs.root = %pcfs_root.display(),
+					pcfs.port = port,
 					suggestions = valuable(&[
-					  "Please file an issue, and reach out!"
+						"Make sure the directory contains a `code`, `content`, and `meta` directory.",
+						"Make sure no other program is already serving on the PCFS port.",
 					]),
 				);
 			} else {
 				error!(
-			  	"\n{:?}",
-			  	miette!(
-			  		help = "PLEASE PLEASE PLEASE FILE AN ISSUE!",
-			  		"Failure to perform hacky non-emulated boot!!! THIS IS STILL EARLY !!! PLEASE FILE AN ISSUE!\n {cause:?}",
-			  	),
-			  );
+					"\n{:?}",
+					miette!(
+						help = "Make sure the directory contains a `code`, `content`, and `meta` directory, and no other program is already serving on the PCFS port.",
+						"Failed to serve {} over PCFS on port {port}.",
+						pcfs_root.display(),
+					)
+					.wrap_err(cause),
+				);
 			}
-
-			std::process::exit(BOOT_CGI_FAILURE);
+			std::process::exit(BOOT_PCFS_SERVER_FAILED);
 		}
 	}
 }
@@ -0,0 +1,246 @@
+//! Handles the `configure` command, which changes the settings of a bridge
+//! through `control.cgi`.
+
+use crate::{
+	commands::argv_helpers::{get_a_bridge_ip, BridgeLookup},
+	exit_codes::{
+		CONFIGURE_FAILED_TO_SET, CONFIGURE_INVALID_SETTING, CONFIGURE_NO_AVAILABLE_BRIDGE,
+		CONFIGURE_NO_BRIDGE_FILTERS, CONFIGURE_NO_SETTINGS,
+	},
+	utils::add_context_to,
+};
+use cat_dev::mion::{
+	cgis::{set_experimental_param, set_param},
+	proto::cgis::SetParameter,
+};
+use miette::miette;
+use std::{net::Ipv4Addr, path::PathBuf, time::Duration};
+use tracing::{error, field::valuable, info, warn};
+
+const CONFIGURE_LOOKUP: BridgeLookup = BridgeLookup {
+	id: "configure",
+	command: "configure",
+	no_filters_exit_code: CONFIGURE_NO_BRIDGE_FILTERS,
+	no_bridge_exit_code: CONFIGURE_NO_AVAILABLE_BRIDGE,
+};
+
+/// Actual command handler for the `configure` command.
+#[allow(
+	// This is unfortunate that there are a lot, but the command accepts lots of
+	// potential parameters.
+	//
+	// The parameters are also fairly different types, so the chances of screwing
+	// up passing them in without noticing is low.
+	clippy::too_many_arguments,
+)]
+pub async fn handle_configure(
+	use_json: bool,
+	just_fetch_default: bool,
+	bridge_flag_arguments: (Option<Ipv4Addr>, Option<String>, Option<String>),
+	bridge_or_settings_argument: Option<String>,
+	only_settings_argument: Option<String>,
+	experimental: bool,
+	find_by_args: (Duration, u16),
+	host_state_path: Option<PathBuf>,
+) {
+	let had_settings_arg = only_settings_argument.is_some();
+	let (settings_string, bridge_name_arg) = if let Some(settings) = only_settings_argument {
+		(settings, bridge_or_settings_argument)
+	} else if let Some(settings) = bridge_or_settings_argument {
+		(settings, None)
+	} else {
+		if use_json {
+			error!(
+				id = "bridgectl::configure::no_settings",
+				suggestions = valuable(&[
+					"You can run `bridgectl configure <bridge> <settings>`, `bridgectl configure --default <settings>`, etc.",
+					"You can run `bridgectl configure --help` to see every setting you can change.",
+				]),
+				"No settings passed to `bridgectl configure`, but we need a list of settings to change!",
+			);
+		} else {
+			error!(
+				"\n{:?}",
+				add_context_to(
+					miette!("No settings passed to `bridgectl configure`, but we need a list of settings to change!"),
+					[
+						miette!("You can run `bridgectl configure <bridge> <settings>`, `bridgectl configure --default <settings>`, etc."),
+						miette!("You can run `bridgectl configure --help` to see every setting you can change."),
+					]
+					.into_iter(),
+				),
+			);
+		}
+		std::process::exit(CONFIGURE_NO_SETTINGS);
+	};
+	let settings = parse_settings_list(use_json, &settings_string, experimental);
+
+	let bridge_ip = get_a_bridge_ip(
+		use_json,
+		&CONFIGURE_LOOKUP,
+		just_fetch_default,
+		bridge_flag_arguments,
+		bridge_name_arg,
+		had_settings_arg,
+		find_by_args,
+		host_state_path,
+	)
+	.await;
+
+	for setting in settings {
+		apply_setting(use_json, bridge_ip, setting).await;
+	}
+}
+
+/// Change a single setting on the bridge, exiting if it couldn't be changed.
+async fn apply_setting(use_json: bool, bridge_ip: Ipv4Addr, setting: SetParameter) {
+	// Experimental settings were refused while parsing unless the user asked
+	// for them.
+	let result = if setting.is_experimental() {
+		set_experimental_param(bridge_ip, setting).await
+	} else {
+		set_param(bridge_ip, setting).await
+	};
+	match result {
+		Ok(true) => {
+			if use_json {
+				info!(
+					id = "bridgectl::configure::set_setting",
+					bridge.ip = %bridge_ip,
+					setting.name = %setting,
+					setting.value = setting.get_value_as_string(),
+				);
+			} else {
+				info!(
+					"Set {setting} to {} on {bridge_ip}.",
+					setting.get_value_as_string()
+				);
+			}
+			if let SetParameter::ParameterPort(port) = setting {
+				warn!(
+					id = "bridgectl::configure::parameter_port_changed",
+					parameter_port = port,
+					"The parameter space port has changed, pass `--port {port}` to commands that read or write parameters.",
+				);
+			}
+		}
+		Ok(false) => {
+			if use_json {
+				error!(
+					id = "bridgectl::configure::setting_rejected",
+					bridge.ip = %bridge_ip,
+					setting.name = %setting,
+					setting.value = setting.get_value_as_string(),
+					"The bridge did not accept the setting.",
+				);
+			} else {
+				error!(
+					"\n{:?}",
+					miette!(
+						help = "Settings before this one were still changed, the bridge may be running an older firmware that doesn't support this setting.",
+						"The bridge at {bridge_ip} did not accept setting {setting} to {}.",
+						setting.get_value_as_string(),
+					),
+				);
+			}
+			std::process::exit(CONFIGURE_FAILED_TO_SET);
+		}
+		Err(cause) => {
+			if use_json {
+				error!(
+					id = "bridgectl::configure::failed_to_set",
+					?cause,
+					bridge.ip = %bridge_ip,
+					setting.name = %setting,
+					"Could not change the setting on the bridge.",
+				);
+			} else {
+				error!(
+					"\n{:?}",
+					miette!(
+						help = "Settings before this one were still changed, please ensure the bridge is running.",
+						"Could not change {setting} on the bridge at {bridge_ip}.",
+					)
+					.wrap_err(cause),
+				);
+			}
+			std::process::exit(CONFIGURE_FAILED_TO_SET);
+		}
+	}
+}
+
+/// Parse a list of settings in the form of `name=value,name=value`.
+///
+/// Every setting is validated before any of them are sent, so a typo in the
+/// last setting doesn't leave the bridge half configured.
+fn parse_settings_list(
+	use_json: bool,
+	settings_string: &str,
+	experimental: bool,
+) -> Vec<SetParameter> {
+	let mut settings = Vec::new();
+	for serialized_setting in settings_string.split(',') {
+		let Some((name, value)) = serialized_setting.split_once('=') else {
+			if use_json {
+				error!(
+					id = "bridgectl::configure::no_equals_sign",
+					setting = %serialized_setting,
+					"Settings should be in the format `(name)=(value)`, but no equals sign was found!",
+				);
+			} else {
+				error!(
+					setting = %serialized_setting,
+					"Settings for configure should be in the format `(name)=(value)` e.g. `atapi_port=7975`!",
+				);
+			}
+			std::process::exit(CONFIGURE_INVALID_SETTING);
+		};
+
+		match SetParameter::from_name_and_value(name, value) {
+			Ok(setting) if setting.is_experimental() && !experimental => {
+				if use_json {
+					error!(
+						id = "bridgectl::configure::experimental_setting",
+						setting.name = %name,
+						setting.value = %value,
+						help = "Pass `--experimental` to change this setting anyway.",
+						"This setting is experimental, a real bridge may not accept it.",
+					);
+				} else {
+					error!(
+						"\n{:?}",
+						miette!(
+							help = "Pass `--experimental` to change this setting anyway.",
+							"The setting: {name} is experimental, we've guessed it's name, and a real bridge may not accept it.",
+						),
+					);
+				}
+				std::process::exit(CONFIGURE_INVALID_SETTING);
+			}
+			Ok(setting) => settings.push(setting),
+			Err(cause) => {
+				if use_json {
+					error!(
+						id = "bridgectl::configure::invalid_setting",
+						?cause,
+						setting.name = %name,
+						setting.value = %value,
+						"Setting is not valid.",
+					);
+				} else {
+					error!(
+						"\n{:?}",
+						miette!(
+							help = "See `bridgectl configure --help` for every setting you can change.",
+							"Cannot set: {name} to: {value}",
+						)
+						.wrap_err(cause),
+					);
+				}
+				std::process::exit(CONFIGURE_INVALID_SETTING);
+			}
+		}
+	}
+
+	settings
+}
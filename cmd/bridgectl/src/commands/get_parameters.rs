@@ -99,7 +99,15 @@ fn print_parameters(use_json: bool, parameter_filters: &str, parameters: &Dumped
 			continue;
 		}
 
-		if let Ok(parameter_value) = parameters.get_parameter_by_name(filter) {
+		// Prefer the full value of a named field, which may span multiple bytes.
+		if let Ok(parameter_value) = parameters
+			.get_field(filter)
+			.map(|value| format!("{value}"))
+			.or_else(|_| {
+				parameters
+					.get_parameter_by_name(filter)
+					.map(|value| format!("{value}"))
+			}) {
 			if use_json {
 				info!(
 					id = "bridgectl::get_parameters::parameter_found",
@@ -2,15 +2,25 @@
 
 use crate::{
 	commands::argv_helpers::get_padded_string,
-	exit_codes::LIST_COULD_NOT_SEARCH,
+	exit_codes::{
+		LIST_COULD_NOT_SEARCH, LIST_INVALID_INTERFACE, LIST_INVALID_PACKETS_PER_SECOND,
+		LIST_INVALID_TARGET,
+	},
+	knobs::env::BRIDGE_HOST_STATE_PATH,
 	utils::{add_context_to, bridge_state_from_path, get_bridge_state_path},
 };
 use cat_dev::{
-	mion::{discovery::discover_bridges, proto::control::MionIdentity},
+	mion::{
+		discovery::{
+			discover_bridges_at_targets, discover_bridges_on_interfaces, DiscoveryTarget,
+			InterfaceFilter, InterfaceMatcher, MAX_SWEEP_PACKETS_PER_SECOND,
+		},
+		proto::control::MionIdentity,
+	},
 	BridgeHostState,
 };
 use miette::miette;
-use std::path::PathBuf;
+use std::{num::NonZeroU32, path::PathBuf};
 use terminal_size::{terminal_size, Width as TermWidth};
 use tokio::time::{sleep, Duration};
 use tracing::{error, field::valuable, info, warn};
@@ -23,6 +33,8 @@ pub async fn handle_list(
 	use_json: bool,
 	use_cache: bool,
 	output_as_table: bool,
+	(targets, packets_per_second): (Vec<String>, Option<u32>),
+	interface_args: (Vec<String>, Vec<String>, bool),
 	scan_args: (Duration, u16),
 	argv_host_state_path: Option<PathBuf>,
 ) {
@@ -37,16 +49,178 @@ pub async fn handle_list(
 			.await,
 		);
 	} else {
-		list_from_network(use_json, output_as_table, scan_args).await;
+		let parsed_targets = parse_targets(use_json, &targets);
+		let parsed_packets_per_second = parse_packets_per_second(use_json, packets_per_second);
+		// Targets are asked directly, so what interfaces we'd broadcast on
+		// doesn't matter.
+		let interface_filter = if parsed_targets.is_empty() {
+			build_interface_filter(use_json, interface_args, argv_host_state_path).await
+		} else {
+			InterfaceFilter::default()
+		};
+		list_from_network(
+			use_json,
+			output_as_table,
+			(&parsed_targets, parsed_packets_per_second),
+			&interface_filter,
+			scan_args,
+		)
+		.await;
 	}
 }
 
+/// Build the filter of which interfaces to broadcast on, exiting if any of
+/// the interfaces a user passed in are invalid.
+///
+/// If a user hasn't specified any interfaces to use, and hasn't asked for
+/// all interfaces, we use the preferred interface from the host state. Listing
+/// doesn't otherwise need the host state, so if it can't be loaded we just
+/// broadcast everywhere.
+async fn build_interface_filter(
+	use_json: bool,
+	(interfaces, exclude_interfaces, all_interfaces): (Vec<String>, Vec<String>, bool),
+	argv_host_state_path: Option<PathBuf>,
+) -> InterfaceFilter {
+	let mut allow = parse_interfaces(use_json, &interfaces);
+	let deny = parse_interfaces(use_json, &exclude_interfaces);
+
+	if allow.is_empty() && !all_interfaces {
+		let host_state = match argv_host_state_path
+			.or_else(|| BRIDGE_HOST_STATE_PATH.clone())
+			.or_else(BridgeHostState::get_default_host_path)
+		{
+			Some(path) => BridgeHostState::load_explicit_path(path).await.ok(),
+			None => None,
+		};
+		if let Some(preferred) = host_state
+			.as_ref()
+			.and_then(BridgeHostState::get_preferred_interface)
+		{
+			info!(
+				id = "bridgectl::list::using_preferred_interface",
+				interface = %preferred,
+				"Only broadcasting on your preferred interface, use `--all-interfaces` to broadcast everywhere.",
+			);
+			allow.push(preferred);
+		}
+	}
+
+	InterfaceFilter::new(allow, deny)
+}
+
+/// Parse all the interfaces a user passed in, exiting if any of them are
+/// invalid.
+fn parse_interfaces(use_json: bool, interfaces: &[String]) -> Vec<InterfaceMatcher> {
+	let mut parsed = Vec::with_capacity(interfaces.len());
+
+	for interface in interfaces {
+		match InterfaceMatcher::try_from(interface.as_str()) {
+			Ok(value) => parsed.push(value),
+			Err(cause) => {
+				if use_json {
+					error!(
+						id = "bridgectl::list::invalid_interface",
+						?cause,
+						%interface,
+						"Invalid network interface to broadcast on",
+					);
+				} else {
+					error!(
+						"\n{:?}",
+						miette!("Invalid network interface to broadcast on: {interface}")
+							.wrap_err(cause),
+					);
+				}
+
+				std::process::exit(LIST_INVALID_INTERFACE);
+			}
+		}
+	}
+
+	parsed
+}
+
+/// Parse all the targets a user passed in to ask directly, exiting if any of
+/// them are invalid.
+fn parse_targets(use_json: bool, targets: &[String]) -> Vec<DiscoveryTarget> {
+	let mut parsed = Vec::with_capacity(targets.len());
+
+	for target in targets {
+		match DiscoveryTarget::try_from(target.as_str()) {
+			Ok(value) => parsed.push(value),
+			Err(cause) => {
+				if use_json {
+					error!(
+						id = "bridgectl::list::invalid_target",
+						?cause,
+						%target,
+						"Invalid target to search for bridges at",
+					);
+				} else {
+					error!(
+						"\n{:?}",
+						miette!("Invalid target to search for bridges at: {target}")
+							.wrap_err(cause),
+					);
+				}
+
+				std::process::exit(LIST_INVALID_TARGET);
+			}
+		}
+	}
+
+	parsed
+}
+
+/// Validate the rate to send announcements to targets at, exiting if it's
+/// out of range.
+fn parse_packets_per_second(use_json: bool, packets_per_second: Option<u32>) -> Option<NonZeroU32> {
+	let packets_per_second = packets_per_second?;
+	if let Some(value) =
+		NonZeroU32::new(packets_per_second).filter(|pps| pps.get() <= MAX_SWEEP_PACKETS_PER_SECOND)
+	{
+		return Some(value);
+	}
+
+	if use_json {
+		error!(
+			id = "bridgectl::list::invalid_packets_per_second",
+			packets_per_second,
+			max = MAX_SWEEP_PACKETS_PER_SECOND,
+			"Invalid amount of packets per second to send",
+		);
+	} else {
+		error!(
+			"\n{:?}",
+			miette!(
+				help = format!("Must be between 1, and {MAX_SWEEP_PACKETS_PER_SECOND}."),
+				"Invalid amount of packets per second to send: {packets_per_second}",
+			),
+		);
+	}
+	std::process::exit(LIST_INVALID_PACKETS_PER_SECOND);
+}
+
 /// List all of the devices that are actively on the network.
-async fn list_from_network(use_json: bool, use_table: bool, scan_args: (Duration, u16)) {
+///
+/// If any targets were specified we only ask those targets directly, rather
+/// than broadcasting, as both need to use the single MION port.
+async fn list_from_network(
+	use_json: bool,
+	use_table: bool,
+	(targets, packets_per_second): (&[DiscoveryTarget], Option<NonZeroU32>),
+	interface_filter: &InterfaceFilter,
+	scan_args: (Duration, u16),
+) {
 	const TABLE_HEADER: &str =      "Bridge Name                    | IP Address      | MAC Address        | FPGA image version | Firmware Version | SDK Version | Boot Mode | Power Status";
 	const TABLE_HEADER_LINE: &str = "------------------------------------------------------------------------------------------------------------------------------------------------------";
 
-	let mut recv_channel = match discover_bridges(true, Some(scan_args.1)).await {
+	let search_result = if targets.is_empty() {
+		discover_bridges_on_interfaces(true, Some(scan_args.1), interface_filter).await
+	} else {
+		discover_bridges_at_targets(targets, true, Some(scan_args.1), packets_per_second).await
+	};
+	let mut recv_channel = match search_result {
 		Ok(channel) => channel,
 		Err(cause) => {
 			if use_json {
@@ -179,7 +353,7 @@ fn print_no_bridge_found_warning(use_json: bool, was_early_exit: bool, early_tim
 		let mut suggestions = vec![
 			"Please ensure the CAT-DEV is powered on, and running.".to_owned(),
 			"Make sure you are on the same Local Network, Subnet, and VLAN as the CAT-DEV device.".to_owned(),
-			"If you're not on the same VLAN, Subnet you can use something like: <https://github.com/udp-redux/udp-broadcast-relay-redux> to forward between the subnets & vlans.".to_owned(),
+			"If you're not on the same VLAN, Subnet you can ask the CAT-DEV directly with `--target <ip or cidr range>`, or use something like: <https://github.com/udp-redux/udp-broadcast-relay-redux> to forward between the subnets & vlans.".to_owned(),
 		];
 		if was_early_exit {
 			suggestions.push(format!(
@@ -197,7 +371,7 @@ fn print_no_bridge_found_warning(use_json: bool, was_early_exit: bool, early_tim
 		let mut suggestions = vec![
 			miette!("Please ensure the CAT-DEV is powered on, and running."),
 			miette!("Make sure you are on the same Local Network, Subnet, and VLAN as the CAT-DEV device."),
-			miette!("If you're not on the same VLAN, Subnet you can use something like: <https://github.com/udp-redux/udp-broadcast-relay-redux> to forward between the two VLANs/Subnets."),
+			miette!("If you're not on the same VLAN, Subnet you can ask the CAT-DEV directly with `--target <ip or cidr range>`, or use something like: <https://github.com/udp-redux/udp-broadcast-relay-redux> to forward between the two VLANs/Subnets."),
 		];
 		if was_early_exit {
 			suggestions.push(miette!(format!(
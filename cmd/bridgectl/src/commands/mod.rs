@@ -5,26 +5,38 @@ mod argv_helpers;
 
 mod add;
 mod boot;
+mod configure;
 mod dump_parameters;
 mod get;
 mod get_parameters;
 mod help;
 mod list;
 mod list_serial_ports;
+mod parameter_snapshots;
 mod remove;
+mod session;
 mod set_default;
 mod set_parameters;
+mod set_preferred_interface;
+mod signals;
 mod tail;
+mod version;
 
 pub use add::*;
 pub use boot::*;
+pub use configure::*;
 pub use dump_parameters::*;
 pub use get::*;
 pub use get_parameters::*;
 pub use help::*;
 pub use list::*;
 pub use list_serial_ports::*;
+pub use parameter_snapshots::*;
 pub use remove::*;
+pub use session::*;
 pub use set_default::*;
 pub use set_parameters::*;
+pub use set_preferred_interface::*;
+pub use signals::*;
 pub use tail::*;
+pub use version::*;
@@ -0,0 +1,466 @@
+//! Handles the commands for saving, comparing, and restoring snapshots of the
+//! parameter space: `snapshot-parameters`, `diff-parameters`, and
+//! `restore-parameters`.
+
+use crate::{
+	commands::argv_helpers::{ensure_parameters_safe_to_write, get_a_bridge_ip, BridgeLookup},
+	exit_codes::{
+		DIFF_PARAMS_COULD_NOT_LOAD_SNAPSHOT, RESTORE_PARAMS_COULD_NOT_LOAD_SNAPSHOT,
+		RESTORE_PARAMS_DIFFERENT_BRIDGE, RESTORE_PARAMS_FAILED_TO_RESTORE,
+		RESTORE_PARAMS_NEEDS_FORCE, RESTORE_PARAMS_NO_AVAILABLE_BRIDGE,
+		RESTORE_PARAMS_NO_BRIDGE_FILTERS, RESTORE_PARAMS_NO_PATH,
+		SNAPSHOT_PARAMS_COULD_NOT_SAVE_TO_DISK, SNAPSHOT_PARAMS_FAILED_TO_GET_PARAMS,
+		SNAPSHOT_PARAMS_NO_AVAILABLE_BRIDGE, SNAPSHOT_PARAMS_NO_BRIDGE_FILTERS,
+		SNAPSHOT_PARAMS_NO_PATH,
+	},
+	utils::add_context_to,
+};
+use cat_dev::mion::{
+	discovery::{find_mion, MIONFindBy},
+	parameter::{ByteDifference, MionParameterClient, ParameterDiff, ParameterSnapshot},
+	proto::{
+		control::MionIdentity,
+		parameter::well_known::{definitions_at_index, ParameterDefinition},
+	},
+};
+use miette::miette;
+use std::{
+	net::Ipv4Addr,
+	path::{Path, PathBuf},
+	time::Duration,
+};
+use tracing::{error, field::valuable, info, warn};
+
+const SNAPSHOT_LOOKUP: BridgeLookup = BridgeLookup {
+	id: "snapshot_parameters",
+	command: "snapshot-parameters",
+	no_filters_exit_code: SNAPSHOT_PARAMS_NO_BRIDGE_FILTERS,
+	no_bridge_exit_code: SNAPSHOT_PARAMS_NO_AVAILABLE_BRIDGE,
+};
+const RESTORE_LOOKUP: BridgeLookup = BridgeLookup {
+	id: "restore_parameters",
+	command: "restore-parameters",
+	no_filters_exit_code: RESTORE_PARAMS_NO_BRIDGE_FILTERS,
+	no_bridge_exit_code: RESTORE_PARAMS_NO_AVAILABLE_BRIDGE,
+};
+
+/// Actual command handler for the `snapshot-parameters` command.
+#[allow(
+	// This is unfortunate that there are a lot, but the command accepts lots of
+	// potential parameters.
+	//
+	// The parameters are also fairly different types, so the chances of screwing
+	// up passing them in without noticing is low.
+	clippy::too_many_arguments,
+)]
+pub async fn handle_snapshot_parameters(
+	use_json: bool,
+	just_fetch_default: bool,
+	bridge_flag_arguments: (Option<Ipv4Addr>, Option<String>, Option<String>),
+	bridge_or_path_argument: Option<String>,
+	only_path_argument: Option<PathBuf>,
+	find_by_args: (Duration, u16),
+	parameter_space_port: Option<u16>,
+	host_state_path: Option<PathBuf>,
+) {
+	let had_path_arg = only_path_argument.is_some();
+	let Some((snapshot_path, bridge_name_arg)) =
+		split_bridge_and_path(bridge_or_path_argument, only_path_argument)
+	else {
+		no_path_error(use_json, &SNAPSHOT_LOOKUP);
+		std::process::exit(SNAPSHOT_PARAMS_NO_PATH);
+	};
+
+	let bridge_ip = get_a_bridge_ip(
+		use_json,
+		&SNAPSHOT_LOOKUP,
+		just_fetch_default,
+		bridge_flag_arguments,
+		bridge_name_arg,
+		had_path_arg,
+		find_by_args,
+		host_state_path,
+	)
+	.await;
+
+	let parameters = match MionParameterClient::new(bridge_ip, parameter_space_port, None)
+		.dump()
+		.await
+	{
+		Ok(params) => params,
+		Err(cause) => {
+			if use_json {
+				error!(
+					id = "bridgectl::snapshot_parameters::failed_to_execute_dump_parameters",
+					?cause,
+					help = "We could not send/receive a packet to your MION to ask for it's parameters, please ensure it is running. If it's been running for awhile, it may need a reboot.",
+				);
+			} else {
+				error!(
+					"\n{:?}",
+					miette!(
+						help = "If you leave a MION running for too long it may stop responding to parameter requests.",
+						"Could not send/receive a packet to your MION to ask for it's parameters, please ensure the device is running.",
+					)
+					.wrap_err(cause),
+				);
+			}
+			std::process::exit(SNAPSHOT_PARAMS_FAILED_TO_GET_PARAMS);
+		}
+	};
+
+	let mut snapshot = ParameterSnapshot::new(parameters);
+	if let Some(identity) = identity_for_ip(use_json, bridge_ip, find_by_args).await {
+		snapshot = snapshot.with_identity(&identity);
+	}
+
+	if let Err(cause) = snapshot.write_to_file(&snapshot_path).await {
+		if use_json {
+			error!(
+				id = "bridgectl::snapshot_parameters::could_not_save_to_disk",
+				?cause,
+				snapshot.path = %snapshot_path.display(),
+				"could not save snapshot to disk",
+			);
+		} else {
+			error!(
+				"\n{:?}",
+				miette!(
+					help = format!(
+						"While trying to write the snapshot to: {}",
+						snapshot_path.display()
+					),
+					"could not save snapshot to disk",
+				)
+				.wrap_err(cause),
+			);
+		}
+		std::process::exit(SNAPSHOT_PARAMS_COULD_NOT_SAVE_TO_DISK);
+	}
+
+	info!(
+		id = "bridgectl::snapshot_parameters::success",
+		bridge.name = snapshot.bridge_name(),
+		bridge.ip = %bridge_ip,
+		snapshot.path = %snapshot_path.display(),
+		"Saved a snapshot of your parameters!",
+	);
+}
+
+/// Actual command handler for the `diff-parameters` command.
+pub async fn handle_diff_parameters(use_json: bool, old_path: PathBuf, new_path: PathBuf) {
+	let old = load_snapshot(use_json, "diff_parameters", &old_path).await;
+	let Some(old) = old else {
+		std::process::exit(DIFF_PARAMS_COULD_NOT_LOAD_SNAPSHOT);
+	};
+	let Some(new) = load_snapshot(use_json, "diff_parameters", &new_path).await else {
+		std::process::exit(DIFF_PARAMS_COULD_NOT_LOAD_SNAPSHOT);
+	};
+
+	print_diff(use_json, "diff_parameters", &old.diff(&new));
+}
+
+/// Actual command handler for the `restore-parameters` command.
+#[allow(
+	// This is unfortunate that there are a lot, but the command accepts lots of
+	// potential parameters.
+	//
+	// The parameters are also fairly different types, so the chances of screwing
+	// up passing them in without noticing is low.
+	clippy::too_many_arguments,
+)]
+pub async fn handle_restore_parameters(
+	use_json: bool,
+	just_fetch_default: bool,
+	bridge_flag_arguments: (Option<Ipv4Addr>, Option<String>, Option<String>),
+	bridge_or_path_argument: Option<String>,
+	only_path_argument: Option<PathBuf>,
+	find_by_args: (Duration, u16),
+	parameter_space_port: Option<u16>,
+	force: bool,
+	host_state_path: Option<PathBuf>,
+) {
+	let had_path_arg = only_path_argument.is_some();
+	let Some((snapshot_path, bridge_name_arg)) =
+		split_bridge_and_path(bridge_or_path_argument, only_path_argument)
+	else {
+		no_path_error(use_json, &RESTORE_LOOKUP);
+		std::process::exit(RESTORE_PARAMS_NO_PATH);
+	};
+	// Load the snapshot before we go searching for bridges, so a typo'd path
+	// fails fast.
+	let Some(snapshot) = load_snapshot(use_json, "restore_parameters", &snapshot_path).await else {
+		std::process::exit(RESTORE_PARAMS_COULD_NOT_LOAD_SNAPSHOT);
+	};
+
+	let bridge_ip = get_a_bridge_ip(
+		use_json,
+		&RESTORE_LOOKUP,
+		just_fetch_default,
+		bridge_flag_arguments,
+		bridge_name_arg,
+		had_path_arg,
+		find_by_args,
+		host_state_path,
+	)
+	.await;
+	if let Some(expected_mac) = snapshot.mac_address() {
+		if let Some(identity) = identity_for_ip(use_json, bridge_ip, find_by_args).await {
+			if identity.mac_address() != expected_mac {
+				if force {
+					warn!(
+						id = "bridgectl::restore_parameters::different_bridge",
+						bridge.mac = %identity.mac_address(),
+						snapshot.mac = %expected_mac,
+						"This snapshot was taken from a different bridge, restoring anyway because of `--force`.",
+					);
+				} else {
+					if use_json {
+						error!(
+							id = "bridgectl::restore_parameters::different_bridge",
+							bridge.mac = %identity.mac_address(),
+							snapshot.mac = %expected_mac,
+							help = "Pass `--force` if you really want to restore a snapshot taken from a different bridge.",
+						);
+					} else {
+						error!(
+							"\n{:?}",
+							miette!(
+								help = "Pass `--force` if you really want to restore a snapshot taken from a different bridge.",
+								"This snapshot was taken from the bridge with the MAC address: {expected_mac}, but the bridge at {bridge_ip} has the MAC address: {}",
+								identity.mac_address(),
+							),
+						);
+					}
+					std::process::exit(RESTORE_PARAMS_DIFFERENT_BRIDGE);
+				}
+			}
+		}
+	}
+
+	let mut client = MionParameterClient::new(bridge_ip, parameter_space_port, None);
+	// Look at what would change before writing anything, so we can make sure
+	// nothing risky gets written without `--force`.
+	let result = match client.dump().await {
+		Ok(current) => {
+			ensure_parameters_safe_to_write(
+				use_json,
+				"restore-parameters",
+				ParameterDiff::between(&current, snapshot.parameters())
+					.byte_differences()
+					.iter()
+					.map(ByteDifference::index),
+				force,
+				RESTORE_PARAMS_NEEDS_FORCE,
+			);
+			client.restore_snapshot(&snapshot).await
+		}
+		Err(cause) => Err(cause),
+	};
+
+	match result {
+		Ok((None, _)) => {
+			info!(
+				id = "bridgectl::restore_parameters::already_matches",
+				bridge.ip = %bridge_ip,
+				"Your bridge already matches the snapshot, nothing to restore!",
+			);
+		}
+		Ok((Some(_), diff)) => {
+			print_diff(use_json, "restore_parameters", &diff);
+			info!(
+				id = "bridgectl::restore_parameters::success",
+				bridge.ip = %bridge_ip,
+				changed_bytes = diff.byte_differences().len(),
+				"Successfully restored your parameters!",
+			);
+		}
+		Err(cause) => {
+			if use_json {
+				error!(
+					id = "bridgectl::restore_parameters::failed_to_restore",
+					?cause,
+					help = "We could not restore the parameters on your MION, please ensure it is running, and that nothing else is changing it's parameters at the same time.",
+				);
+			} else {
+				error!(
+					"\n{:?}",
+					miette!(
+						help = "Please ensure nothing else is changing the parameters on your MION at the same time.",
+						"Could not restore the parameters on your MION, please ensure the device is running.",
+					)
+					.wrap_err(cause),
+				);
+			}
+			std::process::exit(RESTORE_PARAMS_FAILED_TO_RESTORE);
+		}
+	}
+}
+
+/// If only one positional argument was passed it's the path, otherwise the
+/// first is the bridge, and the second is the path.
+fn split_bridge_and_path(
+	bridge_or_path_argument: Option<String>,
+	only_path_argument: Option<PathBuf>,
+) -> Option<(PathBuf, Option<String>)> {
+	if let Some(path) = only_path_argument {
+		Some((path, bridge_or_path_argument))
+	} else {
+		bridge_or_path_argument.map(|path| (PathBuf::from(path), None))
+	}
+}
+
+fn no_path_error(use_json: bool, lookup: &BridgeLookup) {
+	let command = lookup.command;
+	if use_json {
+		error!(
+			id = format!("bridgectl::{}::no_path", lookup.id),
+			suggestions = valuable(&[
+				format!("You can run `bridgectl {command} <bridge> <path>`, `bridgectl {command} --default <path>`, etc."),
+				format!("You can run `bridgectl {command} --help` to get more information."),
+			]),
+			"No path to a snapshot passed to `bridgectl {command}`, but we need a snapshot file!",
+		);
+	} else {
+		error!(
+			"\n{:?}",
+			add_context_to(
+				miette!("No path to a snapshot passed to `bridgectl {command}`, but we need a snapshot file"),
+				[
+					miette!("You can run `bridgectl {command} <bridge> <path>`, `bridgectl {command} --default <path>`, etc."),
+					miette!("You can run `bridgectl {command} --help` to get more information on how to use this command."),
+				]
+				.into_iter(),
+			),
+		);
+	}
+}
+
+async fn load_snapshot(use_json: bool, id: &str, path: &Path) -> Option<ParameterSnapshot> {
+	match ParameterSnapshot::load_from_file(path).await {
+		Ok(snapshot) => Some(snapshot),
+		Err(cause) => {
+			if use_json {
+				error!(
+					id = format!("bridgectl::{id}::could_not_load_snapshot"),
+					?cause,
+					snapshot.path = %path.display(),
+					"could not load snapshot",
+				);
+			} else {
+				error!(
+					"\n{:?}",
+					miette!(
+						help = format!("While trying to load the snapshot at: {}", path.display()),
+						"could not load snapshot",
+					)
+					.wrap_err(cause),
+				);
+			}
+			None
+		}
+	}
+}
+
+fn print_diff(use_json: bool, id: &str, diff: &ParameterDiff) {
+	if diff.is_empty() {
+		info!(
+			id = format!("bridgectl::{id}::no_differences"),
+			"The parameters are identical!",
+		);
+		return;
+	}
+
+	for field in diff.field_differences() {
+		if use_json {
+			info!(
+				id = format!("bridgectl::{id}::field_difference"),
+				parameter.name = field.definition().name(),
+				parameter.old = %field.old_value(),
+				parameter.new = %field.new_value(),
+			);
+		} else {
+			info!(
+				"{}: {} -> {}",
+				field.definition().name(),
+				field.old_value(),
+				field.new_value(),
+			);
+		}
+	}
+	for byte in diff.byte_differences() {
+		let names = definitions_at_index(byte.index())
+			.map(ParameterDefinition::name)
+			.collect::<Vec<_>>();
+		if use_json {
+			info!(
+				id = format!("bridgectl::{id}::byte_difference"),
+				parameter.index = byte.index(),
+				parameter.names = valuable(&names),
+				parameter.old = byte.old_value(),
+				parameter.new = byte.new_value(),
+			);
+		} else if names.is_empty() {
+			info!(
+				"[{}]: 0x{:02x} -> 0x{:02x}",
+				byte.index(),
+				byte.old_value(),
+				byte.new_value(),
+			);
+		} else {
+			info!(
+				"[{}] ({}): 0x{:02x} -> 0x{:02x}",
+				byte.index(),
+				names.join(", "),
+				byte.old_value(),
+				byte.new_value(),
+			);
+		}
+	}
+}
+
+/// Look up the identity of a bridge we already know the IP of, so we can
+/// record/check which bridge a snapshot belongs to.
+///
+/// This is only ever used for metadata, so failing is just a warning.
+async fn identity_for_ip(
+	use_json: bool,
+	bridge_ip: Ipv4Addr,
+	find_by_args: (Duration, u16),
+) -> Option<MionIdentity> {
+	match find_mion(
+		MIONFindBy::Ip(bridge_ip),
+		false,
+		Some(find_by_args.0),
+		Some(find_by_args.1),
+	)
+	.await
+	{
+		Ok(Some(identity)) => Some(identity),
+		Ok(None) => {
+			warn!(
+				id = "bridgectl::parameter_snapshots::could_not_identify_bridge",
+				bridge.ip = %bridge_ip,
+				"Could not find out the name of your bridge, continuing without it.",
+			);
+			None
+		}
+		Err(cause) => {
+			if use_json {
+				warn!(
+					id = "bridgectl::parameter_snapshots::could_not_identify_bridge",
+					?cause,
+					bridge.ip = %bridge_ip,
+					"Could not find out the name of your bridge, continuing without it.",
+				);
+			} else {
+				warn!(
+					"\n{:?}",
+					miette!("Could not find out the name of your bridge, continuing without it.")
+						.wrap_err(cause),
+				);
+			}
+			None
+		}
+	}
+}
@@ -0,0 +1,216 @@
+//! Handles the `claim`, `release`, and `owner` commands, which control which
+//! host has control of a bridge.
+
+use crate::{
+	commands::argv_helpers::{get_a_bridge_ip, BridgeLookup},
+	exit_codes::{
+		CLAIM_BRIDGE_IN_USE, CLAIM_FAILED, CLAIM_NO_AVAILABLE_BRIDGE, CLAIM_NO_BRIDGE_FILTERS,
+		OWNER_FAILED_TO_QUERY, OWNER_NO_AVAILABLE_BRIDGE, OWNER_NO_BRIDGE_FILTERS,
+		RELEASE_BRIDGE_IN_USE, RELEASE_FAILED, RELEASE_NO_AVAILABLE_BRIDGE,
+		RELEASE_NO_BRIDGE_FILTERS,
+	},
+};
+use cat_dev::{
+	errors::{CatBridgeError, NetworkError},
+	mion::session::{claim, force_take, get_owner, release, SessionOwner},
+};
+use miette::miette;
+use std::{net::Ipv4Addr, path::PathBuf, time::Duration};
+use tracing::{error, info, warn};
+
+const CLAIM_LOOKUP: BridgeLookup = BridgeLookup {
+	id: "claim",
+	command: "claim",
+	no_filters_exit_code: CLAIM_NO_BRIDGE_FILTERS,
+	no_bridge_exit_code: CLAIM_NO_AVAILABLE_BRIDGE,
+};
+const RELEASE_LOOKUP: BridgeLookup = BridgeLookup {
+	id: "release",
+	command: "release",
+	no_filters_exit_code: RELEASE_NO_BRIDGE_FILTERS,
+	no_bridge_exit_code: RELEASE_NO_AVAILABLE_BRIDGE,
+};
+const OWNER_LOOKUP: BridgeLookup = BridgeLookup {
+	id: "owner",
+	command: "owner",
+	no_filters_exit_code: OWNER_NO_BRIDGE_FILTERS,
+	no_bridge_exit_code: OWNER_NO_AVAILABLE_BRIDGE,
+};
+
+/// Actual command handler for the `claim` command.
+pub async fn handle_claim(
+	use_json: bool,
+	just_fetch_default: bool,
+	bridge_flag_arguments: (Option<Ipv4Addr>, Option<String>, Option<String>),
+	bridge_name_positional: Option<String>,
+	force: bool,
+	find_by_args: (Duration, u16),
+	host_state_path: Option<PathBuf>,
+) {
+	let bridge_ip = get_a_bridge_ip(
+		use_json,
+		&CLAIM_LOOKUP,
+		just_fetch_default,
+		bridge_flag_arguments,
+		bridge_name_positional,
+		false,
+		find_by_args,
+		host_state_path,
+	)
+	.await;
+
+	let result = if force {
+		force_take(bridge_ip, None).await
+	} else {
+		claim(bridge_ip, None).await.map(|()| None)
+	};
+	match result {
+		Ok(taken_from) => {
+			if use_json {
+				info!(
+					id = "bridgectl::claim::claimed",
+					bridge.ip = %bridge_ip,
+					bridge.previous_owner = taken_from.map(|host| host.to_string()),
+				);
+			} else {
+				if let Some(host) = taken_from {
+					warn!(
+						"Took the bridge at {bridge_ip} from {host}, their device was powered off."
+					);
+				}
+				info!("The bridge at {bridge_ip} is now claimed by this host.");
+			}
+		}
+		Err(CatBridgeError::NetworkError(NetworkError::MionInUseByAnotherHost(host))) => {
+			exit_in_use(use_json, "claim", bridge_ip, &host, CLAIM_BRIDGE_IN_USE);
+		}
+		Err(cause) => {
+			exit_failed(use_json, "claim", bridge_ip, cause, CLAIM_FAILED);
+		}
+	}
+}
+
+/// Actual command handler for the `release` command.
+pub async fn handle_release(
+	use_json: bool,
+	just_fetch_default: bool,
+	bridge_flag_arguments: (Option<Ipv4Addr>, Option<String>, Option<String>),
+	bridge_name_positional: Option<String>,
+	find_by_args: (Duration, u16),
+	host_state_path: Option<PathBuf>,
+) {
+	let bridge_ip = get_a_bridge_ip(
+		use_json,
+		&RELEASE_LOOKUP,
+		just_fetch_default,
+		bridge_flag_arguments,
+		bridge_name_positional,
+		false,
+		find_by_args,
+		host_state_path,
+	)
+	.await;
+
+	match release(bridge_ip, None).await {
+		Ok(()) => {
+			if use_json {
+				info!(id = "bridgectl::release::released", bridge.ip = %bridge_ip);
+			} else {
+				info!("The bridge at {bridge_ip} has been released.");
+			}
+		}
+		Err(CatBridgeError::NetworkError(NetworkError::MionInUseByAnotherHost(host))) => {
+			exit_in_use(use_json, "release", bridge_ip, &host, RELEASE_BRIDGE_IN_USE);
+		}
+		Err(cause) => {
+			exit_failed(use_json, "release", bridge_ip, cause, RELEASE_FAILED);
+		}
+	}
+}
+
+/// Actual command handler for the `owner` command.
+pub async fn handle_owner(
+	use_json: bool,
+	just_fetch_default: bool,
+	bridge_flag_arguments: (Option<Ipv4Addr>, Option<String>, Option<String>),
+	bridge_name_positional: Option<String>,
+	find_by_args: (Duration, u16),
+	host_state_path: Option<PathBuf>,
+) {
+	let bridge_ip = get_a_bridge_ip(
+		use_json,
+		&OWNER_LOOKUP,
+		just_fetch_default,
+		bridge_flag_arguments,
+		bridge_name_positional,
+		false,
+		find_by_args,
+		host_state_path,
+	)
+	.await;
+
+	match get_owner(bridge_ip, None).await {
+		Ok(owner) => {
+			if use_json {
+				info!(
+					id = "bridgectl::owner::owner",
+					bridge.ip = %bridge_ip,
+					bridge.owner = owner.host().map(|host| host.to_string()),
+					bridge.owned_by_us = matches!(owner, SessionOwner::ThisHost(_)),
+				);
+			} else {
+				println!("{owner}");
+			}
+		}
+		Err(cause) => {
+			exit_failed(use_json, "owner", bridge_ip, cause, OWNER_FAILED_TO_QUERY);
+		}
+	}
+}
+
+fn exit_in_use(use_json: bool, command: &str, bridge_ip: Ipv4Addr, host: &str, code: i32) -> ! {
+	if use_json {
+		error!(
+			id = format!("bridgectl::{command}::bridge_in_use"),
+			bridge.ip = %bridge_ip,
+			bridge.owner = host,
+			help = "Another host currently has control of this cat-dev, it needs to release it, or you can take it with `bridgectl claim --force`.",
+		);
+	} else {
+		error!(
+			"\n{:?}",
+			miette!(
+				help = "Another host currently has control of this cat-dev, it needs to release it, or you can take it with `bridgectl claim --force`.",
+				"The cat-dev bridge at {bridge_ip} is in use by: {host}",
+			),
+		);
+	}
+	std::process::exit(code);
+}
+
+fn exit_failed(
+	use_json: bool,
+	command: &str,
+	bridge_ip: Ipv4Addr,
+	cause: CatBridgeError,
+	code: i32,
+) -> ! {
+	if use_json {
+		error!(
+			id = format!("bridgectl::{command}::failed"),
+			?cause,
+			bridge.ip = %bridge_ip,
+			help = "Make sure the cat-dev bridge is still reachable.",
+		);
+	} else {
+		error!(
+			"\n{:?}",
+			miette!(
+				help = "Make sure the cat-dev bridge is still reachable.",
+				"Could not {command} the cat-dev bridge at {bridge_ip}.",
+			)
+			.wrap_err(cause),
+		);
+	}
+	std::process::exit(code);
+}
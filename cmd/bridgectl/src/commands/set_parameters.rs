@@ -1,17 +1,27 @@
 use crate::{
-	commands::argv_helpers::{coalesce_bridge_arguments, get_byte_value, get_default_bridge},
+	commands::argv_helpers::{
+		coalesce_bridge_arguments, ensure_parameters_safe_to_write, get_byte_value,
+		get_default_bridge,
+	},
 	exit_codes::{
 		SET_PARAMS_FAILED_TO_SET_PARAMS, SET_PARAMS_INVALID_PARAMETER_SET_STRING,
-		SET_PARAMS_INVALID_PARAMETER_VALUE, SET_PARAMS_NO_AVAILABLE_BRIDGE,
+		SET_PARAMS_INVALID_PARAMETER_VALUE, SET_PARAMS_NEEDS_FORCE, SET_PARAMS_NO_AVAILABLE_BRIDGE,
 		SET_PARAMS_NO_BRIDGE_FILTERS, SET_PARAMS_NO_PARAMETERS_SPECIFIED,
+		SET_PARAMS_ROLLBACK_FAILED, SET_PARAMS_WRITE_ROLLED_BACK,
 	},
 	knobs::env::{BRIDGE_CURRENT_IP_ADDRESS, BRIDGE_CURRENT_NAME},
 	utils::add_context_to,
 };
-use cat_dev::mion::{
-	discovery::{find_mion, MIONFindBy},
-	parameter::set_parameters,
-	proto::parameter::well_known::ParameterLocationSpecification,
+use cat_dev::{
+	errors::{APIError, CatBridgeError},
+	mion::{
+		discovery::{find_mion, MIONFindBy},
+		parameter::{set_parameters, set_parameters_transactionally},
+		proto::parameter::well_known::{
+			definition_from_parameter_name, index_from_parameter_name,
+			ParameterLocationSpecification,
+		},
+	},
 };
 use miette::miette;
 use std::{net::Ipv4Addr, path::PathBuf, time::Duration};
@@ -25,6 +35,8 @@ use tracing::{error, field::valuable, info};
 	// The parameters are also fairly different types, so the chances of screwing
 	// up passing them in without noticing is low.
 	clippy::too_many_arguments,
+	// These are all independent CLI flags.
+	clippy::fn_params_excessive_bools,
 )]
 pub async fn handle_set_parameters(
 	use_json: bool,
@@ -34,6 +46,8 @@ pub async fn handle_set_parameters(
 	only_params_arguments: Option<String>,
 	find_by_args: (Duration, u16),
 	parameter_space_port: Option<u16>,
+	transactional: bool,
+	force: bool,
 	host_state_path: Option<PathBuf>,
 ) {
 	let had_params_arg = only_params_arguments.is_some();
@@ -68,6 +82,18 @@ pub async fn handle_set_parameters(
 		std::process::exit(SET_PARAMS_NO_PARAMETERS_SPECIFIED);
 	};
 	let parameters_to_set = parse_parameters_to_set_list(use_json, &param_filters);
+	ensure_parameters_safe_to_write(
+		use_json,
+		"set-parameters",
+		parameters_to_set
+			.iter()
+			.filter_map(|(location, _)| match location {
+				ParameterLocationSpecification::Index(index) => Some(usize::from(*index)),
+				ParameterLocationSpecification::NameLike(name) => index_from_parameter_name(name),
+			}),
+		force,
+		SET_PARAMS_NEEDS_FORCE,
+	);
 
 	let bridge_ip = get_a_bridge_ip(
 		use_json,
@@ -79,7 +105,14 @@ pub async fn handle_set_parameters(
 		host_state_path,
 	)
 	.await;
-	do_set_parameters(use_json, bridge_ip, parameter_space_port, parameters_to_set).await;
+	do_set_parameters(
+		use_json,
+		bridge_ip,
+		parameter_space_port,
+		parameters_to_set,
+		transactional,
+	)
+	.await;
 }
 
 async fn do_set_parameters(
@@ -87,18 +120,72 @@ async fn do_set_parameters(
 	ip: Ipv4Addr,
 	parameter_space_port: Option<u16>,
 	parameters_to_set: Vec<(ParameterLocationSpecification, u8)>,
+	transactional: bool,
 ) {
-	match set_parameters(
-		parameters_to_set.into_iter(),
-		ip,
-		parameter_space_port,
-		None,
-	)
-	.await
-	{
-		Ok(_) => {
+	let result = if transactional {
+		set_parameters_transactionally(
+			parameters_to_set.into_iter(),
+			ip,
+			parameter_space_port,
+			None,
+		)
+		.await
+		.map(|_| ())
+	} else {
+		set_parameters(
+			parameters_to_set.into_iter(),
+			ip,
+			parameter_space_port,
+			None,
+		)
+		.await
+		.map(|_| ())
+	};
+
+	match result {
+		Ok(()) => {
 			info!("Successfully set your parameters!");
 		}
+		Err(CatBridgeError::ApiError(APIError::MIONParameterWriteRolledBack(indexes, reason))) => {
+			if use_json {
+				error!(
+					id = "bridgectl::set_parameters::write_rolled_back",
+					parameter.indexes = valuable(&indexes),
+					%reason,
+					"Your parameters did not get written, so we put the original values back.",
+				);
+			} else {
+				error!(
+					"\n{:?}",
+					miette!(
+						help = format!("The parameters at indexes: {indexes:?} were rolled back to their original values."),
+						"Your parameters did not get written ({reason}), so we put the original values back.",
+					),
+				);
+			}
+
+			std::process::exit(SET_PARAMS_WRITE_ROLLED_BACK);
+		}
+		Err(CatBridgeError::ApiError(APIError::MIONParameterRollbackFailed(indexes, reason))) => {
+			if use_json {
+				error!(
+					id = "bridgectl::set_parameters::rollback_failed",
+					parameter.indexes = valuable(&indexes),
+					%reason,
+					"Your parameters did not get written, and we could not put the original values back!",
+				);
+			} else {
+				error!(
+					"\n{:?}",
+					miette!(
+						help = format!("The parameters at indexes: {indexes:?} may be partially written, you may want to check them with `bridgectl dump-parameters`."),
+						"Your parameters did not get written ({reason}), and we could not put the original values back!",
+					),
+				);
+			}
+
+			std::process::exit(SET_PARAMS_ROLLBACK_FAILED);
+		}
 		Err(cause) => {
 			if use_json {
 				error!(
@@ -150,6 +237,46 @@ fn parse_parameters_to_set_list(
 		// Guaranteed to have at least one value, because the equal signs is there.
 		str_value = &str_value[1..];
 
+		if let Some(definition) = definition_from_parameter_name(index_or_name) {
+			match definition.encode(str_value) {
+				// Every byte of a field gets written in the same request, so multi-byte
+				// fields are always updated all at once.
+				Ok(bytes) => {
+					locations.extend(bytes.into_iter().map(|(index, byte)| {
+						(
+							ParameterLocationSpecification::Index(
+								u16::try_from(index).unwrap_or(u16::MAX),
+							),
+							byte,
+						)
+					}));
+					continue;
+				}
+				Err(cause) => {
+					if use_json {
+						error!(
+							id = "bridgectl::set_parameters::bad_parameter_value",
+							?cause,
+							parameter.name = %index_or_name,
+							parameter.value = %str_value,
+							line = "Value is not valid for this parameter.",
+						);
+					} else {
+						error!(
+							"\n{:?}",
+							miette!(
+								help = "See `bridgectl set-params --help` for the parameters we know about.",
+								"Value: {str_value} is not valid for the parameter: {index_or_name}",
+							)
+							.wrap_err(cause),
+						);
+					}
+
+					std::process::exit(SET_PARAMS_INVALID_PARAMETER_VALUE);
+				}
+			}
+		}
+
 		let Ok(specification) = ParameterLocationSpecification::try_from(index_or_name) else {
 			if use_json {
 				error!(
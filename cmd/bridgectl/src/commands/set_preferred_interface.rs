@@ -0,0 +1,145 @@
+//! Handle setting the preferred network interface to discover bridges on.
+//!
+//! This is stored in your `bridge_env.ini` file alongside your bridges, but in
+//! it's own section so the official tools don't notice it.
+
+use crate::{
+	exit_codes::{
+		SET_PREFERRED_INTERFACE_CONFLICTING_ARGUMENTS,
+		SET_PREFERRED_INTERFACE_COULD_NOT_SAVE_TO_DISK, SET_PREFERRED_INTERFACE_INVALID_INTERFACE,
+		SET_PREFERRED_INTERFACE_NO_ARGUMENTS,
+	},
+	utils::{add_context_to, bridge_state_from_path, get_bridge_state_path},
+};
+use cat_dev::{mion::discovery::InterfaceMatcher, BridgeHostState};
+use miette::miette;
+use std::path::PathBuf;
+use tracing::{error, field::valuable, info};
+
+/// Handle the set preferred interface command.
+pub async fn handle_set_preferred_interface(
+	use_json: bool,
+	interface: Option<String>,
+	clear: bool,
+	bridge_state_path: Option<PathBuf>,
+) {
+	let matcher = match (interface, clear) {
+		(Some(interface), true) => {
+			if use_json {
+				error!(
+					id = "bridgectl::set_preferred_interface::conflicting_arguments",
+					%interface,
+					"Cannot set a preferred interface, and clear the preferred interface at the same time.",
+				);
+			} else {
+				error!(
+					"\n{:?}",
+					miette!(
+						help = format!("Interface: {interface} / `--clear`"),
+						"Cannot set a preferred interface, and clear the preferred interface at the same time.",
+					),
+				);
+			}
+
+			std::process::exit(SET_PREFERRED_INTERFACE_CONFLICTING_ARGUMENTS);
+		}
+		(None, false) => {
+			if use_json {
+				error!(
+					id = "bridgectl::set_preferred_interface::no_arguments",
+					suggestions = valuable(&[
+						"You can run `bridgectl set-preferred-interface <name, ip, or subnet>`, or `bridgectl set-preferred-interface --clear`.",
+						"You can run `bridgectl set-preferred-interface --help` to get more information.",
+					]),
+					"No provided arguments to `bridgectl set-preferred-interface`, but we need an interface to prefer!",
+				);
+			} else {
+				error!(
+					"\n{:?}",
+					add_context_to(
+						miette!("No provided arguments to `bridgectl set-preferred-interface`, but we need an interface to prefer"),
+						[
+							miette!("You can run `bridgectl set-preferred-interface <name, ip, or subnet>`, or `bridgectl set-preferred-interface --clear`."),
+							miette!("You can run `bridgectl set-preferred-interface --help` to get more information on how to use this command."),
+						].into_iter(),
+					),
+				);
+			}
+
+			std::process::exit(SET_PREFERRED_INTERFACE_NO_ARGUMENTS);
+		}
+		(None, true) => None,
+		(Some(interface), false) => match InterfaceMatcher::try_from(interface.as_str()) {
+			Ok(matcher) => Some(matcher),
+			Err(cause) => {
+				if use_json {
+					error!(
+						id = "bridgectl::set_preferred_interface::invalid_interface",
+						?cause,
+						%interface,
+						"Invalid network interface to prefer",
+					);
+				} else {
+					error!(
+						"\n{:?}",
+						miette!("Invalid network interface to prefer: {interface}").wrap_err(cause),
+					);
+				}
+
+				std::process::exit(SET_PREFERRED_INTERFACE_INVALID_INTERFACE);
+			}
+		},
+	};
+
+	let state = bridge_state_from_path(
+		get_bridge_state_path(&bridge_state_path, use_json),
+		use_json,
+	)
+	.await;
+	set_preferred_interface(use_json, state, matcher).await;
+}
+
+async fn set_preferred_interface(
+	use_json: bool,
+	mut host_state: BridgeHostState,
+	matcher: Option<InterfaceMatcher>,
+) {
+	let old_preferred = host_state.get_preferred_interface();
+	if let Some(new_preferred) = matcher.as_ref() {
+		host_state.set_preferred_interface(new_preferred);
+	} else {
+		host_state.remove_preferred_interface();
+	}
+
+	if let Err(cause) = host_state.write_to_disk().await {
+		if use_json {
+			error!(
+				id = "bridgectl::set_preferred_interface::could_not_save_to_disk",
+				host_state.path = %host_state.get_path().display(),
+				?cause,
+				"could not save changed to disk",
+			);
+		} else {
+			error!(
+				"\n{:?}",
+				miette!(
+					help = format!(
+						"While trying to update the preferred interface for: {}",
+						host_state.get_path().display()
+					),
+					"could not save changes directly to disk",
+				)
+				.wrap_err(cause),
+			);
+		}
+
+		std::process::exit(SET_PREFERRED_INTERFACE_COULD_NOT_SAVE_TO_DISK);
+	}
+
+	info!(
+		id = "bridgectl::set_preferred_interface::success",
+		preferred.old = ?old_preferred.map(|value| format!("{value}")),
+		preferred.new = ?matcher.map(|value| format!("{value}")),
+		"Updated your preferred interface!"
+	);
+}
@@ -0,0 +1,162 @@
+//! Handles the `signals` command, which reads the hardware signals of a
+//! bridge through `signal_get.cgi`.
+
+use crate::{
+	commands::argv_helpers::{get_a_bridge_ip, BridgeLookup},
+	exit_codes::{
+		SIGNALS_FAILED_TO_GET, SIGNALS_INVALID_SIGNAL, SIGNALS_NO_AVAILABLE_BRIDGE,
+		SIGNALS_NO_BRIDGE_FILTERS,
+	},
+};
+use cat_dev::mion::{cgis::get_signals, proto::cgis::Signal};
+use miette::miette;
+use std::{net::Ipv4Addr, path::PathBuf, time::Duration};
+use tracing::{error, info};
+
+const SIGNALS_LOOKUP: BridgeLookup = BridgeLookup {
+	id: "signals",
+	command: "signals",
+	no_filters_exit_code: SIGNALS_NO_BRIDGE_FILTERS,
+	no_bridge_exit_code: SIGNALS_NO_AVAILABLE_BRIDGE,
+};
+
+/// Actual command handler for the `signals` command.
+#[allow(
+	// It's a CLI parameter, we got a lot of flags.
+	clippy::too_many_arguments,
+)]
+pub async fn handle_signals(
+	use_json: bool,
+	just_fetch_default: bool,
+	bridge_flag_arguments: (Option<Ipv4Addr>, Option<String>, Option<String>),
+	bridge_name_positional: Option<String>,
+	signals_argument: Option<String>,
+	watch_interval: Option<Duration>,
+	find_by_args: (Duration, u16),
+	host_state_path: Option<PathBuf>,
+) {
+	let signals = signals_argument.map_or_else(
+		|| Signal::ALL.to_vec(),
+		|list| parse_signals_list(use_json, &list),
+	);
+
+	let bridge_ip = get_a_bridge_ip(
+		use_json,
+		&SIGNALS_LOOKUP,
+		just_fetch_default,
+		bridge_flag_arguments,
+		bridge_name_positional,
+		false,
+		find_by_args,
+		host_state_path,
+	)
+	.await;
+
+	let mut previous_values = read_signals(use_json, bridge_ip, &signals).await;
+	for (signal, value) in &previous_values {
+		report_signal(use_json, bridge_ip, *signal, value);
+	}
+	let Some(interval) = watch_interval else {
+		return;
+	};
+
+	// When watching only changes are reported, so it's easy to see when
+	// something like cafe powering on happens.
+	loop {
+		tokio::time::sleep(interval).await;
+		let values = read_signals(use_json, bridge_ip, &signals).await;
+		for (signal, value) in &values {
+			let changed = previous_values
+				.iter()
+				.find(|(previous_signal, _)| previous_signal == signal)
+				.map_or(true, |(_, previous_value)| previous_value != value);
+			if changed {
+				report_signal(use_json, bridge_ip, *signal, value);
+			}
+		}
+		previous_values = values;
+	}
+}
+
+/// Read the signals from the bridge, exiting if they couldn't be read.
+async fn read_signals(
+	use_json: bool,
+	bridge_ip: Ipv4Addr,
+	signals: &[Signal],
+) -> Vec<(Signal, String)> {
+	match get_signals(bridge_ip, signals).await {
+		Ok(values) => values,
+		Err(cause) => {
+			if use_json {
+				error!(
+					id = "bridgectl::signals::failed_to_get",
+					?cause,
+					bridge.ip = %bridge_ip,
+					"Could not read the signals from the bridge.",
+				);
+			} else {
+				error!(
+					"\n{:?}",
+					miette!(
+						help = "Please ensure the bridge is running, and reachable.",
+						"Could not read the signals from the bridge at {bridge_ip}.",
+					)
+					.wrap_err(cause),
+				);
+			}
+			std::process::exit(SIGNALS_FAILED_TO_GET);
+		}
+	}
+}
+
+fn report_signal(use_json: bool, bridge_ip: Ipv4Addr, signal: Signal, value: &str) {
+	if use_json {
+		info!(
+			id = "bridgectl::signals::signal",
+			bridge.ip = %bridge_ip,
+			signal.name = %signal,
+			signal.value = %value,
+		);
+	} else {
+		info!(signal.name = %signal, signal.value = %value, "Read signal.");
+	}
+}
+
+/// Parse a list of signal names separated by `,`.
+fn parse_signals_list(use_json: bool, signals_string: &str) -> Vec<Signal> {
+	let mut signals = Vec::new();
+	for name in signals_string.split(',') {
+		match Signal::try_from(name) {
+			Ok(signal) => {
+				if !signals.contains(&signal) {
+					signals.push(signal);
+				}
+			}
+			Err(cause) => {
+				if use_json {
+					error!(
+						id = "bridgectl::signals::invalid_signal",
+						?cause,
+						signal.name = %name,
+						"Unknown signal.",
+					);
+				} else {
+					error!(
+						"\n{:?}",
+						miette!(
+							help = format!(
+								"Known signals are: {}",
+								Signal::ALL.map(|signal| signal.name()).join(", "),
+							),
+							"Cannot read unknown signal: {name}",
+						)
+						.wrap_err(cause),
+					);
+				}
+				std::process::exit(SIGNALS_INVALID_SIGNAL);
+			}
+		}
+	}
+
+	signals
+}
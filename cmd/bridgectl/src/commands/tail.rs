@@ -5,6 +5,7 @@ use crate::{
 };
 use miette::miette;
 use std::path::PathBuf;
+use tokio::io::BufReader;
 use tracing::{error, field::valuable};
 
 /// Tail a serial ports logs until a user manually hits Ctrl-C.
@@ -42,7 +43,7 @@ pub async fn handle_tail(
 		std::process::exit(TAIL_NEEDS_SERIAL_PORT);
 	};
 
-	if let Err(cause) = spawn_serial_log_task(use_json, serial_port, path).await {
+	if let Err(cause) = spawn_serial_log_task(use_json, BufReader::new(serial_port), path).await {
 		if use_json {
 			error!(
 				id = "bridgectl::tail::failed_to_join_task",
@@ -0,0 +1,97 @@
+//! Handles the `version` command, which prints the same version report as
+//! `hostdisplayversion`.
+
+use crate::{
+	commands::argv_helpers::{get_a_bridge_ip, BridgeLookup},
+	exit_codes::{VERSION_FAILED_TO_QUERY, VERSION_NO_AVAILABLE_BRIDGE, VERSION_NO_BRIDGE_FILTERS},
+};
+use cat_dev::{diagnostics::version_report_with_overrides, mion::discovery::MIONFindBy};
+use miette::miette;
+use std::{net::Ipv4Addr, path::PathBuf, time::Duration};
+use tracing::{error, field::valuable, info};
+
+const VERSION_LOOKUP: BridgeLookup = BridgeLookup {
+	id: "version",
+	command: "version",
+	no_filters_exit_code: VERSION_NO_BRIDGE_FILTERS,
+	no_bridge_exit_code: VERSION_NO_AVAILABLE_BRIDGE,
+};
+
+/// Actual command handler for the `version` command.
+pub async fn handle_version(
+	use_json: bool,
+	just_fetch_default: bool,
+	bridge_flag_arguments: (Option<Ipv4Addr>, Option<String>, Option<String>),
+	bridge_name_positional: Option<String>,
+	find_by_args: (Duration, u16),
+	host_state_path: Option<PathBuf>,
+) {
+	let bridge_ip = get_a_bridge_ip(
+		use_json,
+		&VERSION_LOOKUP,
+		just_fetch_default,
+		bridge_flag_arguments,
+		bridge_name_positional,
+		false,
+		find_by_args,
+		host_state_path,
+	)
+	.await;
+
+	let result = version_report_with_overrides(
+		MIONFindBy::Ip(bridge_ip),
+		Some(find_by_args.0),
+		Some(find_by_args.1),
+	)
+	.await;
+	match result {
+		Ok(Some(report)) => {
+			if use_json {
+				info!(
+					id = "bridgectl::version::report",
+					report = valuable(&report),
+				);
+			} else {
+				println!("{report}");
+			}
+		}
+		Ok(None) => {
+			if use_json {
+				error!(
+					id = "bridgectl::version::bridge_did_not_respond",
+					bridge.ip = %bridge_ip,
+					"The bridge did not tell us what versions it is running.",
+				);
+			} else {
+				error!(
+					"\n{:?}",
+					miette!(
+						help = "Please ensure the bridge is running.",
+						"The bridge at {bridge_ip} did not tell us what versions it is running.",
+					),
+				);
+			}
+			std::process::exit(VERSION_FAILED_TO_QUERY);
+		}
+		Err(cause) => {
+			if use_json {
+				error!(
+					id = "bridgectl::version::failed_to_query",
+					?cause,
+					bridge.ip = %bridge_ip,
+					"Could not ask the bridge what versions it is running.",
+				);
+			} else {
+				error!(
+					"\n{:?}",
+					miette!(
+						help = "Please ensure the bridge is running.",
+						"Could not ask the bridge at {bridge_ip} what versions it is running.",
+					)
+					.wrap_err(cause),
+				);
+			}
+			std::process::exit(VERSION_FAILED_TO_QUERY);
+		}
+	}
+}
@@ -50,3 +50,58 @@ pub const CONFLICTING_SERIAL_PORT_ARGS: i32 = 46;
 pub const SERIAL_PORT_CONNECTION_FAILURE: i32 = 47;
 pub const TAIL_NEEDS_SERIAL_PORT: i32 = 48;
 pub const TAIL_COULD_NOT_SPAWN: i32 = 49;
+pub const LIST_INVALID_TARGET: i32 = 50;
+pub const LIST_INVALID_INTERFACE: i32 = 51;
+pub const SET_PREFERRED_INTERFACE_CONFLICTING_ARGUMENTS: i32 = 52;
+pub const SET_PREFERRED_INTERFACE_NO_ARGUMENTS: i32 = 53;
+pub const SET_PREFERRED_INTERFACE_INVALID_INTERFACE: i32 = 54;
+pub const SET_PREFERRED_INTERFACE_COULD_NOT_SAVE_TO_DISK: i32 = 55;
+pub const SNAPSHOT_PARAMS_NO_PATH: i32 = 56;
+pub const SNAPSHOT_PARAMS_NO_BRIDGE_FILTERS: i32 = 57;
+pub const SNAPSHOT_PARAMS_NO_AVAILABLE_BRIDGE: i32 = 58;
+pub const SNAPSHOT_PARAMS_FAILED_TO_GET_PARAMS: i32 = 59;
+pub const SNAPSHOT_PARAMS_COULD_NOT_SAVE_TO_DISK: i32 = 60;
+pub const DIFF_PARAMS_COULD_NOT_LOAD_SNAPSHOT: i32 = 61;
+pub const RESTORE_PARAMS_NO_PATH: i32 = 62;
+pub const RESTORE_PARAMS_COULD_NOT_LOAD_SNAPSHOT: i32 = 63;
+pub const RESTORE_PARAMS_NO_BRIDGE_FILTERS: i32 = 64;
+pub const RESTORE_PARAMS_NO_AVAILABLE_BRIDGE: i32 = 65;
+pub const RESTORE_PARAMS_FAILED_TO_RESTORE: i32 = 66;
+pub const SET_PARAMS_WRITE_ROLLED_BACK: i32 = 67;
+pub const SET_PARAMS_ROLLBACK_FAILED: i32 = 68;
+pub const SET_PARAMS_NEEDS_FORCE: i32 = 69;
+pub const RESTORE_PARAMS_NEEDS_FORCE: i32 = 70;
+pub const BOOT_BRIDGE_IN_USE: i32 = 71;
+pub const CONFIGURE_NO_SETTINGS: i32 = 72;
+pub const CONFIGURE_INVALID_SETTING: i32 = 73;
+pub const CONFIGURE_NO_BRIDGE_FILTERS: i32 = 74;
+pub const CONFIGURE_NO_AVAILABLE_BRIDGE: i32 = 75;
+pub const CONFIGURE_FAILED_TO_SET: i32 = 76;
+pub const SIGNALS_INVALID_SIGNAL: i32 = 77;
+pub const SIGNALS_NO_BRIDGE_FILTERS: i32 = 78;
+pub const SIGNALS_NO_AVAILABLE_BRIDGE: i32 = 79;
+pub const SIGNALS_FAILED_TO_GET: i32 = 80;
+pub const VERSION_NO_BRIDGE_FILTERS: i32 = 88;
+pub const VERSION_NO_AVAILABLE_BRIDGE: i32 = 89;
+pub const VERSION_FAILED_TO_QUERY: i32 = 90;
+pub const BOOT_NO_PCFS_ROOT: i32 = 91;
+pub const BOOT_PCFS_SERVER_FAILED: i32 = 92;
+pub const BOOT_INVALID_BOOT_MODE: i32 = 93;
+pub const BOOT_INVALID_CAFE_ROOT: i32 = 94;
+pub const BOOT_SET_SDK_VERSION_FAILED: i32 = 95;
+pub const BOOT_SET_BOOT_MODE_FAILED: i32 = 96;
+pub const BOOT_NEVER_READY: i32 = 97;
+pub const CLAIM_NO_BRIDGE_FILTERS: i32 = 98;
+pub const CLAIM_NO_AVAILABLE_BRIDGE: i32 = 99;
+pub const CLAIM_BRIDGE_IN_USE: i32 = 100;
+pub const CLAIM_FAILED: i32 = 101;
+pub const RELEASE_NO_BRIDGE_FILTERS: i32 = 102;
+pub const RELEASE_NO_AVAILABLE_BRIDGE: i32 = 103;
+pub const RELEASE_BRIDGE_IN_USE: i32 = 104;
+pub const RELEASE_FAILED: i32 = 105;
+pub const OWNER_NO_BRIDGE_FILTERS: i32 = 106;
+pub const OWNER_NO_AVAILABLE_BRIDGE: i32 = 107;
+pub const OWNER_FAILED_TO_QUERY: i32 = 108;
+pub const LIST_INVALID_PACKETS_PER_SECOND: i32 = 109;
+pub const RESTORE_PARAMS_DIFFERENT_BRIDGE: i32 = 110;
+pub const BOOT_CONFLICTING_FLAGS: i32 = 112;
@@ -1,7 +1,11 @@
 //! Defines the command line interface a.k.a. all the arguments & flags.
 
+use cat_dev::{
+	fsemul::DEFAULT_PCFS_PORT,
+	mion::proto::parameter::well_known::{ParameterSafety, PARAMETER_SCHEMA},
+};
 use clap::Parser;
-use std::{net::Ipv4Addr, path::PathBuf};
+use std::{fmt::Write, net::Ipv4Addr, path::PathBuf};
 
 #[derive(Parser, Debug)]
 #[clap(disable_help_flag = true, disable_help_subcommand = true)]
@@ -136,9 +140,70 @@ pub enum Subcommands {
 			long = "boot-without-pcfs",
 			alias = "boot_without_pcfs",
 			help = "Just boot the device without PCFS",
-			long_help = "Disable almost all other options, and just boot the device without any connection to the PC."
+			long_help = "Just power on the device without any connection to the PC, the same way the original tools do. The boot mode, and SDK version on the bridge are left as is, so this can't be used with `--boot-mode`, `--cafe-root`, `--ready-marker`, or `--ready-timeout-seconds`."
 		)]
 		without_pcfs: bool,
+		#[arg(
+			long = "experimental-pcfs",
+			alias = "experimental_pcfs",
+			conflicts_with = "without_pcfs",
+			help = "Acknowledge that serving a title over PCFS is experimental.",
+			long_help = "The PCFS protocol is our own reconstruction, and has not been checked against a real device. Booting over PCFS (any boot mode other than `NAND`) is refused unless you pass this flag, use `--boot-without-pcfs` to just power on the device."
+		)]
+		experimental_pcfs: bool,
+		#[arg(
+			long = "pcfs-root",
+			alias = "pcfs_root",
+			conflicts_with = "without_pcfs",
+			help = "The title directory to serve to the device over PCFS.",
+			long_help = "The title directory to serve to the device over PCFS, it must contain a `code`, `content`, and `meta` directory. The bridge needs to know the IP of this PC, which can be set with `bridgectl configure host_ip=<ip>`."
+		)]
+		pcfs_root: Option<PathBuf>,
+		#[arg(
+			long = "pcfs-read-write",
+			alias = "pcfs_read_write",
+			conflicts_with = "without_pcfs",
+			help = "Allow the device to change files in the PCFS root.",
+			long_help = "Allow the device to create, write, rename, and remove files in the PCFS root, by default the device can only read files."
+		)]
+		pcfs_read_write: bool,
+		#[arg(
+			long = "pcfs-port",
+			alias = "pcfs_port",
+			conflicts_with = "without_pcfs",
+			default_value_t = DEFAULT_PCFS_PORT,
+			help = "The port to serve PCFS on.",
+			long_help = "The port to serve PCFS on, this needs to match the `atapi_port` the bridge is configured with."
+		)]
+		pcfs_port: u16,
+		#[arg(
+			long = "boot-mode",
+			alias = "boot_mode",
+			help = "Set where the device boots from (`NAND`/`PCFS`/`DUAL`/`HDD`).",
+			long_help = "Set where the device boots from before powering it on, one of `NAND`, `PCFS`, `DUAL`, or `HDD`. Anything other than `NAND` serves the PCFS root while the device is running. Setting the boot mode is experimental, so if this isn't specified the bridges boot mode is left as is, and the PCFS root is served. This can't be used with `--boot-without-pcfs`."
+		)]
+		boot_mode: Option<String>,
+		#[arg(
+			long = "cafe-root",
+			alias = "cafe_root",
+			help = "The SDK to set the bridges SDK version from.",
+			long_help = "The root of the SDK you're booting with, the SDK version is read from here and written to the bridge before booting. If not specified this is read from the `CAFE_ROOT` environment variable, and if that isn't set the bridges SDK version is left as is."
+		)]
+		cafe_root: Option<PathBuf>,
+		#[arg(
+			long = "ready-marker",
+			alias = "ready_marker",
+			help = "The line on the serial port that means the device has finished booting.",
+			long_help = "The line on the serial port that means the device has finished booting, what gets printed differs between SDK versions so you may need to change this. This is only used if a serial port has been specified."
+		)]
+		ready_marker: Option<String>,
+		#[arg(
+			long = "ready-timeout-seconds",
+			alias = "ready_timeout_seconds",
+			help = "The amount of seconds to wait for the device to finish booting (by default this is 300).",
+			long_help = "The amount of seconds to wait for the ready marker to show up on the serial port before giving up. This is only used if a serial port has been specified."
+		)]
+		ready_timeout: Option<u64>,
 		#[arg(
 			short = 's',
 			long = "serial-port-path",
@@ -154,6 +219,127 @@ pub enum Subcommands {
 		)]
 		serial_port_positional: Option<PathBuf>,
 	},
+	/// Take control of a MION for this host, so nobody else can use it.
+	#[command(name = "claim", visible_alias = "take")]
+	Claim {
+		#[arg(
+			short = 'd',
+			long = "default",
+			help = "Claim the default bridge.",
+			long_help = "A shortcut to claim the default bridge, not needing to specify any other lookup fields."
+		)]
+		default: bool,
+		#[arg(
+			short = 'i',
+			long = "ip",
+			help = "The IP Address of the bridge to claim.",
+			long_help = "Claim the bridge located at this IP address."
+		)]
+		bridge_ipaddr: Option<Ipv4Addr>,
+		#[arg(
+			short = 'm',
+			long = "mac-address",
+			alias = "mac_address",
+			help = "The Mac Address of the bridge to claim.",
+			long_help = "Claim the bridge found by searching for the bridge with this MAC Address."
+		)]
+		bridge_mac: Option<String>,
+		#[arg(
+			short = 'n',
+			long = "name",
+			visible_alias = "bridge",
+			help = "The Name of the bridge to claim.",
+			long_help = "Claim the bridge found by searching for the bridge with this Name."
+		)]
+		bridge_name: Option<String>,
+		#[arg(
+			index = 1,
+			help = "Search for a bridge with a particular name/ip/mac address.",
+			long_help = "If you don't want to specify what bridge you want to claim with `--ip`, `--mac-address`, or `--name` you can just pass in a positional argument where we can guess how to find the bridge."
+		)]
+		bridge_name_positional: Option<String>,
+		#[arg(
+			short = 'f',
+			long = "force",
+			help = "Take the bridge even if another host is using it, by impersonating that host.",
+			long_help = "By default a bridge another host is using is refused. This flag impersonates the other host: it sends a power off claiming to be that host (the bridge can't tell the difference), and then claims it, pulling the device out from under whoever was using it. This is unverified, a real bridge may track owners differently."
+		)]
+		force: bool,
+	},
+	/// Change the settings of a MION, like the ports it serves on.
+	#[command(
+		name = "configure",
+		visible_alias = "config",
+		after_long_help = configure_settings_help(),
+	)]
+	Configure {
+		#[arg(
+			short = 'd',
+			long = "default",
+			help = "Configure the default bridge.",
+			long_help = "A shortcut to configure the default bridge, not needing to specify any other lookup fields."
+		)]
+		default: bool,
+		#[arg(
+			short = 'i',
+			long = "ip",
+			help = "The IP of the bridge to configure.",
+			long_help = "Configure the bridge located at this IP address."
+		)]
+		bridge_ipaddr: Option<Ipv4Addr>,
+		#[arg(
+			short = 'm',
+			long = "mac-address",
+			alias = "mac_address",
+			help = "The Mac Address of the bridge to configure.",
+			long_help = "Configure the bridge found by searching for the bridge with this MAC Address."
+		)]
+		bridge_mac: Option<String>,
+		#[arg(
+			short = 'n',
+			long = "name",
+			help = "The Name of the bridge to configure.",
+			long_help = "Configure the bridge found by searching for the bridge with this Name."
+		)]
+		bridge_name: Option<String>,
+		#[arg(
+			index = 1,
+			help = "Search for a bridge with a particular name/ip/mac address.",
+			long_help = "If you don't want to specify what bridge you want to configure with `--ip`, `--mac-address`, or `--name` you can just pass in a positional argument where we can guess how to find the bridge."
+		)]
+		bridge_name_positional: Option<String>,
+		#[arg(
+			index = 2,
+			help = "The list of settings to change in the form of `(name)=(value)`.",
+			long_help = "The list of settings to change in the form of `(name)=(value)`. You can specify multiple settings to change by using ',', every setting is checked before any are changed."
+		)]
+		settings_positional: Option<String>,
+		#[arg(
+			long = "experimental",
+			help = "Allow changing experimental settings.",
+			long_help = "Only `atapi_port` is known to be accepted by a real bridge. Every other setting has a name we've guessed, and a real bridge may reject, or silently ignore it. These are refused unless you pass this flag."
+		)]
+		experimental: bool,
+	},
+	/// Compare two parameter space snapshots, and show what changed.
+	#[command(
+		name = "diff-parameters",
+		visible_aliases = ["diff-params", "diff_parameters"],
+	)]
+	DiffParameters {
+		#[arg(
+			index = 1,
+			help = "The path to the older snapshot.",
+			long_help = "The path to the snapshot to compare from, as created by `bridgectl snapshot-parameters`."
+		)]
+		old_snapshot: PathBuf,
+		#[arg(
+			index = 2,
+			help = "The path to the newer snapshot.",
+			long_help = "The path to the snapshot to compare to, as created by `bridgectl snapshot-parameters`."
+		)]
+		new_snapshot: PathBuf,
+	},
 	/// Dump the entire parameter space of a MION.
 	#[command(
 		name = "dump-parameters",
@@ -254,6 +440,7 @@ pub enum Subcommands {
 	#[command(
 		name = "get-parameters",
 		visible_aliases = ["gp", "get_parameters"],
+		after_long_help = known_parameters_help(),
 	)]
 	GetParameters {
 		#[arg(
@@ -326,6 +513,39 @@ pub enum Subcommands {
 			long_help = "Rather than outputting the information as a bunch of log lines, output the information in a table"
 		)]
 		output_as_table: bool,
+		#[arg(
+			long = "target",
+			help = "Ask an IP, CIDR range, or `broadcast:<ip>` directly for bridges.",
+			long_help = "Send announcements directly to a target, rather than broadcasting on your local interfaces. This can be an IP (`10.0.1.2`), a CIDR range (`10.0.1.0/24`), or a directed broadcast (`broadcast:10.0.1.255`). Useful for finding bridges on another subnet/VLAN without a broadcast relay. When any targets are specified we ONLY ask the targets, and do not broadcast on your local interfaces. Can be specified multiple times."
+		)]
+		targets: Vec<String>,
+		#[arg(
+			long = "packets-per-second",
+			alias = "packets_per_second",
+			help = "How many announcements to send per second when asking targets directly.",
+			long_help = "How many announcements to send per second when asking targets directly with `--target`, this is useful to avoid flooding slow networks when sweeping large CIDR ranges. Must be between 1, and 1000000. By default this is 200."
+		)]
+		packets_per_second: Option<u32>,
+		#[arg(
+			long = "interface",
+			help = "Only broadcast on this interface (by name, IP, or subnet).",
+			long_help = "Only broadcast on the local network interfaces that match, this can be an interface name (`eth0`), an IP on the interface (`192.168.7.2`), or a subnet (`192.168.7.0/24`). When specified this replaces your preferred interface. Can be specified multiple times."
+		)]
+		interfaces: Vec<String>,
+		#[arg(
+			long = "exclude-interface",
+			alias = "exclude_interface",
+			help = "Never broadcast on this interface (by name, IP, or subnet).",
+			long_help = "Never broadcast on the local network interfaces that match, this can be an interface name (`docker0`), an IP on the interface (`10.8.0.2`), or a subnet (`172.17.0.0/16`). Useful for skipping VPN tunnels, or container bridges. Can be specified multiple times."
+		)]
+		exclude_interfaces: Vec<String>,
+		#[arg(
+			long = "all-interfaces",
+			alias = "all_interfaces",
+			help = "Ignore your preferred interface, and broadcast on every interface.",
+			long_help = "By default if you've set a preferred interface with `bridgectl set-preferred-interface` we only broadcast on that interface, this ignores it and broadcasts on every interface (still respecting `--exclude-interface`)."
+		)]
+		all_interfaces: bool,
 	},
 	/// List the available serial ports, to try and find the devices you can use
 	/// to tail logs from.
@@ -334,6 +554,86 @@ pub enum Subcommands {
 		visible_aliases = ["ls-serial-ports", "lssp", "list_serial_ports", "ls_serial_ports"],
 	)]
 	ListSerialPorts {},
+	/// Print which host currently has control of a MION, if any.
+	#[command(name = "owner", visible_alias = "who-owns")]
+	Owner {
+		#[arg(
+			short = 'd',
+			long = "default",
+			help = "Check the owner of the default bridge.",
+			long_help = "A shortcut to check the owner of the default bridge, not needing to specify any other lookup fields."
+		)]
+		default: bool,
+		#[arg(
+			short = 'i',
+			long = "ip",
+			help = "The IP Address of the bridge to check the owner of.",
+			long_help = "Check the owner of the bridge located at this IP address."
+		)]
+		bridge_ipaddr: Option<Ipv4Addr>,
+		#[arg(
+			short = 'm',
+			long = "mac-address",
+			alias = "mac_address",
+			help = "The Mac Address of the bridge to check the owner of.",
+			long_help = "Check the owner of the bridge found by searching for the bridge with this MAC Address."
+		)]
+		bridge_mac: Option<String>,
+		#[arg(
+			short = 'n',
+			long = "name",
+			visible_alias = "bridge",
+			help = "The Name of the bridge to check the owner of.",
+			long_help = "Check the owner of the bridge found by searching for the bridge with this Name."
+		)]
+		bridge_name: Option<String>,
+		#[arg(
+			index = 1,
+			help = "Search for a bridge with a particular name/ip/mac address.",
+			long_help = "If you don't want to specify what bridge you want to check the owner of with `--ip`, `--mac-address`, or `--name` you can just pass in a positional argument where we can guess how to find the bridge."
+		)]
+		bridge_name_positional: Option<String>,
+	},
+	/// Give up control of a MION this host has claimed, powering off the device.
+	#[command(name = "release")]
+	Release {
+		#[arg(
+			short = 'd',
+			long = "default",
+			help = "Release the default bridge.",
+			long_help = "A shortcut to release the default bridge, not needing to specify any other lookup fields."
+		)]
+		default: bool,
+		#[arg(
+			short = 'i',
+			long = "ip",
+			help = "The IP Address of the bridge to release.",
+			long_help = "Release the bridge located at this IP address."
+		)]
+		bridge_ipaddr: Option<Ipv4Addr>,
+		#[arg(
+			short = 'm',
+			long = "mac-address",
+			alias = "mac_address",
+			help = "The Mac Address of the bridge to release.",
+			long_help = "Release the bridge found by searching for the bridge with this MAC Address."
+		)]
+		bridge_mac: Option<String>,
+		#[arg(
+			short = 'n',
+			long = "name",
+			visible_alias = "bridge",
+			help = "The Name of the bridge to release.",
+			long_help = "Release the bridge found by searching for the bridge with this Name."
+		)]
+		bridge_name: Option<String>,
+		#[arg(
+			index = 1,
+			help = "Search for a bridge with a particular name/ip/mac address.",
+			long_help = "If you don't want to specify what bridge you want to release with `--ip`, `--mac-address`, or `--name` you can just pass in a positional argument where we can guess how to find the bridge."
+		)]
+		bridge_name_positional: Option<String>,
+	},
 	/// Remove a bridge from your local configuration file.
 	#[command(name = "remove", visible_alias = "rm")]
 	Remove {
@@ -351,6 +651,68 @@ pub enum Subcommands {
 		)]
 		bridge_name_positional: Option<String>,
 	},
+	/// Restore the parameter space of a MION from a snapshot, only writing what differs.
+	#[command(
+		name = "restore-parameters",
+		visible_aliases = ["restore-params", "restore_parameters"],
+	)]
+	RestoreParameters {
+		#[arg(
+			short = 'd',
+			long = "default",
+			help = "Restore the parameters of the default bridge.",
+			long_help = "A shortcut to restore the parameters of the default bridge, not needing to specify any other lookup fields."
+		)]
+		default: bool,
+		#[arg(
+			short = 'i',
+			long = "ip",
+			help = "The IP Address of the bridge to restore parameters on.",
+			long_help = "Restore the parameters of the bridge located at this IP address."
+		)]
+		bridge_ipaddr: Option<Ipv4Addr>,
+		#[arg(
+			short = 'm',
+			long = "mac-address",
+			alias = "mac_address",
+			help = "The Mac Address of the bridge to restore parameters on.",
+			long_help = "Restore the parameters of the bridge found by searching for the bridge with this MAC Address."
+		)]
+		bridge_mac: Option<String>,
+		#[arg(
+			short = 'n',
+			long = "name",
+			help = "The Name of the bridge to restore parameters on.",
+			long_help = "Restore the parameters of the bridge found by searching for the bridge with this Name."
+		)]
+		bridge_name: Option<String>,
+		#[arg(
+			index = 1,
+			help = "Search for a bridge with a particular name/ip/mac address.",
+			long_help = "If you don't want to specify what bridge you want to restore parameters on with `--ip`, `--mac-address`, or `--name` you can just pass in a positional argument where we can guess how to find the bridge. If this is the only positional argument it is treated as the path to the snapshot."
+		)]
+		bridge_name_positional: Option<String>,
+		#[arg(
+			index = 2,
+			help = "The path to the snapshot file.",
+			long_help = "The path to the snapshot file, if you only pass one positional argument it is used as the path instead of the bridge."
+		)]
+		snapshot_path_positional: Option<PathBuf>,
+		#[arg(
+			short = 'p',
+			long = "port",
+			help = "The 'parameter space' port to use.",
+			long_help = "The 'parameter space' port to use. Official tools don't support changing this, but it is configurable in `setup.cgi`."
+		)]
+		parameter_space_port: Option<u16>,
+		#[arg(
+			short = 'f',
+			long = "force",
+			help = "Allow writing parameters that can cause problems, or restoring a snapshot taken from a different bridge.",
+			long_help = "Some parameters (like the NAND mode) can leave your bridge, or console in a state that needs to be fixed by hand. Writing these is refused unless you pass this flag, see the list of known parameters for which ones are risky. Restoring a snapshot that was taken from a bridge with a different MAC address is also refused unless you pass this flag."
+		)]
+		force: bool,
+	},
 	/// Used to change the default bridge we load up automatically.
 	#[command(name = "set-default", visible_alias = "set_default")]
 	SetDefault {
@@ -372,6 +734,7 @@ pub enum Subcommands {
 	#[command(
 		name = "set-parameters",
 		visible_aliases = ["sp", "set_parameters"],
+		after_long_help = known_parameters_help(),
 	)]
 	SetParameters {
 		#[arg(
@@ -422,6 +785,155 @@ pub enum Subcommands {
 			long_help = "The 'parameter space' port to use. Official tools don't support changing this, but it is configurable in `setup.cgi`."
 		)]
 		parameter_space_port: Option<u16>,
+		#[arg(
+			short = 't',
+			long = "transactional",
+			help = "Verify the parameters were written, and roll back if they weren't.",
+			long_help = "Re-read the parameters after writing them to make sure every change actually stuck. If the write fails, or any value doesn't match, the original values are written back, and we tell you which indexes were rolled back."
+		)]
+		transactional: bool,
+		#[arg(
+			short = 'f',
+			long = "force",
+			help = "Allow writing parameters that can cause problems.",
+			long_help = "Some parameters (like the NAND mode) can leave your bridge, or console in a state that needs to be fixed by hand. Writing these is refused unless you pass this flag, see the list of known parameters for which ones are risky."
+		)]
+		force: bool,
+	},
+	/// Set the network interface we prefer to discover bridges on.
+	#[command(
+		name = "set-preferred-interface",
+		visible_alias = "set_preferred_interface"
+	)]
+	SetPreferredInterface {
+		#[arg(
+			index = 1,
+			help = "The interface name, IP, or subnet to prefer.",
+			long_help = "The network interface to prefer when discovering bridges, this can be an interface name (`eth0`), an IP on the interface (`192.168.7.2`), or a subnet (`192.168.7.0/24`). Conflicts with `--clear`."
+		)]
+		interface: Option<String>,
+		#[arg(
+			long = "clear",
+			help = "Remove the preferred interface, and go back to broadcasting everywhere.",
+			long_help = "Remove the preferred interface, so discovery goes back to broadcasting on every interface. Conflicts with the positional argument."
+		)]
+		clear: bool,
+	},
+	/// Read the hardware signals of a MION, exactly as the MION reports them.
+	#[command(name = "signals", visible_alias = "sig")]
+	Signals {
+		#[arg(
+			short = 'd',
+			long = "default",
+			help = "Read the signals of the default bridge.",
+			long_help = "A shortcut to read the signals of the default bridge, not needing to specify any other lookup fields."
+		)]
+		default: bool,
+		#[arg(
+			short = 'i',
+			long = "ip",
+			help = "The IP Address of the bridge to read signals from.",
+			long_help = "Read the signals of the bridge located at this IP address."
+		)]
+		bridge_ipaddr: Option<Ipv4Addr>,
+		#[arg(
+			short = 'm',
+			long = "mac-address",
+			alias = "mac_address",
+			help = "The Mac Address of the bridge to read signals from.",
+			long_help = "Read the signals of the bridge found by searching for the bridge with this MAC Address."
+		)]
+		bridge_mac: Option<String>,
+		#[arg(
+			short = 'n',
+			long = "name",
+			help = "The Name of the bridge to read signals from.",
+			long_help = "Read the signals of the bridge found by searching for the bridge with this Name."
+		)]
+		bridge_name: Option<String>,
+		#[arg(
+			index = 1,
+			help = "Search for a bridge with a particular name/ip/mac address.",
+			long_help = "If you don't want to specify what bridge you want to read signals from with `--ip`, `--mac-address`, or `--name` you can just pass in a positional argument where we can guess how to find the bridge."
+		)]
+		bridge_name_positional: Option<String>,
+		#[arg(
+			short = 's',
+			long = "signals",
+			alias = "signal",
+			help = "The list of signals to read, separated by ','.",
+			long_help = "The list of signals to read separated by ',', e.g. `VDD2`. Every signal is read if this isn't specified. The only known signal is: VDD2. Values are shown exactly as the bridge reports them, as we don't know what they mean."
+		)]
+		signals: Option<String>,
+		#[arg(
+			short = 'w',
+			long = "watch",
+			help = "Keep reading the signals, and report whenever they change.",
+			long_help = "Keep reading the signals until stopped, after the first read only signals that have changed are reported."
+		)]
+		watch: bool,
+		#[arg(
+			long = "interval",
+			default_value_t = 1,
+			help = "How many seconds to wait between reads when watching.",
+			long_help = "How many seconds to wait between each read of the signals when `--watch` is passed."
+		)]
+		interval_seconds: u64,
+	},
+	/// Save a snapshot of the entire parameter space of a MION to a file.
+	#[command(
+		name = "snapshot-parameters",
+		visible_aliases = ["snapshot-params", "snapshot_parameters"],
+	)]
+	SnapshotParameters {
+		#[arg(
+			short = 'd',
+			long = "default",
+			help = "Snapshot the parameters of the default bridge.",
+			long_help = "A shortcut to snapshot the parameters of the default bridge, not needing to specify any other lookup fields."
+		)]
+		default: bool,
+		#[arg(
+			short = 'i',
+			long = "ip",
+			help = "The IP Address of the bridge to snapshot parameters of.",
+			long_help = "Snapshot the parameters of the bridge located at this IP address."
+		)]
+		bridge_ipaddr: Option<Ipv4Addr>,
+		#[arg(
+			short = 'm',
+			long = "mac-address",
+			alias = "mac_address",
+			help = "The Mac Address of the bridge to snapshot parameters of.",
+			long_help = "Snapshot the parameters of the bridge found by searching for the bridge with this MAC Address."
+		)]
+		bridge_mac: Option<String>,
+		#[arg(
+			short = 'n',
+			long = "name",
+			help = "The Name of the bridge to snapshot parameters of.",
+			long_help = "Snapshot the parameters of the bridge found by searching for the bridge with this Name."
+		)]
+		bridge_name: Option<String>,
+		#[arg(
+			index = 1,
+			help = "Search for a bridge with a particular name/ip/mac address.",
+			long_help = "If you don't want to specify what bridge you want to snapshot parameters of with `--ip`, `--mac-address`, or `--name` you can just pass in a positional argument where we can guess how to find the bridge. If this is the only positional argument it is treated as the path to the snapshot."
+		)]
+		bridge_name_positional: Option<String>,
+		#[arg(
+			index = 2,
+			help = "The path to the snapshot file.",
+			long_help = "The path to the snapshot file, if you only pass one positional argument it is used as the path instead of the bridge."
+		)]
+		snapshot_path_positional: Option<PathBuf>,
+		#[arg(
+			short = 'p',
+			long = "port",
+			help = "The 'parameter space' port to use.",
+			long_help = "The 'parameter space' port to use. Official tools don't support changing this, but it is configurable in `setup.cgi`."
+		)]
+		parameter_space_port: Option<u16>,
 	},
 	/// Tail the logs of a serial port.
 	#[command(
@@ -444,10 +956,54 @@ pub enum Subcommands {
 		)]
 		serial_port_positional: Option<PathBuf>,
 	},
+	/// Print the versions of the host tooling, and everything running on a MION.
+	#[command(name = "version", visible_alias = "ver")]
+	Version {
+		#[arg(
+			short = 'd',
+			long = "default",
+			help = "Print the versions of the default bridge.",
+			long_help = "A shortcut to print the versions of the default bridge, not needing to specify any other lookup fields."
+		)]
+		default: bool,
+		#[arg(
+			short = 'i',
+			long = "ip",
+			help = "The IP Address of the bridge to print the versions of.",
+			long_help = "Print the versions of the bridge located at this IP address."
+		)]
+		bridge_ipaddr: Option<Ipv4Addr>,
+		#[arg(
+			short = 'm',
+			long = "mac-address",
+			alias = "mac_address",
+			help = "The Mac Address of the bridge to print the versions of.",
+			long_help = "Print the versions of the bridge found by searching for the bridge with this MAC Address."
+		)]
+		bridge_mac: Option<String>,
+		#[arg(
+			short = 'n',
+			long = "name",
+			visible_alias = "bridge",
+			help = "The Name of the bridge to print the versions of.",
+			long_help = "Print the versions of the bridge found by searching for the bridge with this Name."
+		)]
+		bridge_name: Option<String>,
+		#[arg(
+			index = 1,
+			help = "Search for a bridge with a particular name/ip/mac address.",
+			long_help = "If you don't want to specify what bridge you want to print the versions of with `--ip`, `--mac-address`, or `--name` you can just pass in a positional argument where we can guess how to find the bridge."
+		)]
+		bridge_name_positional: Option<String>,
+	},
 }
 impl Subcommands {
 	/// If this subcommand matches a particular name.
-	#[allow(unused)]
+	#[allow(
+		unused,
+		// One arm per subcommand, there's no real way to make this shorter.
+		clippy::too_many_lines,
+	)]
 	#[must_use]
 	pub fn name_matches(&self, name: &str) -> bool {
 		match self {
@@ -465,9 +1021,38 @@ impl Subcommands {
 				bridge_name,
 				bridge_name_positional,
 				without_pcfs,
+				experimental_pcfs,
+				pcfs_root,
+				pcfs_read_write,
+				pcfs_port,
+				boot_mode,
+				cafe_root,
+				ready_marker,
+				ready_timeout,
 				serial_port_flag,
 				serial_port_positional,
 			} => name == "boot" || name == "power-on" || name == "power_on",
+			Self::Claim {
+				default,
+				bridge_ipaddr,
+				bridge_mac,
+				bridge_name,
+				bridge_name_positional,
+				force,
+			} => name == "claim" || name == "take",
+			Self::Configure {
+				default,
+				bridge_ipaddr,
+				bridge_mac,
+				bridge_name,
+				bridge_name_positional,
+				settings_positional,
+				experimental,
+			} => name == "configure" || name == "config",
+			Self::DiffParameters {
+				old_snapshot,
+				new_snapshot,
+			} => name == "diff-parameters" || name == "diff_parameters" || name == "diff-params",
 			Self::DumpParameters {
 				default,
 				bridge_ipaddr,
@@ -497,6 +1082,11 @@ impl Subcommands {
 			Self::List {
 				use_cache,
 				output_as_table,
+				targets,
+				packets_per_second,
+				interfaces,
+				exclude_interfaces,
+				all_interfaces,
 			} => name == "list" || name == "ls",
 			Self::ListSerialPorts {} => {
 				name == "list-serial-ports"
@@ -505,10 +1095,38 @@ impl Subcommands {
 					|| name == "ls_serial_ports"
 					|| name == "lssp"
 			}
+			Self::Owner {
+				default,
+				bridge_ipaddr,
+				bridge_mac,
+				bridge_name,
+				bridge_name_positional,
+			} => name == "owner" || name == "who-owns",
+			Self::Release {
+				default,
+				bridge_ipaddr,
+				bridge_mac,
+				bridge_name,
+				bridge_name_positional,
+			} => name == "release",
 			Self::Remove {
 				bridge_name,
 				bridge_name_positional,
 			} => name == "remove" || name == "rm",
+			Self::RestoreParameters {
+				default,
+				bridge_ipaddr,
+				bridge_mac,
+				bridge_name,
+				bridge_name_positional,
+				snapshot_path_positional,
+				parameter_space_port,
+				force,
+			} => {
+				name == "restore-parameters"
+					|| name == "restore_parameters"
+					|| name == "restore-params"
+			}
 			Self::SetDefault {
 				bridge_name,
 				bridge_name_positional,
@@ -521,11 +1139,104 @@ impl Subcommands {
 				bridge_name_positional,
 				parameter_names_positional,
 				parameter_space_port,
+				transactional,
+				force,
 			} => name == "set-parameters" || name == "set_parameters" || name == "sp",
+			Self::SetPreferredInterface { interface, clear } => {
+				name == "set-preferred-interface" || name == "set_preferred_interface"
+			}
+			Self::Signals {
+				default,
+				bridge_ipaddr,
+				bridge_mac,
+				bridge_name,
+				bridge_name_positional,
+				signals,
+				watch,
+				interval_seconds,
+			} => name == "signals" || name == "sig",
+			Self::SnapshotParameters {
+				default,
+				bridge_ipaddr,
+				bridge_mac,
+				bridge_name,
+				bridge_name_positional,
+				snapshot_path_positional,
+				parameter_space_port,
+			} => {
+				name == "snapshot-parameters"
+					|| name == "snapshot_parameters"
+					|| name == "snapshot-params"
+			}
 			Self::Tail {
 				serial_port_flag,
 				serial_port_positional,
 			} => name == "tail" || name == "tail-serial-port" || name == "tail_serial_port",
+			Self::Version {
+				default,
+				bridge_ipaddr,
+				bridge_mac,
+				bridge_name,
+				bridge_name_positional,
+			} => name == "version" || name == "ver",
+		}
+	}
+}
+
+/// List all of the parameters we know the name of, so folks don't have to go
+/// digging through the source to find them.
+fn configure_settings_help() -> String {
+	let mut help = "Settings:\n".to_owned();
+	_ = write!(
+		&mut help,
+		"\n  atapi_port\n      The port ATAPI emulation is served on."
+	);
+	help.push_str(
+		"\n\nExperimental Settings (need `--experimental`, a real bridge may not accept these):\n",
+	);
+	for (name, description) in [
+		("sdio_printf_port", "The port SDIO printf output is served on."),
+		("sdio_block_port", "The port the SDIO block device is served on."),
+		(
+			"parameter_port",
+			"The port the parameter space is served on, other commands need `--port` after changing this.",
+		),
+		("host_ip", "The IP of the host PC that serves files to cafe."),
+		("emulation", "If the disc drive is emulated by the host PC (`on`/`off`)."),
+		("boot_type", "Where cafe boots from (`NAND`/`PCFS`/`DUAL`/`HDD`)."),
+		(
+			"emulation_timeout",
+			"Seconds to wait for the host PC when emulating (1-3600).",
+		),
+		(
+			"shutdown_timeout",
+			"Seconds to wait for cafe to shut down before cutting power (1-3600).",
+		),
+	] {
+		_ = write!(&mut help, "\n  {name}\n      {description}");
+	}
+	help
+}
+
+fn known_parameters_help() -> String {
+	let mut help =
+		"Known Parameters (any other parameter can be referred to by it's index 0-511):\n"
+			.to_owned();
+	for definition in &PARAMETER_SCHEMA {
+		_ = write!(
+			&mut help,
+			"\n  {} (index: {}, bytes: {})",
+			definition.name(),
+			definition.index(),
+			definition.width(),
+		);
+		if definition.safety() != ParameterSafety::Safe {
+			_ = write!(&mut help, " ({})", definition.safety());
+		}
+		if !definition.aliases().is_empty() {
+			_ = write!(&mut help, " [aliases: {}]", definition.aliases().join(", "));
 		}
+		_ = write!(&mut help, "\n      {}", definition.description());
 	}
+	help
 }
@@ -15,7 +15,7 @@ use tracing::warn;
 /// Expected Values: ("1" or "0"), and ("true" or "false")
 /// Type: Boolean
 pub static USE_JSON_OUTPUT: Lazy<bool> =
-	Lazy::new(|| env_var("BRIDGECTL_OUTPUT_JSON").map_or(false, |var| var == "1" || var == "true"));
+	Lazy::new(|| env_var("BRIDGECTL_OUTPUT_JSON").is_ok_and(|var| var == "1" || var == "true"));
 
 /// A way of specifying the path to the `bridge_env.ini` file if it's not in
 /// a standard location.
@@ -102,3 +102,12 @@ pub static BRIDGE_CONTROL_PORT: Lazy<Option<u16>> = Lazy::new(|| {
 		}
 	})
 });
+
+/// Set by `cafe`/`cafex`/`mochiato`, the root of the SDK being used. When
+/// booting we read the SDK version from here, so the bridge matches the SDK.
+///
+/// Environment Variable Name: `CAFE_ROOT`
+/// Expected Values: Empty, or the path to an SDK.
+/// Type: [`PathBuf`]
+pub static CAFE_ROOT: Lazy<Option<PathBuf>> =
+	Lazy::new(|| env_var_os("CAFE_ROOT").map(PathBuf::from));
@@ -14,9 +14,12 @@ pub mod utils;
 
 use crate::{
 	commands::{
-		handle_add_or_update, handle_boot, handle_dump_parameters, handle_get,
-		handle_get_parameters, handle_help, handle_list, handle_list_serial_ports,
-		handle_remove_bridge, handle_set_default_bridge, handle_set_parameters, handle_tail,
+		handle_add_or_update, handle_boot, handle_claim, handle_configure, handle_diff_parameters,
+		handle_dump_parameters, handle_get, handle_get_parameters, handle_help, handle_list,
+		handle_list_serial_ports, handle_owner, handle_release, handle_remove_bridge,
+		handle_restore_parameters, handle_set_default_bridge, handle_set_parameters,
+		handle_set_preferred_interface, handle_signals, handle_snapshot_parameters, handle_tail,
+		handle_version,
 	},
 	exit_codes::{
 		ARGUMENT_PARSING_FAILURE, LOGGING_HANDLER_INSTALL_FAILURE, NO_ARGUMENT_SPECIFIED_FAILURE,
@@ -32,6 +35,7 @@ use crate::{
 use clap::Parser;
 use log::install_logging_handlers;
 use miette::miette;
+use std::time::Duration;
 use tracing::error;
 
 #[allow(
@@ -94,6 +98,14 @@ async fn main() {
 			bridge_name,
 			bridge_name_positional,
 			without_pcfs,
+			experimental_pcfs,
+			pcfs_root,
+			pcfs_read_write,
+			pcfs_port,
+			boot_mode,
+			cafe_root,
+			ready_marker,
+			ready_timeout,
 			serial_port_flag,
 			serial_port_positional,
 		} => {
@@ -105,10 +117,58 @@ async fn main() {
 				(scan_timeout, control_port),
 				argv.bridge_state_path,
 				without_pcfs,
+				(experimental_pcfs, pcfs_root, pcfs_read_write, pcfs_port),
+				(boot_mode, cafe_root, ready_marker, ready_timeout),
 				(serial_port_flag, serial_port_positional),
 			)
 			.await;
 		}
+		Subcommands::Claim {
+			default,
+			bridge_ipaddr,
+			bridge_mac,
+			bridge_name,
+			bridge_name_positional,
+			force,
+		} => {
+			handle_claim(
+				use_json,
+				default,
+				(bridge_ipaddr, bridge_mac, bridge_name),
+				bridge_name_positional,
+				force,
+				(scan_timeout, control_port),
+				argv.bridge_state_path,
+			)
+			.await;
+		}
+		Subcommands::Configure {
+			default,
+			bridge_ipaddr,
+			bridge_mac,
+			bridge_name,
+			bridge_name_positional,
+			settings_positional,
+			experimental,
+		} => {
+			handle_configure(
+				use_json,
+				default,
+				(bridge_ipaddr, bridge_mac, bridge_name),
+				bridge_name_positional,
+				settings_positional,
+				experimental,
+				(scan_timeout, control_port),
+				argv.bridge_state_path,
+			)
+			.await;
+		}
+		Subcommands::DiffParameters {
+			old_snapshot,
+			new_snapshot,
+		} => {
+			handle_diff_parameters(use_json, old_snapshot, new_snapshot).await;
+		}
 		Subcommands::DumpParameters {
 			default,
 			bridge_ipaddr,
@@ -173,11 +233,18 @@ async fn main() {
 		Subcommands::List {
 			use_cache,
 			output_as_table,
+			targets,
+			packets_per_second,
+			interfaces,
+			exclude_interfaces,
+			all_interfaces,
 		} => {
 			handle_list(
 				use_json,
 				use_cache,
 				output_as_table,
+				(targets, packets_per_second),
+				(interfaces, exclude_interfaces, all_interfaces),
 				(scan_timeout, control_port),
 				argv.bridge_state_path,
 			)
@@ -186,6 +253,40 @@ async fn main() {
 		Subcommands::ListSerialPorts {} => {
 			handle_list_serial_ports(use_json);
 		}
+		Subcommands::Owner {
+			default,
+			bridge_ipaddr,
+			bridge_mac,
+			bridge_name,
+			bridge_name_positional,
+		} => {
+			handle_owner(
+				use_json,
+				default,
+				(bridge_ipaddr, bridge_mac, bridge_name),
+				bridge_name_positional,
+				(scan_timeout, control_port),
+				argv.bridge_state_path,
+			)
+			.await;
+		}
+		Subcommands::Release {
+			default,
+			bridge_ipaddr,
+			bridge_mac,
+			bridge_name,
+			bridge_name_positional,
+		} => {
+			handle_release(
+				use_json,
+				default,
+				(bridge_ipaddr, bridge_mac, bridge_name),
+				bridge_name_positional,
+				(scan_timeout, control_port),
+				argv.bridge_state_path,
+			)
+			.await;
+		}
 		Subcommands::Remove {
 			bridge_name,
 			bridge_name_positional,
@@ -198,6 +299,29 @@ async fn main() {
 			)
 			.await;
 		}
+		Subcommands::RestoreParameters {
+			default,
+			bridge_ipaddr,
+			bridge_mac,
+			bridge_name,
+			bridge_name_positional,
+			snapshot_path_positional,
+			parameter_space_port,
+			force,
+		} => {
+			handle_restore_parameters(
+				use_json,
+				default,
+				(bridge_ipaddr, bridge_mac, bridge_name),
+				bridge_name_positional,
+				snapshot_path_positional,
+				(scan_timeout, control_port),
+				parameter_space_port,
+				force,
+				argv.bridge_state_path,
+			)
+			.await;
+		}
 		Subcommands::SetDefault {
 			bridge_name,
 			bridge_name_positional,
@@ -218,6 +342,8 @@ async fn main() {
 			bridge_name_positional,
 			parameter_names_positional,
 			parameter_space_port,
+			transactional,
+			force,
 		} => {
 			handle_set_parameters(
 				use_json,
@@ -227,6 +353,55 @@ async fn main() {
 				parameter_names_positional,
 				(scan_timeout, control_port),
 				parameter_space_port,
+				transactional,
+				force,
+				argv.bridge_state_path,
+			)
+			.await;
+		}
+		Subcommands::SetPreferredInterface { interface, clear } => {
+			handle_set_preferred_interface(use_json, interface, clear, argv.bridge_state_path)
+				.await;
+		}
+		Subcommands::Signals {
+			default,
+			bridge_ipaddr,
+			bridge_mac,
+			bridge_name,
+			bridge_name_positional,
+			signals,
+			watch,
+			interval_seconds,
+		} => {
+			handle_signals(
+				use_json,
+				default,
+				(bridge_ipaddr, bridge_mac, bridge_name),
+				bridge_name_positional,
+				signals,
+				watch.then(|| Duration::from_secs(interval_seconds.max(1))),
+				(scan_timeout, control_port),
+				argv.bridge_state_path,
+			)
+			.await;
+		}
+		Subcommands::SnapshotParameters {
+			default,
+			bridge_ipaddr,
+			bridge_mac,
+			bridge_name,
+			bridge_name_positional,
+			snapshot_path_positional,
+			parameter_space_port,
+		} => {
+			handle_snapshot_parameters(
+				use_json,
+				default,
+				(bridge_ipaddr, bridge_mac, bridge_name),
+				bridge_name_positional,
+				snapshot_path_positional,
+				(scan_timeout, control_port),
+				parameter_space_port,
 				argv.bridge_state_path,
 			)
 			.await;
@@ -237,6 +412,23 @@ async fn main() {
 		} => {
 			handle_tail(use_json, serial_port_flag, serial_port_positional).await;
 		}
+		Subcommands::Version {
+			default,
+			bridge_ipaddr,
+			bridge_mac,
+			bridge_name,
+			bridge_name_positional,
+		} => {
+			handle_version(
+				use_json,
+				default,
+				(bridge_ipaddr, bridge_mac, bridge_name),
+				bridge_name_positional,
+				(scan_timeout, control_port),
+				argv.bridge_state_path,
+			)
+			.await;
+		}
 	}
 }
 
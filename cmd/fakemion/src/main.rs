@@ -0,0 +1,187 @@
+#![allow(
+	// I've always disliked this rule, most of the time imports are used WITHOUT
+	// the module name, and the module name is only used in the top level import.
+	//
+	// Where this becomes significantly more helpful to read as it's out of
+	// context.
+	clippy::module_name_repetitions,
+)]
+
+pub mod profile;
+
+use crate::profile::Profile;
+use cat_dev::mion::emulator::EmulatedMion;
+use clap::Parser;
+use log::install_logging_handlers;
+use miette::{miette, Result};
+use std::{
+	net::{Ipv4Addr, SocketAddrV4},
+	path::PathBuf,
+};
+use tracing::{error, info};
+
+/// Failed to install the logging handlers.
+const LOGGING_HANDLER_INSTALL_FAILURE: i32 = 1;
+/// Failed to load the profile for this fake MION.
+const PROFILE_LOAD_FAILURE: i32 = 2;
+/// Failed to startup the actual fake MION.
+const EMULATOR_SPAWN_FAILURE: i32 = 3;
+
+#[derive(Parser, Debug)]
+#[command(about, author, name = "fakemion", version)]
+struct CliArguments {
+	#[arg(
+		short = 'p',
+		long = "profile",
+		help = "The INI profile describing the MION to pretend to be.",
+		long_help = "The path to an INI profile describing the MION to pretend to be, if not specified a default MION that is powered off, with PCFS boot, and SDK 2.12.13 is used."
+	)]
+	profile: Option<PathBuf>,
+	#[arg(
+		short = 'b',
+		long = "bind-address",
+		alias = "bind_address",
+		help = "The address to listen on, overrides the profile.",
+		long_help = "The IPv4 address of the interface to listen on, this overrides `bind_address` in the profile."
+	)]
+	bind_address: Option<Ipv4Addr>,
+	#[arg(
+		long = "broadcast-address",
+		alias = "broadcast_address",
+		help = "A broadcast address to listen on, overrides the profile.",
+		long_help = "A broadcast address to ALSO listen on for announcements. When this is set the main control socket is bound to a random port, so tools like `findbridge` can run on the same machine as `fakemion`. This overrides `broadcast_address` in the profile."
+	)]
+	broadcast_address: Option<Ipv4Addr>,
+	#[arg(
+		long = "control-port",
+		alias = "control_port",
+		help = "The UDP port to answer announcements on, overrides the profile.",
+		long_help = "The UDP port to answer announcements on (7974 on a real MION). Use this alongside `BRIDGE_CONTROL_PORT_OVERRIDE` to avoid needing elevated permissions."
+	)]
+	control_port: Option<u16>,
+	#[arg(
+		long = "parameter-port",
+		alias = "parameter_port",
+		help = "The TCP port to serve the parameter space on, overrides the profile.",
+		long_help = "The TCP port to serve the 512 byte parameter space on (7978 on a real MION)."
+	)]
+	parameter_port: Option<u16>,
+	#[arg(
+		long = "http-port",
+		alias = "http_port",
+		help = "The TCP port to serve HTTP on, overrides the profile.",
+		long_help = "The TCP port to serve the CGI pages on (80 on a real MION). Tools always talk to port 80, so anything else is only useful for tests."
+	)]
+	http_port: Option<u16>,
+	#[arg(
+		short = 'j',
+		long = "json",
+		help = "Ensures all logging comes out in JSON instead of text.",
+		long_help = "Switch all logging and output to JSON for machine parsable output."
+	)]
+	json: bool,
+}
+
+#[tokio::main]
+async fn main() {
+	let argv = CliArguments::parse();
+	if let Err(cause) = install_logging_handlers(argv.json) {
+		println!("Failed to install the logging handler to setup logging:\n{cause:?}");
+		std::process::exit(LOGGING_HANDLER_INSTALL_FAILURE);
+	}
+
+	let profile = match load_profile(&argv) {
+		Ok(profile) => profile,
+		Err(cause) => {
+			if argv.json {
+				error!(id = "fakemion::profile::load_failure", ?cause);
+			} else {
+				error!("\n{cause:?}");
+			}
+			std::process::exit(PROFILE_LOAD_FAILURE);
+		}
+	};
+
+	let mion = match spawn_mion(&profile).await {
+		Ok(mion) => mion,
+		Err(cause) => {
+			if argv.json {
+				error!(id = "fakemion::spawn_failure", ?cause);
+			} else {
+				error!("\n{cause:?}");
+			}
+			std::process::exit(EMULATOR_SPAWN_FAILURE);
+		}
+	};
+
+	info!(
+		name = %profile.name,
+		control = %mion.control_address(),
+		parameter = %mion.parameter_address(),
+		http = %mion.http_address(),
+		broadcast = ?profile.broadcast_address,
+		"fakemion is up and running, press Ctrl-C to stop",
+	);
+	_ = tokio::signal::ctrl_c().await;
+	info!("shutting down fakemion");
+}
+
+/// Load the profile, and apply any CLI overrides on top of it.
+fn load_profile(argv: &CliArguments) -> Result<Profile> {
+	let mut profile = if let Some(path) = argv.profile.as_ref() {
+		Profile::load(path)?
+	} else {
+		Profile::default()
+	};
+
+	if let Some(bind_address) = argv.bind_address {
+		profile.bind_address = bind_address;
+	}
+	if let Some(broadcast_address) = argv.broadcast_address {
+		profile.broadcast_address = Some(broadcast_address);
+	}
+	if let Some(port) = argv.control_port {
+		profile.control_port = port;
+	}
+	if let Some(port) = argv.parameter_port {
+		profile.parameter_port = port;
+	}
+	if let Some(port) = argv.http_port {
+		profile.http_port = port;
+	}
+
+	Ok(profile)
+}
+
+/// Spawn the actual emulated MION described by a profile.
+async fn spawn_mion(profile: &Profile) -> Result<EmulatedMion> {
+	// If we're listening on a broadcast address, the broadcast socket gets the
+	// real control port, and we just need somewhere to respond from.
+	let control_port = if profile.broadcast_address.is_some() {
+		0
+	} else {
+		profile.control_port
+	};
+
+	let mut mion = EmulatedMion::spawn_on(
+		profile.identity()?,
+		profile.bind_address,
+		control_port,
+		profile.parameter_port,
+		profile.http_port,
+	)
+	.await
+	.map_err(|cause| miette!("Failed to start the emulated MION: {cause}"))?;
+	mion.replace_parameters(&profile.parameter_space())
+		.map_err(|cause| miette!("Failed to set initial parameters: {cause}"))?;
+
+	if let Some(broadcast_address) = profile.broadcast_address {
+		mion.listen_for_broadcasts(SocketAddrV4::new(broadcast_address, profile.control_port))
+			.await
+			.map_err(|cause| {
+				miette!("Failed to listen on broadcast address {broadcast_address}: {cause}")
+			})?;
+	}
+
+	Ok(mion)
+}
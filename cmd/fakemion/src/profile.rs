@@ -0,0 +1,357 @@
+//! A "profile" describes the MION we're pretending to be.
+//!
+//! Profiles are INI files just like `bridge_env.ini`, an example profile
+//! with every key filled out looks like:
+//!
+//! ```ini
+//! [bridge]
+//! name = 00-25-5C-BA-5A-00
+//! mac_address = 00-25-5C-BA-5A-00
+//! ; As displayed by `findbridge -detailed`.
+//! fpga_version = 13052071
+//! ; The four raw bytes of the firmware version, this one is displayed as
+//! ; `0.0.14.80` by `findbridge`.
+//! firmware_version = 0.14.80.1
+//! sdk_version = 2.12.13
+//! ; Either the name of a boot type (NAND, PCFS, DUAL, HDD), or it's raw number.
+//! boot_type = PCFS
+//! power = off
+//!
+//! [network]
+//! bind_address = 0.0.0.0
+//! control_port = 7974
+//! parameter_port = 7978
+//! http_port = 80
+//! ; Optional, listen for broadcasts on this address.
+//! broadcast_address = 192.168.1.255
+//!
+//! [parameters]
+//! ; index = value
+//! 80 = 1
+//! ```
+//!
+//! Every key is optional, and will fallback to the values shown above (with
+//! the exception of `broadcast_address`, and `parameters`).
+
+use bytes::BytesMut;
+use cat_dev::mion::proto::control::{
+	MIONBootType, MionIdentity, DETAILED_BOOT_TYPE_OFFSET, DETAILED_CAFE_ON_OFFSET,
+	DETAILED_DATA_SIZE, DETAILED_SDK_VERSION_OFFSET,
+};
+use configparser::ini::Ini;
+use mac_address::MacAddress;
+use miette::{miette, Result};
+use std::{net::Ipv4Addr, path::Path, str::FromStr};
+
+/// The section that contains the identity of the bridge.
+const BRIDGE_SECTION: &str = "bridge";
+/// The section that contains where we should listen.
+const NETWORK_SECTION: &str = "network";
+/// The section that contains any parameters that should be pre-set.
+const PARAMETERS_SECTION: &str = "parameters";
+
+/// Everything we need to know in order to spawn a fake MION.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Profile {
+	pub name: String,
+	pub mac_address: MacAddress,
+	pub fpga_version: [u8; 4],
+	pub firmware_version: [u8; 4],
+	pub sdk_version: [u8; 4],
+	pub boot_type: MIONBootType,
+	pub powered_on: bool,
+	pub bind_address: Ipv4Addr,
+	pub control_port: u16,
+	pub parameter_port: u16,
+	pub http_port: u16,
+	pub broadcast_address: Option<Ipv4Addr>,
+	pub parameters: Vec<(usize, u8)>,
+}
+
+impl Profile {
+	/// Load a profile from an INI file on disk.
+	///
+	/// ## Errors
+	///
+	/// - If we cannot read, or parse the INI file.
+	/// - If any of the values in the profile are invalid.
+	pub fn load(path: &Path) -> Result<Self> {
+		let mut ini = Ini::new_cs();
+		ini.load(path)
+			.map_err(|cause| miette!("Failed to load profile {}: {cause}", path.display()))?;
+		Self::from_ini(&ini)
+	}
+
+	/// Create a profile from an already parsed INI file.
+	///
+	/// ## Errors
+	///
+	/// - If any of the values in the profile are invalid.
+	pub fn from_ini(ini: &Ini) -> Result<Self> {
+		let mut profile = Self::default();
+
+		if let Some(name) = ini.get(BRIDGE_SECTION, "name") {
+			profile.name = name;
+		}
+		if let Some(mac) = ini.get(BRIDGE_SECTION, "mac_address") {
+			profile.mac_address = MacAddress::from_str(&mac)
+				.map_err(|cause| miette!("Invalid `mac_address` {mac}: {cause}"))?;
+		}
+		if let Some(fpga) = ini.get(BRIDGE_SECTION, "fpga_version") {
+			profile.fpga_version = u32::from_str_radix(&fpga, 16)
+				.map_err(|cause| miette!("Invalid `fpga_version` {fpga}: {cause}"))?
+				.to_le_bytes();
+		}
+		if let Some(firmware) = ini.get(BRIDGE_SECTION, "firmware_version") {
+			profile.firmware_version = parse_dotted_version(&firmware, 4).ok_or_else(|| {
+				miette!("Invalid `firmware_version` {firmware}, expected: `a.b.c.d`")
+			})?;
+		}
+		if let Some(sdk) = ini.get(BRIDGE_SECTION, "sdk_version") {
+			profile.sdk_version = parse_dotted_version(&sdk, 3)
+				.ok_or_else(|| miette!("Invalid `sdk_version` {sdk}, expected: `a.b.c`"))?;
+		}
+		if let Some(boot_type) = ini.get(BRIDGE_SECTION, "boot_type") {
+			profile.boot_type = MIONBootType::try_from(boot_type.as_str())
+				.map_err(|cause| miette!("Invalid `boot_type`: {cause}"))?;
+		}
+		if let Some(power) = ini.get(BRIDGE_SECTION, "power") {
+			profile.powered_on = match power.to_ascii_lowercase().as_str() {
+				"on" | "true" | "1" => true,
+				"off" | "false" | "0" => false,
+				_ => return Err(miette!("Invalid `power` {power}, expected: `on`, or `off`")),
+			};
+		}
+
+		if let Some(bind) = ini.get(NETWORK_SECTION, "bind_address") {
+			profile.bind_address = Ipv4Addr::from_str(&bind)
+				.map_err(|cause| miette!("Invalid `bind_address` {bind}: {cause}"))?;
+		}
+		if let Some(port) = ini.get(NETWORK_SECTION, "control_port") {
+			profile.control_port = parse_port("control_port", &port)?;
+		}
+		if let Some(port) = ini.get(NETWORK_SECTION, "parameter_port") {
+			profile.parameter_port = parse_port("parameter_port", &port)?;
+		}
+		if let Some(port) = ini.get(NETWORK_SECTION, "http_port") {
+			profile.http_port = parse_port("http_port", &port)?;
+		}
+		if let Some(broadcast) = ini.get(NETWORK_SECTION, "broadcast_address") {
+			profile.broadcast_address =
+				Some(Ipv4Addr::from_str(&broadcast).map_err(|cause| {
+					miette!("Invalid `broadcast_address` {broadcast}: {cause}")
+				})?);
+		}
+
+		if let Some(section) = ini.get_map_ref().get(PARAMETERS_SECTION) {
+			for (index, value) in section {
+				let parsed_index = index
+					.parse::<usize>()
+					.ok()
+					.filter(|idx| *idx < 512)
+					.ok_or_else(|| miette!("Invalid parameter index {index}, expected: 0-511"))?;
+				let parsed_value = value
+					.as_ref()
+					.and_then(|val| val.parse::<u8>().ok())
+					.ok_or_else(|| {
+						miette!("Invalid value for parameter {index}, expected: 0-255")
+					})?;
+				profile.parameters.push((parsed_index, parsed_value));
+			}
+			profile.parameters.sort_unstable();
+		}
+
+		Ok(profile)
+	}
+
+	/// Build the identity this MION should announce.
+	///
+	/// ## Errors
+	///
+	/// - If the name is not a valid MION name.
+	pub fn identity(&self) -> Result<MionIdentity> {
+		let mut detailed = BytesMut::zeroed(DETAILED_DATA_SIZE);
+		detailed[DETAILED_SDK_VERSION_OFFSET..DETAILED_SDK_VERSION_OFFSET + 4]
+			.copy_from_slice(&self.sdk_version);
+		// Always set on real devices, we don't know what it means yet.
+		detailed[DETAILED_SDK_VERSION_OFFSET + 4] = 1;
+		detailed[DETAILED_BOOT_TYPE_OFFSET] = u8::from(self.boot_type);
+		detailed[DETAILED_CAFE_ON_OFFSET] = u8::from(self.powered_on);
+
+		MionIdentity::new(
+			Some(detailed.freeze()),
+			self.firmware_version,
+			self.fpga_version,
+			self.bind_address,
+			self.mac_address,
+			self.name.clone(),
+		)
+		.map_err(|cause| miette!("Invalid bridge in profile: {cause}"))
+	}
+
+	/// The parameter space this MION should start with.
+	///
+	/// Parameters 3, 4, and 5 are the SDK version, unless they've been
+	/// explicitly set in the profile.
+	#[must_use]
+	pub fn parameter_space(&self) -> [u8; 512] {
+		let mut parameters = [0_u8; 512];
+		parameters[3..6].copy_from_slice(&self.sdk_version[..3]);
+		for (index, value) in &self.parameters {
+			parameters[*index] = *value;
+		}
+		parameters
+	}
+}
+
+impl Default for Profile {
+	fn default() -> Self {
+		Self {
+			name: "00-25-5C-BA-5A-00".to_owned(),
+			mac_address: MacAddress::new([0x00, 0x25, 0x5C, 0xBA, 0x5A, 0x00]),
+			fpga_version: [0x71, 0x20, 0x05, 0x13],
+			firmware_version: [0x00, 0x0E, 0x50, 0x01],
+			sdk_version: [2, 12, 13, 0],
+			boot_type: MIONBootType::PCFS,
+			powered_on: false,
+			bind_address: Ipv4Addr::UNSPECIFIED,
+			control_port: 7974,
+			parameter_port: 7978,
+			http_port: 80,
+			broadcast_address: None,
+			parameters: Vec::new(),
+		}
+	}
+}
+
+/// Parse a version like `2.12.13`, requiring at least `min_parts` parts.
+fn parse_dotted_version(version: &str, min_parts: usize) -> Option<[u8; 4]> {
+	let mut parsed = [0_u8; 4];
+	let mut count = 0_usize;
+	for (idx, part) in version.trim().split('.').enumerate() {
+		if idx >= 4 {
+			return None;
+		}
+		parsed[idx] = part.parse::<u8>().ok()?;
+		count += 1;
+	}
+
+	if count < min_parts {
+		None
+	} else {
+		Some(parsed)
+	}
+}
+
+/// Parse a port number.
+fn parse_port(key: &str, port: &str) -> Result<u16> {
+	port.parse::<u16>()
+		.map_err(|cause| miette!("Invalid `{key}` {port}: {cause}"))
+}
+
+#[cfg(test)]
+mod unit_tests {
+	use super::*;
+
+	fn parse_profile(contents: &str) -> Result<Profile> {
+		let mut ini = Ini::new_cs();
+		ini.read(contents.to_owned())
+			.map_err(|cause| miette!("Failed to parse profile: {cause}"))?;
+		Profile::from_ini(&ini)
+	}
+
+	#[test]
+	fn dotted_version_parsing() {
+		assert_eq!(parse_dotted_version("2.12.13", 3), Some([2, 12, 13, 0]));
+		assert_eq!(parse_dotted_version(" 0.14.80.1 ", 4), Some([0, 14, 80, 1]));
+		// Not enough parts.
+		assert_eq!(parse_dotted_version("2.12", 3), None);
+		assert_eq!(parse_dotted_version("2.12.13", 4), None);
+		// Too many parts.
+		assert_eq!(parse_dotted_version("1.2.3.4.5", 3), None);
+		// Parts must all fit in a byte.
+		assert_eq!(parse_dotted_version("2.256.13", 3), None);
+		assert_eq!(parse_dotted_version("2.a.13", 3), None);
+		assert_eq!(parse_dotted_version("", 3), None);
+	}
+
+	#[test]
+	fn empty_profile_is_default() {
+		assert_eq!(
+			parse_profile("").expect("Failed to parse empty profile!"),
+			Profile::default(),
+		);
+	}
+
+	#[test]
+	fn full_profile_parsing() {
+		let profile = parse_profile(
+			"[bridge]
+name = my-bridge
+mac_address = 00-25-5C-BA-5A-01
+fpga_version = 13052071
+firmware_version = 0.14.80.2
+sdk_version = 2.11.4
+boot_type = NAND
+power = on
+
+[network]
+bind_address = 127.0.0.1
+control_port = 17974
+parameter_port = 17978
+http_port = 8080
+broadcast_address = 192.168.1.255
+
+[parameters]
+80 = 1
+4 = 12
+",
+		)
+		.expect("Failed to parse full profile!");
+
+		assert_eq!(
+			profile,
+			Profile {
+				name: "my-bridge".to_owned(),
+				mac_address: MacAddress::new([0x00, 0x25, 0x5C, 0xBA, 0x5A, 0x01]),
+				fpga_version: [0x71, 0x20, 0x05, 0x13],
+				firmware_version: [0, 14, 80, 2],
+				sdk_version: [2, 11, 4, 0],
+				boot_type: MIONBootType::NAND,
+				powered_on: true,
+				bind_address: Ipv4Addr::LOCALHOST,
+				control_port: 17974,
+				parameter_port: 17978,
+				http_port: 8080,
+				broadcast_address: Some(Ipv4Addr::new(192, 168, 1, 255)),
+				parameters: vec![(4, 12), (80, 1)],
+			},
+		);
+
+		let parameters = profile.parameter_space();
+		assert_eq!(&parameters[3..6], &[2, 12, 4]);
+		assert_eq!(parameters[80], 1);
+	}
+
+	#[test]
+	fn invalid_profiles_are_rejected() {
+		for contents in [
+			"[bridge]\nmac_address = not-a-mac",
+			"[bridge]\nfpga_version = xyz",
+			"[bridge]\nfirmware_version = 1.2.3",
+			"[bridge]\nsdk_version = 2.12",
+			"[bridge]\nboot_type = FLOPPY",
+			"[bridge]\npower = maybe",
+			"[network]\nbind_address = localhost",
+			"[network]\ncontrol_port = 65536",
+			"[network]\nbroadcast_address = 192.168.1",
+			"[parameters]\n512 = 1",
+			"[parameters]\n80 = 256",
+		] {
+			assert!(
+				parse_profile(contents).is_err(),
+				"Profile was not rejected: {contents:?}",
+			);
+		}
+	}
+}
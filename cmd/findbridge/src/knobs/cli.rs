@@ -2,6 +2,12 @@
 //!
 //! This is the exact same arguments, that do the exact same things as an
 //! official version of `findbridge`. Specifically v5.1.
+//!
+//! The only additions are `-interface`, `-exclude`, and `-preferred` which
+//! limit which network interfaces we scan on. These are purposefully left out
+//! of the help text, so it continues to match the original tool.
+
+use cat_dev::mion::discovery::InterfaceMatcher;
 
 /// The top-level command line options.
 #[allow(
@@ -33,6 +39,12 @@ pub struct CliOpts {
 	pub list: bool,
 	/// If extra output will be produced, and displayed to the user.
 	pub verbose: bool,
+	/// Only scan on these interfaces (`-interface <name, ip, or subnet>`).
+	pub allow_interfaces: Vec<InterfaceMatcher>,
+	/// Never scan on these interfaces (`-exclude <name, ip, or subnet>`).
+	pub deny_interfaces: Vec<InterfaceMatcher>,
+	/// Only scan on the preferred interface from `bridge_env.ini`.
+	pub use_preferred_interface: bool,
 }
 impl CliOpts {
 	pub fn print_help() {
@@ -74,10 +86,14 @@ impl<Ty: Iterator<Item = String>> From<Ty> for CliOpts {
 			is_forced_mac: false,
 			list: false,
 			verbose: false,
+			allow_interfaces: Vec::new(),
+			deny_interfaces: Vec::new(),
+			use_preferred_interface: false,
 		};
 
+		let mut arguments = arguments;
 		let mut set_any = false;
-		for item in arguments {
+		while let Some(item) = arguments.next() {
 			match item.as_str() {
 				"-v" => opts.verbose = true,
 				"-h" | "/?" => opts.help = true,
@@ -85,6 +101,26 @@ impl<Ty: Iterator<Item = String>> From<Ty> for CliOpts {
 				"-detail" => opts.detail = true,
 				"-list" | "-getinfo" => opts.list = true,
 				"-mac" => opts.is_forced_mac = true,
+				"-preferred" => opts.use_preferred_interface = true,
+				"-interface" | "-exclude" => {
+					let Some(matcher) = arguments
+						.next()
+						.and_then(|value| InterfaceMatcher::try_from(value.as_str()).ok())
+					else {
+						println!(
+							"ERROR: Interface name, IP, or subnet required with \"{item}\" flag!"
+						);
+						opts.had_error = true;
+						opts.help = true;
+						break;
+					};
+
+					if item == "-interface" {
+						opts.allow_interfaces.push(matcher);
+					} else {
+						opts.deny_interfaces.push(matcher);
+					}
+				}
 				_ => {
 					if opts.find_specific.is_some() {
 						// This outputs TWO newlines.
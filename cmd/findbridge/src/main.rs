@@ -14,9 +14,12 @@ use crate::output::{
 	create_interface_logging_hook, print_bridge, print_bridge_header,
 	print_verbose_search_suggestions,
 };
-use cat_dev::mion::discovery::{
-	discover_bridges_with_logging_hooks, find_mion_with_logging_hooks, get_all_broadcast_addresses,
-	MIONFindBy,
+use cat_dev::{
+	mion::discovery::{
+		discover_bridges_with_interface_filter, find_mion_with_interface_filter,
+		get_broadcast_addresses, InterfaceFilter, MIONFindBy,
+	},
+	BridgeHostState,
 };
 use knobs::cli::CliOpts;
 use mac_address::MacAddress;
@@ -64,7 +67,7 @@ async fn find_one(find_arg: String, opts: &CliOpts) -> bool {
 		let Ok(mac) = MacAddress::try_from(find_arg.as_str()) else {
 			// When the mac address isn't valid the original findbridge still
 			// tries to scan anyway.
-			fake_logging_hooks_for_scan(opts.verbose);
+			fake_logging_hooks_for_scan(opts).await;
 			// We print an extra newline if not using verbose to match.
 			if !opts.verbose {
 				println!();
@@ -86,7 +89,7 @@ async fn find_one(find_arg: String, opts: &CliOpts) -> bool {
 	//
 	// I don't know why.
 	let force_non_detailed = matches!(search_type, MIONFindBy::Name(_));
-	let Ok(bridge_opt) = find_mion_with_logging_hooks(
+	let Ok(bridge_opt) = find_mion_with_interface_filter(
 		search_type.clone(),
 		if force_non_detailed {
 			false
@@ -95,6 +98,7 @@ async fn find_one(find_arg: String, opts: &CliOpts) -> bool {
 		},
 		Some(Duration::from_secs(3)),
 		None,
+		&interface_filter(opts).await,
 		create_interface_logging_hook(opts.verbose),
 	)
 	.await
@@ -124,9 +128,10 @@ async fn find_one(find_arg: String, opts: &CliOpts) -> bool {
 }
 
 async fn scan_all(opts: &CliOpts) {
-	let Ok(mut recv_channel) = discover_bridges_with_logging_hooks(
+	let Ok(mut recv_channel) = discover_bridges_with_interface_filter(
 		opts.detail,
 		None,
+		&interface_filter(opts).await,
 		create_interface_logging_hook(opts.verbose),
 	)
 	.await
@@ -179,16 +184,35 @@ async fn scan_all(opts: &CliOpts) {
 /// We actually don't always do a scan, because it's incredibly ineffecient to
 /// do so. However, we still need to create logs for interfaces to match the
 /// output 1:1.
-fn fake_logging_hooks_for_scan(is_verbose: bool) {
-	let interface_hook = create_interface_logging_hook(is_verbose);
+async fn fake_logging_hooks_for_scan(opts: &CliOpts) {
+	let interface_hook = create_interface_logging_hook(opts.verbose);
 
-	if let Ok(broadcast_addresses) = get_all_broadcast_addresses() {
+	if let Ok(broadcast_addresses) = get_broadcast_addresses(&interface_filter(opts).await) {
 		for (addr, _ipv4) in broadcast_addresses {
 			interface_hook(&addr);
 		}
 	}
 
-	if is_verbose {
+	if opts.verbose {
 		println!();
 	}
 }
+
+/// Build the filter for which interfaces we should scan on.
+///
+/// The preferred interface (if requested, and configured) is just another
+/// interface that is allowed, alongside any `-interface` flags.
+async fn interface_filter(opts: &CliOpts) -> InterfaceFilter {
+	let mut allow = opts.allow_interfaces.clone();
+	if opts.use_preferred_interface {
+		if let Some(preferred) = BridgeHostState::load()
+			.await
+			.ok()
+			.and_then(|state| state.get_preferred_interface())
+		{
+			allow.push(preferred);
+		}
+	}
+
+	InterfaceFilter::new(allow, opts.deny_interfaces.clone())
+}
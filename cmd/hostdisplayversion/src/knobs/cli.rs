@@ -0,0 +1,134 @@
+//! The command line argument parser we have inherited from the Nintendo CLI.
+//!
+//! `hostdisplayversion` is normally run without any arguments, and reports on
+//! the current bridge. We also accept the same `-bridge`, and `-ip` options
+//! as our other re-implementations so it can report on any bridge.
+
+use std::net::Ipv4Addr;
+
+/// The top-level command line options.
+#[derive(Debug)]
+pub struct CliOpts {
+	/// The name of the bridge to use, instead of the current bridge.
+	pub bridge_name: Option<String>,
+	/// The IP Address of the bridge to use, instead of the current bridge.
+	pub bridge_ipaddr: Option<Ipv4Addr>,
+	/// If the user requested the help page.
+	pub help: bool,
+	/// If an argument couldn't be understood.
+	pub invalid_argument: Option<String>,
+}
+impl CliOpts {
+	pub fn print_help() {
+		println!(
+			"hostdisplayversion - Copyright (c) 2011-2013 Nintendo Co., Ltd.
+
+  Displays the version of the Host Bridge Software, and the versions
+  of the firmware, FPGA, and SDK on the current bridge.
+
+  If no bridge is specified the current bridge is used.
+
+Usage:
+  hostdisplayversion [options]
+
+Options:
+  -bridge <name>....Uses the bridge specified by <name>.
+  -ip <ip_addr>.....Uses the bridge at <ip_addr>.
+  -h................Prints this help message.\n"
+		);
+	}
+}
+impl<Ty: Iterator<Item = String>> From<Ty> for CliOpts {
+	fn from(arguments: Ty) -> Self {
+		let mut opts = Self {
+			bridge_name: None,
+			bridge_ipaddr: None,
+			help: false,
+			invalid_argument: None,
+		};
+
+		let mut arguments = arguments.peekable();
+		while let Some(item) = arguments.next() {
+			match item.as_str() {
+				"-h" | "-help" | "-?" => {
+					opts.help = true;
+					continue;
+				}
+				"-bridge" | "-ip" => {}
+				_ => {
+					opts.invalid_argument = Some(item);
+					continue;
+				}
+			}
+			let Some(value) = arguments.next_if(|value| !value.starts_with('-')) else {
+				opts.invalid_argument = Some(item);
+				continue;
+			};
+			if item == "-bridge" {
+				opts.bridge_name = Some(value);
+			} else {
+				match value.parse::<Ipv4Addr>() {
+					Ok(ip) => opts.bridge_ipaddr = Some(ip),
+					Err(_cause) => opts.invalid_argument = Some(format!("{item} {value}")),
+				}
+			}
+		}
+
+		opts
+	}
+}
+
+#[cfg(test)]
+mod unit_tests {
+	use super::*;
+
+	fn parse(arguments: &[&str]) -> CliOpts {
+		CliOpts::from(arguments.iter().map(|argument| (*argument).to_owned()))
+	}
+
+	#[test]
+	pub fn no_arguments_uses_the_current_bridge() {
+		let opts = parse(&[]);
+		assert_eq!(opts.bridge_name, None);
+		assert_eq!(opts.bridge_ipaddr, None);
+		assert!(!opts.help);
+		assert_eq!(opts.invalid_argument, None);
+	}
+
+	#[test]
+	pub fn can_pick_a_bridge() {
+		let opts = parse(&["-bridge", "my-bridge", "-ip", "192.168.7.40"]);
+		assert_eq!(opts.bridge_name.as_deref(), Some("my-bridge"));
+		assert_eq!(opts.bridge_ipaddr, Some(Ipv4Addr::new(192, 168, 7, 40)));
+		assert_eq!(opts.invalid_argument, None);
+
+		for help in ["-h", "-help", "-?"] {
+			assert!(parse(&[help]).help);
+		}
+	}
+
+	#[test]
+	pub fn reports_invalid_arguments() {
+		assert_eq!(
+			parse(&["-unknown"]).invalid_argument.as_deref(),
+			Some("-unknown"),
+		);
+		assert_eq!(
+			parse(&["my-bridge"]).invalid_argument.as_deref(),
+			Some("my-bridge"),
+		);
+		// Options that need a value, but don't get one.
+		assert_eq!(
+			parse(&["-bridge"]).invalid_argument.as_deref(),
+			Some("-bridge")
+		);
+		assert_eq!(
+			parse(&["-ip", "-h"]).invalid_argument.as_deref(),
+			Some("-ip"),
+		);
+		assert_eq!(
+			parse(&["-ip", "not-an-ip"]).invalid_argument.as_deref(),
+			Some("-ip not-an-ip"),
+		);
+	}
+}
@@ -0,0 +1,5 @@
+//! All of the potential knobs that you can use to configure the application.
+//!
+//! NOTE: this doesn't techincally include things provided by shared libraries.
+
+pub mod cli;
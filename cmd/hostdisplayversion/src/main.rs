@@ -0,0 +1,107 @@
+#![allow(
+	// I've always disliked this rule, most of the time imports are used WITHOUT
+	// the module name, and the module name is only used in the top level import.
+	//
+	// Where this becomes significantly more helpful to read as it's out of
+	// context.
+	clippy::module_name_repetitions,
+)]
+
+pub mod knobs;
+
+use crate::knobs::cli::CliOpts;
+use cat_dev::{
+	diagnostics::{version_report_with_overrides, HOST_SOFTWARE_VERSION},
+	mion::discovery::MIONFindBy,
+	BridgeHostState,
+};
+use std::{net::Ipv4Addr, time::Duration};
+use tokio::runtime::Runtime;
+
+/// How long to wait for the bridge to respond, the same as `findbridge`.
+const SCAN_TIMEOUT: Duration = Duration::from_secs(3);
+
+fn main() {
+	let opts = CliOpts::from(std::env::args().skip(1));
+	if opts.help {
+		CliOpts::print_help();
+		std::process::exit(-1);
+	}
+	if let Some(argument) = opts.invalid_argument.as_ref() {
+		println!("\nERROR : Invalid argument \"{argument}\"");
+		std::process::exit(-1);
+	}
+	let Ok(runtime) = Runtime::new() else {
+		println!("\nERROR : Could not start up, please try again.");
+		std::process::exit(-1);
+	};
+
+	let bridge_ip = get_bridge_ip(&runtime, &opts);
+	match runtime.block_on(version_report_with_overrides(
+		MIONFindBy::Ip(bridge_ip),
+		Some(SCAN_TIMEOUT),
+		None,
+	)) {
+		Ok(Some(report)) => println!("{report}"),
+		Ok(None) => {
+			// The host version is still useful, even if the bridge is off.
+			println!("Host Bridge Software Version : {HOST_SOFTWARE_VERSION}");
+			println!("\nERROR : Bridge at {bridge_ip} did not respond");
+			std::process::exit(-1);
+		}
+		Err(cause) => {
+			println!("Host Bridge Software Version : {HOST_SOFTWARE_VERSION}");
+			println!("\nERROR : Could not search for bridge at {bridge_ip}: {cause}");
+			std::process::exit(-1);
+		}
+	}
+}
+
+/// Figure out which bridge to talk to, the same way the rest of the SDK does.
+fn get_bridge_ip(runtime: &Runtime, opts: &CliOpts) -> Ipv4Addr {
+	if let Some(ip) = opts.bridge_ipaddr {
+		return ip;
+	}
+	if opts.bridge_name.is_none() {
+		if let Some(ip) = std::env::var("BRIDGE_CURRENT_IP_ADDRESS")
+			.ok()
+			.and_then(|ip| ip.parse::<Ipv4Addr>().ok())
+		{
+			return ip;
+		}
+	}
+
+	let Some(host_path) = BridgeHostState::get_default_host_path() else {
+		println!(
+			"\nERROR 203: Could not retrieve install path. Is Host Bridge Software installed?"
+		);
+		std::process::exit(-1);
+	};
+	let Ok(host_env) = runtime.block_on(BridgeHostState::load_explicit_path(host_path)) else {
+		println!("\nERROR 30: Could not retrieve install path. Is Host Bridge Software installed?");
+		std::process::exit(-1);
+	};
+	let name = opts
+		.bridge_name
+		.clone()
+		.or_else(|| std::env::var("BRIDGE_CURRENT_NAME").ok());
+	let found = if let Some(name) = name {
+		host_env
+			.get_bridge(&name)
+			.map(|(ip, _is_default)| (name, ip))
+	} else {
+		host_env.get_default_bridge()
+	};
+
+	match found {
+		Some((_name, Some(ip))) => ip,
+		Some((name, None)) => {
+			println!("\nERROR : Bridge \"{name}\" does not have an IP address");
+			std::process::exit(-1);
+		}
+		None => {
+			println!("\nERROR : No bridge specified, and no default bridge is set");
+			std::process::exit(-1);
+		}
+	}
+}
@@ -0,0 +1,143 @@
+//! The command line argument parser we have inherited from the Nintendo CLI.
+//!
+//! We have never had a copy of `imageuploader` to compare against, so these
+//! arguments are our best reconstruction of the official tool, using the same
+//! style of options as `setbridgeconfig`, and `getbridgeconfig`.
+
+use std::net::Ipv4Addr;
+
+/// The top-level command line options.
+#[derive(Debug)]
+pub struct CliOpts {
+	/// The name of the bridge to use, instead of the default bridge.
+	pub bridge_name: Option<String>,
+	/// The IP Address of the bridge to use, instead of the default bridge.
+	pub bridge_ipaddr: Option<Ipv4Addr>,
+	/// The name of an image to delete from the HDD.
+	pub delete: Option<String>,
+	/// If the user acknowledged that changing the images on the HDD is
+	/// unverified.
+	pub experimental: bool,
+	/// If the user requested the help page.
+	pub help: bool,
+	/// If an argument couldn't be understood.
+	pub invalid_argument: Option<String>,
+	/// If we should list the images on the HDD.
+	pub list: bool,
+	/// The name to store the uploaded image as, defaults to the file name.
+	pub name: Option<String>,
+	/// The name of an image on the HDD to select.
+	pub select: Option<String>,
+	/// The path to an image to upload to the HDD.
+	pub upload: Option<String>,
+}
+impl CliOpts {
+	pub fn print_help() {
+		println!(
+			"imageuploader - Copyright (c) 2011-2013 Nintendo Co., Ltd.
+
+  Manages the mastered WUM/WUMAD images stored on the internal HDD
+  of a CAT-DEV.
+
+  Images are checked before being uploaded, and an interrupted upload
+  continues where it stopped when run again with the same image.
+  Operations are run in the order: delete, upload, select, list.
+
+  If no bridge is specified the current bridge is used.
+
+  The image format, and how images are managed on the bridge are
+  unverified, a real bridge will most likely reject them. Nothing is
+  uploaded, selected, or deleted unless -experimental is passed.
+
+Usage:
+  imageuploader [options] -experimental -upload <image_file> [-name <name>]
+  imageuploader [options] -list
+  imageuploader [options] -experimental -select <name>
+  imageuploader [options] -experimental -delete <name>
+
+Options:
+  -bridge <name>....Uses the bridge specified by <name>.
+  -ip <ip_addr>.....Uses the bridge at <ip_addr>.
+  -upload <file>....Uploads the image <file> to the HDD.
+  -name <name>......Stores the uploaded image as <name>, instead of
+                    the name of the file.
+  -select <name>....Selects the image <name> as the disc to read.
+  -delete <name>....Deletes the image <name> from the HDD.
+  -list.............Lists the images on the HDD.
+  -experimental.....Acknowledges that changing the images on the HDD
+                    is unverified.
+  -h................Prints this help message.\n"
+		);
+	}
+
+	/// If any operation was requested at all.
+	#[must_use]
+	pub const fn has_operation(&self) -> bool {
+		self.list || self.has_change()
+	}
+
+	/// If any operation that changes the images on the HDD was requested.
+	#[must_use]
+	pub const fn has_change(&self) -> bool {
+		self.upload.is_some() || self.select.is_some() || self.delete.is_some()
+	}
+}
+impl<Ty: Iterator<Item = String>> From<Ty> for CliOpts {
+	fn from(arguments: Ty) -> Self {
+		let mut opts = Self {
+			bridge_name: None,
+			bridge_ipaddr: None,
+			delete: None,
+			experimental: false,
+			help: false,
+			invalid_argument: None,
+			list: false,
+			name: None,
+			select: None,
+			upload: None,
+		};
+
+		let mut arguments = arguments.peekable();
+		while let Some(item) = arguments.next() {
+			// Every option besides these takes a value.
+			match item.as_str() {
+				"-list" => {
+					opts.list = true;
+					continue;
+				}
+				"-experimental" => {
+					opts.experimental = true;
+					continue;
+				}
+				"-h" | "-help" | "-?" => {
+					opts.help = true;
+					continue;
+				}
+				_ if !item.starts_with('-') => {
+					opts.invalid_argument = Some(item);
+					continue;
+				}
+				_ => {}
+			}
+			let value = arguments.next_if(|value| !value.starts_with('-'));
+			let Some(value) = value else {
+				opts.invalid_argument = Some(item);
+				continue;
+			};
+			match item.as_str() {
+				"-bridge" => opts.bridge_name = Some(value),
+				"-ip" => match value.parse::<Ipv4Addr>() {
+					Ok(ip) => opts.bridge_ipaddr = Some(ip),
+					Err(_cause) => opts.invalid_argument = Some(format!("{item} {value}")),
+				},
+				"-upload" => opts.upload = Some(value),
+				"-name" => opts.name = Some(value),
+				"-select" => opts.select = Some(value),
+				"-delete" => opts.delete = Some(value),
+				_ => opts.invalid_argument = Some(item),
+			}
+		}
+
+		opts
+	}
+}
@@ -0,0 +1,226 @@
+#![allow(
+	// I've always disliked this rule, most of the time imports are used WITHOUT
+	// the module name, and the module name is only used in the top level import.
+	//
+	// Where this becomes significantly more helpful to read as it's out of
+	// context.
+	clippy::module_name_repetitions,
+)]
+
+pub mod knobs;
+
+use crate::knobs::cli::CliOpts;
+use cat_dev::{
+	errors::{CatBridgeError, NetworkError},
+	mion::{
+		cgis::{MionHttpClient, RetryPolicy},
+		image::ImageHeader,
+	},
+	BridgeHostState,
+};
+use std::{
+	io::{stdout, Write},
+	net::Ipv4Addr,
+	path::Path,
+	time::Duration,
+};
+use tokio::runtime::Runtime;
+
+/// How many times a chunk of an upload that got no response is retried, a
+/// multi-gigabyte upload shouldn't have to start over because of one dropped
+/// connection.
+const UPLOAD_RETRIES: u8 = 5;
+/// How long to wait before retrying a chunk.
+const UPLOAD_RETRY_DELAY: Duration = Duration::from_secs(2);
+
+fn main() {
+	let opts = CliOpts::from(std::env::args().skip(1));
+	if opts.help || !opts.has_operation() {
+		CliOpts::print_help();
+		std::process::exit(-1);
+	}
+	if let Some(argument) = opts.invalid_argument.as_ref() {
+		println!("\nERROR : Invalid argument \"{argument}\"");
+		std::process::exit(-1);
+	}
+	if opts.has_change() && !opts.experimental {
+		println!("\nERROR : Changing the images on the HDD is unverified, and a real bridge will most likely reject it (use -experimental to try anyway)");
+		std::process::exit(-1);
+	}
+	let Ok(runtime) = Runtime::new() else {
+		println!("\nERROR : Could not start up, please try again.");
+		std::process::exit(-1);
+	};
+
+	let bridge_ip = get_bridge_ip(&runtime, &opts);
+	let client = MionHttpClient::new()
+		.with_retry_policy(RetryPolicy::new(UPLOAD_RETRIES, UPLOAD_RETRY_DELAY));
+
+	if let Some(name) = opts.delete.as_ref() {
+		if let Err(cause) = runtime.block_on(client.delete_experimental_image(bridge_ip, name)) {
+			println!("\nERROR : Could not delete image \"{name}\": {cause}");
+			std::process::exit(-1);
+		}
+		println!("Deleted image \"{name}\"");
+	}
+	if let Some(path) = opts.upload.as_ref() {
+		upload(
+			&runtime,
+			&client,
+			bridge_ip,
+			Path::new(path),
+			opts.name.as_deref(),
+		);
+	}
+	if let Some(name) = opts.select.as_ref() {
+		if let Err(cause) = runtime.block_on(client.select_experimental_image(bridge_ip, name)) {
+			println!("\nERROR : Could not select image \"{name}\": {cause}");
+			std::process::exit(-1);
+		}
+		println!("Selected image \"{name}\"");
+	}
+	if opts.list {
+		list(&runtime, &client, bridge_ip);
+	}
+}
+
+/// Figure out which bridge to talk to, the same way the rest of the SDK does.
+fn get_bridge_ip(runtime: &Runtime, opts: &CliOpts) -> Ipv4Addr {
+	if let Some(ip) = opts.bridge_ipaddr {
+		return ip;
+	}
+	if opts.bridge_name.is_none() {
+		if let Some(ip) = std::env::var("BRIDGE_CURRENT_IP_ADDRESS")
+			.ok()
+			.and_then(|ip| ip.parse::<Ipv4Addr>().ok())
+		{
+			return ip;
+		}
+	}
+
+	let Some(host_path) = BridgeHostState::get_default_host_path() else {
+		println!(
+			"\nERROR 203: Could not retrieve install path. Is Host Bridge Software installed?"
+		);
+		std::process::exit(-1);
+	};
+	let Ok(host_env) = runtime.block_on(BridgeHostState::load_explicit_path(host_path)) else {
+		println!("\nERROR 30: Could not retrieve install path. Is Host Bridge Software installed?");
+		std::process::exit(-1);
+	};
+	let name = opts
+		.bridge_name
+		.clone()
+		.or_else(|| std::env::var("BRIDGE_CURRENT_NAME").ok());
+	let found = if let Some(name) = name {
+		host_env
+			.get_bridge(&name)
+			.map(|(ip, _is_default)| (name, ip))
+	} else {
+		host_env.get_default_bridge()
+	};
+
+	match found {
+		Some((_name, Some(ip))) => ip,
+		Some((name, None)) => {
+			println!("\nERROR : Bridge \"{name}\" does not have an IP address");
+			std::process::exit(-1);
+		}
+		None => {
+			println!("\nERROR : No bridge specified, and no default bridge is set");
+			std::process::exit(-1);
+		}
+	}
+}
+
+fn upload(
+	runtime: &Runtime,
+	client: &MionHttpClient,
+	bridge_ip: Ipv4Addr,
+	path: &Path,
+	name: Option<&str>,
+) {
+	let header = match runtime.block_on(ImageHeader::load(path)) {
+		Ok(header) => header,
+		Err(cause) => {
+			println!(
+				"\nERROR : Could not load image \"{}\": {cause}",
+				path.display()
+			);
+			std::process::exit(-1);
+		}
+	};
+	let name = name.map_or_else(
+		|| {
+			path.file_name()
+				.map(|file_name| file_name.to_string_lossy().into_owned())
+				.unwrap_or_default()
+		},
+		ToOwned::to_owned,
+	);
+	println!("Image name            : {name}");
+	println!("Image type            : {}", header.kind());
+	println!("Product code          : {}", header.product_code());
+	println!("Title ID              : {:016X}", header.title_id());
+	println!("Image size            : {} bytes", header.image_size());
+
+	print!("Uploading");
+	_ = stdout().flush();
+	let mut last_step = None;
+	let result = runtime.block_on(client.upload_experimental_image(
+		bridge_ip,
+		path,
+		&name,
+		|sent, total| {
+			if last_step.is_none() && sent > 0 {
+				print!(" (resuming at {sent} bytes)");
+			}
+			let step = sent.saturating_mul(10).checked_div(total).unwrap_or(10);
+			let mut printed = last_step.unwrap_or(0);
+			while printed < step {
+				printed += 1;
+				print!(".");
+			}
+			last_step = Some(printed);
+			_ = stdout().flush();
+		},
+	));
+	println!();
+
+	if let Err(cause) = result {
+		println!("ERROR : Could not upload image \"{name}\": {cause}");
+		if matches!(
+			cause,
+			CatBridgeError::NetworkError(NetworkError::HyperError(_) | NetworkError::TimeoutError)
+		) {
+			println!("Run the same upload again to continue where it stopped.");
+		}
+		std::process::exit(-1);
+	}
+	println!("Uploaded image \"{name}\"");
+}
+
+fn list(runtime: &Runtime, client: &MionHttpClient, bridge_ip: Ipv4Addr) {
+	let images = match runtime.block_on(client.list_images(bridge_ip)) {
+		Ok(images) => images,
+		Err(cause) => {
+			println!("\nERROR : Could not list images: {cause}");
+			std::process::exit(-1);
+		}
+	};
+
+	println!();
+	println!("  Image Name                                                       : Size");
+	println!(
+		"--------------------------------------------------------------------------------------"
+	);
+	for image in images {
+		println!(
+			"{} {:<64} : {}",
+			if image.is_selected() { '*' } else { ' ' },
+			image.name(),
+			image.size(),
+		);
+	}
+	println!();
+}
@@ -30,6 +30,10 @@ pub struct CliOpts {
 	pub verbose: bool,
 	/// Yes... the official tool does act differently if verbose appeared before/after the ip.
 	pub verbose_appeared_before_ip: bool,
+	/// If someone passed `--force` to allow setting risky offsets.
+	///
+	/// This is not part of the official tool, which would write anything.
+	pub force: bool,
 }
 impl CliOpts {
 	pub fn print_help() {
@@ -40,7 +44,8 @@ where: -v        verbose
        -t        sets timeout value in milliseconds
        MION_IP   IP address of the MION device
        OFFSET    byte offset(0-511) of the value to get/set
-       -s VALUE  set the value(0-255) at the offset"#
+       -s VALUE  set the value(0-255) at the offset
+       --force   allow setting offsets that may leave the unit unbootable"#
 		);
 	}
 }
@@ -55,6 +60,7 @@ impl<Ty: Iterator<Item = String>> From<Ty> for CliOpts {
 			tried_to_set_value: false,
 			verbose: false,
 			verbose_appeared_before_ip: false,
+			force: false,
 		};
 
 		let mut next_is_set = false;
@@ -74,6 +80,7 @@ impl<Ty: Iterator<Item = String>> From<Ty> for CliOpts {
 
 			match item.as_str() {
 				"-d" => opts.hex_dump = true,
+				"--force" => opts.force = true,
 				"-t" => next_is_timeout = true,
 				"-s" => {
 					next_is_set = true;
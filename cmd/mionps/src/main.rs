@@ -23,7 +23,13 @@ use cat_dev::{
 	errors::{CatBridgeError, NetworkError, NetworkParseError},
 	mion::{
 		parameter::{get_parameters_with_logging_hooks, set_parameters_with_logging_hooks},
-		proto::parameter::{well_known::ParameterLocationSpecification, DumpedMionParameters},
+		proto::parameter::{
+			well_known::{
+				definitions_at_index, safety_of_index, ParameterDefinition,
+				ParameterLocationSpecification, ParameterSafety,
+			},
+			DumpedMionParameters,
+		},
 	},
 };
 use std::{env::args, net::Ipv4Addr};
@@ -100,6 +106,13 @@ fn main() {
 			}
 			exit_with_verbose_message(10, opts.verbose);
 		};
+		if let Err(message) = ensure_offset_is_safe_to_write(offset, opts.force) {
+			log_error(&message);
+			if opts.verbose {
+				log_verbose("Returning early, error=11");
+			}
+			exit_with_verbose_message(11, opts.verbose);
+		}
 
 		runtime.block_on(do_set(
 			ip,
@@ -135,6 +148,27 @@ fn main() {
 	}
 }
 
+/// The official tool will write anything to any offset, we refuse to write
+/// risky offsets without `--force`, and read-only offsets at all.
+fn ensure_offset_is_safe_to_write(offset: u16, force: bool) -> Result<(), String> {
+	let index = usize::from(offset);
+	let safety = safety_of_index(index);
+	if safety == ParameterSafety::ReadOnly {
+		return Err(format!("offset({offset}) is read-only"));
+	}
+	if safety.requires_force() && !force {
+		let consequences = definitions_at_index(index)
+			.filter_map(ParameterDefinition::consequences)
+			.collect::<Vec<_>>();
+		return Err(format!(
+			"offset({offset}) is {safety}, pass --force to set it anyway. {}",
+			consequences.first().copied().unwrap_or_default(),
+		));
+	}
+
+	Ok(())
+}
+
 async fn do_set(ip: Ipv4Addr, timeout: Option<Duration>, offset: u16, value: u8, verbose: bool) {
 	let (result, old_values) = match set_parameters_with_logging_hooks(
 		vec![(ParameterLocationSpecification::Index(offset), value)].into_iter(),
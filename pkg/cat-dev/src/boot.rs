@@ -0,0 +1,743 @@
+//! Booting a device the same way `cafe`/`cafex run` do.
+//!
+//! Booting is a handful of steps that all have to happen in order, and any of
+//! them can hang on a bridge that isn't behaving. So rather than one large
+//! function, booting is broken up into [`BootStage`]s:
+//!
+//! 1. [`BootStage::DetectSdkVersion`]: read the SDK version out of
+//!    `CAFE_ROOT`.
+//! 2. [`BootStage::SetSdkVersion`]: write that version to the parameter space
+//!    (indexes 3-5), so the bridge matches the SDK we're booting with.
+//! 3. [`BootStage::SetBootMode`]: tell the MION where to boot from, only if a
+//!    boot mode was explicitly chosen as `boot_type` is an experimental
+//!    setting.
+//! 4. [`BootStage::PowerOn`]: power on through `control.cgi`.
+//! 5. [`BootStage::WaitForReady`]: tail the serial console until the device
+//!    prints a "ready" marker.
+//!
+//! Every stage has it's own timeout, and reports it's progress through
+//! [`BootEvent`]s, so a CLI only has to render events as they arrive. Stages
+//! that have nothing to do (e.g. there's no serial port to wait on) are
+//! reported as skipped rather than silently left out.
+
+use crate::{
+	errors::{BootError, CatBridgeError, FSError},
+	mion::{
+		cgis::MionHttpClient,
+		parameter::MionParameterClient,
+		proto::{
+			cgis::{PowerOnRequest, SetParameter},
+			control::MIONBootType,
+			parameter::well_known::ParameterLocationSpecification,
+		},
+	},
+};
+use fnv::FnvHashMap;
+use hyper::client::{connect::Connect, HttpConnector};
+use std::{
+	fmt::{Display, Formatter, Result as FmtResult},
+	future::Future,
+	net::Ipv4Addr,
+	path::{Path, PathBuf},
+	time::{Duration, Instant},
+};
+use tokio::{
+	io::{AsyncBufRead, AsyncBufReadExt},
+	time::timeout,
+};
+use valuable::Valuable;
+
+/// Where the SDK version lives, relative to `CAFE_ROOT`.
+pub const SDK_VERSION_HEADER: [&str; 3] = ["system", "include", "sdk_ver.h"];
+/// The define in [`SDK_VERSION_HEADER`] that holds the version, e.g. `21213`
+/// for `2.12.13`.
+pub const SDK_VERSION_DEFINE: &str = "CAFE_OS_SDK_VERSION";
+/// The line we wait for on the serial console before considering the device
+/// booted.
+///
+/// *note: this is our best guess, what gets printed differs between SDK
+/// versions, and titles, so callers should let users override it.*
+pub const DEFAULT_READY_MARKER: &str = "COS: Initialization complete";
+
+/// A single step of booting a device.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, Valuable)]
+pub enum BootStage {
+	/// Read the SDK version out of `CAFE_ROOT`.
+	DetectSdkVersion,
+	/// Write the SDK version to the parameter space.
+	SetSdkVersion,
+	/// Set what the MION boots from.
+	SetBootMode,
+	/// Power on through `control.cgi`.
+	PowerOn,
+	/// Tail the serial console until the device is ready.
+	WaitForReady,
+}
+impl BootStage {
+	/// Every stage, in the order they run.
+	pub const ALL: [Self; 5] = [
+		Self::DetectSdkVersion,
+		Self::SetSdkVersion,
+		Self::SetBootMode,
+		Self::PowerOn,
+		Self::WaitForReady,
+	];
+
+	/// How long a stage gets if it hasn't been overridden.
+	#[must_use]
+	pub const fn default_timeout(self) -> Duration {
+		match self {
+			Self::DetectSdkVersion => Duration::from_secs(5),
+			Self::SetSdkVersion | Self::SetBootMode => Duration::from_secs(15),
+			Self::PowerOn => Duration::from_secs(30),
+			// Booting a title off of PCFS can take quite a while.
+			Self::WaitForReady => Duration::from_secs(300),
+		}
+	}
+}
+impl Display for BootStage {
+	fn fmt(&self, fmt: &mut Formatter<'_>) -> FmtResult {
+		match self {
+			Self::DetectSdkVersion => write!(fmt, "detect-sdk-version"),
+			Self::SetSdkVersion => write!(fmt, "set-sdk-version"),
+			Self::SetBootMode => write!(fmt, "set-boot-mode"),
+			Self::PowerOn => write!(fmt, "power-on"),
+			Self::WaitForReady => write!(fmt, "wait-for-ready"),
+		}
+	}
+}
+
+/// Progress of a [`BootPipeline`], sent as it happens.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub enum BootEvent {
+	/// A stage has started.
+	StageStarted(BootStage),
+	/// A stage had nothing to do, and was skipped.
+	StageSkipped(BootStage),
+	/// A stage finished successfully, and how long it took.
+	StageFinished(BootStage, Duration),
+	/// The SDK version that will be written to the bridge.
+	SdkVersion([u8; 3]),
+	/// A line read from the serial console while waiting for the device.
+	SerialLine(String),
+}
+
+/// Boot a device, see the module documentation for what each stage does.
+#[derive(Clone, Debug)]
+pub struct BootPipeline<ClientConnectorTy = HttpConnector> {
+	bridge_ip: Ipv4Addr,
+	boot_mode: Option<MIONBootType>,
+	cafe_root: Option<PathBuf>,
+	client: MionHttpClient<ClientConnectorTy>,
+	emulation: bool,
+	host: Option<Ipv4Addr>,
+	parameter_port: Option<u16>,
+	ready_marker: String,
+	sdk_version: Option<[u8; 3]>,
+	timeouts: FnvHashMap<BootStage, Duration>,
+}
+
+impl BootPipeline {
+	/// Create a pipeline that powers on a bridge with disc emulation, leaving
+	/// it's boot mode as is, with every stage using it's default timeout.
+	#[must_use]
+	pub fn new(bridge_ip: Ipv4Addr) -> Self {
+		Self {
+			bridge_ip,
+			boot_mode: None,
+			cafe_root: None,
+			client: MionHttpClient::new(),
+			emulation: true,
+			host: None,
+			parameter_port: None,
+			ready_marker: DEFAULT_READY_MARKER.to_owned(),
+			sdk_version: None,
+			timeouts: FnvHashMap::default(),
+		}
+	}
+}
+
+impl<ClientConnectorTy> BootPipeline<ClientConnectorTy>
+where
+	ClientConnectorTy: Clone + Connect + Send + Sync + 'static,
+{
+	/// Use a specific HTTP client for every CGI request.
+	#[must_use]
+	pub fn with_http_client<OtherConnectorTy>(
+		self,
+		client: MionHttpClient<OtherConnectorTy>,
+	) -> BootPipeline<OtherConnectorTy> {
+		BootPipeline {
+			bridge_ip: self.bridge_ip,
+			boot_mode: self.boot_mode,
+			cafe_root: self.cafe_root,
+			client,
+			emulation: self.emulation,
+			host: self.host,
+			parameter_port: self.parameter_port,
+			ready_marker: self.ready_marker,
+			sdk_version: self.sdk_version,
+			timeouts: self.timeouts,
+		}
+	}
+
+	/// Set what the MION boots from before powering on, anything other than
+	/// [`MIONBootType::NAND`] also powers on with disc emulation.
+	///
+	/// Without this [`BootStage::SetBootMode`] is skipped.
+	#[must_use]
+	pub fn with_boot_mode(mut self, boot_mode: MIONBootType) -> Self {
+		self.boot_mode = Some(boot_mode);
+		self.emulation = !matches!(boot_mode, MIONBootType::NAND);
+		self
+	}
+
+	/// Set if the MION powers on with disc emulation, without changing it's
+	/// boot mode.
+	#[must_use]
+	pub fn with_emulation(mut self, emulation: bool) -> Self {
+		self.emulation = emulation;
+		self
+	}
+
+	/// Read the SDK version to write to the bridge from this `CAFE_ROOT`.
+	#[must_use]
+	pub fn with_cafe_root(mut self, cafe_root: impl Into<PathBuf>) -> Self {
+		self.cafe_root = Some(cafe_root.into());
+		self
+	}
+
+	/// Write this SDK version to the bridge, rather than reading it from
+	/// `CAFE_ROOT`.
+	#[must_use]
+	pub fn with_sdk_version(mut self, sdk_version: [u8; 3]) -> Self {
+		self.sdk_version = Some(sdk_version);
+		self
+	}
+
+	/// Set the host that is taking control of the MION, if not set this is
+	/// the local ip of this machine.
+	#[must_use]
+	pub fn with_host(mut self, host: Ipv4Addr) -> Self {
+		self.host = Some(host);
+		self
+	}
+
+	/// Use a parameter port other than the default.
+	#[must_use]
+	pub fn with_parameter_port(mut self, parameter_port: u16) -> Self {
+		self.parameter_port = Some(parameter_port);
+		self
+	}
+
+	/// Wait for a different line on the serial console, see
+	/// [`DEFAULT_READY_MARKER`].
+	#[must_use]
+	pub fn with_ready_marker(mut self, ready_marker: impl Into<String>) -> Self {
+		self.ready_marker = ready_marker.into();
+		self
+	}
+
+	/// Override how long a single stage is allowed to take.
+	#[must_use]
+	pub fn with_stage_timeout(mut self, stage: BootStage, stage_timeout: Duration) -> Self {
+		self.timeouts.insert(stage, stage_timeout);
+		self
+	}
+
+	#[must_use]
+	pub const fn bridge_ip(&self) -> Ipv4Addr {
+		self.bridge_ip
+	}
+
+	#[must_use]
+	pub const fn boot_mode(&self) -> Option<MIONBootType> {
+		self.boot_mode
+	}
+
+	#[must_use]
+	pub const fn emulation(&self) -> bool {
+		self.emulation
+	}
+
+	#[must_use]
+	pub fn ready_marker(&self) -> &str {
+		&self.ready_marker
+	}
+
+	/// How long a stage is allowed to take.
+	#[must_use]
+	pub fn stage_timeout(&self, stage: BootStage) -> Duration {
+		self.timeouts
+			.get(&stage)
+			.copied()
+			.unwrap_or_else(|| stage.default_timeout())
+	}
+
+	/// Run every stage in order, calling `on_event` as things happen.
+	///
+	/// If no serial console is passed in [`BootStage::WaitForReady`] is
+	/// skipped, and this returns as soon as the device has been powered on.
+	/// The serial console is only borrowed so callers can keep reading from it
+	/// once the device is ready.
+	///
+	/// ## Errors
+	///
+	/// - [`BootError::StageTimedOut`] if any stage takes longer than it's
+	///   timeout.
+	/// - [`BootError::StageFailed`] if any stage fails, wrapping the error that
+	///   caused it.
+	#[allow(
+		// Every stage is small, but there are quite a few of them.
+		clippy::too_many_lines,
+	)]
+	pub async fn run<SerialTy, EventFnTy>(
+		&self,
+		serial: Option<&mut SerialTy>,
+		mut on_event: EventFnTy,
+	) -> Result<(), CatBridgeError>
+	where
+		SerialTy: AsyncBufRead + Unpin,
+		EventFnTy: FnMut(BootEvent),
+	{
+		let sdk_version = if let Some(version) = self.sdk_version {
+			on_event(BootEvent::StageSkipped(BootStage::DetectSdkVersion));
+			Some(version)
+		} else if let Some(cafe_root) = self.cafe_root.as_ref() {
+			on_event(BootEvent::StageStarted(BootStage::DetectSdkVersion));
+			let started = Instant::now();
+			let version = self
+				.timed(
+					BootStage::DetectSdkVersion,
+					sdk_version_from_cafe_root(cafe_root),
+				)
+				.await?;
+			on_event(BootEvent::StageFinished(
+				BootStage::DetectSdkVersion,
+				started.elapsed(),
+			));
+			Some(version)
+		} else {
+			on_event(BootEvent::StageSkipped(BootStage::DetectSdkVersion));
+			None
+		};
+
+		if let Some(version) = sdk_version {
+			on_event(BootEvent::SdkVersion(version));
+			on_event(BootEvent::StageStarted(BootStage::SetSdkVersion));
+			let started = Instant::now();
+			let mut parameters = MionParameterClient::new(
+				self.bridge_ip,
+				self.parameter_port,
+				Some(self.stage_timeout(BootStage::SetSdkVersion)),
+			);
+			self.timed(BootStage::SetSdkVersion, async {
+				parameters
+					.set_many_transactionally(version.iter().zip(3_u16..).map(
+						|(component, index)| {
+							(ParameterLocationSpecification::Index(index), *component)
+						},
+					))
+					.await
+					.map(|_| ())
+			})
+			.await?;
+			on_event(BootEvent::StageFinished(
+				BootStage::SetSdkVersion,
+				started.elapsed(),
+			));
+		} else {
+			on_event(BootEvent::StageSkipped(BootStage::SetSdkVersion));
+		}
+
+		if let Some(boot_mode) = self.boot_mode {
+			on_event(BootEvent::StageStarted(BootStage::SetBootMode));
+			let started = Instant::now();
+			self.timed(BootStage::SetBootMode, async {
+				// `boot_type` is an experimental setting, a real MION may not accept it.
+				if self
+					.client
+					.set_experimental_param(self.bridge_ip, SetParameter::BootType(boot_mode))
+					.await?
+				{
+					Ok(())
+				} else {
+					Err(BootError::BootTypeRejected(boot_mode).into())
+				}
+			})
+			.await?;
+			on_event(BootEvent::StageFinished(
+				BootStage::SetBootMode,
+				started.elapsed(),
+			));
+		} else {
+			on_event(BootEvent::StageSkipped(BootStage::SetBootMode));
+		}
+
+		on_event(BootEvent::StageStarted(BootStage::PowerOn));
+		let started = Instant::now();
+		let mut request = PowerOnRequest::new().with_emulation(self.emulation);
+		if let Some(host) = self.host {
+			request = request.with_host(host);
+		}
+		self.timed(BootStage::PowerOn, async {
+			self.client
+				.power_on(self.bridge_ip, &request)
+				.await
+				.map(|_| ())
+		})
+		.await?;
+		on_event(BootEvent::StageFinished(
+			BootStage::PowerOn,
+			started.elapsed(),
+		));
+
+		if let Some(serial) = serial {
+			on_event(BootEvent::StageStarted(BootStage::WaitForReady));
+			let started = Instant::now();
+			self.timed(
+				BootStage::WaitForReady,
+				wait_for_ready(serial, &self.ready_marker, &mut on_event),
+			)
+			.await?;
+			on_event(BootEvent::StageFinished(
+				BootStage::WaitForReady,
+				started.elapsed(),
+			));
+		} else {
+			on_event(BootEvent::StageSkipped(BootStage::WaitForReady));
+		}
+
+		Ok(())
+	}
+
+	/// Run a single stage with it's timeout, tagging any error with the stage.
+	async fn timed<ResultTy, FutureTy>(
+		&self,
+		stage: BootStage,
+		future: FutureTy,
+	) -> Result<ResultTy, CatBridgeError>
+	where
+		FutureTy: Future<Output = Result<ResultTy, CatBridgeError>>,
+	{
+		let stage_timeout = self.stage_timeout(stage);
+		match timeout(stage_timeout, future).await {
+			Ok(Ok(result)) => Ok(result),
+			Ok(Err(cause)) => Err(BootError::StageFailed(stage, Box::new(cause)).into()),
+			Err(_) => Err(BootError::StageTimedOut(stage, stage_timeout).into()),
+		}
+	}
+}
+
+/// Read the SDK version out of a `CAFE_ROOT`, see [`SDK_VERSION_HEADER`].
+///
+/// ## Errors
+///
+/// - If the header could not be read.
+/// - If the header doesn't define [`SDK_VERSION_DEFINE`], or it isn't a
+///   version we can write to the bridge.
+pub async fn sdk_version_from_cafe_root(cafe_root: &Path) -> Result<[u8; 3], CatBridgeError> {
+	let header = SDK_VERSION_HEADER
+		.iter()
+		.fold(cafe_root.to_path_buf(), |path, component| {
+			path.join(component)
+		});
+	let contents = tokio::fs::read(&header).await.map_err(FSError::from)?;
+	parse_sdk_version_header(&String::from_utf8_lossy(&contents))
+		.ok_or_else(|| BootError::SdkVersionNotFound(header).into())
+}
+
+/// Find the SDK version in the contents of [`SDK_VERSION_HEADER`].
+fn parse_sdk_version_header(contents: &str) -> Option<[u8; 3]> {
+	let raw = contents.lines().find_map(|line| {
+		let mut parts = line.split_whitespace();
+		if parts.next() != Some("#define") || parts.next() != Some(SDK_VERSION_DEFINE) {
+			return None;
+		}
+		parts.next()?.parse::<u32>().ok()
+	})?;
+
+	Some([
+		u8::try_from(raw / 10000).ok()?,
+		u8::try_from((raw / 100) % 100).ok()?,
+		u8::try_from(raw % 100).ok()?,
+	])
+}
+
+/// Read lines from the serial console until one contains the marker.
+///
+/// The serial console ends lines with `\r`, `\n`, or both depending on who is
+/// printing, so we split on either, and skip the empty lines in between.
+async fn wait_for_ready<SerialTy, EventFnTy>(
+	serial: &mut SerialTy,
+	ready_marker: &str,
+	on_event: &mut EventFnTy,
+) -> Result<(), CatBridgeError>
+where
+	SerialTy: AsyncBufRead + Unpin,
+	EventFnTy: FnMut(BootEvent),
+{
+	let mut line = Vec::new();
+	loop {
+		let available = serial.fill_buf().await.map_err(FSError::from)?;
+		if available.is_empty() {
+			return Err(BootError::SerialClosedBeforeReady(ready_marker.to_owned()).into());
+		}
+
+		let (consumed, finished_line) = if let Some(end) = available
+			.iter()
+			.position(|byte| matches!(byte, b'\r' | b'\n'))
+		{
+			line.extend_from_slice(&available[..end]);
+			(end + 1, true)
+		} else {
+			line.extend_from_slice(available);
+			(available.len(), false)
+		};
+		serial.consume(consumed);
+		if !finished_line || line.is_empty() {
+			continue;
+		}
+
+		let text = String::from_utf8_lossy(&line).into_owned();
+		line.clear();
+		let is_ready = text.contains(ready_marker);
+		on_event(BootEvent::SerialLine(text));
+		if is_ready {
+			return Ok(());
+		}
+	}
+}
+
+#[cfg(test)]
+mod unit_tests {
+	use super::*;
+	use crate::{
+		errors::NetworkError,
+		mion::{emulator::EmulatedMion, proto::control::MionIdentity},
+	};
+	use mac_address::MacAddress;
+	use tokio::io::{AsyncWriteExt, BufReader};
+
+	async fn emulator() -> EmulatedMion {
+		EmulatedMion::spawn(
+			MionIdentity::new(
+				None,
+				[0, 14, 80, 1],
+				[0x71, 0x20, 0x05, 0x13],
+				Ipv4Addr::LOCALHOST,
+				MacAddress::new([0x00, 0x25, 0x5c, 0xba, 0x5a, 0x00]),
+				"00-25-5C-BA-5A-00".to_owned(),
+			)
+			.expect("Failed to create identity!"),
+		)
+		.await
+		.expect("Failed to spawn emulated MION!")
+	}
+
+	fn pipeline(mion: &EmulatedMion) -> BootPipeline<impl Clone + Connect + Send + Sync + 'static> {
+		BootPipeline::new(Ipv4Addr::LOCALHOST)
+			.with_http_client(MionHttpClient::from_raw_client(mion.http_client()))
+			.with_parameter_port(mion.parameter_address().port())
+			.with_host(Ipv4Addr::new(192, 168, 7, 10))
+	}
+
+	#[test]
+	pub fn can_parse_sdk_version_headers() {
+		assert_eq!(
+			parse_sdk_version_header(
+				"#ifndef __SDK_VER_H__\n#define __SDK_VER_H__\n\n#define CAFE_OS_SDK_VERSION 21213\n\n#endif\n"
+			),
+			Some([2, 12, 13]),
+		);
+		assert_eq!(
+			parse_sdk_version_header("#define  CAFE_OS_SDK_VERSION\t20801 // 2.08.01\r\n"),
+			Some([2, 8, 1]),
+		);
+		assert_eq!(
+			parse_sdk_version_header("#define CAFE_OS_SDK_VERSION"),
+			None
+		);
+		assert_eq!(
+			parse_sdk_version_header("#define CAFE_OS_SDK_VERSION_MAJOR 2"),
+			None
+		);
+		assert_eq!(
+			parse_sdk_version_header("#define CAFE_OS_SDK_VERSION 3000000"),
+			None,
+			"Versions that can't fit in the parameter space should be refused!",
+		);
+	}
+
+	#[tokio::test]
+	pub async fn runs_every_stage_in_order() {
+		let cafe_root = tempfile::tempdir().expect("Failed to create temporary directory!");
+		let include = cafe_root.path().join("system").join("include");
+		tokio::fs::create_dir_all(&include)
+			.await
+			.expect("Failed to create include directory!");
+		tokio::fs::write(
+			include.join("sdk_ver.h"),
+			"#define CAFE_OS_SDK_VERSION 21213\n",
+		)
+		.await
+		.expect("Failed to write sdk_ver.h!");
+
+		let mion = emulator().await;
+		let (mut device, host) = tokio::io::duplex(64);
+		device
+			.write_all(b"booting...\r\n\r\nCOS: Initialization complete\rleftover")
+			.await
+			.expect("Failed to write serial output!");
+		let mut serial = BufReader::new(host);
+
+		let mut events = Vec::new();
+		pipeline(&mion)
+			.with_cafe_root(cafe_root.path())
+			.with_boot_mode(MIONBootType::DUAL)
+			.run(Some(&mut serial), |event| events.push(event))
+			.await
+			.expect("Failed to run boot pipeline!");
+
+		let parameters = mion.parameters();
+		assert_eq!(&parameters[3..6], &[2, 12, 13]);
+		assert_eq!(
+			mion.set_params().get("boot_type").map(String::as_str),
+			Some("DUAL")
+		);
+		assert!(mion.is_powered_on());
+		assert!(mion.is_emulating());
+		assert_eq!(mion.owner(), Some(Ipv4Addr::new(192, 168, 7, 10)));
+
+		let started = events
+			.iter()
+			.filter_map(|event| match event {
+				BootEvent::StageStarted(stage) => Some(*stage),
+				_ => None,
+			})
+			.collect::<Vec<_>>();
+		assert_eq!(started, BootStage::ALL);
+		let finished = events
+			.iter()
+			.filter(|event| matches!(event, BootEvent::StageFinished(..)))
+			.count();
+		assert_eq!(finished, BootStage::ALL.len());
+		assert!(events.contains(&BootEvent::SdkVersion([2, 12, 13])));
+		assert_eq!(
+			events
+				.iter()
+				.filter_map(|event| match event {
+					BootEvent::SerialLine(line) => Some(line.as_str()),
+					_ => None,
+				})
+				.collect::<Vec<_>>(),
+			["booting...", "COS: Initialization complete"],
+		);
+
+		// Anything after the marker is left for the caller to keep reading.
+		drop(device);
+		let mut rest = String::new();
+		tokio::io::AsyncReadExt::read_to_string(&mut serial, &mut rest)
+			.await
+			.expect("Failed to read the rest of the serial output!");
+		assert_eq!(rest, "leftover");
+	}
+
+	#[tokio::test]
+	pub async fn skips_stages_with_nothing_to_do() {
+		let mion = emulator().await;
+		let mut events = Vec::new();
+		pipeline(&mion)
+			.with_emulation(false)
+			.run(None::<&mut BufReader<&[u8]>>, |event| events.push(event))
+			.await
+			.expect("Failed to run boot pipeline!");
+
+		for stage in [
+			BootStage::DetectSdkVersion,
+			BootStage::SetSdkVersion,
+			BootStage::SetBootMode,
+			BootStage::WaitForReady,
+		] {
+			assert!(
+				events.contains(&BootEvent::StageSkipped(stage)),
+				"Stage: {stage} was not skipped!",
+			);
+		}
+		assert!(
+			!mion.set_params().contains_key("boot_type"),
+			"The boot mode should be left alone unless one was chosen!",
+		);
+		assert!(mion.is_powered_on());
+		assert!(!mion.is_emulating(), "Emulation was turned off!");
+	}
+
+	#[tokio::test]
+	pub async fn tags_failures_with_their_stage() {
+		let mion = emulator().await;
+		mion.set_owner(Some(Ipv4Addr::new(192, 168, 7, 99)));
+		match pipeline(&mion)
+			.with_sdk_version([2, 12, 13])
+			.run(None::<&mut BufReader<&[u8]>>, |_| {})
+			.await
+		{
+			Err(CatBridgeError::BootError(BootError::StageFailed(BootStage::PowerOn, cause))) => {
+				assert!(matches!(
+					*cause,
+					CatBridgeError::NetworkError(NetworkError::MionInUseByAnotherHost(_))
+				));
+			}
+			val => panic!("Power on to an owned MION did not fail correctly:\n\n {val:?}"),
+		}
+
+		let missing_root = tempfile::tempdir().expect("Failed to create temporary directory!");
+		assert!(matches!(
+			pipeline(&mion)
+				.with_cafe_root(missing_root.path())
+				.run(None::<&mut BufReader<&[u8]>>, |_| {})
+				.await,
+			Err(CatBridgeError::BootError(BootError::StageFailed(
+				BootStage::DetectSdkVersion,
+				_
+			))),
+		));
+	}
+
+	#[tokio::test]
+	pub async fn times_out_waiting_for_ready() {
+		let mion = emulator().await;
+		let (mut device, host) = tokio::io::duplex(64);
+		device
+			.write_all(b"still booting\n")
+			.await
+			.expect("Failed to write serial output!");
+		let mut serial = BufReader::new(host);
+
+		let mut lines = 0;
+		match pipeline(&mion)
+			.with_stage_timeout(BootStage::WaitForReady, Duration::from_millis(100))
+			.run(Some(&mut serial), |event| {
+				if matches!(event, BootEvent::SerialLine(_)) {
+					lines += 1;
+				}
+			})
+			.await
+		{
+			Err(CatBridgeError::BootError(BootError::StageTimedOut(stage, duration))) => {
+				assert_eq!(stage, BootStage::WaitForReady);
+				assert_eq!(duration, Duration::from_millis(100));
+			}
+			val => panic!("Waiting for ready did not time out:\n\n {val:?}"),
+		}
+		assert_eq!(lines, 1);
+
+		drop(device);
+		assert!(matches!(
+			pipeline(&mion).run(Some(&mut serial), |_| {}).await,
+			Err(CatBridgeError::BootError(BootError::StageFailed(
+				BootStage::WaitForReady,
+				_
+			))),
+		));
+	}
+}
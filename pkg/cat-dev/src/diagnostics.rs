@@ -0,0 +1,271 @@
+//! Diagnostics about the host tooling, and the bridges it talks to.
+//!
+//! When something breaks the first question is always "what versions are you
+//! running?", and the answer is spread out between the host, the MION, and
+//! the detailed data the MION only sends when asked for it. This module
+//! collects all of those into a single report, which is what
+//! `hostdisplayversion` and `bridgectl version` print.
+
+use crate::{
+	errors::CatBridgeError,
+	mion::{
+		discovery::{find_mion, MIONFindBy},
+		proto::control::MionIdentity,
+	},
+	BridgeType,
+};
+use serde::Serialize;
+use std::{
+	fmt::{Display, Formatter, Result as FmtResult},
+	time::Duration,
+};
+use valuable::Valuable;
+
+/// The version of the host tooling, this is the version of `sprig` that is
+/// installed rather than the version of the official Host Bridge Software.
+pub const HOST_SOFTWARE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Everything we know about the versions running on the host, and a bridge.
+///
+/// Everything is already rendered as the strings the official tools display,
+/// so this can be serialized, or logged as is.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, Serialize, Valuable)]
+pub struct VersionReport {
+	/// The version of the host tooling, see [`HOST_SOFTWARE_VERSION`].
+	host_software_version: String,
+	/// The type of bridge we're configured to use, see [`BridgeType`].
+	bridge_type: String,
+	/// The name of the bridge.
+	bridge_name: String,
+	/// The IP Address of the bridge.
+	bridge_ip_address: String,
+	/// The MAC Address of the bridge.
+	bridge_mac_address: String,
+	/// The firmware version of the bridge.
+	firmware_version: String,
+	/// The detailed FPGA version of the bridge.
+	fpga_version: String,
+	/// The SDK version the bridge is running, only present if the bridge sent
+	/// detailed data.
+	sdk_version: Option<String>,
+	/// The boot type the bridge is configured to use, only present if the
+	/// bridge sent detailed data.
+	boot_type: Option<String>,
+}
+impl VersionReport {
+	/// Create a report from an identity that has already been fetched.
+	///
+	/// The identity should have been fetched with detailed data, otherwise
+	/// the SDK version, and boot type will be missing.
+	#[must_use]
+	pub fn from_identity(identity: &MionIdentity) -> Self {
+		Self {
+			host_software_version: HOST_SOFTWARE_VERSION.to_owned(),
+			bridge_type: format!("{}", BridgeType::fetch_bridge_type().unwrap_or_default()),
+			bridge_name: identity.name().to_owned(),
+			bridge_ip_address: format!("{}", identity.ip_address()),
+			bridge_mac_address: format!("{}", identity.mac_address()),
+			firmware_version: identity.firmware_version(),
+			fpga_version: identity.detailed_fpga_version(),
+			sdk_version: identity.detailed_sdk_version(),
+			boot_type: identity
+				.detailed_boot_type()
+				.map(|boot_type| format!("{boot_type}")),
+		}
+	}
+
+	#[must_use]
+	pub fn host_software_version(&self) -> &str {
+		&self.host_software_version
+	}
+
+	#[must_use]
+	pub fn bridge_type(&self) -> &str {
+		&self.bridge_type
+	}
+
+	#[must_use]
+	pub fn bridge_name(&self) -> &str {
+		&self.bridge_name
+	}
+
+	#[must_use]
+	pub fn bridge_ip_address(&self) -> &str {
+		&self.bridge_ip_address
+	}
+
+	#[must_use]
+	pub fn bridge_mac_address(&self) -> &str {
+		&self.bridge_mac_address
+	}
+
+	#[must_use]
+	pub fn firmware_version(&self) -> &str {
+		&self.firmware_version
+	}
+
+	#[must_use]
+	pub fn fpga_version(&self) -> &str {
+		&self.fpga_version
+	}
+
+	#[must_use]
+	pub fn sdk_version(&self) -> Option<&str> {
+		self.sdk_version.as_deref()
+	}
+
+	#[must_use]
+	pub fn boot_type(&self) -> Option<&str> {
+		self.boot_type.as_deref()
+	}
+}
+impl From<&MionIdentity> for VersionReport {
+	fn from(identity: &MionIdentity) -> Self {
+		Self::from_identity(identity)
+	}
+}
+impl Display for VersionReport {
+	/// Render the report the way `hostdisplayversion` does, one value per
+	/// line.
+	fn fmt(&self, fmt: &mut Formatter<'_>) -> FmtResult {
+		writeln!(
+			fmt,
+			"Host Bridge Software Version : {}",
+			self.host_software_version
+		)?;
+		writeln!(fmt, "Bridge Type                  : {}", self.bridge_type)?;
+		writeln!(fmt, "Bridge Name                  : {}", self.bridge_name)?;
+		writeln!(
+			fmt,
+			"Bridge IP Address            : {}",
+			self.bridge_ip_address
+		)?;
+		writeln!(
+			fmt,
+			"Bridge MAC Address           : {}",
+			self.bridge_mac_address
+		)?;
+		writeln!(
+			fmt,
+			"Bridge Firmware Version      : {}",
+			self.firmware_version
+		)?;
+		writeln!(fmt, "FPGA Image Version           : {}", self.fpga_version)?;
+		writeln!(
+			fmt,
+			"SDK Version                  : {}",
+			self.sdk_version.as_deref().unwrap_or("<unknown>")
+		)?;
+		write!(
+			fmt,
+			"Boot Type                    : {}",
+			self.boot_type.as_deref().unwrap_or("<unknown>")
+		)
+	}
+}
+
+/// Fetch a version report for a single bridge.
+///
+/// This asks the bridge for detailed data, so the SDK version, and boot type
+/// are filled in when the firmware supports them. Returns `Ok(None)` if the
+/// bridge could not be found.
+///
+/// ## Errors
+///
+/// - Any error case from [`find_mion`].
+pub async fn version_report(bridge: MIONFindBy) -> Result<Option<VersionReport>, CatBridgeError> {
+	version_report_with_overrides(bridge, None, None).await
+}
+
+/// Fetch a version report for a single bridge, overriding how long we search
+/// for it, and which control port it's on.
+///
+/// ## Errors
+///
+/// - Any error case from [`find_mion`].
+pub async fn version_report_with_overrides(
+	bridge: MIONFindBy,
+	early_scan_timeout: Option<Duration>,
+	override_control_port: Option<u16>,
+) -> Result<Option<VersionReport>, CatBridgeError> {
+	Ok(
+		find_mion(bridge, true, early_scan_timeout, override_control_port)
+			.await?
+			.as_ref()
+			.map(VersionReport::from_identity),
+	)
+}
+
+#[cfg(test)]
+mod unit_tests {
+	use super::*;
+	use crate::mion::proto::control::{
+		DETAILED_BOOT_TYPE_OFFSET, DETAILED_DATA_SIZE, DETAILED_SDK_VERSION_OFFSET,
+	};
+	use bytes::{Bytes, BytesMut};
+	use mac_address::MacAddress;
+	use std::net::Ipv4Addr;
+
+	fn identity(detailed_data: Option<Bytes>) -> MionIdentity {
+		MionIdentity::new(
+			detailed_data,
+			[0, 14, 80, 1],
+			[0x71, 0x20, 0x05, 0x13],
+			Ipv4Addr::new(192, 168, 7, 40),
+			MacAddress::new([0x00, 0x25, 0x5c, 0xba, 0x5a, 0x00]),
+			"00-25-5C-BA-5A-00".to_owned(),
+		)
+		.expect("Failed to create identity!")
+	}
+
+	#[test]
+	pub fn can_build_reports_from_identities() {
+		let mut detailed = BytesMut::zeroed(DETAILED_DATA_SIZE);
+		detailed[DETAILED_SDK_VERSION_OFFSET..DETAILED_SDK_VERSION_OFFSET + 4]
+			.copy_from_slice(&[2, 12, 13, 0]);
+		detailed[DETAILED_BOOT_TYPE_OFFSET] = 0x2;
+
+		let report = VersionReport::from(&identity(Some(detailed.freeze())));
+		assert_eq!(report.host_software_version(), HOST_SOFTWARE_VERSION);
+		assert_eq!(report.bridge_name(), "00-25-5C-BA-5A-00");
+		assert_eq!(report.bridge_ip_address(), "192.168.7.40");
+		assert_eq!(report.bridge_mac_address(), "00:25:5C:BA:5A:00");
+		assert_eq!(report.firmware_version(), "0.0.14.80");
+		assert_eq!(report.fpga_version(), "13052071");
+		assert_eq!(report.sdk_version(), Some("2.12.13"));
+		assert_eq!(report.boot_type(), Some("PCFS"));
+		assert!(format!("{report}").contains("SDK Version                  : 2.12.13"));
+
+		let report = VersionReport::from(&identity(None));
+		assert_eq!(report.fpga_version(), "13052071");
+		assert_eq!(report.sdk_version(), None);
+		assert_eq!(report.boot_type(), None);
+		assert!(format!("{report}").ends_with("Boot Type                    : <unknown>"));
+	}
+
+	#[test]
+	pub fn renders_every_line_of_a_report() {
+		let mut detailed = BytesMut::zeroed(DETAILED_DATA_SIZE);
+		detailed[DETAILED_SDK_VERSION_OFFSET..DETAILED_SDK_VERSION_OFFSET + 4]
+			.copy_from_slice(&[2, 12, 13, 0]);
+		detailed[DETAILED_BOOT_TYPE_OFFSET] = 0x2;
+
+		assert_eq!(
+			format!(
+				"{}",
+				VersionReport::from(&identity(Some(detailed.freeze())))
+			),
+			format!(
+				"Host Bridge Software Version : {HOST_SOFTWARE_VERSION}
+Bridge Type                  : Mion
+Bridge Name                  : 00-25-5C-BA-5A-00
+Bridge IP Address            : 192.168.7.40
+Bridge MAC Address           : 00:25:5C:BA:5A:00
+Bridge Firmware Version      : 0.0.14.80
+FPGA Image Version           : 13052071
+SDK Version                  : 2.12.13
+Boot Type                    : PCFS"
+			),
+		);
+	}
+}
@@ -4,12 +4,13 @@
 //! types of errors. You can find more specific error types documented on each
 //! specific item.
 
+use crate::{boot::BootStage, mion::proto::control::MIONBootType};
 use bytes::Bytes;
 use hyper::{http::Error as HttpError, Error as HyperError};
 use local_ip_address::Error as LocalIpAddressError;
 use miette::Diagnostic;
 use serde_urlencoded::ser::Error as SerdeUrlEncodeError;
-use std::string::FromUtf8Error;
+use std::{path::PathBuf, string::FromUtf8Error, time::Duration};
 use thiserror::Error;
 use tokio::{io::Error as IoError, task::JoinError};
 
@@ -21,6 +22,10 @@ pub enum CatBridgeError {
 	#[error(transparent)]
 	#[diagnostic(transparent)]
 	ApiError(#[from] APIError),
+	/// See [`BootError`] for details.
+	#[error(transparent)]
+	#[diagnostic(transparent)]
+	BootError(#[from] BootError),
 	/// See [`FSError`] for details.
 	#[error(transparent)]
 	#[diagnostic(transparent)]
@@ -93,6 +98,11 @@ pub enum APIError {
 	#[error("A Device Name can only be 255 bytes long, but you specified one: {0} bytes long.")]
 	#[diagnostic(code(cat_dev::api::name_too_long))]
 	DeviceNameTooLong(usize),
+	/// You attempted to create a MION identity with detailed data, but the
+	/// detailed data was not the correct length (239 bytes).
+	#[error("The detailed data for a MION Identity must be exactly 239 bytes long, but you specified one: {0} bytes long.")]
+	#[diagnostic(code(cat_dev::api::detailed_data_incorrect_length))]
+	DetailedDataNotCorrectLength(usize),
 	/// You tried asking for a parameter of a specific name, but we could not
 	/// find a parameter with the name you specified.
 	///
@@ -116,9 +126,147 @@ pub enum APIError {
 	#[error("The MION Parameter body you passed in was: {0} bytes long, but must be exactly 512 bytes long!")]
 	#[diagnostic(code(cat_dev::api::parameter::body_incorrect_length))]
 	MIONParameterBodyNotCorrectLength(usize),
+	/// You asked to only update parameters if they still had a value you
+	/// expected, but the MION had a different value.
+	///
+	/// Nothing was written to the MION when this error is returned.
+	#[error("The MION Parameter at index: {0} was expected to be: {1}, but was actually: {2}, refusing to update parameters.")]
+	#[diagnostic(code(cat_dev::api::parameter::compare_failed))]
+	MIONParameterCompareFailed(usize, u8, u8),
+	/// You tried to set a named MION Parameter to a value that doesn't make
+	/// sense for that parameter (e.g. a version without enough components, or
+	/// a number too large to fit).
+	#[error("The value: [{1}] is not valid for the MION Parameter: {0}.")]
+	#[diagnostic(code(cat_dev::api::parameter::value_invalid))]
+	MIONParameterValueInvalid(&'static str, String),
+	/// You tried to write to a MION Parameter that is marked read-only in the
+	/// parameter schema.
+	///
+	/// Nothing was written to the MION when this error is returned.
+	#[error("The MION Parameter at index: {0} is part of the read-only field: {1}, refusing to update parameters.")]
+	#[diagnostic(code(cat_dev::api::parameter::read_only))]
+	MIONParameterReadOnly(usize, &'static str),
+	/// A transactional write of MION Parameters failed part way through, or
+	/// didn't stick, so we put the original values back.
+	///
+	/// The indexes are the ones that had to be restored, the string is why the
+	/// write failed.
+	#[error("Writing MION Parameters failed ({1}), the parameters at indexes: {0:?} were rolled back to their original values.")]
+	#[diagnostic(code(cat_dev::api::parameter::write_rolled_back))]
+	MIONParameterWriteRolledBack(Vec<usize>, String),
+	/// A transactional write of MION Parameters failed, and we could not put
+	/// the original values back.
+	///
+	/// The indexes are the ones we could not confirm were restored, the string
+	/// is why the original write failed.
+	#[error("Writing MION Parameters failed ({1}), and we could not roll back the parameters at indexes: {0:?}, they may be partially written.")]
+	#[diagnostic(code(cat_dev::api::parameter::rollback_failed))]
+	MIONParameterRollbackFailed(Vec<usize>, String),
+	/// The data we were given is not a parameter snapshot we know how to read.
+	#[error("This is not a valid parameter snapshot: {0}")]
+	#[diagnostic(code(cat_dev::api::parameter::invalid_snapshot))]
+	InvalidParameterSnapshot(String),
+	/// You tried to parse a discovery target, but it was not an IPv4 address,
+	/// a CIDR range, or a `broadcast:` address.
+	#[error("Invalid discovery target: [{0}], expected an IPv4 address, a CIDR range like `10.0.0.0/24`, or a directed broadcast like `broadcast:10.0.0.255`.")]
+	#[diagnostic(code(cat_dev::api::discovery::invalid_target))]
+	InvalidDiscoveryTarget(String),
+	/// You tried to sweep a CIDR range that is too large to sweep one address
+	/// at a time.
+	///
+	/// The smallest prefix length we will sweep is a `/16`, for anything
+	/// larger you probably want a directed broadcast instead.
+	#[error("The CIDR range: [{0}] is too large to sweep, the prefix length must be at least 16.")]
+	#[diagnostic(code(cat_dev::api::discovery::range_too_large))]
+	DiscoveryRangeTooLarge(String),
+	/// You asked to sweep targets faster than we are able to send
+	/// announcements.
+	///
+	/// See: [`crate::mion::discovery::MAX_SWEEP_PACKETS_PER_SECOND`].
+	#[error("Cannot sweep at {0} packets per second, the most we can send is {max} per second.", max = crate::mion::discovery::MAX_SWEEP_PACKETS_PER_SECOND)]
+	#[diagnostic(code(cat_dev::api::discovery::sweep_rate_too_high))]
+	DiscoverySweepRateTooHigh(u32),
+	/// You tried to parse a way of matching a network interface, but it was
+	/// empty, or was a subnet with an invalid prefix length.
+	#[error("Invalid network interface: [{0}], expected an interface name, an IPv4 address, or a subnet like `10.0.0.0/24`.")]
+	#[diagnostic(code(cat_dev::api::discovery::invalid_interface))]
+	InvalidInterfaceMatcher(String),
+	/// You tried to parse a boot type from a string, but it was not a name we
+	/// know, or a raw number.
+	#[error("Unknown boot type: [{0}], expected one of: NAND, PCFS, DUAL, HDD, or a number.")]
+	#[diagnostic(code(cat_dev::api::unknown_boot_type))]
+	UnknownBootType(String),
 	#[error("Unknown operation for `control.cgi`: [{0}]")]
 	#[diagnostic(code(cat_dev::api::control::unknown_operation))]
 	UnknownControlOperation(String),
+	/// A name was passed in for a value to set on `control.cgi` that we don't
+	/// know about.
+	#[error("Unknown setting for `control.cgi`: [{0}], expected one of: {1}")]
+	#[diagnostic(code(cat_dev::api::control::unknown_set_parameter))]
+	UnknownSetParameter(String, String),
+	/// A value to set on `control.cgi` isn't valid for that setting.
+	#[error("The value: [{1}] is not valid for the setting: {0}, {2}")]
+	#[diagnostic(code(cat_dev::api::control::invalid_set_parameter_value))]
+	InvalidSetParameterValue(&'static str, String, &'static str),
+	/// A setting for `control.cgi` that we've never seen the official tools
+	/// send was passed to a function that only sends known settings.
+	#[error("The setting: {0} for `control.cgi` is experimental, it has to be explicitly sent as an experimental setting.")]
+	#[diagnostic(code(cat_dev::api::control::experimental_set_parameter))]
+	ExperimentalSetParameter(&'static str),
+	/// A value on the setup page of a MION isn't valid for that field.
+	#[error("The value: [{1}] is not valid for the setup field: {0}, {2}")]
+	#[diagnostic(code(cat_dev::api::setup::invalid_value))]
+	InvalidSetupValue(&'static str, String, &'static str),
+	/// A signal name was passed in that `signal_get.cgi` doesn't know about.
+	#[error("Unknown signal: [{0}], the only known signal is: VDD2")]
+	#[diagnostic(code(cat_dev::api::signal::unknown_signal))]
+	UnknownSignal(String),
+	/// A firmware version was not in the form `0.x.y.z` we display them as.
+	#[error("Invalid firmware version: [{0}], expected a version like `0.0.14.80`.")]
+	#[diagnostic(code(cat_dev::api::firmware::invalid_version))]
+	InvalidFirmwareVersion(String),
+	/// The data we were given is not a `WUM`, or `WUMAD` image we know how to
+	/// upload.
+	#[error("This is not a valid WUM/WUMAD image: {0}")]
+	#[diagnostic(code(cat_dev::api::image::invalid_image))]
+	InvalidDiscImage(String),
+	/// Images on the HDD of a CAT-DEV are referred to by name, so names have to
+	/// be something the bridge can store.
+	#[error("Invalid image name: [{0}], names must be 1-64 printable ASCII characters, and cannot contain `/`, `\\`, or `,`.")]
+	#[diagnostic(code(cat_dev::api::image::invalid_name))]
+	InvalidImageName(String),
+}
+
+/// Booting a device through [`crate::boot::BootPipeline`] has failed.
+///
+/// Every error that happens part way through booting is tagged with the
+/// stage it happened in, as the same underlying error (e.g. a timeout) can
+/// mean very different things depending on what we were doing.
+#[derive(Error, Diagnostic, Debug)]
+pub enum BootError {
+	/// A stage did not finish in the time it was given.
+	#[error("The boot stage: `{0}` did not finish within {1:?}")]
+	#[diagnostic(code(cat_dev::boot::stage_timed_out))]
+	StageTimedOut(BootStage, Duration),
+	/// A stage failed, see the source for why.
+	#[error("The boot stage: `{0}` failed")]
+	#[diagnostic(code(cat_dev::boot::stage_failed))]
+	StageFailed(BootStage, #[source] Box<CatBridgeError>),
+	/// The SDK in `CAFE_ROOT` didn't have a version we could read.
+	///
+	/// We read the version from `system/include/sdk_ver.h`, which every SDK
+	/// we know about ships with.
+	#[error("Could not find the SDK version in: {}", .0.display())]
+	#[diagnostic(code(cat_dev::boot::sdk_version_not_found))]
+	SdkVersionNotFound(PathBuf),
+	/// The MION refused to change what it boots from.
+	#[error("The MION refused to set the boot type to: {0}")]
+	#[diagnostic(code(cat_dev::boot::boot_type_rejected))]
+	BootTypeRejected(MIONBootType),
+	/// The serial port closed before the device printed that it was ready.
+	#[error("The serial port closed before the device printed: `{0}`")]
+	#[diagnostic(code(cat_dev::boot::serial_closed_before_ready))]
+	SerialClosedBeforeReady(String),
 }
 
 /// Trying to interact with the filesystem has resulted in an error.
@@ -145,6 +293,33 @@ pub enum FSError {
 	#[error("Error writing/reading data from the filesystem: {0}")]
 	#[diagnostic(code(cat_dev::fs::io_failure))]
 	IOError(#[from] IoError),
+	/// A directory served over PCFS has to be laid out like a title, with a
+	/// `code`, `content`, and `meta` directory.
+	#[error("The directory: {} cannot be served over PCFS, it is missing the `{1}` directory", .0.display())]
+	#[diagnostic(code(cat_dev::fs::not_a_title_directory))]
+	NotATitleDirectory(PathBuf, &'static str),
+	/// A path asked for over PCFS resolved to somewhere outside of the
+	/// directory being served through `..`, or went through a symlink.
+	///
+	/// Symlinks are never followed, even ones that point back inside of the
+	/// directory being served.
+	///
+	/// These are always refused so a devkit can never read, or write files
+	/// the host didn't explicitly share.
+	#[error("The path: {0} is outside of the directory being served")]
+	#[diagnostic(code(cat_dev::fs::outside_of_sandbox))]
+	PathOutsideOfSandbox(String),
+	/// A path asked for over PCFS contained characters that can't appear in a
+	/// path on the devkit (e.g. `\`, `:`, or NUL).
+	#[error("The path: {0} is not a valid path to serve")]
+	#[diagnostic(code(cat_dev::fs::invalid_sandbox_path))]
+	InvalidSandboxPath(String),
+	/// Serving files over PCFS needs to open paths relative to a directory
+	/// without following symlinks, which we can only do on unix like
+	/// platforms right now.
+	#[error("Serving files over PCFS is not supported on this platform yet")]
+	#[diagnostic(code(cat_dev::fs::sandbox_unsupported))]
+	SandboxUnsupported,
 }
 
 /// Trying to interact with the network has resulted in an error.
@@ -179,6 +354,31 @@ pub enum NetworkError {
 	#[error("Error talking to the network could not send/receive data: {0}")]
 	#[diagnostic(code(cat_dev::net::native_failure))]
 	IOError(#[from] IoError),
+	/// The MION refused to do what we asked, because another host is
+	/// currently using it.
+	#[error("This MION is already being used by another host ({0}), it has to be released before you can control it from here.")]
+	#[diagnostic(code(cat_dev::net::mion_in_use))]
+	MionInUseByAnotherHost(String),
+	/// The MION responded to an operation on `control.cgi`, but said it
+	/// didn't succeed.
+	#[error("The MION could not perform the `control.cgi` operation: {0}, it responded with result: [{1}], and logs: {2:?}")]
+	#[diagnostic(code(cat_dev::net::control_operation_failed))]
+	ControlOperationFailed(&'static str, String, Vec<String>),
+	/// The MION did not accept the changes we submitted to `setup.cgi`.
+	#[error("The MION did not accept the changes to it's setup page, it responded with: {0:?}")]
+	#[diagnostic(code(cat_dev::net::setup_rejected))]
+	SetupRejected(Vec<String>),
+	/// The MION could not perform an operation on the images stored on it's
+	/// HDD.
+	#[error("The MION could not {0} the image, it responded with: {1:?}")]
+	#[diagnostic(code(cat_dev::net::image_operation_rejected))]
+	ImageOperationRejected(&'static str, Vec<String>),
+	/// The MION responded with `401 Unauthorized` to a CGI page, the bridge
+	/// has probably had it's web password changed. The new credentials can be
+	/// passed to [`crate::mion::cgis::MionHttpClient::with_credentials`].
+	#[error("The MION did not accept our username, and password for it's web pages.")]
+	#[diagnostic(code(cat_dev::net::mion_rejected_credentials))]
+	MionRejectedCredentials,
 	/// See [`network_interface::Error::GetIfAddrsError`] for details.
 	#[error("Failed to list the network interfaces on your device.")]
 	#[diagnostic(code(cat_dev::net::list_interfaces_error))]
@@ -250,6 +450,21 @@ pub enum NetworkParseError {
 	#[error("Error code received from MION Params: `{0}`")]
 	#[diagnostic(code(cat_dev::net::parse::params::error_code))]
 	ParamsPacketErrorCode(i32),
+	/// A packet for the MION Params port said it had a body larger than any
+	/// packet we know about.
+	///
+	/// We reject these rather than buffering however much data the other side
+	/// claims it's going to send.
+	#[error("MION Params packet claims to have a body of {0} bytes, but the largest body we accept is {1} bytes")]
+	#[diagnostic(code(cat_dev::net::parse::params::frame_too_large))]
+	ParamsFrameTooLarge(usize, usize),
+	/// A PCFS packet said it had a body larger than any packet we accept.
+	///
+	/// Like the parameter port we reject these rather than buffering however
+	/// much data the other side claims it's going to send.
+	#[error("PCFS packet claims to have a body of {0} bytes, but the largest body we accept is {1} bytes")]
+	#[diagnostic(code(cat_dev::net::parse::pcfs::frame_too_large))]
+	PcfsFrameTooLarge(usize, usize),
 	/// See [`serde_urlencoded::ser::Error`] for details.
 	#[error("Failed to encode data as form data: {0}")]
 	#[diagnostic(code(cat_dev::net::parse::http::encode::form_data_error))]
@@ -267,4 +482,22 @@ pub enum NetworkParseError {
 	#[error("Could not parse HTML response could not find one of the body tags: `<body>`, or `</body>`: {0}")]
 	#[diagnostic(code(cat_dev::net::parse::html::no_body_tag))]
 	HtmlResponseMissingBody(String),
+	/// `get_info` did not report a field we need, or reported it with a value
+	/// we couldn't understand.
+	#[error("`get_info` did not report a usable value for the field: {0}, got: [{1}]")]
+	#[diagnostic(code(cat_dev::net::parse::info_field_missing))]
+	InfoFieldMissing(&'static str, String),
+	/// The setup page of the MION was missing a field we need.
+	#[error("The setup page from the MION was missing the field: {0}")]
+	#[diagnostic(code(cat_dev::net::parse::setup_field_missing))]
+	SetupFieldMissing(&'static str),
+	/// A field on the setup page of the MION had a value we couldn't
+	/// understand.
+	#[error("The field: {0} on the setup page had a value we couldn't understand: [{1}]")]
+	#[diagnostic(code(cat_dev::net::parse::invalid_setup_field))]
+	InvalidSetupField(&'static str, String),
+	/// A field `image.cgi` responded with had a value we couldn't understand.
+	#[error("The field: {0} from `image.cgi` had a value we couldn't understand: [{1}]")]
+	#[diagnostic(code(cat_dev::net::parse::invalid_image_field))]
+	InvalidImageField(&'static str, String),
 }
@@ -0,0 +1,30 @@
+//! Serving files to a devkit from the host, also known as `FSEmul`, or
+//! "PCFS".
+//!
+//! Instead of installing a title to the devkit, a MION can emulate a disc
+//! drive, and forward every file system request cafe makes over the network
+//! to the host. On the host a title directory is laid out the same way it
+//! would be on a disc:
+//!
+//! ```text
+//! title/
+//!   code/     the executables, and `app.xml`/`cos.xml`.
+//!   content/  the data for the title.
+//!   meta/     `meta.xml`, icons, and the like.
+//! ```
+//!
+//! [`PcfsServer`] serves one of these directories, either read-only (which is
+//! all booting needs), or read-write. Every path is opened through a
+//! [`sandbox::PathSandbox`] so the devkit can never reach anything outside of
+//! the title directory, symlinks inside of the title directory are never
+//! followed. Sandboxing is only supported on unix like platforms right now.
+//!
+//! ***Unverified:** the wire format in [`proto`] has never been seen against a
+//! real devkit, it is made up, and a real devkit will most likely not
+//! understand it. See the [`proto`] module documentation.*
+
+pub mod proto;
+pub mod sandbox;
+mod server;
+
+pub use server::*;
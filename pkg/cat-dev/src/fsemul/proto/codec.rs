@@ -0,0 +1,282 @@
+//! Encoders, and decoders for framing packets on a PCFS connection.
+//!
+//! Just like the parameter port, PCFS runs over a TCP stream so a single read
+//! may contain only part of a packet, or several packets. These codecs buffer
+//! until a full packet has arrived, and can be used with
+//! [`tokio_util::codec::Framed`].
+//!
+//! There are two codecs, one for each side of the connection:
+//!
+//! - [`PcfsClientCodec`] sends requests, and receives responses (this is the
+//!   side cafe normally speaks, and what we use in tests).
+//! - [`PcfsServerCodec`] receives requests, and sends responses.
+
+use crate::{
+	errors::{NetworkError, NetworkParseError},
+	fsemul::proto::{
+		PcfsRequest, PcfsRequestFrame, PcfsResponseFrame, PcfsStatus, MAX_PCFS_BODY_SIZE,
+		PCFS_HEADER_SIZE,
+	},
+};
+use bytes::{BufMut, Bytes, BytesMut};
+use tokio_util::codec::{Decoder, Encoder};
+
+/// The codec to use when talking to a PCFS server.
+///
+/// Encodes [`PcfsRequestFrame`]s, and decodes [`PcfsResponseFrame`]s.
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq)]
+pub struct PcfsClientCodec;
+
+impl Decoder for PcfsClientCodec {
+	type Item = PcfsResponseFrame;
+	type Error = NetworkError;
+
+	fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+		let Some((id, status, body)) = split_frame(src)? else {
+			return Ok(None);
+		};
+
+		let status = PcfsStatus::from(i32::from_be_bytes(status.to_be_bytes()));
+		if status.is_ok() {
+			Ok(Some(PcfsResponseFrame::ok(id, body)))
+		} else if body.is_empty() {
+			Ok(Some(PcfsResponseFrame::error(id, status)))
+		} else {
+			Err(NetworkParseError::UnexpectedTrailer("PcfsResponseFrame", body).into())
+		}
+	}
+}
+impl Encoder<PcfsRequestFrame> for PcfsClientCodec {
+	type Error = NetworkError;
+
+	fn encode(&mut self, item: PcfsRequestFrame, dst: &mut BytesMut) -> Result<(), Self::Error> {
+		let command = match item.request().command() {
+			Ok(command) => u32::from(command),
+			Err(raw) => raw,
+		};
+		put_frame(dst, item.id(), command, &item.request().body())
+	}
+}
+
+/// The codec to use when serving files over PCFS.
+///
+/// Decodes [`PcfsRequestFrame`]s, and encodes [`PcfsResponseFrame`]s.
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq)]
+pub struct PcfsServerCodec;
+
+impl Decoder for PcfsServerCodec {
+	type Item = PcfsRequestFrame;
+	type Error = NetworkError;
+
+	fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+		let Some((id, command, body)) = split_frame(src)? else {
+			return Ok(None);
+		};
+
+		Ok(Some(PcfsRequestFrame::new(
+			id,
+			PcfsRequest::parse(command, body)?,
+		)))
+	}
+}
+impl Encoder<PcfsResponseFrame> for PcfsServerCodec {
+	type Error = NetworkError;
+
+	fn encode(&mut self, item: PcfsResponseFrame, dst: &mut BytesMut) -> Result<(), Self::Error> {
+		let status = u32::from_be_bytes(i32::from(item.status()).to_be_bytes());
+		put_frame(dst, item.id(), status, item.body())
+	}
+}
+
+/// Write a full packet, header, and body.
+///
+/// ## Errors
+///
+/// - If the body is larger than [`MAX_PCFS_BODY_SIZE`], the other side would
+///   never accept it.
+fn put_frame(dst: &mut BytesMut, id: u32, code: u32, body: &Bytes) -> Result<(), NetworkError> {
+	if body.len() > MAX_PCFS_BODY_SIZE {
+		return Err(NetworkParseError::PcfsFrameTooLarge(body.len(), MAX_PCFS_BODY_SIZE).into());
+	}
+
+	dst.reserve(PCFS_HEADER_SIZE + body.len());
+	// Checked against `MAX_PCFS_BODY_SIZE` above, which always fits.
+	dst.put_u32(u32::try_from(body.len()).unwrap_or(u32::MAX));
+	dst.put_u32(id);
+	dst.put_u32(code);
+	dst.extend_from_slice(body);
+	Ok(())
+}
+
+/// Split a single full packet off the front of the buffer, if one has fully
+/// arrived.
+///
+/// Returns the request id, the command/status (still raw), and the body.
+///
+/// ## Errors
+///
+/// - If the header says the body is larger than [`MAX_PCFS_BODY_SIZE`].
+fn split_frame(src: &mut BytesMut) -> Result<Option<(u32, u32, Bytes)>, NetworkError> {
+	if src.len() < PCFS_HEADER_SIZE {
+		src.reserve(PCFS_HEADER_SIZE - src.len());
+		return Ok(None);
+	}
+
+	let body_size =
+		usize::try_from(u32::from_be_bytes([src[0], src[1], src[2], src[3]])).unwrap_or(usize::MAX);
+	if body_size > MAX_PCFS_BODY_SIZE {
+		return Err(NetworkParseError::PcfsFrameTooLarge(body_size, MAX_PCFS_BODY_SIZE).into());
+	}
+
+	let frame_size = PCFS_HEADER_SIZE + body_size;
+	if src.len() < frame_size {
+		src.reserve(frame_size - src.len());
+		return Ok(None);
+	}
+
+	let id = u32::from_be_bytes([src[4], src[5], src[6], src[7]]);
+	let code = u32::from_be_bytes([src[8], src[9], src[10], src[11]]);
+	let mut frame = src.split_to(frame_size).freeze();
+	Ok(Some((id, code, frame.split_off(PCFS_HEADER_SIZE))))
+}
+
+#[cfg(test)]
+mod unit_tests {
+	use super::*;
+	use crate::fsemul::proto::{PcfsOpenMode, MAX_PCFS_IO_SIZE};
+
+	fn encode_request(request: PcfsRequestFrame) -> BytesMut {
+		let mut buff = BytesMut::new();
+		PcfsClientCodec
+			.encode(request, &mut buff)
+			.expect("Failed to encode request!");
+		buff
+	}
+
+	#[test]
+	pub fn server_reassembles_fragmented_requests() {
+		let request = PcfsRequestFrame::new(
+			0x1234_5678,
+			PcfsRequest::Open {
+				path: "/content/Common/Sound/bgm.bfsar".to_owned(),
+				mode: PcfsOpenMode::Read,
+			},
+		);
+		let packet = encode_request(request.clone());
+
+		let mut codec = PcfsServerCodec;
+		let mut buff = BytesMut::new();
+		let (last, rest) = packet.split_last().expect("Packet cannot be empty!");
+		for byte in rest {
+			buff.extend_from_slice(&[*byte]);
+			assert!(
+				codec
+					.decode(&mut buff)
+					.expect("Failed to decode partial packet!")
+					.is_none(),
+				"Decoded a packet before all of the bytes arrived!",
+			);
+		}
+		buff.extend_from_slice(&[*last]);
+		assert_eq!(
+			codec.decode(&mut buff).expect("Failed to decode packet!"),
+			Some(request),
+		);
+		assert!(buff.is_empty(), "Decoder left bytes behind!");
+	}
+
+	#[test]
+	pub fn decodes_multiple_packets_in_one_read() {
+		let mut buff = BytesMut::new();
+		PcfsServerCodec
+			.encode(
+				PcfsResponseFrame::ok(1, Bytes::from_static(b"hello")),
+				&mut buff,
+			)
+			.expect("Failed to encode response!");
+		PcfsServerCodec
+			.encode(PcfsResponseFrame::error(2, PcfsStatus::NotFound), &mut buff)
+			.expect("Failed to encode response!");
+		// Start of a third packet that hasn't fully arrived.
+		buff.extend_from_slice(&[0x0, 0x0]);
+
+		let mut codec = PcfsClientCodec;
+		assert_eq!(
+			codec.decode(&mut buff).expect("Failed to decode packet!"),
+			Some(PcfsResponseFrame::ok(1, Bytes::from_static(b"hello"))),
+		);
+		assert_eq!(
+			codec.decode(&mut buff).expect("Failed to decode packet!"),
+			Some(PcfsResponseFrame::error(2, PcfsStatus::NotFound)),
+		);
+		assert_eq!(
+			codec
+				.decode(&mut buff)
+				.expect("Failed to decode partial packet!"),
+			None,
+		);
+		assert_eq!(
+			buff.len(),
+			2,
+			"Partial packet should be left in the buffer!"
+		);
+	}
+
+	#[test]
+	pub fn rejects_bad_frames() {
+		// A body larger than any we accept, before the body has even arrived.
+		let mut oversized = BytesMut::from(&[0x0_u8, 0x20, 0x0, 0x0, 0, 0, 0, 1, 0, 0, 0, 0][..]);
+		match PcfsServerCodec.decode(&mut oversized) {
+			Err(NetworkError::ParseError(cause)) => {
+				assert_eq!(
+					cause,
+					NetworkParseError::PcfsFrameTooLarge(0x20_0000, MAX_PCFS_BODY_SIZE)
+				);
+			}
+			val => panic!("Oversized frame did not error correctly:\n\n {val:?}"),
+		}
+
+		// Errors never have a body.
+		let mut errored = BytesMut::from(
+			&[
+				0x0_u8, 0x0, 0x0, 0x1, 0, 0, 0, 1, 0xFF, 0xFF, 0xFF, 0xFF, 0x0,
+			][..],
+		);
+		assert!(PcfsClientCodec.decode(&mut errored).is_err());
+
+		// We should never send something the other side won't accept.
+		let mut buff = BytesMut::new();
+		assert!(PcfsServerCodec
+			.encode(
+				PcfsResponseFrame::ok(1, vec![0_u8; MAX_PCFS_BODY_SIZE + 1]),
+				&mut buff,
+			)
+			.is_err());
+		assert!(buff.is_empty(), "Nothing should be written on error!");
+	}
+
+	#[test]
+	pub fn passes_through_unknown_commands() {
+		let mut buff = encode_request(PcfsRequestFrame::new(9, PcfsRequest::Unknown(0x99)));
+		assert_eq!(
+			PcfsServerCodec
+				.decode(&mut buff)
+				.expect("Failed to decode unknown command!"),
+			Some(PcfsRequestFrame::new(9, PcfsRequest::Unknown(0x99))),
+		);
+
+		// A full size write still fits in a single frame.
+		let mut buff = encode_request(PcfsRequestFrame::new(
+			10,
+			PcfsRequest::Write {
+				handle: 1,
+				offset: 0,
+				data: Bytes::from(vec![0xAA_u8; MAX_PCFS_IO_SIZE]),
+			},
+		));
+		assert!(PcfsServerCodec
+			.decode(&mut buff)
+			.expect("Failed to decode full size write!")
+			.is_some());
+	}
+}
@@ -0,0 +1,865 @@
+//! Protocols for serving files to a devkit over PCFS.
+//!
+//! When a MION boots with emulation turned on it connects to the ATAPI port
+//! on the host (see `host_ip`, and `atapi_port` in `setup.cgi`), and all file
+//! system requests cafe makes for the emulated drive get sent over that
+//! connection.
+//!
+//! Every packet in either direction is a 12 byte header, followed by a body.
+//! All numbers are big endian, as that's what cafe natively speaks:
+//!
+//! ```text
+//! 0x00  4 bytes  length of the body that follows the header
+//! 0x04  4 bytes  request id, replies always echo the id of the request
+//! 0x08  4 bytes  requests: the command, replies: the status (signed)
+//! 0x0C  ...      body
+//! ```
+//!
+//! Strings in bodies are a 4 byte length, followed by that many bytes of
+//! UTF-8, and are never NUL terminated.
+//!
+//! ***Unverified:** we've never seen PCFS traffic from a real devkit. The
+//! header, the command, and status numbers, and every body below are made up,
+//! and only match what our own server accepts. A real devkit will most likely
+//! not understand any of it.*
+
+pub mod codec;
+
+use crate::errors::{NetworkError, NetworkParseError};
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use valuable::Valuable;
+
+/// The size of the header at the start of every PCFS packet.
+pub const PCFS_HEADER_SIZE: usize = 0x0C;
+/// The most data a single read, or write can move at once.
+pub const MAX_PCFS_IO_SIZE: usize = 0x10_0000;
+/// The longest path cafe can ask for.
+pub const MAX_PCFS_PATH_LENGTH: usize = 0x27F;
+/// The largest body any PCFS packet can have, a full write, plus the handle,
+/// and offset that come before the data.
+pub const MAX_PCFS_BODY_SIZE: usize = MAX_PCFS_IO_SIZE + 0x10;
+
+/// A command that can be sent to a PCFS server.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, Valuable)]
+pub enum PcfsCommand {
+	/// Check the server is still alive.
+	Ping,
+	/// Get the type, and size of a path.
+	GetInfo,
+	/// Open a file.
+	Open,
+	/// Read from an open file.
+	Read,
+	/// Write to an open file.
+	Write,
+	/// Close an open file.
+	Close,
+	/// Open a directory to list it.
+	OpenDir,
+	/// Read the next entry of an open directory.
+	ReadDir,
+	/// Close an open directory.
+	CloseDir,
+	/// Create a new directory.
+	CreateDir,
+	/// Remove a file, or an empty directory.
+	Remove,
+	/// Rename, or move a file, or directory.
+	Rename,
+}
+impl PcfsCommand {
+	/// If this command changes anything on the host, these are refused when
+	/// serving read only.
+	#[must_use]
+	pub const fn is_write(&self) -> bool {
+		matches!(
+			self,
+			Self::Write | Self::CreateDir | Self::Remove | Self::Rename
+		)
+	}
+}
+impl Display for PcfsCommand {
+	fn fmt(&self, fmt: &mut Formatter<'_>) -> FmtResult {
+		write!(fmt, "{self:?}")
+	}
+}
+impl From<PcfsCommand> for u32 {
+	fn from(value: PcfsCommand) -> Self {
+		match value {
+			PcfsCommand::Ping => 0x00,
+			PcfsCommand::GetInfo => 0x01,
+			PcfsCommand::Open => 0x02,
+			PcfsCommand::Read => 0x03,
+			PcfsCommand::Write => 0x04,
+			PcfsCommand::Close => 0x05,
+			PcfsCommand::OpenDir => 0x06,
+			PcfsCommand::ReadDir => 0x07,
+			PcfsCommand::CloseDir => 0x08,
+			PcfsCommand::CreateDir => 0x09,
+			PcfsCommand::Remove => 0x0A,
+			PcfsCommand::Rename => 0x0B,
+		}
+	}
+}
+impl TryFrom<u32> for PcfsCommand {
+	type Error = u32;
+
+	fn try_from(value: u32) -> Result<Self, Self::Error> {
+		match value {
+			0x00 => Ok(Self::Ping),
+			0x01 => Ok(Self::GetInfo),
+			0x02 => Ok(Self::Open),
+			0x03 => Ok(Self::Read),
+			0x04 => Ok(Self::Write),
+			0x05 => Ok(Self::Close),
+			0x06 => Ok(Self::OpenDir),
+			0x07 => Ok(Self::ReadDir),
+			0x08 => Ok(Self::CloseDir),
+			0x09 => Ok(Self::CreateDir),
+			0x0A => Ok(Self::Remove),
+			0x0B => Ok(Self::Rename),
+			unknown => Err(unknown),
+		}
+	}
+}
+
+/// How a file should be opened.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, Valuable)]
+pub enum PcfsOpenMode {
+	/// Open an existing file for reading.
+	Read,
+	/// Create, or truncate a file for writing.
+	Write,
+	/// Open a file for reading, and writing, creating it if it doesn't exist.
+	ReadWrite,
+}
+impl PcfsOpenMode {
+	/// If opening a file with this mode can change it.
+	#[must_use]
+	pub const fn is_write(&self) -> bool {
+		!matches!(self, Self::Read)
+	}
+}
+impl From<PcfsOpenMode> for u32 {
+	fn from(value: PcfsOpenMode) -> Self {
+		match value {
+			PcfsOpenMode::Read => 0,
+			PcfsOpenMode::Write => 1,
+			PcfsOpenMode::ReadWrite => 2,
+		}
+	}
+}
+
+/// The status a PCFS server replies with.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, Valuable)]
+pub enum PcfsStatus {
+	/// The request was successful.
+	Ok,
+	/// The path does not exist.
+	NotFound,
+	/// The path already exists.
+	AlreadyExists,
+	/// The server is read only, and the request would change something.
+	ReadOnly,
+	/// The path is outside of the directory being served, or is not a valid
+	/// path.
+	OutsideOfSandbox,
+	/// The handle isn't open, or is the wrong type of handle.
+	InvalidHandle,
+	/// The path is not a file.
+	NotAFile,
+	/// The path is not a directory.
+	NotADirectory,
+	/// There are no more entries in the directory.
+	EndOfDirectory,
+	/// The directory being removed still has entries in it.
+	DirectoryNotEmpty,
+	/// This connection has too many open handles.
+	TooManyOpenHandles,
+	/// The request was well formed, but asked for something we can't do
+	/// (e.g. reading more than [`MAX_PCFS_IO_SIZE`] at once).
+	InvalidRequest,
+	/// The command isn't one we know about.
+	UnknownCommand,
+	/// The host failed to read, or write.
+	IoError,
+	/// A status we don't know about.
+	Unknown(i32),
+}
+impl PcfsStatus {
+	/// If this status is a success.
+	#[must_use]
+	pub const fn is_ok(&self) -> bool {
+		matches!(self, Self::Ok)
+	}
+}
+impl Display for PcfsStatus {
+	fn fmt(&self, fmt: &mut Formatter<'_>) -> FmtResult {
+		write!(fmt, "{self:?}")
+	}
+}
+impl From<PcfsStatus> for i32 {
+	fn from(value: PcfsStatus) -> Self {
+		match value {
+			PcfsStatus::Ok => 0,
+			PcfsStatus::NotFound => -1,
+			PcfsStatus::AlreadyExists => -2,
+			PcfsStatus::ReadOnly => -3,
+			PcfsStatus::OutsideOfSandbox => -4,
+			PcfsStatus::InvalidHandle => -5,
+			PcfsStatus::NotAFile => -6,
+			PcfsStatus::NotADirectory => -7,
+			PcfsStatus::EndOfDirectory => -8,
+			PcfsStatus::DirectoryNotEmpty => -9,
+			PcfsStatus::TooManyOpenHandles => -10,
+			PcfsStatus::InvalidRequest => -11,
+			PcfsStatus::UnknownCommand => -12,
+			PcfsStatus::IoError => -13,
+			PcfsStatus::Unknown(value) => value,
+		}
+	}
+}
+impl From<i32> for PcfsStatus {
+	fn from(value: i32) -> Self {
+		match value {
+			0 => Self::Ok,
+			-1 => Self::NotFound,
+			-2 => Self::AlreadyExists,
+			-3 => Self::ReadOnly,
+			-4 => Self::OutsideOfSandbox,
+			-5 => Self::InvalidHandle,
+			-6 => Self::NotAFile,
+			-7 => Self::NotADirectory,
+			-8 => Self::EndOfDirectory,
+			-9 => Self::DirectoryNotEmpty,
+			-10 => Self::TooManyOpenHandles,
+			-11 => Self::InvalidRequest,
+			-12 => Self::UnknownCommand,
+			-13 => Self::IoError,
+			value => Self::Unknown(value),
+		}
+	}
+}
+
+/// A single request sent to a PCFS server.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub enum PcfsRequest {
+	Ping,
+	GetInfo {
+		path: String,
+	},
+	Open {
+		path: String,
+		mode: PcfsOpenMode,
+	},
+	Read {
+		handle: u32,
+		offset: u64,
+		length: u32,
+	},
+	Write {
+		handle: u32,
+		offset: u64,
+		data: Bytes,
+	},
+	Close {
+		handle: u32,
+	},
+	OpenDir {
+		path: String,
+	},
+	ReadDir {
+		handle: u32,
+	},
+	CloseDir {
+		handle: u32,
+	},
+	CreateDir {
+		path: String,
+	},
+	Remove {
+		path: String,
+	},
+	Rename {
+		from: String,
+		to: String,
+	},
+	/// A command we don't know about, the server still replies to these so
+	/// the connection can keep going.
+	Unknown(u32),
+}
+impl PcfsRequest {
+	/// The command this request is sent as.
+	///
+	/// Returns `Err` with the raw command for [`PcfsRequest::Unknown`].
+	///
+	/// ## Errors
+	///
+	/// - If this is an unknown request.
+	pub const fn command(&self) -> Result<PcfsCommand, u32> {
+		Ok(match self {
+			Self::Ping => PcfsCommand::Ping,
+			Self::GetInfo { .. } => PcfsCommand::GetInfo,
+			Self::Open { .. } => PcfsCommand::Open,
+			Self::Read { .. } => PcfsCommand::Read,
+			Self::Write { .. } => PcfsCommand::Write,
+			Self::Close { .. } => PcfsCommand::Close,
+			Self::OpenDir { .. } => PcfsCommand::OpenDir,
+			Self::ReadDir { .. } => PcfsCommand::ReadDir,
+			Self::CloseDir { .. } => PcfsCommand::CloseDir,
+			Self::CreateDir { .. } => PcfsCommand::CreateDir,
+			Self::Remove { .. } => PcfsCommand::Remove,
+			Self::Rename { .. } => PcfsCommand::Rename,
+			Self::Unknown(raw) => return Err(*raw),
+		})
+	}
+
+	/// Parse the body of a request, given the command from the header.
+	///
+	/// ## Errors
+	///
+	/// - If the body is too short, or too long for the command.
+	/// - If a path in the body is too long, or isn't UTF-8.
+	/// - If a file is opened with a mode we don't know about.
+	pub fn parse(command: u32, body: Bytes) -> Result<Self, NetworkError> {
+		let Ok(command) = PcfsCommand::try_from(command) else {
+			return Ok(Self::Unknown(command));
+		};
+		let mut reader = BodyReader::new("PcfsRequest", body);
+		let request = match command {
+			PcfsCommand::Ping => Self::Ping,
+			PcfsCommand::GetInfo => Self::GetInfo {
+				path: reader.path("path")?,
+			},
+			PcfsCommand::Open => {
+				let mode = match reader.u32("mode")? {
+					0 => PcfsOpenMode::Read,
+					1 => PcfsOpenMode::Write,
+					2 => PcfsOpenMode::ReadWrite,
+					_ => {
+						return Err(NetworkParseError::FieldEncodedIncorrectly(
+							"PcfsRequest",
+							"mode",
+							"one of 0 (read), 1 (write), or 2 (read write)",
+						)
+						.into());
+					}
+				};
+				Self::Open {
+					path: reader.path("path")?,
+					mode,
+				}
+			}
+			PcfsCommand::Read => Self::Read {
+				handle: reader.u32("handle")?,
+				offset: reader.u64("offset")?,
+				length: reader.u32("length")?,
+			},
+			PcfsCommand::Write => Self::Write {
+				handle: reader.u32("handle")?,
+				offset: reader.u64("offset")?,
+				data: reader.rest(),
+			},
+			PcfsCommand::Close => Self::Close {
+				handle: reader.u32("handle")?,
+			},
+			PcfsCommand::OpenDir => Self::OpenDir {
+				path: reader.path("path")?,
+			},
+			PcfsCommand::ReadDir => Self::ReadDir {
+				handle: reader.u32("handle")?,
+			},
+			PcfsCommand::CloseDir => Self::CloseDir {
+				handle: reader.u32("handle")?,
+			},
+			PcfsCommand::CreateDir => Self::CreateDir {
+				path: reader.path("path")?,
+			},
+			PcfsCommand::Remove => Self::Remove {
+				path: reader.path("path")?,
+			},
+			PcfsCommand::Rename => Self::Rename {
+				from: reader.path("from")?,
+				to: reader.path("to")?,
+			},
+		};
+		reader.finish()?;
+		Ok(request)
+	}
+
+	/// Serialize the body of this request, not including the header.
+	#[must_use]
+	pub fn body(&self) -> Bytes {
+		let mut body = BytesMut::new();
+		match self {
+			Self::Ping | Self::Unknown(_) => {}
+			Self::GetInfo { path }
+			| Self::OpenDir { path }
+			| Self::CreateDir { path }
+			| Self::Remove { path } => put_string(&mut body, path),
+			Self::Open { path, mode } => {
+				body.put_u32(u32::from(*mode));
+				put_string(&mut body, path);
+			}
+			Self::Read {
+				handle,
+				offset,
+				length,
+			} => {
+				body.put_u32(*handle);
+				body.put_u64(*offset);
+				body.put_u32(*length);
+			}
+			Self::Write {
+				handle,
+				offset,
+				data,
+			} => {
+				body.put_u32(*handle);
+				body.put_u64(*offset);
+				body.extend_from_slice(data);
+			}
+			Self::Close { handle } | Self::ReadDir { handle } | Self::CloseDir { handle } => {
+				body.put_u32(*handle);
+			}
+			Self::Rename { from, to } => {
+				put_string(&mut body, from);
+				put_string(&mut body, to);
+			}
+		}
+		body.freeze()
+	}
+}
+
+/// A request, alongside the id it was sent with.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct PcfsRequestFrame {
+	id: u32,
+	request: PcfsRequest,
+}
+impl PcfsRequestFrame {
+	#[must_use]
+	pub const fn new(id: u32, request: PcfsRequest) -> Self {
+		Self { id, request }
+	}
+
+	#[must_use]
+	pub const fn id(&self) -> u32 {
+		self.id
+	}
+
+	#[must_use]
+	pub const fn request(&self) -> &PcfsRequest {
+		&self.request
+	}
+
+	#[must_use]
+	pub fn into_request(self) -> PcfsRequest {
+		self.request
+	}
+}
+
+/// A reply from a PCFS server.
+///
+/// The body of a reply depends on the command it's replying to, so it's kept
+/// as raw bytes, and parsed with the type the caller expects (e.g.
+/// [`PcfsEntryInfo`], or [`PcfsOpened`]).
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct PcfsResponseFrame {
+	id: u32,
+	status: PcfsStatus,
+	body: Bytes,
+}
+impl PcfsResponseFrame {
+	/// A successful reply, with a body.
+	#[must_use]
+	pub fn ok(id: u32, body: impl Into<Bytes>) -> Self {
+		Self {
+			id,
+			status: PcfsStatus::Ok,
+			body: body.into(),
+		}
+	}
+
+	/// A failed reply, these never have a body.
+	#[must_use]
+	pub const fn error(id: u32, status: PcfsStatus) -> Self {
+		Self {
+			id,
+			status,
+			body: Bytes::new(),
+		}
+	}
+
+	#[must_use]
+	pub const fn id(&self) -> u32 {
+		self.id
+	}
+
+	#[must_use]
+	pub const fn status(&self) -> PcfsStatus {
+		self.status
+	}
+
+	#[must_use]
+	pub const fn body(&self) -> &Bytes {
+		&self.body
+	}
+}
+
+/// If an entry is a file, or a directory.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, Valuable)]
+pub enum PcfsEntryKind {
+	File,
+	Directory,
+}
+
+/// The reply to [`PcfsRequest::GetInfo`].
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, Valuable)]
+pub struct PcfsEntryInfo {
+	kind: PcfsEntryKind,
+	size: u64,
+}
+impl PcfsEntryInfo {
+	/// Create new info, directories always have a size of 0.
+	#[must_use]
+	pub const fn new(kind: PcfsEntryKind, size: u64) -> Self {
+		Self {
+			kind,
+			size: if matches!(kind, PcfsEntryKind::Directory) {
+				0
+			} else {
+				size
+			},
+		}
+	}
+
+	#[must_use]
+	pub const fn kind(&self) -> PcfsEntryKind {
+		self.kind
+	}
+
+	#[must_use]
+	pub const fn size(&self) -> u64 {
+		self.size
+	}
+
+	fn put(&self, body: &mut BytesMut) {
+		body.put_u32(match self.kind {
+			PcfsEntryKind::File => 0,
+			PcfsEntryKind::Directory => 1,
+		});
+		body.put_u64(self.size);
+	}
+
+	fn read(reader: &mut BodyReader) -> Result<Self, NetworkError> {
+		let kind = match reader.u32("kind")? {
+			0 => PcfsEntryKind::File,
+			1 => PcfsEntryKind::Directory,
+			_ => {
+				return Err(NetworkParseError::FieldEncodedIncorrectly(
+					reader.packet,
+					"kind",
+					"one of 0 (file), or 1 (directory)",
+				)
+				.into());
+			}
+		};
+		Ok(Self::new(kind, reader.u64("size")?))
+	}
+}
+impl From<&PcfsEntryInfo> for Bytes {
+	fn from(value: &PcfsEntryInfo) -> Self {
+		let mut body = BytesMut::with_capacity(12);
+		value.put(&mut body);
+		body.freeze()
+	}
+}
+impl TryFrom<Bytes> for PcfsEntryInfo {
+	type Error = NetworkError;
+
+	fn try_from(body: Bytes) -> Result<Self, Self::Error> {
+		let mut reader = BodyReader::new("PcfsEntryInfo", body);
+		let info = Self::read(&mut reader)?;
+		reader.finish()?;
+		Ok(info)
+	}
+}
+
+/// The reply to [`PcfsRequest::ReadDir`], a single entry in a directory.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, Valuable)]
+pub struct PcfsDirEntry {
+	name: String,
+	info: PcfsEntryInfo,
+}
+impl PcfsDirEntry {
+	#[must_use]
+	pub const fn new(name: String, info: PcfsEntryInfo) -> Self {
+		Self { name, info }
+	}
+
+	#[must_use]
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	#[must_use]
+	pub const fn info(&self) -> PcfsEntryInfo {
+		self.info
+	}
+}
+impl From<&PcfsDirEntry> for Bytes {
+	fn from(value: &PcfsDirEntry) -> Self {
+		let mut body = BytesMut::with_capacity(16 + value.name.len());
+		value.info.put(&mut body);
+		put_string(&mut body, &value.name);
+		body.freeze()
+	}
+}
+impl TryFrom<Bytes> for PcfsDirEntry {
+	type Error = NetworkError;
+
+	fn try_from(body: Bytes) -> Result<Self, Self::Error> {
+		let mut reader = BodyReader::new("PcfsDirEntry", body);
+		let info = PcfsEntryInfo::read(&mut reader)?;
+		let name = reader.path("name")?;
+		reader.finish()?;
+		Ok(Self { name, info })
+	}
+}
+
+/// The reply to [`PcfsRequest::Open`], and [`PcfsRequest::OpenDir`].
+///
+/// The size is always 0 for directories.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, Valuable)]
+pub struct PcfsOpened {
+	handle: u32,
+	size: u64,
+}
+impl PcfsOpened {
+	#[must_use]
+	pub const fn new(handle: u32, size: u64) -> Self {
+		Self { handle, size }
+	}
+
+	#[must_use]
+	pub const fn handle(&self) -> u32 {
+		self.handle
+	}
+
+	#[must_use]
+	pub const fn size(&self) -> u64 {
+		self.size
+	}
+}
+impl From<&PcfsOpened> for Bytes {
+	fn from(value: &PcfsOpened) -> Self {
+		let mut body = BytesMut::with_capacity(12);
+		body.put_u32(value.handle);
+		body.put_u64(value.size);
+		body.freeze()
+	}
+}
+impl TryFrom<Bytes> for PcfsOpened {
+	type Error = NetworkError;
+
+	fn try_from(body: Bytes) -> Result<Self, Self::Error> {
+		let mut reader = BodyReader::new("PcfsOpened", body);
+		let opened = Self {
+			handle: reader.u32("handle")?,
+			size: reader.u64("size")?,
+		};
+		reader.finish()?;
+		Ok(opened)
+	}
+}
+
+/// Write a length prefixed string.
+fn put_string(body: &mut BytesMut, value: &str) {
+	// Paths are capped well below `u32::MAX` when parsed, and anything we
+	// send is one of those paths.
+	body.put_u32(u32::try_from(value.len()).unwrap_or(u32::MAX));
+	body.extend_from_slice(value.as_bytes());
+}
+
+/// A small cursor over the body of a packet, that produces the right parse
+/// errors when the body is too short.
+struct BodyReader {
+	packet: &'static str,
+	body: Bytes,
+}
+impl BodyReader {
+	const fn new(packet: &'static str, body: Bytes) -> Self {
+		Self { packet, body }
+	}
+
+	fn ensure(&self, field: &'static str, needed: usize) -> Result<(), NetworkError> {
+		if self.body.len() < needed {
+			return Err(NetworkParseError::FieldNotLongEnough(
+				self.packet,
+				field,
+				needed,
+				self.body.len(),
+				self.body.clone(),
+			)
+			.into());
+		}
+		Ok(())
+	}
+
+	fn u32(&mut self, field: &'static str) -> Result<u32, NetworkError> {
+		self.ensure(field, 4)?;
+		Ok(self.body.get_u32())
+	}
+
+	fn u64(&mut self, field: &'static str) -> Result<u64, NetworkError> {
+		self.ensure(field, 8)?;
+		Ok(self.body.get_u64())
+	}
+
+	fn path(&mut self, field: &'static str) -> Result<String, NetworkError> {
+		let length = usize::try_from(self.u32(field)?).unwrap_or(usize::MAX);
+		if length > MAX_PCFS_PATH_LENGTH {
+			return Err(NetworkParseError::FieldTooLong(
+				self.packet,
+				field,
+				MAX_PCFS_PATH_LENGTH,
+				length,
+				self.body.clone(),
+			)
+			.into());
+		}
+		self.ensure(field, length)?;
+		String::from_utf8(self.body.split_to(length).to_vec()).map_err(|_| {
+			NetworkParseError::FieldEncodedIncorrectly(self.packet, field, "UTF-8").into()
+		})
+	}
+
+	fn rest(&mut self) -> Bytes {
+		self.body.split_off(0)
+	}
+
+	fn finish(self) -> Result<(), NetworkError> {
+		if self.body.is_empty() {
+			Ok(())
+		} else {
+			Err(NetworkParseError::UnexpectedTrailer(self.packet, self.body).into())
+		}
+	}
+}
+
+#[cfg(test)]
+mod unit_tests {
+	use super::*;
+
+	#[test]
+	pub fn can_round_trip_requests() {
+		let requests = [
+			PcfsRequest::Ping,
+			PcfsRequest::GetInfo {
+				path: "/content/audio/bgm.bfstm".to_owned(),
+			},
+			PcfsRequest::Open {
+				path: "/meta/meta.xml".to_owned(),
+				mode: PcfsOpenMode::ReadWrite,
+			},
+			PcfsRequest::Read {
+				handle: 3,
+				offset: 0x1_0000_0000,
+				length: 0x8000,
+			},
+			PcfsRequest::Write {
+				handle: 4,
+				offset: 12,
+				data: Bytes::from_static(b"save data"),
+			},
+			PcfsRequest::Close { handle: 3 },
+			PcfsRequest::OpenDir {
+				path: "/code".to_owned(),
+			},
+			PcfsRequest::ReadDir { handle: 1 },
+			PcfsRequest::CloseDir { handle: 1 },
+			PcfsRequest::CreateDir {
+				path: "/content/saves".to_owned(),
+			},
+			PcfsRequest::Remove {
+				path: "/content/saves/old.bin".to_owned(),
+			},
+			PcfsRequest::Rename {
+				from: "/content/a".to_owned(),
+				to: "/content/b".to_owned(),
+			},
+		];
+
+		for request in requests {
+			let command = u32::from(request.command().expect("Known request had no command!"));
+			assert_eq!(
+				PcfsRequest::parse(command, request.body()).expect("Failed to parse request!"),
+				request,
+			);
+		}
+		assert_eq!(
+			PcfsRequest::parse(0xFF, Bytes::from_static(b"whatever")).expect("Failed to parse!"),
+			PcfsRequest::Unknown(0xFF),
+		);
+	}
+
+	#[test]
+	pub fn rejects_bad_request_bodies() {
+		let open = u32::from(PcfsCommand::Open);
+		// Missing the path entirely.
+		assert!(PcfsRequest::parse(open, Bytes::from_static(&[0, 0, 0, 0])).is_err());
+		// An open mode we don't know about.
+		assert!(PcfsRequest::parse(open, Bytes::from_static(&[0, 0, 0, 9, 0, 0, 0, 0])).is_err());
+		// A path that claims to be longer than any path.
+		assert!(PcfsRequest::parse(
+			u32::from(PcfsCommand::GetInfo),
+			Bytes::from_static(&[0, 0, 0x10, 0])
+		)
+		.is_err());
+		// Not UTF-8.
+		assert!(PcfsRequest::parse(
+			u32::from(PcfsCommand::GetInfo),
+			Bytes::from_static(&[0, 0, 0, 1, 0xFF])
+		)
+		.is_err());
+		// Extra data after the handle.
+		assert!(PcfsRequest::parse(
+			u32::from(PcfsCommand::Close),
+			Bytes::from_static(&[0, 0, 0, 1, 0])
+		)
+		.is_err());
+	}
+
+	#[test]
+	pub fn can_round_trip_replies() {
+		let info = PcfsEntryInfo::new(PcfsEntryKind::File, 1234);
+		assert_eq!(
+			PcfsEntryInfo::try_from(Bytes::from(&info)).expect("Failed to parse info!"),
+			info,
+		);
+		assert_eq!(
+			PcfsEntryInfo::new(PcfsEntryKind::Directory, 1234).size(),
+			0,
+			"Directories should never have a size!",
+		);
+
+		let entry = PcfsDirEntry::new("boot.rpx".to_owned(), info);
+		assert_eq!(
+			PcfsDirEntry::try_from(Bytes::from(&entry)).expect("Failed to parse entry!"),
+			entry,
+		);
+
+		let opened = PcfsOpened::new(7, 0x00FF_FFFF_FFFF);
+		assert_eq!(
+			PcfsOpened::try_from(Bytes::from(&opened)).expect("Failed to parse opened!"),
+			opened,
+		);
+
+		for raw in -13..=0 {
+			assert_eq!(i32::from(PcfsStatus::from(raw)), raw);
+		}
+		assert_eq!(PcfsStatus::from(-100), PcfsStatus::Unknown(-100));
+	}
+}
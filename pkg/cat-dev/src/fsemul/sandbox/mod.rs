@@ -0,0 +1,675 @@
+//! Keeps every path asked for over PCFS inside of the directory being served.
+//!
+//! Paths from a devkit are always absolute, `/` separated, and relative to the
+//! root of the emulated drive (e.g. `/content/Common/bgm.bfsar`). They are
+//! first normalized lexically into a [`SandboxPath`]: `.`, and empty
+//! components are dropped, and `..` pops a component, refusing to go above the
+//! root.
+//!
+//! A [`SandboxPath`] is never turned back into a path on the host. Instead the
+//! sandbox keeps a handle to the root open, and every operation opens each
+//! component relative to the one before it without following symlinks. A
+//! symlink anywhere in a path is refused (even one pointing back inside of the
+//! root), and because nothing is ever looked up by its full path on the host,
+//! swapping a directory for a symlink while a request is being handled can't
+//! escape the root either.
+//!
+//! This needs `openat`, and friends, so is only available on unix like
+//! platforms right now. Everywhere else [`PathSandbox::new`] fails with
+//! [`FSError::SandboxUnsupported`].
+
+#[cfg(any(
+	target_os = "freebsd",
+	target_os = "linux",
+	target_os = "macos",
+	target_os = "netbsd",
+	target_os = "openbsd",
+))]
+mod unix;
+#[cfg(any(
+	target_os = "freebsd",
+	target_os = "linux",
+	target_os = "macos",
+	target_os = "netbsd",
+	target_os = "openbsd",
+))]
+use unix as sys;
+
+#[cfg(not(any(
+	target_os = "freebsd",
+	target_os = "linux",
+	target_os = "macos",
+	target_os = "netbsd",
+	target_os = "openbsd",
+)))]
+mod unsupported;
+#[cfg(not(any(
+	target_os = "freebsd",
+	target_os = "linux",
+	target_os = "macos",
+	target_os = "netbsd",
+	target_os = "openbsd",
+)))]
+use unsupported as sys;
+
+use crate::errors::FSError;
+use std::{
+	fmt::{Display, Formatter, Result as FmtResult},
+	io::Error as IoError,
+	path::{Path, PathBuf},
+	sync::Arc,
+};
+use tokio::fs::File;
+
+/// A directory that paths can be safely opened inside of.
+#[derive(Clone, Debug)]
+pub struct PathSandbox {
+	root: PathBuf,
+	handle: Arc<sys::RootHandle>,
+}
+
+impl PathSandbox {
+	/// Create a new sandbox rooted at a directory.
+	///
+	/// The root is opened once here, if it gets moved, or replaced afterwards
+	/// the sandbox keeps serving the directory it originally opened.
+	///
+	/// ## Errors
+	///
+	/// - If the root does not exist, cannot be canonicalized, or is not a
+	///   directory.
+	/// - [`FSError::SandboxUnsupported`] if this platform can't sandbox paths.
+	pub async fn new(root: impl Into<PathBuf>) -> Result<Self, FSError> {
+		let root = tokio::fs::canonicalize(root.into()).await?;
+		let handle = {
+			let root = root.clone();
+			blocking(move || sys::open_root(&root)).await?
+		};
+
+		Ok(Self {
+			root,
+			handle: Arc::new(handle),
+		})
+	}
+
+	/// The canonicalized root of this sandbox.
+	#[must_use]
+	pub fn root(&self) -> &Path {
+		&self.root
+	}
+
+	/// Normalize a path from a devkit.
+	///
+	/// This only looks at the path itself, nothing on disk is checked until
+	/// the path is actually used.
+	///
+	/// ## Errors
+	///
+	/// - [`FSError::InvalidSandboxPath`] if the path contains `\`, `:`, or NUL.
+	/// - [`FSError::PathOutsideOfSandbox`] if the path leaves the root through
+	///   `..`.
+	pub fn normalize(&self, path: &str) -> Result<SandboxPath, FSError> {
+		SandboxPath::new(path)
+	}
+
+	/// Get the type, and size of whatever is at a path.
+	///
+	/// ## Errors
+	///
+	/// - [`FSError::PathOutsideOfSandbox`] if the path goes through a symlink.
+	/// - [`FSError::IOError`] if the path doesn't exist, or is something other
+	///   than a file, or directory.
+	pub async fn metadata(&self, path: &SandboxPath) -> Result<SandboxMetadata, FSError> {
+		let (handle, path) = (self.handle.clone(), path.clone());
+		blocking(move || sys::metadata(&handle, &path)).await
+	}
+
+	/// Open a file inside of the sandbox.
+	///
+	/// ## Errors
+	///
+	/// - [`FSError::PathOutsideOfSandbox`] if the path goes through a symlink.
+	/// - [`FSError::IOError`] if the file can't be opened, or is not a regular
+	///   file.
+	pub async fn open(
+		&self,
+		path: &SandboxPath,
+		options: SandboxOpenOptions,
+	) -> Result<File, FSError> {
+		let (handle, path) = (self.handle.clone(), path.clone());
+		blocking(move || sys::open(&handle, &path, options))
+			.await
+			.map(File::from_std)
+	}
+
+	/// List every file, and directory inside of a directory.
+	///
+	/// Symlinks, anything that isn't a file or directory, and names that aren't
+	/// UTF-8 are skipped, as a devkit could never open them anyway.
+	///
+	/// ## Errors
+	///
+	/// - [`FSError::PathOutsideOfSandbox`] if the path goes through a symlink.
+	/// - [`FSError::IOError`] if the path is not a directory, or can't be
+	///   listed.
+	pub async fn read_dir(
+		&self,
+		path: &SandboxPath,
+	) -> Result<Vec<(String, SandboxMetadata)>, FSError> {
+		let (handle, path) = (self.handle.clone(), path.clone());
+		blocking(move || sys::read_dir(&handle, &path)).await
+	}
+
+	/// Create a single directory.
+	///
+	/// ## Errors
+	///
+	/// - [`FSError::PathOutsideOfSandbox`] if the path goes through a symlink.
+	/// - [`FSError::IOError`] if the directory can't be created.
+	pub async fn create_dir(&self, path: &SandboxPath) -> Result<(), FSError> {
+		let (handle, path) = (self.handle.clone(), path.clone());
+		blocking(move || sys::create_dir(&handle, &path)).await
+	}
+
+	/// Remove a file, or an empty directory.
+	///
+	/// If the last component of the path is a symlink, the symlink itself is
+	/// removed, never what it points to.
+	///
+	/// ## Errors
+	///
+	/// - [`FSError::PathOutsideOfSandbox`] if the path goes through a symlink.
+	/// - [`FSError::IOError`] if the path can't be removed, or is the root.
+	pub async fn remove(&self, path: &SandboxPath) -> Result<(), FSError> {
+		let (handle, path) = (self.handle.clone(), path.clone());
+		blocking(move || sys::remove(&handle, &path)).await
+	}
+
+	/// Rename a file, or directory, refusing to replace anything that already
+	/// exists.
+	///
+	/// If the last component of `from` is a symlink, the symlink itself is
+	/// renamed, never what it points to.
+	///
+	/// ## Errors
+	///
+	/// - [`FSError::PathOutsideOfSandbox`] if either path goes through a
+	///   symlink.
+	/// - [`FSError::IOError`] if `to` already exists, either path is the root,
+	///   or the rename fails.
+	pub async fn rename(&self, from: &SandboxPath, to: &SandboxPath) -> Result<(), FSError> {
+		let (handle, from, to) = (self.handle.clone(), from.clone(), to.clone());
+		blocking(move || sys::rename(&handle, &from, &to)).await
+	}
+}
+
+/// A path from a devkit that has been lexically normalized, but not yet
+/// looked at on disk.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct SandboxPath {
+	components: Vec<String>,
+}
+
+impl SandboxPath {
+	fn new(path: &str) -> Result<Self, FSError> {
+		if path.contains(['\\', ':', '\0']) {
+			return Err(FSError::InvalidSandboxPath(path.to_owned()));
+		}
+
+		let mut components = Vec::new();
+		for component in path.split('/') {
+			match component {
+				"" | "." => {}
+				".." => {
+					if components.pop().is_none() {
+						return Err(FSError::PathOutsideOfSandbox(path.to_owned()));
+					}
+				}
+				name => components.push(name.to_owned()),
+			}
+		}
+
+		Ok(Self { components })
+	}
+
+	/// The names of every directory, and finally the entry this path points
+	/// to, starting from the root.
+	#[must_use]
+	pub fn components(&self) -> &[String] {
+		&self.components
+	}
+
+	/// If this path is the root of the sandbox itself.
+	#[must_use]
+	pub fn is_root(&self) -> bool {
+		self.components.is_empty()
+	}
+}
+
+impl Display for SandboxPath {
+	fn fmt(&self, fmt: &mut Formatter<'_>) -> FmtResult {
+		if self.is_root() {
+			return write!(fmt, "/");
+		}
+		for component in &self.components {
+			write!(fmt, "/{component}")?;
+		}
+		Ok(())
+	}
+}
+
+/// What is at a path inside of a sandbox.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct SandboxMetadata {
+	is_dir: bool,
+	size: u64,
+}
+
+impl SandboxMetadata {
+	#[must_use]
+	pub const fn file(size: u64) -> Self {
+		Self {
+			is_dir: false,
+			size,
+		}
+	}
+
+	#[must_use]
+	pub const fn directory() -> Self {
+		Self {
+			is_dir: true,
+			size: 0,
+		}
+	}
+
+	#[must_use]
+	pub const fn is_dir(&self) -> bool {
+		self.is_dir
+	}
+
+	/// The size of a file in bytes, always `0` for directories.
+	#[must_use]
+	pub const fn size(&self) -> u64 {
+		self.size
+	}
+}
+
+/// The errors from a sandbox PCFS reports with a status of their own.
+///
+/// [`std::io::ErrorKind`] only has variants for these from Rust 1.83, so they
+/// are picked out of the OS error code instead.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum SandboxErrorKind {
+	/// A file was expected, but the path is a directory.
+	IsADirectory,
+	/// A directory was expected, but the path is something else.
+	NotADirectory,
+	/// A directory that still has entries was removed.
+	DirectoryNotEmpty,
+}
+
+impl SandboxErrorKind {
+	/// Figure out which of these errors an I/O error is, if any.
+	#[must_use]
+	pub fn of(cause: &IoError) -> Option<Self> {
+		sys::error_kind(cause)
+	}
+}
+
+/// How to open a file inside of a sandbox, mirroring the parts of
+/// [`std::fs::OpenOptions`] PCFS needs.
+#[allow(
+	// These really are just four independent flags.
+	clippy::struct_excessive_bools,
+)]
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq)]
+pub struct SandboxOpenOptions {
+	read: bool,
+	write: bool,
+	create: bool,
+	truncate: bool,
+}
+
+impl SandboxOpenOptions {
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			read: false,
+			write: false,
+			create: false,
+			truncate: false,
+		}
+	}
+
+	#[must_use]
+	pub const fn read(mut self, read: bool) -> Self {
+		self.read = read;
+		self
+	}
+
+	#[must_use]
+	pub const fn write(mut self, write: bool) -> Self {
+		self.write = write;
+		self
+	}
+
+	/// Create the file if it doesn't exist, only used when also writing.
+	#[must_use]
+	pub const fn create(mut self, create: bool) -> Self {
+		self.create = create;
+		self
+	}
+
+	/// Truncate the file if it exists, only used when also writing.
+	#[must_use]
+	pub const fn truncate(mut self, truncate: bool) -> Self {
+		self.truncate = truncate;
+		self
+	}
+}
+
+/// Run a blocking sandbox operation on tokio's blocking thread pool.
+async fn blocking<ResultTy, FnTy>(func: FnTy) -> Result<ResultTy, FSError>
+where
+	ResultTy: Send + 'static,
+	FnTy: FnOnce() -> Result<ResultTy, FSError> + Send + 'static,
+{
+	tokio::task::spawn_blocking(func)
+		.await
+		.map_err(|cause| FSError::IOError(IoError::other(cause)))?
+}
+
+#[cfg(all(
+	test,
+	any(
+		target_os = "freebsd",
+		target_os = "linux",
+		target_os = "macos",
+		target_os = "netbsd",
+		target_os = "openbsd",
+	),
+))]
+mod unit_tests {
+	use super::*;
+
+	#[tokio::test]
+	pub async fn normalizes_paths_inside_the_root() {
+		let directory = tempfile::tempdir().expect("Failed to create temporary directory!");
+		let sandbox = PathSandbox::new(directory.path())
+			.await
+			.expect("Failed to create sandbox!");
+
+		assert_eq!(
+			sandbox
+				.normalize("/content/sub/file.bin")
+				.expect("Failed to normalize path!")
+				.components(),
+			["content", "sub", "file.bin"],
+		);
+		assert_eq!(
+			sandbox
+				.normalize("//content/./sub/../new/dir")
+				.expect("Failed to normalize path with dots!")
+				.to_string(),
+			"/content/new/dir",
+		);
+		assert!(sandbox
+			.normalize("/")
+			.expect("Failed to normalize root!")
+			.is_root());
+		assert!(sandbox
+			.normalize("/content/..")
+			.expect("Failed to normalize back to root!")
+			.is_root());
+	}
+
+	#[tokio::test]
+	pub async fn refuses_paths_outside_the_root() {
+		let directory = tempfile::tempdir().expect("Failed to create temporary directory!");
+		let sandbox = PathSandbox::new(directory.path())
+			.await
+			.expect("Failed to create sandbox!");
+
+		for path in ["/..", "/content/../../etc/passwd", "../"] {
+			assert!(
+				matches!(
+					sandbox.normalize(path),
+					Err(FSError::PathOutsideOfSandbox(_))
+				),
+				"Path: {path} was not refused!",
+			);
+		}
+		for path in ["/content\\..\\..", "C:/Windows", "/content/a\0b"] {
+			assert!(
+				matches!(sandbox.normalize(path), Err(FSError::InvalidSandboxPath(_))),
+				"Path: {path:?} was not refused!",
+			);
+		}
+	}
+
+	#[tokio::test]
+	pub async fn can_use_files_and_directories() {
+		let directory = tempfile::tempdir().expect("Failed to create temporary directory!");
+		let sandbox = PathSandbox::new(directory.path())
+			.await
+			.expect("Failed to create sandbox!");
+		let path = |path: &str| sandbox.normalize(path).expect("Failed to normalize path!");
+
+		sandbox
+			.create_dir(&path("/content"))
+			.await
+			.expect("Failed to create directory!");
+		let mut file = sandbox
+			.open(
+				&path("/content/file.bin"),
+				SandboxOpenOptions::new().write(true).create(true),
+			)
+			.await
+			.expect("Failed to create file!");
+		tokio::io::AsyncWriteExt::write_all(&mut file, b"data")
+			.await
+			.expect("Failed to write file!");
+		drop(file);
+
+		assert_eq!(
+			sandbox
+				.metadata(&path("/content/file.bin"))
+				.await
+				.expect("Failed to get file metadata!"),
+			SandboxMetadata::file(4),
+		);
+		assert_eq!(
+			sandbox
+				.read_dir(&path("/"))
+				.await
+				.expect("Failed to list root!"),
+			vec![("content".to_owned(), SandboxMetadata::directory())],
+		);
+		assert!(matches!(
+			sandbox
+				.open(&path("/content"), SandboxOpenOptions::new().read(true))
+				.await,
+			Err(FSError::IOError(cause))
+				if SandboxErrorKind::of(&cause) == Some(SandboxErrorKind::IsADirectory),
+		));
+		assert!(matches!(
+			sandbox.read_dir(&path("/content/file.bin")).await,
+			Err(FSError::IOError(cause))
+				if SandboxErrorKind::of(&cause) == Some(SandboxErrorKind::NotADirectory),
+		));
+
+		sandbox
+			.rename(&path("/content/file.bin"), &path("/content/moved.bin"))
+			.await
+			.expect("Failed to rename file!");
+		assert!(matches!(
+			sandbox.remove(&path("/content")).await,
+			Err(FSError::IOError(cause))
+				if SandboxErrorKind::of(&cause) == Some(SandboxErrorKind::DirectoryNotEmpty),
+		));
+		sandbox
+			.remove(&path("/content/moved.bin"))
+			.await
+			.expect("Failed to remove file!");
+		sandbox
+			.remove(&path("/content"))
+			.await
+			.expect("Failed to remove directory!");
+		assert!(!directory.path().join("content").exists());
+	}
+
+	#[tokio::test]
+	pub async fn refuses_symlinks() {
+		let directory = tempfile::tempdir().expect("Failed to create temporary directory!");
+		let outside = tempfile::tempdir().expect("Failed to create temporary directory!");
+		tokio::fs::symlink(outside.path(), directory.path().join("escape"))
+			.await
+			.expect("Failed to create symlink!");
+		tokio::fs::symlink(
+			outside.path().join("missing"),
+			directory.path().join("dangling"),
+		)
+		.await
+		.expect("Failed to create symlink!");
+		tokio::fs::create_dir(directory.path().join("content"))
+			.await
+			.expect("Failed to create content directory!");
+		tokio::fs::symlink(
+			directory.path().join("content"),
+			directory.path().join("inside"),
+		)
+		.await
+		.expect("Failed to create symlink!");
+		let sandbox = PathSandbox::new(directory.path())
+			.await
+			.expect("Failed to create sandbox!");
+		let path = |path: &str| sandbox.normalize(path).expect("Failed to normalize path!");
+
+		// Even symlinks that point back inside of the root are refused.
+		for link in ["/escape", "/dangling", "/inside"] {
+			assert!(
+				matches!(
+					sandbox.metadata(&path(link)).await,
+					Err(FSError::PathOutsideOfSandbox(_))
+				),
+				"Path: {link} was not refused!",
+			);
+		}
+		for file in ["/escape/new-file", "/dangling", "/inside/file"] {
+			assert!(
+				matches!(
+					sandbox
+						.open(
+							&path(file),
+							SandboxOpenOptions::new().write(true).create(true),
+						)
+						.await,
+					Err(FSError::PathOutsideOfSandbox(_))
+				),
+				"Path: {file} was not refused!",
+			);
+		}
+		assert!(matches!(
+			sandbox.create_dir(&path("/escape/new-dir")).await,
+			Err(FSError::PathOutsideOfSandbox(_))
+		));
+		assert!(!outside.path().join("new-file").exists());
+		assert!(!outside.path().join("missing").exists());
+		assert!(!outside.path().join("new-dir").exists());
+		assert_eq!(
+			sandbox
+				.read_dir(&path("/"))
+				.await
+				.expect("Failed to list root!"),
+			vec![("content".to_owned(), SandboxMetadata::directory())],
+			"Symlinks should not be listed!",
+		);
+	}
+
+	#[tokio::test]
+	pub async fn refuses_directories_swapped_for_symlinks() {
+		let directory = tempfile::tempdir().expect("Failed to create temporary directory!");
+		let outside = tempfile::tempdir().expect("Failed to create temporary directory!");
+		tokio::fs::create_dir(directory.path().join("content"))
+			.await
+			.expect("Failed to create content directory!");
+		let sandbox = PathSandbox::new(directory.path())
+			.await
+			.expect("Failed to create sandbox!");
+		let content = sandbox
+			.normalize("/content/file.bin")
+			.expect("Failed to normalize path!");
+		assert!(sandbox.metadata(&content).await.is_err());
+
+		// Swap the directory for a symlink after the path was already looked
+		// at once.
+		tokio::fs::remove_dir(directory.path().join("content"))
+			.await
+			.expect("Failed to remove content directory!");
+		tokio::fs::symlink(outside.path(), directory.path().join("content"))
+			.await
+			.expect("Failed to create symlink!");
+		assert!(matches!(
+			sandbox
+				.open(&content, SandboxOpenOptions::new().write(true).create(true))
+				.await,
+			Err(FSError::PathOutsideOfSandbox(_))
+		));
+		assert!(!outside.path().join("file.bin").exists());
+	}
+
+	#[tokio::test]
+	pub async fn remove_and_rename_act_on_symlinks_themselves() {
+		let directory = tempfile::tempdir().expect("Failed to create temporary directory!");
+		let outside = tempfile::tempdir().expect("Failed to create temporary directory!");
+		tokio::fs::write(outside.path().join("target.bin"), b"outside")
+			.await
+			.expect("Failed to write target!");
+		tokio::fs::symlink(
+			outside.path().join("target.bin"),
+			directory.path().join("link"),
+		)
+		.await
+		.expect("Failed to create symlink!");
+		tokio::fs::symlink(outside.path(), directory.path().join("linked-dir"))
+			.await
+			.expect("Failed to create symlink!");
+		let sandbox = PathSandbox::new(directory.path())
+			.await
+			.expect("Failed to create sandbox!");
+		let path = |path: &str| sandbox.normalize(path).expect("Failed to normalize path!");
+
+		sandbox
+			.rename(&path("/link"), &path("/renamed"))
+			.await
+			.expect("Failed to rename symlink!");
+		assert!(
+			tokio::fs::symlink_metadata(directory.path().join("renamed"))
+				.await
+				.expect("Renamed symlink is missing!")
+				.is_symlink()
+		);
+		assert!(outside.path().join("target.bin").exists());
+
+		assert!(matches!(
+			sandbox.remove(&path("/linked-dir/target.bin")).await,
+			Err(FSError::PathOutsideOfSandbox(_)),
+		));
+		sandbox
+			.remove(&path("/renamed"))
+			.await
+			.expect("Failed to remove symlink!");
+		sandbox
+			.remove(&path("/linked-dir"))
+			.await
+			.expect("Failed to remove symlink to a directory!");
+		assert!(!directory.path().join("renamed").exists());
+		assert!(!directory.path().join("linked-dir").exists());
+		assert_eq!(
+			tokio::fs::read(outside.path().join("target.bin"))
+				.await
+				.expect("Symlink target was removed!"),
+			b"outside",
+		);
+	}
+}
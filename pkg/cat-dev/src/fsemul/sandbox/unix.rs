@@ -0,0 +1,332 @@
+//! The unix implementation of a [`super::PathSandbox`].
+//!
+//! Everything here is built on `openat`, and friends. Each component of a
+//! path is opened relative to the directory before it with `O_NOFOLLOW`, and
+//! the last component is only ever looked at with `AT_SYMLINK_NOFOLLOW`, so a
+//! symlink can never be followed no matter when it shows up.
+
+use crate::{
+	errors::FSError,
+	fsemul::sandbox::{SandboxErrorKind, SandboxMetadata, SandboxOpenOptions, SandboxPath},
+};
+use libc::{
+	c_int, mode_t, AT_REMOVEDIR, AT_SYMLINK_NOFOLLOW, EISDIR, ENOTDIR, ENOTEMPTY, O_CLOEXEC,
+	O_CREAT, O_DIRECTORY, O_NOFOLLOW, O_NONBLOCK, O_RDONLY, O_RDWR, O_TRUNC, O_WRONLY, S_IFDIR,
+	S_IFLNK, S_IFMT, S_IFREG,
+};
+use std::{
+	ffi::{CStr, CString},
+	fs::{File, OpenOptions},
+	io::{Error as IoError, ErrorKind},
+	mem::MaybeUninit,
+	os::{
+		fd::{AsFd, AsRawFd, BorrowedFd, FromRawFd, IntoRawFd, OwnedFd},
+		unix::fs::OpenOptionsExt,
+	},
+	path::Path,
+	ptr::NonNull,
+};
+
+#[cfg(any(target_os = "netbsd", target_os = "openbsd"))]
+use libc::__errno as errno_location;
+#[cfg(target_os = "linux")]
+use libc::__errno_location as errno_location;
+#[cfg(any(target_os = "freebsd", target_os = "macos"))]
+use libc::__error as errno_location;
+
+/// The permissions new files are created with, before the umask.
+const FILE_MODE: mode_t = 0o666;
+/// The permissions new directories are created with, before the umask.
+const DIRECTORY_MODE: mode_t = 0o777;
+
+/// An open handle to the root of a sandbox.
+pub type RootHandle = OwnedFd;
+
+pub fn open_root(root: &Path) -> Result<RootHandle, FSError> {
+	// The root was picked by the host, so following symlinks to get to it is
+	// fine.
+	Ok(OpenOptions::new()
+		.read(true)
+		.custom_flags(O_DIRECTORY)
+		.open(root)?
+		.into())
+}
+
+pub fn metadata(root: &RootHandle, path: &SandboxPath) -> Result<SandboxMetadata, FSError> {
+	let stat = match open_parent(root, path)? {
+		Some((parent, name)) => stat_at(parent.as_fd(), &name)?,
+		None => stat_fd(root.as_fd())?,
+	};
+	to_metadata(path, &stat)
+}
+
+pub fn open(
+	root: &RootHandle,
+	path: &SandboxPath,
+	options: SandboxOpenOptions,
+) -> Result<File, FSError> {
+	let Some((parent, name)) = open_parent(root, path)? else {
+		return Err(IoError::from_raw_os_error(EISDIR).into());
+	};
+
+	let mut flags = match (options.read, options.write) {
+		(true, true) => O_RDWR,
+		(false, true) => O_WRONLY,
+		_ => O_RDONLY,
+	};
+	if options.write {
+		if options.create {
+			flags |= O_CREAT;
+		}
+		if options.truncate {
+			flags |= O_TRUNC;
+		}
+	}
+	// Non-blocking so opening something like a FIFO can't hang us, it gets
+	// refused right after anyway.
+	let file = File::from(open_at(parent.as_fd(), &name, flags | O_NONBLOCK, path)?);
+	match stat_fd(file.as_fd())?.st_mode & S_IFMT {
+		S_IFREG => Ok(file),
+		S_IFDIR => Err(IoError::from_raw_os_error(EISDIR).into()),
+		_ => Err(IoError::from(ErrorKind::Unsupported).into()),
+	}
+}
+
+pub fn read_dir(
+	root: &RootHandle,
+	path: &SandboxPath,
+) -> Result<Vec<(String, SandboxMetadata)>, FSError> {
+	let mut stream = DirectoryStream::new(open_directory(root, path.components(), path)?)?;
+	let mut entries = Vec::new();
+	while let Some(name) = stream.next_name()? {
+		if matches!(name.as_bytes(), b"." | b"..") {
+			continue;
+		}
+		// Names that aren't UTF-8 can't be sent to cafe.
+		let Ok(utf8_name) = name.to_str() else {
+			continue;
+		};
+		// Skip symlinks, and anything else a devkit couldn't open anyway.
+		let Ok(metadata) = stat_at(stream.fd(), &name)
+			.map_err(FSError::from)
+			.and_then(|stat| to_metadata(path, &stat))
+		else {
+			continue;
+		};
+		entries.push((utf8_name.to_owned(), metadata));
+	}
+
+	Ok(entries)
+}
+
+pub fn create_dir(root: &RootHandle, path: &SandboxPath) -> Result<(), FSError> {
+	let Some((parent, name)) = open_parent(root, path)? else {
+		return Err(IoError::from(ErrorKind::AlreadyExists).into());
+	};
+	// `mkdirat` never follows a symlink in the last component.
+	check(unsafe { libc::mkdirat(parent.as_raw_fd(), name.as_ptr(), DIRECTORY_MODE) })
+}
+
+pub fn remove(root: &RootHandle, path: &SandboxPath) -> Result<(), FSError> {
+	let Some((parent, name)) = open_parent(root, path)? else {
+		return Err(IoError::from(ErrorKind::InvalidInput).into());
+	};
+	// Looked at without following symlinks, so a symlink to a directory gets
+	// removed like any other file. If it gets swapped out before we remove it
+	// `unlinkat` fails, as the flag no longer matches.
+	let flags = if stat_at(parent.as_fd(), &name)?.st_mode & S_IFMT == S_IFDIR {
+		AT_REMOVEDIR
+	} else {
+		0
+	};
+	check(unsafe { libc::unlinkat(parent.as_raw_fd(), name.as_ptr(), flags) })
+}
+
+pub fn rename(root: &RootHandle, from: &SandboxPath, to: &SandboxPath) -> Result<(), FSError> {
+	let (Some((from_parent, from_name)), Some((to_parent, to_name))) =
+		(open_parent(root, from)?, open_parent(root, to)?)
+	else {
+		return Err(IoError::from(ErrorKind::InvalidInput).into());
+	};
+	// `renameat` silently replaces whatever is at `to`, PCFS never does. It
+	// never follows symlinks in either last component, so at worst losing
+	// this race replaces something else inside of the root.
+	match stat_at(to_parent.as_fd(), &to_name) {
+		Ok(_) => return Err(IoError::from(ErrorKind::AlreadyExists).into()),
+		Err(cause) if cause.kind() == ErrorKind::NotFound => {}
+		Err(cause) => return Err(cause.into()),
+	}
+	check(unsafe {
+		libc::renameat(
+			from_parent.as_raw_fd(),
+			from_name.as_ptr(),
+			to_parent.as_raw_fd(),
+			to_name.as_ptr(),
+		)
+	})
+}
+
+pub fn error_kind(cause: &IoError) -> Option<SandboxErrorKind> {
+	match cause.raw_os_error()? {
+		EISDIR => Some(SandboxErrorKind::IsADirectory),
+		ENOTDIR => Some(SandboxErrorKind::NotADirectory),
+		ENOTEMPTY => Some(SandboxErrorKind::DirectoryNotEmpty),
+		_ => None,
+	}
+}
+
+/// Open the directory containing the last component of a path, returning it
+/// alongside the name of that last component.
+///
+/// Returns `None` for the root itself, which has no parent we can reach.
+fn open_parent(
+	root: &RootHandle,
+	path: &SandboxPath,
+) -> Result<Option<(OwnedFd, CString)>, FSError> {
+	let Some((name, parents)) = path.components().split_last() else {
+		return Ok(None);
+	};
+	Ok(Some((open_directory(root, parents, path)?, c_name(name)?)))
+}
+
+/// Open a directory one component at a time, starting from the root.
+fn open_directory(
+	root: &RootHandle,
+	components: &[String],
+	path: &SandboxPath,
+) -> Result<OwnedFd, FSError> {
+	let mut directory = root.try_clone()?;
+	for component in components {
+		directory = open_at(
+			directory.as_fd(),
+			&c_name(component)?,
+			O_RDONLY | O_DIRECTORY,
+			path,
+		)?;
+	}
+	Ok(directory)
+}
+
+fn open_at(
+	directory: BorrowedFd<'_>,
+	name: &CStr,
+	flags: c_int,
+	path: &SandboxPath,
+) -> Result<OwnedFd, FSError> {
+	let fd = unsafe {
+		libc::openat(
+			directory.as_raw_fd(),
+			name.as_ptr(),
+			flags | O_CLOEXEC | O_NOFOLLOW,
+			libc::c_uint::from(FILE_MODE),
+		)
+	};
+	if fd < 0 {
+		let cause = IoError::last_os_error();
+		// `O_NOFOLLOW` fails with a different error on almost every platform,
+		// so look at what is actually there.
+		if stat_at(directory, name).is_ok_and(|stat| stat.st_mode & S_IFMT == S_IFLNK) {
+			return Err(FSError::PathOutsideOfSandbox(path.to_string()));
+		}
+		return Err(cause.into());
+	}
+
+	Ok(unsafe { OwnedFd::from_raw_fd(fd) })
+}
+
+fn stat_at(directory: BorrowedFd<'_>, name: &CStr) -> Result<libc::stat, IoError> {
+	let mut stat = MaybeUninit::<libc::stat>::uninit();
+	if unsafe {
+		libc::fstatat(
+			directory.as_raw_fd(),
+			name.as_ptr(),
+			stat.as_mut_ptr(),
+			AT_SYMLINK_NOFOLLOW,
+		)
+	} != 0
+	{
+		return Err(IoError::last_os_error());
+	}
+	Ok(unsafe { stat.assume_init() })
+}
+
+fn stat_fd(fd: BorrowedFd<'_>) -> Result<libc::stat, IoError> {
+	let mut stat = MaybeUninit::<libc::stat>::uninit();
+	if unsafe { libc::fstat(fd.as_raw_fd(), stat.as_mut_ptr()) } != 0 {
+		return Err(IoError::last_os_error());
+	}
+	Ok(unsafe { stat.assume_init() })
+}
+
+fn to_metadata(path: &SandboxPath, stat: &libc::stat) -> Result<SandboxMetadata, FSError> {
+	match stat.st_mode & S_IFMT {
+		S_IFDIR => Ok(SandboxMetadata::directory()),
+		S_IFREG => Ok(SandboxMetadata::file(
+			u64::try_from(stat.st_size).unwrap_or_default(),
+		)),
+		S_IFLNK => Err(FSError::PathOutsideOfSandbox(path.to_string())),
+		_ => Err(IoError::from(ErrorKind::Unsupported).into()),
+	}
+}
+
+fn c_name(name: &str) -> Result<CString, FSError> {
+	// NUL is already refused when normalizing, so this should never fail.
+	CString::new(name).map_err(|_| FSError::InvalidSandboxPath(name.to_owned()))
+}
+
+fn check(result: c_int) -> Result<(), FSError> {
+	if result == 0 {
+		Ok(())
+	} else {
+		Err(IoError::last_os_error().into())
+	}
+}
+
+/// A directory being listed with `readdir`, closed when dropped.
+struct DirectoryStream(NonNull<libc::DIR>);
+
+impl DirectoryStream {
+	fn new(directory: OwnedFd) -> Result<Self, IoError> {
+		let fd = directory.into_raw_fd();
+		match NonNull::new(unsafe { libc::fdopendir(fd) }) {
+			Some(stream) => Ok(Self(stream)),
+			None => {
+				let cause = IoError::last_os_error();
+				// The file descriptor is still ours if `fdopendir` fails.
+				drop(unsafe { OwnedFd::from_raw_fd(fd) });
+				Err(cause)
+			}
+		}
+	}
+
+	/// The directory being listed, for looking up entries relative to it.
+	fn fd(&self) -> BorrowedFd<'_> {
+		unsafe { BorrowedFd::borrow_raw(libc::dirfd(self.0.as_ptr())) }
+	}
+
+	/// The name of the next entry, or `None` once every entry has been seen.
+	fn next_name(&mut self) -> Result<Option<CString>, IoError> {
+		// `readdir` returns NULL both at the end, and on errors, the only way to
+		// tell them apart is errno.
+		unsafe { *errno_location() = 0 };
+		let entry = unsafe { libc::readdir(self.0.as_ptr()) };
+		if entry.is_null() {
+			let cause = IoError::last_os_error();
+			return if cause.raw_os_error() == Some(0) {
+				Ok(None)
+			} else {
+				Err(cause)
+			};
+		}
+
+		Ok(Some(
+			unsafe { CStr::from_ptr((*entry).d_name.as_ptr()) }.to_owned(),
+		))
+	}
+}
+
+impl Drop for DirectoryStream {
+	fn drop(&mut self) {
+		unsafe { libc::closedir(self.0.as_ptr()) };
+	}
+}
@@ -0,0 +1,52 @@
+//! Platforms we can't sandbox paths on yet.
+//!
+//! Creating a sandbox always fails, so none of the other operations can ever
+//! be reached.
+
+use crate::{
+	errors::FSError,
+	fsemul::sandbox::{SandboxErrorKind, SandboxMetadata, SandboxOpenOptions, SandboxPath},
+};
+use std::{convert::Infallible, fs::File, io::Error as IoError, path::Path};
+
+/// A sandbox can never be created, so there is never a handle to a root.
+pub type RootHandle = Infallible;
+
+pub fn open_root(_root: &Path) -> Result<RootHandle, FSError> {
+	Err(FSError::SandboxUnsupported)
+}
+
+pub fn error_kind(_cause: &IoError) -> Option<SandboxErrorKind> {
+	None
+}
+
+pub fn metadata(root: &RootHandle, _path: &SandboxPath) -> Result<SandboxMetadata, FSError> {
+	match *root {}
+}
+
+pub fn open(
+	root: &RootHandle,
+	_path: &SandboxPath,
+	_options: SandboxOpenOptions,
+) -> Result<File, FSError> {
+	match *root {}
+}
+
+pub fn read_dir(
+	root: &RootHandle,
+	_path: &SandboxPath,
+) -> Result<Vec<(String, SandboxMetadata)>, FSError> {
+	match *root {}
+}
+
+pub fn create_dir(root: &RootHandle, _path: &SandboxPath) -> Result<(), FSError> {
+	match *root {}
+}
+
+pub fn remove(root: &RootHandle, _path: &SandboxPath) -> Result<(), FSError> {
+	match *root {}
+}
+
+pub fn rename(root: &RootHandle, _from: &SandboxPath, _to: &SandboxPath) -> Result<(), FSError> {
+	match *root {}
+}
@@ -0,0 +1,946 @@
+//! A PCFS server, serving a single title directory to a devkit.
+
+use crate::{
+	errors::{CatBridgeError, FSError, NetworkError},
+	fsemul::{
+		proto::{
+			codec::PcfsServerCodec, PcfsDirEntry, PcfsEntryInfo, PcfsEntryKind, PcfsOpenMode,
+			PcfsOpened, PcfsRequest, PcfsResponseFrame, PcfsStatus, MAX_PCFS_IO_SIZE,
+		},
+		sandbox::{
+			PathSandbox, SandboxErrorKind, SandboxMetadata, SandboxOpenOptions, SandboxPath,
+		},
+	},
+};
+use bytes::{Bytes, BytesMut};
+use fnv::FnvHashMap;
+use futures::{SinkExt, StreamExt};
+use std::{
+	collections::VecDeque,
+	fmt::{Display, Formatter, Result as FmtResult},
+	io::{ErrorKind, SeekFrom},
+	net::{Ipv4Addr, SocketAddr, SocketAddrV4},
+	path::{Path, PathBuf},
+	sync::Arc,
+};
+use tokio::{
+	fs::File,
+	io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt},
+	net::{TcpListener, TcpStream},
+	task::JoinSet,
+};
+use tokio_util::codec::Framed;
+use tracing::{debug, warn};
+use valuable::Valuable;
+
+/// The port cafe connects to by default, this is what `atapi_port` is set to
+/// out of the box.
+pub const DEFAULT_PCFS_PORT: u16 = 7974;
+/// The directories a title has to have to be served.
+pub const TITLE_DIRECTORIES: [&str; 3] = ["code", "content", "meta"];
+/// The most files, and directories a single connection can have open at
+/// once.
+pub const MAX_PCFS_OPEN_HANDLES: usize = 256;
+
+/// If a devkit is allowed to change files on the host.
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq, Valuable)]
+pub enum PcfsMode {
+	/// Only reading files, and listing directories is allowed, this is what
+	/// booting a title normally needs.
+	#[default]
+	ReadOnly,
+	/// Files, and directories can also be created, written, renamed, and
+	/// removed (e.g. for titles that write save data to the emulated drive).
+	ReadWrite,
+}
+impl Display for PcfsMode {
+	fn fmt(&self, fmt: &mut Formatter<'_>) -> FmtResult {
+		match self {
+			Self::ReadOnly => write!(fmt, "read-only"),
+			Self::ReadWrite => write!(fmt, "read-write"),
+		}
+	}
+}
+
+/// A PCFS server running in the background of the current tokio runtime.
+///
+/// All of the background tasks are stopped when this gets dropped.
+#[derive(Debug)]
+pub struct PcfsServer {
+	address: SocketAddrV4,
+	mode: PcfsMode,
+	sandbox: Arc<PathSandbox>,
+	// Kept around so the tasks get aborted when we get dropped.
+	_tasks: JoinSet<()>,
+}
+
+impl PcfsServer {
+	/// Serve a title directory on loopback, with a port picked by the OS.
+	///
+	/// ## Errors
+	///
+	/// - If the directory is not laid out like a title (see
+	///   [`TITLE_DIRECTORIES`]).
+	/// - If we cannot bind to an address.
+	pub async fn spawn(root: impl Into<PathBuf>, mode: PcfsMode) -> Result<Self, CatBridgeError> {
+		Self::spawn_on(root, mode, Ipv4Addr::LOCALHOST, 0).await
+	}
+
+	/// Serve a title directory on a specific address, and port.
+	///
+	/// If the port is `0` it will be picked by the OS. A real devkit will
+	/// connect to [`DEFAULT_PCFS_PORT`] unless the MION has been configured
+	/// otherwise.
+	///
+	/// ## Errors
+	///
+	/// - If the directory is not laid out like a title (see
+	///   [`TITLE_DIRECTORIES`]).
+	/// - If we cannot bind to the address.
+	pub async fn spawn_on(
+		root: impl Into<PathBuf>,
+		mode: PcfsMode,
+		bind_address: Ipv4Addr,
+		port: u16,
+	) -> Result<Self, CatBridgeError> {
+		let sandbox = Arc::new(PathSandbox::new(root).await?);
+		for directory in TITLE_DIRECTORIES {
+			let is_dir = sandbox
+				.metadata(&sandbox.normalize(directory)?)
+				.await
+				.is_ok_and(|metadata| metadata.is_dir());
+			if !is_dir {
+				return Err(
+					FSError::NotATitleDirectory(sandbox.root().to_path_buf(), directory).into(),
+				);
+			}
+		}
+
+		let listener = TcpListener::bind(SocketAddrV4::new(bind_address, port))
+			.await
+			.map_err(|_| NetworkError::BindAddressError)?;
+		let address = match listener.local_addr().map_err(NetworkError::IOError)? {
+			SocketAddr::V4(v4) => v4,
+			SocketAddr::V6(_) => return Err(NetworkError::BindAddressError.into()),
+		};
+
+		let mut tasks = JoinSet::new();
+		tasks
+			.build_task()
+			.name(&format!("cat_dev::fsemul::pcfs::{address}"))
+			.spawn(serve_pcfs(listener, sandbox.clone(), mode))
+			.map_err(|_| CatBridgeError::SpawnFailure)?;
+
+		Ok(Self {
+			address,
+			mode,
+			sandbox,
+			_tasks: tasks,
+		})
+	}
+
+	/// The address the server is listening on.
+	#[must_use]
+	pub const fn address(&self) -> SocketAddrV4 {
+		self.address
+	}
+
+	/// If devkits are allowed to change files.
+	#[must_use]
+	pub const fn mode(&self) -> PcfsMode {
+		self.mode
+	}
+
+	/// The canonicalized directory being served.
+	#[must_use]
+	pub fn root(&self) -> &Path {
+		self.sandbox.root()
+	}
+}
+
+/// Serve PCFS forever, handling each connection in it's own task.
+async fn serve_pcfs(listener: TcpListener, sandbox: Arc<PathSandbox>, mode: PcfsMode) {
+	loop {
+		let (stream, from) = match listener.accept().await {
+			Ok(data) => data,
+			Err(cause) => {
+				warn!(?cause, "pcfs server failed to accept connection");
+				continue;
+			}
+		};
+
+		let connection = PcfsConnection::new(sandbox.clone(), mode);
+		tokio::task::spawn(async move {
+			if let Err(cause) = connection.serve(stream).await {
+				debug!(?cause, %from, "pcfs server closed connection");
+			}
+		});
+	}
+}
+
+/// Something a devkit has open.
+#[derive(Debug)]
+enum OpenHandle {
+	File(File),
+	/// Directories are listed entirely when opened, and handed out one entry
+	/// at a time.
+	Directory(VecDeque<PcfsDirEntry>),
+}
+
+/// The state of a single connection, handles are never shared between
+/// connections.
+#[derive(Debug)]
+struct PcfsConnection {
+	handles: FnvHashMap<u32, OpenHandle>,
+	mode: PcfsMode,
+	next_handle: u32,
+	sandbox: Arc<PathSandbox>,
+}
+
+impl PcfsConnection {
+	fn new(sandbox: Arc<PathSandbox>, mode: PcfsMode) -> Self {
+		Self {
+			handles: FnvHashMap::default(),
+			mode,
+			next_handle: 0,
+			sandbox,
+		}
+	}
+
+	/// Handle requests until the devkit hangs up.
+	async fn serve(mut self, stream: TcpStream) -> Result<(), CatBridgeError> {
+		let mut framed = Framed::new(stream, PcfsServerCodec);
+		// A devkit hanging up (`None`) is totally fine.
+		while let Some(frame) = framed.next().await {
+			let frame = frame?;
+			let id = frame.id();
+			let response = match self.handle(frame.into_request()).await {
+				Ok(body) => PcfsResponseFrame::ok(id, body),
+				Err(status) => PcfsResponseFrame::error(id, status),
+			};
+			framed.send(response).await?;
+		}
+
+		Ok(())
+	}
+
+	async fn handle(&mut self, request: PcfsRequest) -> Result<Bytes, PcfsStatus> {
+		if self.mode == PcfsMode::ReadOnly {
+			let is_write = match &request {
+				PcfsRequest::Open { mode, .. } => mode.is_write(),
+				other => other.command().is_ok_and(|command| command.is_write()),
+			};
+			if is_write {
+				return Err(PcfsStatus::ReadOnly);
+			}
+		}
+
+		match request {
+			PcfsRequest::Ping => Ok(Bytes::new()),
+			PcfsRequest::GetInfo { path } => {
+				let metadata = self.sandbox.metadata(&self.normalize(&path)?).await?;
+				Ok(Bytes::from(&entry_info(metadata)))
+			}
+			PcfsRequest::Open { path, mode } => self.open(&path, mode).await,
+			PcfsRequest::Read {
+				handle,
+				offset,
+				length,
+			} => self.read(handle, offset, length).await,
+			PcfsRequest::Write {
+				handle,
+				offset,
+				data,
+			} => {
+				let OpenHandle::File(file) = self.get_handle(handle)? else {
+					return Err(PcfsStatus::InvalidHandle);
+				};
+				file.seek(SeekFrom::Start(offset)).await?;
+				file.write_all(&data).await?;
+				// Data can never be larger than a single frame.
+				Ok(Bytes::copy_from_slice(
+					&u32::try_from(data.len()).unwrap_or(u32::MAX).to_be_bytes(),
+				))
+			}
+			PcfsRequest::Close { handle } => match self.handles.remove(&handle) {
+				Some(OpenHandle::File(mut file)) => {
+					file.flush().await?;
+					Ok(Bytes::new())
+				}
+				Some(directory) => {
+					self.handles.insert(handle, directory);
+					Err(PcfsStatus::InvalidHandle)
+				}
+				None => Err(PcfsStatus::InvalidHandle),
+			},
+			PcfsRequest::OpenDir { path } => self.open_dir(&path).await,
+			PcfsRequest::ReadDir { handle } => {
+				let OpenHandle::Directory(entries) = self.get_handle(handle)? else {
+					return Err(PcfsStatus::InvalidHandle);
+				};
+				entries
+					.pop_front()
+					.map(|entry| Bytes::from(&entry))
+					.ok_or(PcfsStatus::EndOfDirectory)
+			}
+			PcfsRequest::CloseDir { handle } => match self.handles.remove(&handle) {
+				Some(OpenHandle::Directory(_)) => Ok(Bytes::new()),
+				Some(file) => {
+					self.handles.insert(handle, file);
+					Err(PcfsStatus::InvalidHandle)
+				}
+				None => Err(PcfsStatus::InvalidHandle),
+			},
+			PcfsRequest::CreateDir { path } => {
+				self.sandbox.create_dir(&self.normalize(&path)?).await?;
+				Ok(Bytes::new())
+			}
+			PcfsRequest::Remove { path } => self.remove(&path).await,
+			PcfsRequest::Rename { from, to } => {
+				let from = self.normalize(&from)?;
+				let to = self.normalize(&to)?;
+				if from.is_root() || to.is_root() {
+					return Err(PcfsStatus::InvalidRequest);
+				}
+				self.sandbox.rename(&from, &to).await?;
+				Ok(Bytes::new())
+			}
+			PcfsRequest::Unknown(_) => Err(PcfsStatus::UnknownCommand),
+		}
+	}
+
+	async fn open(&mut self, path: &str, mode: PcfsOpenMode) -> Result<Bytes, PcfsStatus> {
+		let options = match mode {
+			PcfsOpenMode::Read => SandboxOpenOptions::new().read(true),
+			PcfsOpenMode::Write => SandboxOpenOptions::new()
+				.write(true)
+				.create(true)
+				.truncate(true),
+			PcfsOpenMode::ReadWrite => SandboxOpenOptions::new()
+				.read(true)
+				.write(true)
+				.create(true),
+		};
+		let file = self.sandbox.open(&self.normalize(path)?, options).await?;
+		let size = file.metadata().await?.len();
+		let handle = self.insert_handle(OpenHandle::File(file))?;
+		Ok(Bytes::from(&PcfsOpened::new(handle, size)))
+	}
+
+	async fn read(&mut self, handle: u32, offset: u64, length: u32) -> Result<Bytes, PcfsStatus> {
+		let length = usize::try_from(length).unwrap_or(usize::MAX);
+		if length > MAX_PCFS_IO_SIZE {
+			return Err(PcfsStatus::InvalidRequest);
+		}
+		let OpenHandle::File(file) = self.get_handle(handle)? else {
+			return Err(PcfsStatus::InvalidHandle);
+		};
+
+		file.seek(SeekFrom::Start(offset)).await?;
+		let mut data = BytesMut::zeroed(length);
+		let mut read = 0;
+		// Reads may come back short, keep going until we hit the end of the
+		// file so cafe gets exactly what it asked for when it can.
+		while read < length {
+			let just_read = file.read(&mut data[read..]).await?;
+			if just_read == 0 {
+				break;
+			}
+			read += just_read;
+		}
+		data.truncate(read);
+		Ok(data.freeze())
+	}
+
+	async fn open_dir(&mut self, path: &str) -> Result<Bytes, PcfsStatus> {
+		let mut entries = self
+			.sandbox
+			.read_dir(&self.normalize(path)?)
+			.await?
+			.into_iter()
+			.map(|(name, metadata)| PcfsDirEntry::new(name, entry_info(metadata)))
+			.collect::<Vec<_>>();
+		entries.sort_by(|left, right| left.name().cmp(right.name()));
+
+		let handle = self.insert_handle(OpenHandle::Directory(entries.into()))?;
+		Ok(Bytes::from(&PcfsOpened::new(handle, 0)))
+	}
+
+	async fn remove(&mut self, path: &str) -> Result<Bytes, PcfsStatus> {
+		let path = self.normalize(path)?;
+		if path.is_root() {
+			return Err(PcfsStatus::InvalidRequest);
+		}
+
+		self.sandbox.remove(&path).await?;
+		Ok(Bytes::new())
+	}
+
+	fn normalize(&self, path: &str) -> Result<SandboxPath, PcfsStatus> {
+		Ok(self.sandbox.normalize(path)?)
+	}
+
+	fn get_handle(&mut self, handle: u32) -> Result<&mut OpenHandle, PcfsStatus> {
+		self.handles
+			.get_mut(&handle)
+			.ok_or(PcfsStatus::InvalidHandle)
+	}
+
+	fn insert_handle(&mut self, open: OpenHandle) -> Result<u32, PcfsStatus> {
+		if self.handles.len() >= MAX_PCFS_OPEN_HANDLES {
+			return Err(PcfsStatus::TooManyOpenHandles);
+		}
+		// Handle 0 is never given out, so it can't be confused with an
+		// uninitialized handle on the devkit.
+		loop {
+			self.next_handle = self.next_handle.wrapping_add(1).max(1);
+			if !self.handles.contains_key(&self.next_handle) {
+				break;
+			}
+		}
+		self.handles.insert(self.next_handle, open);
+		Ok(self.next_handle)
+	}
+}
+
+impl From<std::io::Error> for PcfsStatus {
+	fn from(value: std::io::Error) -> Self {
+		match SandboxErrorKind::of(&value) {
+			Some(SandboxErrorKind::NotADirectory) => return Self::NotADirectory,
+			Some(SandboxErrorKind::IsADirectory) => return Self::NotAFile,
+			Some(SandboxErrorKind::DirectoryNotEmpty) => return Self::DirectoryNotEmpty,
+			None => {}
+		}
+		match value.kind() {
+			ErrorKind::NotFound => Self::NotFound,
+			ErrorKind::AlreadyExists => Self::AlreadyExists,
+			_ => Self::IoError,
+		}
+	}
+}
+
+impl From<FSError> for PcfsStatus {
+	fn from(value: FSError) -> Self {
+		match value {
+			FSError::IOError(cause) => Self::from(cause),
+			_ => Self::OutsideOfSandbox,
+		}
+	}
+}
+
+fn entry_info(metadata: SandboxMetadata) -> PcfsEntryInfo {
+	if metadata.is_dir() {
+		PcfsEntryInfo::new(PcfsEntryKind::Directory, 0)
+	} else {
+		PcfsEntryInfo::new(PcfsEntryKind::File, metadata.size())
+	}
+}
+
+// Serving needs a sandbox, which is only available on some platforms.
+#[cfg(all(
+	test,
+	any(
+		target_os = "freebsd",
+		target_os = "linux",
+		target_os = "macos",
+		target_os = "netbsd",
+		target_os = "openbsd",
+	),
+))]
+mod unit_tests {
+	use super::*;
+	use crate::fsemul::proto::{codec::PcfsClientCodec, PcfsRequestFrame};
+	use tempfile::TempDir;
+
+	/// A minimal client that speaks the same framing cafe does.
+	struct TestClient {
+		framed: Framed<TcpStream, PcfsClientCodec>,
+		next_id: u32,
+	}
+	impl TestClient {
+		async fn connect(server: &PcfsServer) -> Self {
+			Self {
+				framed: Framed::new(
+					TcpStream::connect(server.address())
+						.await
+						.expect("Failed to connect to PCFS server!"),
+					PcfsClientCodec,
+				),
+				next_id: 0,
+			}
+		}
+
+		async fn request(&mut self, request: PcfsRequest) -> Result<Bytes, PcfsStatus> {
+			self.next_id += 1;
+			self.framed
+				.send(PcfsRequestFrame::new(self.next_id, request))
+				.await
+				.expect("Failed to send request!");
+			let response = self
+				.framed
+				.next()
+				.await
+				.expect("Server hung up!")
+				.expect("Failed to decode response!");
+			assert_eq!(response.id(), self.next_id, "Response had the wrong id!");
+			if response.status().is_ok() {
+				Ok(response.body().clone())
+			} else {
+				Err(response.status())
+			}
+		}
+
+		async fn open(&mut self, path: &str, mode: PcfsOpenMode) -> Result<PcfsOpened, PcfsStatus> {
+			self.request(PcfsRequest::Open {
+				path: path.to_owned(),
+				mode,
+			})
+			.await
+			.map(|body| PcfsOpened::try_from(body).expect("Failed to parse opened!"))
+		}
+	}
+
+	async fn title_directory() -> TempDir {
+		let directory = tempfile::tempdir().expect("Failed to create temporary directory!");
+		for sub in TITLE_DIRECTORIES {
+			tokio::fs::create_dir(directory.path().join(sub))
+				.await
+				.expect("Failed to create title directory!");
+		}
+		tokio::fs::write(directory.path().join("code").join("app.xml"), b"<app/>")
+			.await
+			.expect("Failed to write app.xml!");
+		tokio::fs::write(
+			directory.path().join("content").join("data.bin"),
+			(0..=255_u8).collect::<Vec<_>>(),
+		)
+		.await
+		.expect("Failed to write data.bin!");
+		directory
+	}
+
+	#[tokio::test]
+	pub async fn refuses_directories_that_arent_titles() {
+		let directory = tempfile::tempdir().expect("Failed to create temporary directory!");
+		tokio::fs::create_dir(directory.path().join("code"))
+			.await
+			.expect("Failed to create code directory!");
+		assert!(matches!(
+			PcfsServer::spawn(directory.path(), PcfsMode::ReadOnly).await,
+			Err(CatBridgeError::FilesystemError(
+				FSError::NotATitleDirectory(_, "content")
+			)),
+		));
+	}
+
+	#[tokio::test]
+	pub async fn can_read_files_and_list_directories() {
+		let directory = title_directory().await;
+		let server = PcfsServer::spawn(directory.path(), PcfsMode::ReadOnly)
+			.await
+			.expect("Failed to spawn PCFS server!");
+		let mut client = TestClient::connect(&server).await;
+
+		assert_eq!(client.request(PcfsRequest::Ping).await, Ok(Bytes::new()));
+		assert_eq!(
+			PcfsEntryInfo::try_from(
+				client
+					.request(PcfsRequest::GetInfo {
+						path: "/content/data.bin".to_owned(),
+					})
+					.await
+					.expect("Failed to get info!")
+			)
+			.expect("Failed to parse info!"),
+			PcfsEntryInfo::new(PcfsEntryKind::File, 256),
+		);
+
+		let opened = client
+			.open("/content/data.bin", PcfsOpenMode::Read)
+			.await
+			.expect("Failed to open file!");
+		assert_eq!(opened.size(), 256);
+		assert_eq!(
+			client
+				.request(PcfsRequest::Read {
+					handle: opened.handle(),
+					offset: 250,
+					length: 16,
+				})
+				.await,
+			Ok(Bytes::from_static(&[250, 251, 252, 253, 254, 255])),
+			"Reads past the end of the file should come back short!",
+		);
+		assert_eq!(
+			client
+				.request(PcfsRequest::Close {
+					handle: opened.handle()
+				})
+				.await,
+			Ok(Bytes::new()),
+		);
+		assert_eq!(
+			client
+				.request(PcfsRequest::Close {
+					handle: opened.handle()
+				})
+				.await,
+			Err(PcfsStatus::InvalidHandle),
+		);
+
+		let opened = PcfsOpened::try_from(
+			client
+				.request(PcfsRequest::OpenDir {
+					path: "/".to_owned(),
+				})
+				.await
+				.expect("Failed to open directory!"),
+		)
+		.expect("Failed to parse opened!");
+		let mut names = Vec::new();
+		loop {
+			match client
+				.request(PcfsRequest::ReadDir {
+					handle: opened.handle(),
+				})
+				.await
+			{
+				Ok(body) => names.push(
+					PcfsDirEntry::try_from(body)
+						.expect("Failed to parse entry!")
+						.name()
+						.to_owned(),
+				),
+				Err(status) => {
+					assert_eq!(status, PcfsStatus::EndOfDirectory);
+					break;
+				}
+			}
+		}
+		assert_eq!(names, TITLE_DIRECTORIES);
+		assert_eq!(
+			client
+				.request(PcfsRequest::Read {
+					handle: opened.handle(),
+					offset: 0,
+					length: 1,
+				})
+				.await,
+			Err(PcfsStatus::InvalidHandle),
+			"Directories can't be read like files!",
+		);
+
+		assert_eq!(
+			client.open("/content", PcfsOpenMode::Read).await,
+			Err(PcfsStatus::NotAFile),
+		);
+		assert_eq!(
+			client
+				.open("/content/missing.bin", PcfsOpenMode::Read)
+				.await,
+			Err(PcfsStatus::NotFound),
+		);
+		assert_eq!(
+			client.request(PcfsRequest::Unknown(0x99)).await,
+			Err(PcfsStatus::UnknownCommand),
+		);
+		assert_eq!(
+			client.request(PcfsRequest::Ping).await,
+			Ok(Bytes::new()),
+			"Connection should keep working after an unknown command!",
+		);
+	}
+
+	#[tokio::test]
+	pub async fn read_only_refuses_changes() {
+		let directory = title_directory().await;
+		let server = PcfsServer::spawn(directory.path(), PcfsMode::ReadOnly)
+			.await
+			.expect("Failed to spawn PCFS server!");
+		let mut client = TestClient::connect(&server).await;
+
+		assert_eq!(
+			client.open("/content/data.bin", PcfsOpenMode::Write).await,
+			Err(PcfsStatus::ReadOnly),
+		);
+		assert_eq!(
+			client
+				.open("/content/new.bin", PcfsOpenMode::ReadWrite)
+				.await,
+			Err(PcfsStatus::ReadOnly),
+		);
+		let opened = client
+			.open("/content/data.bin", PcfsOpenMode::Read)
+			.await
+			.expect("Failed to open file!");
+		for request in [
+			PcfsRequest::Write {
+				handle: opened.handle(),
+				offset: 0,
+				data: Bytes::from_static(b"overwritten"),
+			},
+			PcfsRequest::CreateDir {
+				path: "/content/saves".to_owned(),
+			},
+			PcfsRequest::Remove {
+				path: "/content/data.bin".to_owned(),
+			},
+			PcfsRequest::Rename {
+				from: "/content/data.bin".to_owned(),
+				to: "/content/moved.bin".to_owned(),
+			},
+		] {
+			assert_eq!(client.request(request).await, Err(PcfsStatus::ReadOnly));
+		}
+
+		assert_eq!(
+			tokio::fs::read(directory.path().join("content").join("data.bin"))
+				.await
+				.expect("Failed to read data.bin!"),
+			(0..=255_u8).collect::<Vec<_>>(),
+			"Read only server changed a file!",
+		);
+		assert!(!directory.path().join("content").join("new.bin").exists());
+		assert!(!directory.path().join("content").join("saves").exists());
+	}
+
+	#[tokio::test]
+	pub async fn read_write_can_change_files() {
+		let directory = title_directory().await;
+		let server = PcfsServer::spawn(directory.path(), PcfsMode::ReadWrite)
+			.await
+			.expect("Failed to spawn PCFS server!");
+		assert_eq!(server.mode(), PcfsMode::ReadWrite);
+		let mut client = TestClient::connect(&server).await;
+
+		assert_eq!(
+			client
+				.request(PcfsRequest::CreateDir {
+					path: "/content/saves".to_owned(),
+				})
+				.await,
+			Ok(Bytes::new()),
+		);
+		let opened = client
+			.open("/content/saves/slot0.bin", PcfsOpenMode::ReadWrite)
+			.await
+			.expect("Failed to create file!");
+		assert_eq!(opened.size(), 0);
+		assert_eq!(
+			client
+				.request(PcfsRequest::Write {
+					handle: opened.handle(),
+					offset: 4,
+					data: Bytes::from_static(b"save"),
+				})
+				.await,
+			Ok(Bytes::from_static(&[0, 0, 0, 4])),
+		);
+		assert_eq!(
+			client
+				.request(PcfsRequest::Read {
+					handle: opened.handle(),
+					offset: 0,
+					length: 8,
+				})
+				.await,
+			Ok(Bytes::from_static(b"\0\0\0\0save")),
+		);
+		assert_eq!(
+			client
+				.request(PcfsRequest::Close {
+					handle: opened.handle()
+				})
+				.await,
+			Ok(Bytes::new()),
+		);
+
+		assert_eq!(
+			client
+				.request(PcfsRequest::Rename {
+					from: "/content/saves/slot0.bin".to_owned(),
+					to: "/content/saves/slot1.bin".to_owned(),
+				})
+				.await,
+			Ok(Bytes::new()),
+		);
+		assert_eq!(
+			client
+				.request(PcfsRequest::Rename {
+					from: "/content/saves/slot1.bin".to_owned(),
+					to: "/content/data.bin".to_owned(),
+				})
+				.await,
+			Err(PcfsStatus::AlreadyExists),
+		);
+		assert_eq!(
+			tokio::fs::read(
+				directory
+					.path()
+					.join("content")
+					.join("saves")
+					.join("slot1.bin")
+			)
+			.await
+			.expect("Failed to read written file!"),
+			b"\0\0\0\0save",
+		);
+
+		assert_eq!(
+			client
+				.request(PcfsRequest::Remove {
+					path: "/content/saves".to_owned(),
+				})
+				.await,
+			Err(PcfsStatus::DirectoryNotEmpty),
+		);
+		for path in ["/content/saves/slot1.bin", "/content/saves"] {
+			assert_eq!(
+				client
+					.request(PcfsRequest::Remove {
+						path: path.to_owned(),
+					})
+					.await,
+				Ok(Bytes::new()),
+			);
+		}
+		assert!(!directory.path().join("content").join("saves").exists());
+		assert_eq!(
+			client
+				.request(PcfsRequest::Remove {
+					path: "/".to_owned(),
+				})
+				.await,
+			Err(PcfsStatus::InvalidRequest),
+		);
+	}
+
+	#[tokio::test]
+	pub async fn refuses_paths_outside_of_the_title() {
+		let directory = title_directory().await;
+		let server = PcfsServer::spawn(
+			directory.path().join("..").join(
+				directory
+					.path()
+					.file_name()
+					.expect("Temporary directory had no name!"),
+			),
+			PcfsMode::ReadWrite,
+		)
+		.await
+		.expect("Failed to spawn PCFS server!");
+		let mut client = TestClient::connect(&server).await;
+
+		assert_eq!(
+			client.open("/../escaped.bin", PcfsOpenMode::Write).await,
+			Err(PcfsStatus::OutsideOfSandbox),
+		);
+		assert_eq!(
+			client
+				.request(PcfsRequest::GetInfo {
+					path: "/content/../../".to_owned(),
+				})
+				.await
+				.map(|_| ()),
+			Err(PcfsStatus::OutsideOfSandbox),
+		);
+		assert_eq!(
+			client
+				.request(PcfsRequest::Rename {
+					from: "/content/data.bin".to_owned(),
+					to: "/content\\..\\..\\data.bin".to_owned(),
+				})
+				.await,
+			Err(PcfsStatus::OutsideOfSandbox),
+		);
+		assert!(!directory
+			.path()
+			.parent()
+			.expect("Temporary directory had no parent!")
+			.join("escaped.bin")
+			.exists());
+	}
+
+	#[tokio::test]
+	pub async fn refuses_symlinks() {
+		let directory = title_directory().await;
+		let outside = tempfile::tempdir().expect("Failed to create temporary directory!");
+		tokio::fs::write(outside.path().join("secret.bin"), b"secret")
+			.await
+			.expect("Failed to write secret.bin!");
+		tokio::fs::symlink(
+			outside.path(),
+			directory.path().join("content").join("escape"),
+		)
+		.await
+		.expect("Failed to create symlink!");
+		tokio::fs::symlink(
+			outside.path().join("secret.bin"),
+			directory.path().join("content").join("secret.bin"),
+		)
+		.await
+		.expect("Failed to create symlink!");
+		let server = PcfsServer::spawn(directory.path(), PcfsMode::ReadWrite)
+			.await
+			.expect("Failed to spawn PCFS server!");
+		let mut client = TestClient::connect(&server).await;
+
+		for path in ["/content/secret.bin", "/content/escape/secret.bin"] {
+			assert_eq!(
+				client.open(path, PcfsOpenMode::Read).await,
+				Err(PcfsStatus::OutsideOfSandbox),
+				"Path: {path} was not refused!",
+			);
+		}
+		assert_eq!(
+			client
+				.open("/content/escape/new.bin", PcfsOpenMode::Write)
+				.await,
+			Err(PcfsStatus::OutsideOfSandbox),
+		);
+		assert!(!outside.path().join("new.bin").exists());
+
+		// Removing a symlink removes the link, never what it points to.
+		assert_eq!(
+			client
+				.request(PcfsRequest::Remove {
+					path: "/content/secret.bin".to_owned(),
+				})
+				.await,
+			Ok(Bytes::new()),
+		);
+		assert_eq!(
+			tokio::fs::read(outside.path().join("secret.bin"))
+				.await
+				.expect("Symlink target was removed!"),
+			b"secret",
+		);
+	}
+
+	#[tokio::test]
+	pub async fn limits_open_handles() {
+		let directory = title_directory().await;
+		let server = PcfsServer::spawn(directory.path(), PcfsMode::ReadOnly)
+			.await
+			.expect("Failed to spawn PCFS server!");
+		let mut client = TestClient::connect(&server).await;
+
+		for _ in 0..MAX_PCFS_OPEN_HANDLES {
+			client
+				.open("/code/app.xml", PcfsOpenMode::Read)
+				.await
+				.expect("Failed to open file!");
+		}
+		assert_eq!(
+			client.open("/code/app.xml", PcfsOpenMode::Read).await,
+			Err(PcfsStatus::TooManyOpenHandles),
+		);
+
+		// Handles belong to a single connection.
+		let mut other = TestClient::connect(&server).await;
+		other
+			.open("/code/app.xml", PcfsOpenMode::Read)
+			.await
+			.expect("Second connection should have its own handles!");
+	}
+}
@@ -5,11 +5,17 @@
 	clippy::module_name_repetitions,
 )]
 
+pub mod boot;
+pub mod diagnostics;
 pub mod errors;
+pub mod fsemul;
 pub mod mion;
 pub mod serial;
 
-use crate::errors::{APIError, FSError};
+use crate::{
+	errors::{APIError, FSError},
+	mion::discovery::InterfaceMatcher,
+};
 use configparser::ini::Ini;
 use fnv::FnvHashMap;
 use std::{
@@ -30,6 +36,12 @@ const BRIDGE_NAME_KEY_PREFIX: &str = "BRIDGE_NAME_";
 const HOST_BRIDGES_SECTION: &str = "HOST_BRIDGES";
 /// The key that contains that stores which bridge is marked as the default.
 const DEFAULT_BRIDGE_KEY: &str = "BRIDGE_DEFAULT_NAME";
+/// The section name in the ini file we store our own settings in, kept
+/// separate from [`HOST_BRIDGES_SECTION`] so the official tools never see
+/// keys they don't know about.
+const SPRIG_SECTION: &str = "SPRIG";
+/// The key that stores which network interface discovery should prefer.
+const PREFERRED_INTERFACE_KEY: &str = "PREFERRED_INTERFACE";
 
 /// As far as I can derive from the sources available that we can cleanly read
 /// (e.g. shell scripts) there are two types of CAT-DEV units. This enum
@@ -348,6 +360,53 @@ impl BridgeHostState {
 		Ok(())
 	}
 
+	/// Get the network interface that discovery should prefer, if one has been
+	/// configured.
+	///
+	/// This returns `None` if no preferred interface has been set, or if the
+	/// value that has been set is not a valid [`InterfaceMatcher`].
+	#[must_use]
+	pub fn get_preferred_interface(&self) -> Option<InterfaceMatcher> {
+		self.configuration
+			.get(SPRIG_SECTION, PREFERRED_INTERFACE_KEY)
+			.and_then(|value| InterfaceMatcher::try_from(value.as_str()).ok())
+	}
+
+	/// Set the network interface that discovery should prefer.
+	///
+	/// This can be an interface name, an IP on the interface, or a subnet, see
+	/// [`InterfaceMatcher`] for more information.
+	///
+	/// *note: this will be visible in memory immediately, but in order to
+	/// persist it, or have it seen in another process you need to call
+	/// [`BridgeHostState::write_to_disk`].*
+	pub fn set_preferred_interface(&mut self, interface: &InterfaceMatcher) {
+		self.configuration.set(
+			SPRIG_SECTION,
+			PREFERRED_INTERFACE_KEY,
+			Some(format!("{interface}")),
+		);
+	}
+
+	/// Remove the preferred network interface from the configuration file.
+	///
+	/// *note: this will be visible in memory immediately, but in order to
+	/// persist it, or have it seen in another process you need to call
+	/// [`BridgeHostState::write_to_disk`].*
+	pub fn remove_preferred_interface(&mut self) {
+		self.configuration
+			.remove_key(SPRIG_SECTION, PREFERRED_INTERFACE_KEY);
+		// Don't leave an empty section behind for the official tools to trip on.
+		if self
+			.configuration
+			.get_map_ref()
+			.get(SPRIG_SECTION)
+			.is_some_and(std::collections::HashMap::is_empty)
+		{
+			self.configuration.remove_section(SPRIG_SECTION);
+		}
+	}
+
 	/// Write the current configuration to disk as a Windows INI file.
 	///
 	/// We always write the file with carriage returns `\r\n` (windows line
@@ -720,4 +779,42 @@ mod unit_tests {
 			panic!("Unexpected host bridges ini file:\n{read_data}");
 		}
 	}
+
+	#[tokio::test]
+	pub async fn can_set_preferred_interface() {
+		use tempfile::tempdir;
+
+		let temporary_directory =
+			tempdir().expect("Failed to create temporary directory for tests!");
+		let mut path = PathBuf::from(temporary_directory.path());
+		path.push("bridge_env_preferred_interface.ini");
+
+		let mut host_env = BridgeHostState::load_explicit_path(path.clone())
+			.await
+			.expect("Failed to load non-existant file to write too!");
+		assert_eq!(host_env.get_preferred_interface(), None);
+
+		host_env
+			.set_preferred_interface(&InterfaceMatcher::Subnet(Ipv4Addr::new(192, 168, 7, 0), 24));
+		assert!(host_env
+			.upsert_bridge("00-25-5C-BA-5A-00", Ipv4Addr::new(192, 168, 7, 40))
+			.is_ok());
+		assert!(host_env.write_to_disk().await.is_ok());
+
+		let mut reloaded = BridgeHostState::load_explicit_path(path.clone())
+			.await
+			.expect("Failed to reload written host state!");
+		assert_eq!(
+			reloaded.get_preferred_interface(),
+			Some(InterfaceMatcher::Subnet(Ipv4Addr::new(192, 168, 7, 0), 24)),
+		);
+		assert_eq!(
+			reloaded.list_bridges().len(),
+			1,
+			"Preferred interface should not show up as a bridge!",
+		);
+
+		reloaded.remove_preferred_interface();
+		assert_eq!(reloaded.get_preferred_interface(), None);
+	}
 }
@@ -2,23 +2,28 @@
 //! turning the device on & off.
 
 use crate::{
-	errors::{CatBridgeError, NetworkError, NetworkParseError},
+	errors::{APIError, CatBridgeError, NetworkError, NetworkParseError},
 	mion::{
-		cgis::AUTHZ_HEADER,
-		proto::cgis::{ControlOperation, SetParameter},
+		cgis::MionHttpClient,
+		proto::cgis::{
+			ControlOperation, ControlResponse, MionInfo, PowerOffRequest, PowerOnRequest,
+			PowerState, ResetRequest, SetParameter,
+		},
 	},
 };
 use fnv::FnvHashMap;
 use hyper::{
-	body::to_bytes as read_http_body_bytes,
 	client::{connect::Connect, Client},
-	Body, Request, Response, Version,
+	Body, Method, Response,
 };
 use local_ip_address::local_ip;
 use serde::Serialize;
 use std::net::Ipv4Addr;
 use tracing::{field::valuable, warn};
 
+/// The path `control.cgi` is served on.
+const CONTROL_CGI_PATH: &str = "/mion/control.cgi";
+
 /// Perform a `get_info` request given a host, and a name.
 ///
 /// ## Errors
@@ -32,7 +37,7 @@ pub async fn get_info(
 	mion_ip: Ipv4Addr,
 	name: &str,
 ) -> Result<FnvHashMap<String, String>, CatBridgeError> {
-	get_info_with_raw_client(&Client::default(), mion_ip, name).await
+	MionHttpClient::new().get_info(mion_ip, name).await
 }
 
 /// Perform a get info request, but with an already existing HTTP client.
@@ -52,41 +57,41 @@ pub async fn get_info_with_raw_client<ClientConnectorTy>(
 where
 	ClientConnectorTy: Clone + Connect + Send + Sync + 'static,
 {
-	let response = do_raw_control_request(
-		client,
-		mion_ip,
-		&[
-			("operation", Into::<&str>::into(ControlOperation::GetInfo)),
-			(
-				"host",
-				&format!("{}", local_ip().map_err(NetworkError::LocalIpError)?),
-			),
-			("shutdown", "1"),
-			("name", name),
-		],
-	)
-	.await?;
-	let status = response.status().as_u16();
-	let body_result = read_http_body_bytes(response.into_body())
+	MionHttpClient::from_raw_client(client.clone())
+		.get_info(mion_ip, name)
 		.await
-		.map_err(NetworkError::HyperError);
-	if status != 200 {
-		if let Ok(body) = body_result {
-			return Err(CatBridgeError::NetworkError(NetworkError::ParseError(
-				NetworkParseError::UnexpectedStatusCode(status, body),
-			)));
-		}
+}
 
-		return Err(CatBridgeError::NetworkError(NetworkError::ParseError(
-			NetworkParseError::UnexpectedStatusCodeNoBody(status),
-		)));
-	}
-	let read_body_bytes = body_result?;
-	let body_as_string = String::from_utf8(read_body_bytes.into())
-		.map_err(NetworkParseError::InvalidDataNeedsUTF8)
-		.map_err(NetworkError::ParseError)?;
+/// Perform a `get_info` request given a host, and a name, parsing the fields
+/// we know about.
+///
+/// Fields with values we don't understand don't cause an error, see
+/// [`MionInfo`].
+///
+/// ## Errors
+///
+/// See [`get_info`].
+pub async fn get_mion_info(mion_ip: Ipv4Addr, name: &str) -> Result<MionInfo, CatBridgeError> {
+	MionHttpClient::new().get_mion_info(mion_ip, name).await
+}
 
-	extract_body_tags(&body_as_string, ControlOperation::GetInfo.into())
+/// Perform a `get_info` request parsing the fields we know about, but with an
+/// already existing HTTP client.
+///
+/// ## Errors
+///
+/// See [`get_info`].
+pub async fn get_mion_info_with_raw_client<ClientConnectorTy>(
+	client: &Client<ClientConnectorTy>,
+	mion_ip: Ipv4Addr,
+	name: &str,
+) -> Result<MionInfo, CatBridgeError>
+where
+	ClientConnectorTy: Clone + Connect + Send + Sync + 'static,
+{
+	MionHttpClient::from_raw_client(client.clone())
+		.get_mion_info(mion_ip, name)
+		.await
 }
 
 /// Perform a `set_param` request given a host, and the parameter to set.
@@ -98,11 +103,36 @@ where
 /// - If the server does not respond with a 200.
 /// - If we cannot read the body from HTTP.
 /// - If we cannot parse the HTML response.
+/// - If the value to set is not valid, see [`SetParameter::validate`].
+/// - If the value to set is experimental, see
+///   [`SetParameter::is_experimental`].
 pub async fn set_param(
 	mion_ip: Ipv4Addr,
 	parameter_to_set: SetParameter,
 ) -> Result<bool, CatBridgeError> {
-	set_param_with_raw_client(&Client::default(), mion_ip, parameter_to_set).await
+	MionHttpClient::new()
+		.set_param(mion_ip, parameter_to_set)
+		.await
+}
+
+/// Perform a `set_param` request that is allowed to send experimental
+/// settings.
+///
+/// Experimental settings have key names we've guessed, so a real MION may
+/// reject them (this returns `false`), or silently ignore them. See
+/// [`SetParameter::is_experimental`].
+///
+/// ## Errors
+///
+/// - If the value to set is not valid, see [`SetParameter::validate`].
+/// - See [`set_param`].
+pub async fn set_experimental_param(
+	mion_ip: Ipv4Addr,
+	parameter_to_set: SetParameter,
+) -> Result<bool, CatBridgeError> {
+	MionHttpClient::new()
+		.set_experimental_param(mion_ip, parameter_to_set)
+		.await
 }
 
 /// Set a parameter on the MION, but with an already existing HTTP Client.
@@ -114,6 +144,9 @@ pub async fn set_param(
 /// - If the server does not respond with a 200.
 /// - If we cannot read the body from HTTP.
 /// - If we cannot parse the HTML response.
+/// - If the value to set is not valid, see [`SetParameter::validate`].
+/// - If the value to set is experimental, see
+///   [`SetParameter::is_experimental`].
 pub async fn set_param_with_raw_client<ClientConnectorTy>(
 	client: &Client<ClientConnectorTy>,
 	mion_ip: Ipv4Addr,
@@ -122,88 +155,365 @@ pub async fn set_param_with_raw_client<ClientConnectorTy>(
 where
 	ClientConnectorTy: Clone + Connect + Send + Sync + 'static,
 {
-	let response = do_raw_control_request(
-		client,
-		mion_ip,
-		&[
-			(
-				"operation".to_owned(),
-				Into::<&str>::into(ControlOperation::SetParam).to_owned(),
-			),
-			(
-				format!("{parameter_to_set}"),
-				parameter_to_set.get_value_as_string(),
-			),
-		],
-	)
-	.await?;
-	let status = response.status().as_u16();
-	let body_result = read_http_body_bytes(response.into_body())
+	MionHttpClient::from_raw_client(client.clone())
+		.set_param(mion_ip, parameter_to_set)
 		.await
-		.map_err(NetworkError::HyperError);
-	if status != 200 {
-		if let Ok(body) = body_result {
-			return Err(CatBridgeError::NetworkError(NetworkError::ParseError(
-				NetworkParseError::UnexpectedStatusCode(status, body),
-			)));
-		}
+}
 
-		return Err(CatBridgeError::NetworkError(NetworkError::ParseError(
-			NetworkParseError::UnexpectedStatusCodeNoBody(status),
-		)));
-	}
-	let read_body_bytes = body_result?;
-	let body_as_string = String::from_utf8(read_body_bytes.into())
-		.map_err(NetworkParseError::InvalidDataNeedsUTF8)
-		.map_err(NetworkError::ParseError)?;
-
-	parse_result_from_body(
-		&body_as_string,
-		Into::<&str>::into(ControlOperation::SetParam),
-	)
+/// Perform a `set_param` request that is allowed to send experimental
+/// settings, but with an already existing HTTP Client.
+///
+/// ## Errors
+///
+/// See [`set_experimental_param`].
+pub async fn set_experimental_param_with_raw_client<ClientConnectorTy>(
+	client: &Client<ClientConnectorTy>,
+	mion_ip: Ipv4Addr,
+	parameter_to_set: SetParameter,
+) -> Result<bool, CatBridgeError>
+where
+	ClientConnectorTy: Clone + Connect + Send + Sync + 'static,
+{
+	MionHttpClient::from_raw_client(client.clone())
+		.set_experimental_param(mion_ip, parameter_to_set)
+		.await
 }
 
-#[doc(hidden)]
-pub async fn very_hacky_will_break_dont_use_power_on(
+/// Power on cafe.
+///
+/// ## Errors
+///
+/// - If we cannot figure out the local ip to send as the host (and one
+///   wasn't set on the request).
+/// - If we cannot make the HTTP request, or parse the HTML response.
+/// - If the MION is in use by another host:
+///   [`NetworkError::MionInUseByAnotherHost`].
+/// - If the MION reports the operation failed:
+///   [`NetworkError::ControlOperationFailed`].
+pub async fn power_on(
 	mion_ip: Ipv4Addr,
-) -> Result<bool, CatBridgeError> {
-	let response = do_raw_control_request(
-		&Client::default(),
-		mion_ip,
-		&[
-			("operation", Into::<&str>::into(ControlOperation::PowerOnV2)),
-			("emulation", "off"),
-			(
-				"host",
-				&format!("{}", local_ip().map_err(NetworkError::LocalIpError)?),
-			),
-		],
-	)
-	.await?;
-	let status = response.status().as_u16();
-	let body_result = read_http_body_bytes(response.into_body())
+	request: &PowerOnRequest,
+) -> Result<ControlResponse, CatBridgeError> {
+	MionHttpClient::new().power_on(mion_ip, request).await
+}
+
+/// Power on cafe, but with an already existing HTTP client.
+///
+/// ## Errors
+///
+/// See [`power_on`].
+pub async fn power_on_with_raw_client<ClientConnectorTy>(
+	client: &Client<ClientConnectorTy>,
+	mion_ip: Ipv4Addr,
+	request: &PowerOnRequest,
+) -> Result<ControlResponse, CatBridgeError>
+where
+	ClientConnectorTy: Clone + Connect + Send + Sync + 'static,
+{
+	MionHttpClient::from_raw_client(client.clone())
+		.power_on(mion_ip, request)
+		.await
+}
+
+/// Power off cafe.
+///
+/// **Unverified**: the `power_off` operation has never been seen being sent by
+/// the official tools, so a real bridge may reject it, or ignore it.
+///
+/// ## Errors
+///
+/// See [`power_on`].
+pub async fn power_off(
+	mion_ip: Ipv4Addr,
+	request: &PowerOffRequest,
+) -> Result<ControlResponse, CatBridgeError> {
+	MionHttpClient::new().power_off(mion_ip, request).await
+}
+
+/// Power off cafe, but with an already existing HTTP client.
+///
+/// **Unverified**: see [`power_off`].
+///
+/// ## Errors
+///
+/// See [`power_on`].
+pub async fn power_off_with_raw_client<ClientConnectorTy>(
+	client: &Client<ClientConnectorTy>,
+	mion_ip: Ipv4Addr,
+	request: &PowerOffRequest,
+) -> Result<ControlResponse, CatBridgeError>
+where
+	ClientConnectorTy: Clone + Connect + Send + Sync + 'static,
+{
+	MionHttpClient::from_raw_client(client.clone())
+		.power_off(mion_ip, request)
 		.await
-		.map_err(NetworkError::HyperError);
-	if status != 200 {
-		if let Ok(body) = body_result {
-			return Err(CatBridgeError::NetworkError(NetworkError::ParseError(
-				NetworkParseError::UnexpectedStatusCode(status, body),
-			)));
+}
+
+/// Hard reset cafe, as if the reset button was pressed.
+///
+/// **Unverified**: the `reset` operation has never been seen being sent by
+/// the official tools, so a real bridge may reject it, or ignore it.
+///
+/// ## Errors
+///
+/// See [`power_on`].
+pub async fn reset(
+	mion_ip: Ipv4Addr,
+	request: &ResetRequest,
+) -> Result<ControlResponse, CatBridgeError> {
+	MionHttpClient::new().reset(mion_ip, request).await
+}
+
+/// Hard reset cafe, but with an already existing HTTP client.
+///
+/// **Unverified**: see [`reset`].
+///
+/// ## Errors
+///
+/// See [`power_on`].
+pub async fn reset_with_raw_client<ClientConnectorTy>(
+	client: &Client<ClientConnectorTy>,
+	mion_ip: Ipv4Addr,
+	request: &ResetRequest,
+) -> Result<ControlResponse, CatBridgeError>
+where
+	ClientConnectorTy: Clone + Connect + Send + Sync + 'static,
+{
+	MionHttpClient::from_raw_client(client.clone())
+		.reset(mion_ip, request)
+		.await
+}
+
+/// Check if cafe is currently powered on.
+///
+/// This is based off of the `power` field `get_info` reports. We don't know
+/// what the values of the `VDD2` signal mean, so it isn't used for this.
+///
+/// **Unverified**: the `power` field is a guess, see [`MionInfo`].
+///
+/// ## Errors
+///
+/// - If we cannot make the HTTP request, or parse the HTML response.
+/// - If the MION did not report a power state we understand.
+pub async fn get_power_state(mion_ip: Ipv4Addr) -> Result<PowerState, CatBridgeError> {
+	MionHttpClient::new().get_power_state(mion_ip).await
+}
+
+/// Check if cafe is currently powered on, but with an already existing HTTP
+/// client.
+///
+/// ## Errors
+///
+/// See [`get_power_state`].
+pub async fn get_power_state_with_raw_client<ClientConnectorTy>(
+	client: &Client<ClientConnectorTy>,
+	mion_ip: Ipv4Addr,
+) -> Result<PowerState, CatBridgeError>
+where
+	ClientConnectorTy: Clone + Connect + Send + Sync + 'static,
+{
+	MionHttpClient::from_raw_client(client.clone())
+		.get_power_state(mion_ip)
+		.await
+}
+
+impl<ClientConnectorTy> MionHttpClient<ClientConnectorTy>
+where
+	ClientConnectorTy: Clone + Connect + Send + Sync + 'static,
+{
+	/// Perform a `get_info` request with this client.
+	///
+	/// ## Errors
+	///
+	/// See [`get_info`].
+	pub async fn get_info(
+		&self,
+		mion_ip: Ipv4Addr,
+		name: &str,
+	) -> Result<FnvHashMap<String, String>, CatBridgeError> {
+		let body_as_string = self
+			.post_form(
+				mion_ip,
+				CONTROL_CGI_PATH,
+				&[
+					("operation", Into::<&str>::into(ControlOperation::GetInfo)),
+					(
+						"host",
+						&format!("{}", local_ip().map_err(NetworkError::LocalIpError)?),
+					),
+					("shutdown", "1"),
+					("name", name),
+				],
+			)
+			.await?;
+
+		extract_body_tags(&body_as_string, ControlOperation::GetInfo.into())
+	}
+
+	/// Perform a `get_info` request with this client, parsing the fields we
+	/// know about.
+	///
+	/// ## Errors
+	///
+	/// See [`get_info`].
+	pub async fn get_mion_info(
+		&self,
+		mion_ip: Ipv4Addr,
+		name: &str,
+	) -> Result<MionInfo, CatBridgeError> {
+		self.get_info(mion_ip, name).await.map(MionInfo::from)
+	}
+
+	/// Set a parameter on the MION with this client.
+	///
+	/// ## Errors
+	///
+	/// See [`set_param`].
+	pub async fn set_param(
+		&self,
+		mion_ip: Ipv4Addr,
+		parameter_to_set: SetParameter,
+	) -> Result<bool, CatBridgeError> {
+		if parameter_to_set.is_experimental() {
+			return Err(APIError::ExperimentalSetParameter(parameter_to_set.into()).into());
 		}
+		self.set_experimental_param(mion_ip, parameter_to_set).await
+	}
+
+	/// Set a parameter on the MION with this client, allowing experimental
+	/// settings.
+	///
+	/// ## Errors
+	///
+	/// See [`set_experimental_param`].
+	pub async fn set_experimental_param(
+		&self,
+		mion_ip: Ipv4Addr,
+		parameter_to_set: SetParameter,
+	) -> Result<bool, CatBridgeError> {
+		parameter_to_set.validate()?;
+		let body_as_string = self
+			.post_form(
+				mion_ip,
+				CONTROL_CGI_PATH,
+				&[
+					(
+						"operation".to_owned(),
+						Into::<&str>::into(ControlOperation::SetParam).to_owned(),
+					),
+					(
+						format!("{parameter_to_set}"),
+						parameter_to_set.get_value_as_string(),
+					),
+				],
+			)
+			.await?;
 
-		return Err(CatBridgeError::NetworkError(NetworkError::ParseError(
-			NetworkParseError::UnexpectedStatusCodeNoBody(status),
-		)));
+		Ok(parse_control_response(&body_as_string, ControlOperation::SetParam)?.is_success())
+	}
+
+	/// Power on cafe with this client.
+	///
+	/// ## Errors
+	///
+	/// See [`power_on`].
+	pub async fn power_on(
+		&self,
+		mion_ip: Ipv4Addr,
+		request: &PowerOnRequest,
+	) -> Result<ControlResponse, CatBridgeError> {
+		let body_as_string = self
+			.post_form(
+				mion_ip,
+				CONTROL_CGI_PATH,
+				&[
+					("operation", Into::<&str>::into(ControlOperation::PowerOnV2)),
+					("emulation", if request.emulation() { "on" } else { "off" }),
+					("host", &host_or_local_ip(request.host())?),
+				],
+			)
+			.await?;
+
+		expect_success(parse_control_response(
+			&body_as_string,
+			ControlOperation::PowerOnV2,
+		)?)
+	}
+
+	/// Power off cafe with this client.
+	///
+	/// **Unverified**: see [`power_off`].
+	///
+	/// ## Errors
+	///
+	/// See [`power_on`].
+	pub async fn power_off(
+		&self,
+		mion_ip: Ipv4Addr,
+		request: &PowerOffRequest,
+	) -> Result<ControlResponse, CatBridgeError> {
+		let body_as_string = self
+			.post_form(
+				mion_ip,
+				CONTROL_CGI_PATH,
+				&[
+					("operation", Into::<&str>::into(ControlOperation::PowerOff)),
+					("host", &host_or_local_ip(request.host())?),
+				],
+			)
+			.await?;
+
+		expect_success(parse_control_response(
+			&body_as_string,
+			ControlOperation::PowerOff,
+		)?)
+	}
+
+	/// Hard reset cafe with this client.
+	///
+	/// **Unverified**: see [`reset`].
+	///
+	/// ## Errors
+	///
+	/// See [`power_on`].
+	pub async fn reset(
+		&self,
+		mion_ip: Ipv4Addr,
+		request: &ResetRequest,
+	) -> Result<ControlResponse, CatBridgeError> {
+		let body_as_string = self
+			.post_form(
+				mion_ip,
+				CONTROL_CGI_PATH,
+				&[
+					("operation", Into::<&str>::into(ControlOperation::Reset)),
+					("host", &host_or_local_ip(request.host())?),
+				],
+			)
+			.await?;
+
+		expect_success(parse_control_response(
+			&body_as_string,
+			ControlOperation::Reset,
+		)?)
+	}
+
+	/// Check if cafe is currently powered on with this client.
+	///
+	/// ## Errors
+	///
+	/// See [`get_power_state`].
+	pub async fn get_power_state(&self, mion_ip: Ipv4Addr) -> Result<PowerState, CatBridgeError> {
+		let info = self.get_mion_info(mion_ip, "").await?;
+		info.power().ok_or_else(|| {
+			NetworkError::ParseError(NetworkParseError::InfoFieldMissing(
+				"power",
+				info.raw().get("power").cloned().unwrap_or_default(),
+			))
+			.into()
+		})
 	}
-	let read_body_bytes = body_result?;
-	let body_as_string = String::from_utf8(read_body_bytes.into())
-		.map_err(NetworkParseError::InvalidDataNeedsUTF8)
-		.map_err(NetworkError::ParseError)?;
-
-	parse_result_from_body(
-		&body_as_string,
-		Into::<&str>::into(ControlOperation::SetParam),
-	)
 }
 
 /// Perform a raw operation on the MION board's `control.cgi` page.
@@ -216,7 +526,7 @@ pub async fn very_hacky_will_break_dont_use_power_on(
 ///
 /// - If we cannot make an HTTP request to the MION Request.
 /// - If we fail to encode your parameters into a request body.
-pub async fn do_raw_control_request<'key, 'value, ClientConnectorTy, UrlEncodableType>(
+pub async fn do_raw_control_request<ClientConnectorTy, UrlEncodableType>(
 	client: &Client<ClientConnectorTy>,
 	mion_ip: Ipv4Addr,
 	url_parameters: UrlEncodableType,
@@ -225,23 +535,52 @@ where
 	ClientConnectorTy: Clone + Connect + Send + Sync + 'static,
 	UrlEncodableType: Serialize,
 {
-	Ok(client
-		.request(
-			Request::post(format!("http://{mion_ip}/mion/control.cgi"))
-				.version(Version::HTTP_11)
-				.header("authorization", format!("Basic {AUTHZ_HEADER}"))
-				.header("content-type", "application/x-www-form-urlencoded")
-				.header(
-					"user-agent",
-					format!("cat-dev/{}", env!("CARGO_PKG_VERSION")),
-				)
-				.body(
-					serde_urlencoded::to_string(&url_parameters)
-						.map_err(NetworkParseError::FormDataEncodeError)?
-						.into(),
-				)?,
+	let body = serde_urlencoded::to_string(&url_parameters)
+		.map_err(NetworkParseError::FormDataEncodeError)?;
+	MionHttpClient::from_raw_client(client.clone())
+		.send_raw(Method::POST, mion_ip, CONTROL_CGI_PATH, Some(body))
+		.await
+}
+
+/// Use the host the caller asked for, or the local ip of this machine.
+fn host_or_local_ip(host: Option<Ipv4Addr>) -> Result<String, NetworkError> {
+	if let Some(host) = host {
+		Ok(format!("{host}"))
+	} else {
+		Ok(format!(
+			"{}",
+			local_ip().map_err(NetworkError::LocalIpError)?
+		))
+	}
+}
+
+/// Turn a response that wasn't successful into an error.
+fn expect_success(response: ControlResponse) -> Result<ControlResponse, CatBridgeError> {
+	if response.is_success() {
+		return Ok(response);
+	}
+
+	if response.is_in_use() {
+		return Err(NetworkError::MionInUseByAnotherHost(
+			response
+				.in_use_by()
+				.map_or_else(|| "unknown host".to_owned(), |host| format!("{host}")),
 		)
-		.await?)
+		.into());
+	}
+
+	Err(NetworkError::ControlOperationFailed(
+		response.operation().into(),
+		response.result_code().to_owned(),
+		response.log_lines().to_vec(),
+	)
+	.into())
+}
+
+/// Parse the response of a `get_info` request.
+#[cfg(test)]
+fn parse_info_from_body(body: &str) -> Result<MionInfo, CatBridgeError> {
+	extract_body_tags(body, ControlOperation::GetInfo.into()).map(MionInfo::from)
 }
 
 /// Extract tags from body request.
@@ -294,7 +633,11 @@ fn extract_body_tags(
 	Ok(fields)
 }
 
-fn parse_result_from_body(body: &str, operation_name: &str) -> Result<bool, CatBridgeError> {
+fn parse_control_response(
+	body: &str,
+	operation: ControlOperation,
+) -> Result<ControlResponse, CatBridgeError> {
+	let operation_name: &str = operation.into();
 	let start_tag_location = body.find("<body>").map(|num| num + 6).ok_or_else(|| {
 		CatBridgeError::NetworkError(NetworkError::ParseError(
 			NetworkParseError::HtmlResponseMissingBody(body.to_owned()),
@@ -309,7 +652,6 @@ fn parse_result_from_body(body: &str, operation_name: &str) -> Result<bool, CatB
 	let just_inner_body = body_without_start_tag.split_at(end_tag_location).0;
 	let without_newlines = just_inner_body.replace('\n', "");
 
-	let mut was_successful = false;
 	let mut returned_result_code = "";
 	let mut log_lines = Vec::with_capacity(0);
 	let mut extra_lines = Vec::with_capacity(0);
@@ -326,9 +668,6 @@ fn parse_result_from_body(body: &str, operation_name: &str) -> Result<bool, CatB
 
 		if let Some(result_code) = trimmed_line.strip_prefix("RESULT:") {
 			returned_result_code = result_code;
-			if result_code == "OK" {
-				was_successful = true;
-			}
 		} else if trimmed_line.starts_with("INFO:")
 			|| trimmed_line.starts_with("ERROR:")
 			|| trimmed_line.starts_with("WARN:")
@@ -339,7 +678,13 @@ fn parse_result_from_body(body: &str, operation_name: &str) -> Result<bool, CatB
 		}
 	}
 
-	if !was_successful {
+	let response = ControlResponse::new(
+		operation,
+		returned_result_code.to_owned(),
+		log_lines.iter().map(ToString::to_string).collect(),
+		extra_lines.iter().map(ToString::to_string).collect(),
+	);
+	if !response.is_success() {
 		warn!(
 			log_lines = valuable(&log_lines),
 			extra_lines = valuable(&extra_lines),
@@ -349,5 +694,87 @@ fn parse_result_from_body(body: &str, operation_name: &str) -> Result<bool, CatB
 		);
 	}
 
-	Ok(was_successful)
+	Ok(response)
+}
+
+#[cfg(test)]
+mod unit_tests {
+	use super::*;
+	use crate::mion::proto::control::MIONBootType;
+	use mac_address::MacAddress;
+
+	// The `get_info` fixtures are hand-written from the fields we've seen
+	// reported, they are not captures from a real bridge.
+	#[test]
+	pub fn can_parse_get_info_while_owned() {
+		let info = parse_info_from_body(include_str!("fixtures/get_info_owned.html"))
+			.expect("Failed to parse get_info fixture!");
+
+		assert_eq!(info.name(), Some("00-25-5C-BA-5A-00"));
+		assert_eq!(
+			info.mac_address(),
+			Some(MacAddress::new([0x00, 0x25, 0x5c, 0xba, 0x5a, 0x00])),
+		);
+		assert_eq!(info.firmware_version(), Some("0.0.14.80"));
+		assert_eq!(info.fpga_version(), Some("13052071"));
+		assert_eq!(info.sdk_version(), Some("2.12.13"));
+		assert_eq!(info.power(), Some(PowerState::On));
+		assert_eq!(info.owner(), Some(Ipv4Addr::new(192, 168, 7, 40)));
+		assert_eq!(info.emulation(), Some(true));
+		assert_eq!(info.boot_type(), Some(MIONBootType::DUAL));
+		assert_eq!(info.atapi_port(), Some(7975));
+		assert_eq!(info.sdio_printf_port(), Some(7976));
+		assert_eq!(info.sdio_block_port(), Some(7977));
+		assert_eq!(info.parameter_port(), Some(7978));
+		assert_eq!(info.unknown_fields().count(), 0);
+		assert!(info.unparsable_fields().is_empty());
+		assert_eq!(
+			info.raw().get("fw_version").map(String::as_str),
+			Some("0.0.14.80"),
+		);
+	}
+
+	#[test]
+	pub fn can_parse_get_info_while_idle() {
+		let info = parse_info_from_body(include_str!("fixtures/get_info_idle.html"))
+			.expect("Failed to parse get_info fixture!");
+
+		assert_eq!(info.name(), Some("my-cool-bridge"));
+		assert_eq!(info.power(), Some(PowerState::Off));
+		// Nobody owning the MION is reported as all zeroes.
+		assert_eq!(info.owner(), None);
+		assert_eq!(info.emulation(), Some(false));
+		assert_eq!(info.boot_type(), Some(MIONBootType::NAND));
+		// Older firmwares don't report every field.
+		assert_eq!(info.sdk_version(), None);
+		assert_eq!(info.sdio_block_port(), None);
+		assert_eq!(
+			info.unknown_fields().collect::<Vec<_>>(),
+			vec![("serial_number", "HTE10000001")],
+		);
+	}
+
+	#[test]
+	pub fn get_info_keeps_invalid_fields() {
+		let info = parse_info_from_body(
+			"<html><body>name:bridge<br>atapi_port:not-a-port<br>power:sideways<br>parameter_port:7978<br></body></html>",
+		)
+		.expect("A bad field should not fail the whole response!");
+		assert_eq!(info.name(), Some("bridge"));
+		assert_eq!(info.atapi_port(), None);
+		assert_eq!(info.power(), None);
+		assert_eq!(info.parameter_port(), Some(7978));
+		assert_eq!(info.unparsable_fields(), &["power", "atapi_port"]);
+		assert_eq!(
+			info.raw().get("atapi_port").map(String::as_str),
+			Some("not-a-port"),
+		);
+
+		assert!(matches!(
+			parse_info_from_body("<html>name:bridge<br></html>"),
+			Err(CatBridgeError::NetworkError(NetworkError::ParseError(
+				NetworkParseError::HtmlResponseMissingBody(_)
+			))),
+		));
+	}
 }
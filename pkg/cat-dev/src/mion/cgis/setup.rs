@@ -0,0 +1,214 @@
+//! API's for interacting with `/setup.cgi`, the HTTP page for changing the
+//! network settings, name, and service ports of a MION.
+//!
+//! This is the same page you would normally fill out in a browser, so
+//! bridges can be provisioned from scripts.
+//!
+//! **Unverified**: nobody has checked this against a real bridge yet. The
+//! path, and the names of the form fields are guesses, so reading the page
+//! may fail with a missing field, and submitting it may be rejected (or worse,
+//! reset fields we don't know about). Treat everything in here as
+//! experimental until it's been confirmed.
+
+use crate::{
+	errors::{CatBridgeError, NetworkError},
+	mion::{cgis::MionHttpClient, proto::cgis::MionSetup},
+};
+use hyper::{
+	client::{connect::Connect, Client},
+	Body, Method, Response,
+};
+use std::net::Ipv4Addr;
+
+/// The path `setup.cgi` is served on.
+const SETUP_CGI_PATH: &str = "/setup.cgi";
+
+/// Read the current setup page of a MION.
+///
+/// ## Errors
+///
+/// - If we cannot make the HTTP request.
+/// - If the server does not respond with a 200.
+/// - If we cannot read the body from HTTP.
+/// - If the form is missing a field we need, or a field has a value we can't
+///   understand.
+pub async fn get_setup(mion_ip: Ipv4Addr) -> Result<MionSetup, CatBridgeError> {
+	MionHttpClient::new().get_setup(mion_ip).await
+}
+
+/// Read the current setup page of a MION, but with an already existing HTTP
+/// client.
+///
+/// ## Errors
+///
+/// See [`get_setup`].
+pub async fn get_setup_with_raw_client<ClientConnectorTy>(
+	client: &Client<ClientConnectorTy>,
+	mion_ip: Ipv4Addr,
+) -> Result<MionSetup, CatBridgeError>
+where
+	ClientConnectorTy: Clone + Connect + Send + Sync + 'static,
+{
+	MionHttpClient::from_raw_client(client.clone())
+		.get_setup(mion_ip)
+		.await
+}
+
+/// Submit a changed setup page to a MION.
+///
+/// Changes to the network settings, or ports generally only apply after the
+/// MION has been restarted.
+///
+/// This is experimental, as the names of the form fields are guesses (see
+/// the module documentation). A real MION may reject the form, or reset any
+/// fields we don't know about.
+///
+/// ## Errors
+///
+/// - If the setup is not valid, see [`MionSetup::validate`].
+/// - If we cannot encode the form.
+/// - If we cannot make the HTTP request.
+/// - If the server does not respond with a 200.
+/// - If we cannot read the body from HTTP.
+/// - If the MION responds with any errors: [`NetworkError::SetupRejected`].
+pub async fn set_experimental_setup(
+	mion_ip: Ipv4Addr,
+	setup: &MionSetup,
+) -> Result<(), CatBridgeError> {
+	MionHttpClient::new()
+		.set_experimental_setup(mion_ip, setup)
+		.await
+}
+
+/// Submit a changed setup page to a MION, but with an already existing HTTP
+/// client.
+///
+/// ## Errors
+///
+/// See [`set_experimental_setup`].
+pub async fn set_experimental_setup_with_raw_client<ClientConnectorTy>(
+	client: &Client<ClientConnectorTy>,
+	mion_ip: Ipv4Addr,
+	setup: &MionSetup,
+) -> Result<(), CatBridgeError>
+where
+	ClientConnectorTy: Clone + Connect + Send + Sync + 'static,
+{
+	MionHttpClient::from_raw_client(client.clone())
+		.set_experimental_setup(mion_ip, setup)
+		.await
+}
+
+impl<ClientConnectorTy> MionHttpClient<ClientConnectorTy>
+where
+	ClientConnectorTy: Clone + Connect + Send + Sync + 'static,
+{
+	/// Read the current setup page of a MION with this client.
+	///
+	/// ## Errors
+	///
+	/// See [`get_setup`].
+	pub async fn get_setup(&self, mion_ip: Ipv4Addr) -> Result<MionSetup, CatBridgeError> {
+		let body_as_string = self.get(mion_ip, SETUP_CGI_PATH).await?;
+		Ok(MionSetup::from_html(&body_as_string).map_err(NetworkError::ParseError)?)
+	}
+
+	/// Submit a changed setup page to a MION with this client.
+	///
+	/// ## Errors
+	///
+	/// See [`set_experimental_setup`].
+	pub async fn set_experimental_setup(
+		&self,
+		mion_ip: Ipv4Addr,
+		setup: &MionSetup,
+	) -> Result<(), CatBridgeError> {
+		setup.validate()?;
+
+		let body_as_string = self
+			.post_form(mion_ip, SETUP_CGI_PATH, &setup.to_form())
+			.await?;
+		let errors = body_as_string
+			.split(['\n', '>', '<'])
+			.map(str::trim)
+			.filter(|line| line.starts_with("ERROR:"))
+			.map(ToOwned::to_owned)
+			.collect::<Vec<_>>();
+		if errors.is_empty() {
+			Ok(())
+		} else {
+			Err(NetworkError::SetupRejected(errors).into())
+		}
+	}
+}
+
+/// Perform a raw request to the MION board's `setup.cgi` page.
+///
+/// If no form is passed this is a `GET` for the current page, otherwise the
+/// form is `POST`'d as is.
+///
+/// *note: you probably want to call one of the actual methods, as this is
+/// basically just a thin wrapper around an HTTP Request.*
+///
+/// ## Errors
+///
+/// - If we cannot make an HTTP request to the MION.
+pub async fn do_raw_setup_request<ClientConnectorTy>(
+	client: &Client<ClientConnectorTy>,
+	mion_ip: Ipv4Addr,
+	form: Option<String>,
+) -> Result<Response<Body>, NetworkError>
+where
+	ClientConnectorTy: Clone + Connect + Send + Sync + 'static,
+{
+	MionHttpClient::from_raw_client(client.clone())
+		.send_raw(
+			if form.is_some() {
+				Method::POST
+			} else {
+				Method::GET
+			},
+			mion_ip,
+			SETUP_CGI_PATH,
+			form,
+		)
+		.await
+}
+
+#[cfg(test)]
+mod unit_tests {
+	use super::*;
+	use crate::{errors::NetworkParseError, mion::proto::cgis::IpMode};
+
+	// The setup fixture is hand-written, not captured from a real bridge.
+	#[test]
+	pub fn can_parse_setup_page() {
+		let setup = MionSetup::from_html(include_str!("fixtures/setup.html"))
+			.expect("Failed to parse setup fixture!");
+
+		assert_eq!(setup.ip_mode(), IpMode::Static);
+		assert_eq!(setup.ip_address(), Ipv4Addr::new(192, 168, 7, 40));
+		assert_eq!(setup.netmask(), Ipv4Addr::new(255, 255, 255, 0));
+		assert_eq!(setup.gateway(), Ipv4Addr::new(192, 168, 7, 1));
+		assert_eq!(setup.name(), "00-25-5C-BA-5A-00");
+		assert_eq!(setup.atapi_port(), 7975);
+		assert_eq!(setup.sdio_printf_port(), 7976);
+		assert_eq!(setup.sdio_block_port(), 7977);
+		assert_eq!(setup.parameter_port(), 7978);
+		assert!(setup.validate().is_ok());
+		// Fields we don't know about are still submitted back.
+		assert!(setup
+			.to_form()
+			.contains(&("dns_server".to_owned(), "192.168.7.1".to_owned())));
+	}
+
+	#[test]
+	pub fn setup_page_needs_every_field() {
+		assert_eq!(
+			MionSetup::from_html(
+				r#"<form><input type="radio" name="ip_mode" value="dhcp" checked></form>"#
+			),
+			Err(NetworkParseError::SetupFieldMissing("ip_address")),
+		);
+	}
+}
@@ -1,18 +1,20 @@
 //! API's for interacting with `/signal_get.cgi`, an HTTP interface for
-//! getting signals
+//! getting the hardware signals of the board.
 
 use crate::{
 	errors::{CatBridgeError, NetworkError, NetworkParseError},
-	mion::cgis::AUTHZ_HEADER,
+	mion::{cgis::MionHttpClient, proto::cgis::Signal},
 };
 use hyper::{
-	body::to_bytes as read_http_body_bytes,
 	client::{connect::Connect, Client},
-	Body, Request, Response, Version,
+	Body, Method, Response,
 };
 use serde::Serialize;
 use std::net::Ipv4Addr;
 
+/// The path `signal_get.cgi` is served on.
+const SIGNAL_GET_CGI_PATH: &str = "/signal_get.cgi";
+
 /// Perform a `signal_get` request for the `VDD2` signal given a host.
 ///
 /// ## Errors
@@ -23,7 +25,7 @@ use std::net::Ipv4Addr;
 /// - If we cannot read the body from HTTP.
 /// - If we cannot parse the HTML response.
 pub async fn get_vdd2(mion_ip: Ipv4Addr) -> Result<String, CatBridgeError> {
-	get_vdd2_with_raw_client(&Client::default(), mion_ip).await
+	MionHttpClient::new().get_vdd2(mion_ip).await
 }
 
 /// Perform a get info request, but with an already existing HTTP client.
@@ -42,49 +44,144 @@ pub async fn get_vdd2_with_raw_client<ClientConnectorTy>(
 where
 	ClientConnectorTy: Clone + Connect + Send + Sync + 'static,
 {
-	let response = do_raw_signal_http_request(client, mion_ip, &[("sig", "VDD2")]).await?;
-	let status = response.status().as_u16();
-	let body_result = read_http_body_bytes(response.into_body())
+	MionHttpClient::from_raw_client(client.clone())
+		.get_vdd2(mion_ip)
 		.await
-		.map_err(NetworkError::HyperError);
-	if status != 200 {
-		if let Ok(body) = body_result {
-			return Err(CatBridgeError::NetworkError(NetworkError::ParseError(
-				NetworkParseError::UnexpectedStatusCode(status, body),
-			)));
-		}
+}
 
-		return Err(CatBridgeError::NetworkError(NetworkError::ParseError(
-			NetworkParseError::UnexpectedStatusCodeNoBody(status),
-		)));
+/// Read the value of a single signal, exactly as the MION sent it.
+///
+/// ## Errors
+///
+/// - If we cannot make the HTTP request.
+/// - If the server does not respond with a 200.
+/// - If we cannot read the body from HTTP, or parse the HTML response.
+pub async fn get_signal(mion_ip: Ipv4Addr, signal: Signal) -> Result<String, CatBridgeError> {
+	MionHttpClient::new().get_signal(mion_ip, signal).await
+}
+
+/// Read the value of a single signal, but with an already existing HTTP
+/// client.
+///
+/// ## Errors
+///
+/// See [`get_signal`].
+pub async fn get_signal_with_raw_client<ClientConnectorTy>(
+	client: &Client<ClientConnectorTy>,
+	mion_ip: Ipv4Addr,
+	signal: Signal,
+) -> Result<String, CatBridgeError>
+where
+	ClientConnectorTy: Clone + Connect + Send + Sync + 'static,
+{
+	MionHttpClient::from_raw_client(client.clone())
+		.get_signal(mion_ip, signal)
+		.await
+}
+
+/// Read the values of many signals at once, exactly as the MION sent them.
+///
+/// `signal_get.cgi` only takes one signal per request, so this makes one
+/// request per signal over the same client, returning them in the same order
+/// they were asked for.
+///
+/// ## Errors
+///
+/// If reading any of the signals fails, see [`get_signal`].
+pub async fn get_signals(
+	mion_ip: Ipv4Addr,
+	signals: &[Signal],
+) -> Result<Vec<(Signal, String)>, CatBridgeError> {
+	MionHttpClient::new().get_signals(mion_ip, signals).await
+}
+
+/// Read the values of many signals at once, but with an already existing HTTP
+/// client.
+///
+/// ## Errors
+///
+/// See [`get_signals`].
+pub async fn get_signals_with_raw_client<ClientConnectorTy>(
+	client: &Client<ClientConnectorTy>,
+	mion_ip: Ipv4Addr,
+	signals: &[Signal],
+) -> Result<Vec<(Signal, String)>, CatBridgeError>
+where
+	ClientConnectorTy: Clone + Connect + Send + Sync + 'static,
+{
+	MionHttpClient::from_raw_client(client.clone())
+		.get_signals(mion_ip, signals)
+		.await
+}
+
+impl<ClientConnectorTy> MionHttpClient<ClientConnectorTy>
+where
+	ClientConnectorTy: Clone + Connect + Send + Sync + 'static,
+{
+	/// Perform a `signal_get` request for the `VDD2` signal with this client.
+	///
+	/// ## Errors
+	///
+	/// See [`get_vdd2`].
+	pub async fn get_vdd2(&self, mion_ip: Ipv4Addr) -> Result<String, CatBridgeError> {
+		self.get_signal(mion_ip, Signal::Vdd2).await
 	}
-	let read_body_bytes = body_result?;
-	let body_as_string = String::from_utf8(read_body_bytes.into())
-		.map_err(NetworkParseError::InvalidDataNeedsUTF8)
-		.map_err(NetworkError::ParseError)?;
 
-	let start_tag_location = body_as_string
-		.find("<body>")
-		.map(|num| num + 6)
-		.ok_or_else(|| {
+	/// Read the values of many signals at once with this client.
+	///
+	/// ## Errors
+	///
+	/// See [`get_signals`].
+	pub async fn get_signals(
+		&self,
+		mion_ip: Ipv4Addr,
+		signals: &[Signal],
+	) -> Result<Vec<(Signal, String)>, CatBridgeError> {
+		let mut values = Vec::with_capacity(signals.len());
+		for signal in signals {
+			values.push((*signal, self.get_signal(mion_ip, *signal).await?));
+		}
+		Ok(values)
+	}
+
+	/// Read the value of a single signal with this client, exactly as the MION
+	/// sent it.
+	///
+	/// ## Errors
+	///
+	/// See [`get_signal`].
+	pub async fn get_signal(
+		&self,
+		mion_ip: Ipv4Addr,
+		signal: Signal,
+	) -> Result<String, CatBridgeError> {
+		let body_as_string = self
+			.post_form(mion_ip, SIGNAL_GET_CGI_PATH, &[("sig", signal.name())])
+			.await?;
+
+		let start_tag_location = body_as_string
+			.find("<body>")
+			.map(|num| num + 6)
+			.ok_or_else(|| {
+				CatBridgeError::NetworkError(NetworkError::ParseError(
+					NetworkParseError::HtmlResponseMissingBody(body_as_string.clone()),
+				))
+			})?;
+		let body_without_start_tag = body_as_string.split_at(start_tag_location).1;
+		let end_tag_location = body_without_start_tag.find("</body>").ok_or_else(|| {
 			CatBridgeError::NetworkError(NetworkError::ParseError(
 				NetworkParseError::HtmlResponseMissingBody(body_as_string.clone()),
 			))
 		})?;
-	let body_without_start_tag = body_as_string.split_at(start_tag_location).1;
-	let end_tag_location = body_without_start_tag.find("</body>").ok_or_else(|| {
-		CatBridgeError::NetworkError(NetworkError::ParseError(
-			NetworkParseError::HtmlResponseMissingBody(body_as_string.clone()),
-		))
-	})?;
 
-	Ok(body_without_start_tag
-		.split_at(end_tag_location)
-		.0
-		.to_owned())
+		Ok(body_without_start_tag
+			.split_at(end_tag_location)
+			.0
+			.to_owned())
+	}
 }
 
-/// Perform a raw operation on the MION board's `control.cgi` page.
+/// Perform a raw request to the MION board's `signal_get.cgi` page.
 ///
 /// *note: you probably want to call one of the actual methods, as this is
 /// basically just a thin wrapper around an HTTP Post Request. Not doing much
@@ -94,7 +191,7 @@ where
 ///
 /// - If we cannot make an HTTP request to the MION Request.
 /// - If we fail to encode your parameters into a request body.
-pub async fn do_raw_signal_http_request<'key, 'value, ClientConnectorTy, UrlEncodableType>(
+pub async fn do_raw_signal_http_request<ClientConnectorTy, UrlEncodableType>(
 	client: &Client<ClientConnectorTy>,
 	mion_ip: Ipv4Addr,
 	url_parameters: UrlEncodableType,
@@ -103,21 +200,9 @@ where
 	ClientConnectorTy: Clone + Connect + Send + Sync + 'static,
 	UrlEncodableType: Serialize,
 {
-	Ok(client
-		.request(
-			Request::post(format!("http://{mion_ip}/signal_get.cgi"))
-				.version(Version::HTTP_11)
-				.header("authorization", format!("Basic {AUTHZ_HEADER}"))
-				.header("content-type", "application/x-www-form-urlencoded")
-				.header(
-					"user-agent",
-					format!("cat-dev/{}", env!("CARGO_PKG_VERSION")),
-				)
-				.body(
-					serde_urlencoded::to_string(&url_parameters)
-						.map_err(NetworkParseError::FormDataEncodeError)?
-						.into(),
-				)?,
-		)
-		.await?)
+	let body = serde_urlencoded::to_string(&url_parameters)
+		.map_err(NetworkParseError::FormDataEncodeError)?;
+	MionHttpClient::from_raw_client(client.clone())
+		.send_raw(Method::POST, mion_ip, SIGNAL_GET_CGI_PATH, Some(body))
+		.await
 }
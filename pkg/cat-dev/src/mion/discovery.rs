@@ -12,11 +12,27 @@
 //! 2. [`find_mion`] which finds a specific MION board based on one of the
 //!    identifiers we know how to search for. *NOTE: in some cases this can
 //!    lead to a full scan. See the API information for details.*
+//! 3. [`BridgeMonitor`] which keeps scanning forever, and tells you when
+//!    bridges appear, change, or disappear from the network.
+//! 4. [`discover_bridges_at_targets`] which sends announcements directly to
+//!    specific addresses, CIDR ranges, or directed broadcast addresses.
+//!
+//! By default we broadcast on every network interface that has a private
+//! IPv4 address. If your machine has a lot of interfaces (docker bridges, VPN
+//! tunnels, etc.) you can limit which ones get used with an
+//! [`InterfaceFilter`], e.g. with [`discover_bridges_on_interfaces`], or
+//! [`discover_bridges_on_preferred_interface`] which uses the interface
+//! configured in your `bridge_env.ini`.
 //!
 //! It should be noted you can only find bridges that are on the same broadcast
 //! domain within your local network. In general this means under the same
 //! Subnet, and VLAN (unless your repeating broadcast packets across VLANs).
 //!
+//! If you know where your bridges are, but can't receive their broadcasts
+//! you can use [`discover_bridges_at_targets`] to ask them directly. As long
+//! as the network routes between you, and the bridges this doesn't need any
+//! extra infrastructure.
+//!
 //! If you are in different VLANs/Subnets and you do have the ability to run
 //! a repeater heading in BOTH directions (both are required for all bits of
 //! functionality!), you want to broadcast the port
@@ -25,29 +41,35 @@
 //! port is (by default this is also 7974, so not a worry.)
 
 use crate::{
-	errors::{CatBridgeError, NetworkError},
+	errors::{APIError, CatBridgeError, NetworkError},
 	mion::proto::{
 		control::{MionIdentity, MionIdentityAnnouncement},
 		DEFAULT_MION_CONTROL_PORT, MION_ANNOUNCE_TIMEOUT_SECONDS,
 	},
+	BridgeHostState,
 };
 use bytes::{Bytes, BytesMut};
-use fnv::FnvHashSet;
-use futures::stream::{unfold, StreamExt};
+use fnv::{FnvHashMap, FnvHashSet};
+use futures::stream::{unfold, Stream, StreamExt};
 use mac_address::MacAddress;
 use network_interface::{Addr, NetworkInterface, NetworkInterfaceConfig};
 use std::{
 	fmt::{Display, Formatter, Result as FmtResult},
 	hash::BuildHasherDefault,
 	net::{IpAddr, Ipv4Addr, SocketAddr, SocketAddrV4},
+	num::NonZeroU32,
+	pin::Pin,
+	sync::{Arc, Weak},
+	task::{Context, Poll},
 };
 use tokio::{
 	net::UdpSocket,
-	sync::mpsc::{unbounded_channel, UnboundedReceiver},
-	task::JoinSet,
-	time::{sleep, Duration, Instant},
+	sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender},
+	task::{Builder as TaskBuilder, JoinHandle, JoinSet},
+	time::{interval, sleep, Duration, Instant, MissedTickBehavior},
 };
 use tracing::{debug, error, warn};
+use valuable::Valuable;
 
 /// A small wrapper around [`discover_bridges`] that collects all the results
 /// into a list for you to parse through.
@@ -113,9 +135,36 @@ pub async fn discover_and_collect_bridges_with_logging_hooks<InterfaceLoggingHoo
 where
 	InterfaceLoggingHook: Fn(&'_ Addr) + Clone + Send + 'static,
 {
-	let mut recv_channel = discover_bridges_with_logging_hooks(
+	discover_and_collect_bridges_with_interface_filter(
+		fetch_detailed_info,
+		early_timeout,
+		override_control_port,
+		&InterfaceFilter::default(),
+		interface_logging_hook,
+	)
+	.await
+}
+
+/// The same as [`discover_and_collect_bridges_with_logging_hooks`], but only
+/// broadcasting on the interfaces allowed by `interface_filter`.
+///
+/// ## Errors
+///
+/// See the error notes for [`discover_bridges`].
+pub async fn discover_and_collect_bridges_with_interface_filter<InterfaceLoggingHook>(
+	fetch_detailed_info: bool,
+	early_timeout: Option<Duration>,
+	override_control_port: Option<u16>,
+	interface_filter: &InterfaceFilter,
+	interface_logging_hook: InterfaceLoggingHook,
+) -> Result<Vec<MionIdentity>, CatBridgeError>
+where
+	InterfaceLoggingHook: Fn(&'_ Addr) + Clone + Send + 'static,
+{
+	let mut recv_channel = discover_bridges_with_interface_filter(
 		fetch_detailed_info,
 		override_control_port,
+		interface_filter,
 		interface_logging_hook,
 	)
 	.await?;
@@ -184,21 +233,70 @@ pub async fn discover_bridges(
 	fetch_detailed_info: bool,
 	override_control_port: Option<u16>,
 ) -> Result<UnboundedReceiver<MionIdentity>, CatBridgeError> {
-	discover_bridges_with_logging_hooks(
+	discover_bridges_on_interfaces(
+		fetch_detailed_info,
+		override_control_port,
+		&InterfaceFilter::default(),
+	)
+	.await
+}
+
+/// Discover all the Cat-Dev Bridges on the network, only broadcasting on some
+/// of the interfaces on this machine.
+///
+/// This is the same as [`discover_bridges`], but any interface that isn't
+/// allowed by `interface_filter` is skipped entirely. This is useful for
+/// machines that have a lot of interfaces (docker bridges, VPN tunnels, etc.)
+/// where broadcasting on everything is slow, or sets off firewall alerts.
+///
+/// ## Errors
+///
+/// See the error notes for [`discover_bridges`].
+pub async fn discover_bridges_on_interfaces(
+	fetch_detailed_info: bool,
+	override_control_port: Option<u16>,
+	interface_filter: &InterfaceFilter,
+) -> Result<UnboundedReceiver<MionIdentity>, CatBridgeError> {
+	discover_bridges_with_interface_filter(
 		fetch_detailed_info,
 		override_control_port,
+		interface_filter,
 		noop_logger_interface,
 	)
 	.await
 }
 
+/// Discover all the Cat-Dev Bridges on the network, only broadcasting on the
+/// preferred interface configured in the host state (`bridge_env.ini`).
+///
+/// If no preferred interface has been configured (see:
+/// [`BridgeHostState::set_preferred_interface`]) this will broadcast on every
+/// interface just like [`discover_bridges`].
+///
+/// ## Errors
+///
+/// See the error notes for [`discover_bridges`].
+pub async fn discover_bridges_on_preferred_interface(
+	host_state: &BridgeHostState,
+	fetch_detailed_info: bool,
+	override_control_port: Option<u16>,
+) -> Result<UnboundedReceiver<MionIdentity>, CatBridgeError> {
+	discover_bridges_on_interfaces(
+		fetch_detailed_info,
+		override_control_port,
+		&InterfaceFilter::from_host_state(host_state),
+	)
+	.await
+}
+
 /// Discover all the Cat-Dev Bridges actively on the network.
 ///
 /// This is the function that allows you to specify EXTRA logging hooks (e.g.
 /// those that aren't written to [`tracing`], for like when you need to manually
 /// recreate a CLI with old hacky `println!`).
 ///
-/// You probably want [`discover_bridges`].
+/// You probably want [`discover_bridges`], or
+/// [`discover_bridges_on_interfaces`].
 ///
 /// *note: you probably do not want to set `control_port`, we have not seen
 /// a mion respond on a separate port to this day, but certain tools do try
@@ -213,13 +311,37 @@ pub async fn discover_bridges_with_logging_hooks<InterfaceLoggingHook>(
 	override_control_port: Option<u16>,
 	interface_logging_hook: InterfaceLoggingHook,
 ) -> Result<UnboundedReceiver<MionIdentity>, CatBridgeError>
+where
+	InterfaceLoggingHook: Fn(&'_ Addr) + Clone + Send + 'static,
+{
+	discover_bridges_with_interface_filter(
+		fetch_detailed_info,
+		override_control_port,
+		&InterfaceFilter::default(),
+		interface_logging_hook,
+	)
+	.await
+}
+
+/// The same as [`discover_bridges_with_logging_hooks`], but only broadcasting
+/// on the interfaces allowed by `interface_filter`.
+///
+/// ## Errors
+///
+/// See the error notes for [`discover_bridges`].
+pub async fn discover_bridges_with_interface_filter<InterfaceLoggingHook>(
+	fetch_detailed_info: bool,
+	override_control_port: Option<u16>,
+	interface_filter: &InterfaceFilter,
+	interface_logging_hook: InterfaceLoggingHook,
+) -> Result<UnboundedReceiver<MionIdentity>, CatBridgeError>
 where
 	InterfaceLoggingHook: Fn(&'_ Addr) + Clone + Send + 'static,
 {
 	let to_broadcast = Bytes::from(MionIdentityAnnouncement::new(fetch_detailed_info));
 	let mut tasks = JoinSet::new();
 
-	for (interface_addr, interface_ipv4) in get_all_broadcast_addresses()? {
+	for (interface_addr, interface_ipv4) in get_broadcast_addresses(interface_filter)? {
 		let broadcast_messaged_cloned = to_broadcast.clone();
 		let cloned_iface_hook = interface_logging_hook.clone();
 		tasks
@@ -255,68 +377,14 @@ where
 			}
 		};
 		if let Some(socket) = opt_socket.take() {
-			listening_sockets.push(socket);
-		}
-	}
-
-	let mut our_addresses = FnvHashSet::with_capacity_and_hasher(
-		listening_sockets.len(),
-		BuildHasherDefault::default(),
-	);
-	for sock in &listening_sockets {
-		if let Ok(our_addr) = sock.local_addr() {
-			our_addresses.insert(our_addr.ip());
+			listening_sockets.push(Arc::new(socket));
 		}
 	}
 
-	let streams = listening_sockets
-		.into_iter()
-		.map(|socket| Box::pin(unfold(socket, unfold_socket)))
-		.collect::<Vec<_>>();
-	// Combine every single socket receive into a single receive stream.
-	let mut single_stream = futures::stream::select_all(streams);
-	let timeout_at = Instant::now() + Duration::from_secs(MION_ANNOUNCE_TIMEOUT_SECONDS);
-	let (send, recv) = unbounded_channel::<MionIdentity>();
-
-	tokio::task::spawn(async move {
-		loop {
-			tokio::select! {
-				opt = single_stream.next() => {
-					let Some((read_data_len, from, mut buff)) = opt else {
-						continue;
-					};
-					buff.truncate(read_data_len);
-					let frozen = buff.freeze();
-
-					let from_ip = from.ip();
-					if our_addresses.contains(&from_ip) {
-						debug!("broadcast saw our own message");
-						continue;
-					}
-					let ip_address = match from_ip {
-						IpAddr::V4(v4) => v4,
-						IpAddr::V6(v6) => {
-							debug!(%v6, "broadcast packet from IPv6, ignoring, can't be announcement");
-							continue;
-						},
-					};
-
-					let Ok(identity) = MionIdentity::try_from((ip_address, frozen.clone())) else {
-						warn!(%from, packet = %format!("{frozen:02x?}"), "could not parse packet from MION");
-						continue;
-					};
-					if let Err(_closed) = send.send(identity) {
-						break;
-					}
-				}
-				() = tokio::time::sleep_until(timeout_at) => {
-					break;
-				}
-			}
-		}
-	});
-
-	Ok(recv)
+	Ok(listen_for_identities(
+		listening_sockets,
+		Duration::from_secs(MION_ANNOUNCE_TIMEOUT_SECONDS),
+	))
 }
 
 /// Attempt to find a specific MION by searching for a specific field.
@@ -380,45 +448,58 @@ pub async fn find_mion_with_logging_hooks<InterfaceLoggingHook>(
 	override_control_port: Option<u16>,
 	interface_logging_hook: InterfaceLoggingHook,
 ) -> Result<Option<MionIdentity>, CatBridgeError>
+where
+	InterfaceLoggingHook: Fn(&'_ Addr) + Clone + Send + 'static,
+{
+	find_mion_with_interface_filter(
+		find_by,
+		find_detailed_info,
+		early_scan_timeout,
+		override_control_port,
+		&InterfaceFilter::default(),
+		interface_logging_hook,
+	)
+	.await
+}
+
+/// The same as [`find_mion_with_logging_hooks`], but if we need to do a full
+/// scan only the interfaces allowed by `interface_filter` will be broadcast
+/// on.
+///
+/// ## Errors
+///
+/// - If we fail to spawn a task to concurrently look up the MIONs, and we need
+///   to do a full discovery search.
+/// - If any task fails to create a socket, and broadcast on that socket.
+pub async fn find_mion_with_interface_filter<InterfaceLoggingHook>(
+	find_by: MIONFindBy,
+	find_detailed_info: bool,
+	early_scan_timeout: Option<Duration>,
+	override_control_port: Option<u16>,
+	interface_filter: &InterfaceFilter,
+	interface_logging_hook: InterfaceLoggingHook,
+) -> Result<Option<MionIdentity>, CatBridgeError>
 where
 	InterfaceLoggingHook: Fn(&'_ Addr) + Clone + Send + 'static,
 {
 	let port = override_control_port.unwrap_or(DEFAULT_MION_CONTROL_PORT);
 	let (find_by_mac, find_by_name) = match find_by {
 		MIONFindBy::Ip(ipv4) => {
-			let local_socket = UdpSocket::bind(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, port))
-				.await
-				.map_err(|_| NetworkError::BindAddressError)?;
-			local_socket
-				.connect(SocketAddrV4::new(ipv4, port))
-				.await
-				.map_err(NetworkError::IOError)?;
-			local_socket
-				.send(&Bytes::from(MionIdentityAnnouncement::new(
-					find_detailed_info,
-				)))
-				.await
-				.map_err(NetworkError::IOError)?;
-
-			let mut buff = BytesMut::zeroed(8192);
-			tokio::select! {
-				result = local_socket.recv(&mut buff) => {
-					let actual_size = result.map_err(NetworkError::IOError)?;
-					buff.truncate(actual_size);
-				}
-				() = sleep(Duration::from_secs(MION_ANNOUNCE_TIMEOUT_SECONDS)) => {
-					return Ok(None);
-				}
-			}
-			return Ok(Some(MionIdentity::try_from((ipv4, buff.freeze()))?));
+			return query_mion_directly(
+				SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, port),
+				SocketAddrV4::new(ipv4, port),
+				find_detailed_info,
+			)
+			.await;
 		}
 		MIONFindBy::MacAddress(mac) => (Some(mac), None),
 		MIONFindBy::Name(name) => (None, Some(name)),
 	};
 
-	let mut recv_channel = discover_bridges_with_logging_hooks(
+	let mut recv_channel = discover_bridges_with_interface_filter(
 		find_detailed_info,
 		override_control_port,
+		interface_filter,
 		interface_logging_hook,
 	)
 	.await?;
@@ -537,53 +618,787 @@ impl Display for MIONFindBy {
 	}
 }
 
-/// Get a list of all the network interfaces to actively scanning on.
+/// A way to match one of the network interfaces on this machine.
 ///
-/// NOTE: this doesn't actually fetch all the broadcast addresses, just the
-/// potential ones we MIGHT be able to scan on. This is specifically required
-/// for implementing the broken behavior of `findbridge`, which DOES NOT
-/// actually ensure that a broadcast address could be made at all.
+/// These can be parsed from strings:
 ///
-/// Thanks `findbridge`.
+/// - `10.0.0.0/24` is an [`InterfaceMatcher::Subnet`].
+/// - `10.0.0.2` is an [`InterfaceMatcher::Ip`].
+/// - Anything else (e.g. `eth0`, or `docker0`) is an
+///   [`InterfaceMatcher::Name`].
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub enum InterfaceMatcher {
+	/// Match an interface by it's name, e.g. `eth0` on linux, or `Ethernet 2`
+	/// on windows.
+	Name(String),
+	/// Match an interface that has a specific IPv4 address.
+	Ip(Ipv4Addr),
+	/// Match an interface that has an IPv4 address within a subnet.
+	Subnet(Ipv4Addr, u8),
+}
+impl InterfaceMatcher {
+	/// If an interface with a particular name, and IPv4 address matches.
+	///
+	/// *note: an interface with multiple IPv4 addresses gets checked once
+	/// per address, so matching by ip/subnet only matches that one address.*
+	#[must_use]
+	pub fn matches(&self, interface_name: &str, interface_ip: Ipv4Addr) -> bool {
+		match self {
+			Self::Name(name) => name == interface_name,
+			Self::Ip(ip) => *ip == interface_ip,
+			Self::Subnet(network, prefix_length) => {
+				let mask = prefix_mask(*prefix_length);
+				u32::from(*network) & mask == u32::from(interface_ip) & mask
+			}
+		}
+	}
+}
+impl Display for InterfaceMatcher {
+	fn fmt(&self, fmt: &mut Formatter<'_>) -> FmtResult {
+		match self {
+			Self::Name(name) => write!(fmt, "{name}"),
+			Self::Ip(ip) => write!(fmt, "{ip}"),
+			Self::Subnet(network, prefix_length) => write!(fmt, "{network}/{prefix_length}"),
+		}
+	}
+}
+impl TryFrom<&str> for InterfaceMatcher {
+	type Error = APIError;
+
+	fn try_from(value: &str) -> Result<Self, Self::Error> {
+		let trimmed = value.trim();
+		let invalid = || APIError::InvalidInterfaceMatcher(value.to_owned());
+
+		if trimmed.is_empty() {
+			return Err(invalid());
+		}
+		if let Some((network, prefix_length)) = trimmed.split_once('/') {
+			let network = network.parse::<Ipv4Addr>().map_err(|_| invalid())?;
+			let prefix_length = prefix_length
+				.parse::<u8>()
+				.ok()
+				.filter(|length| *length <= 32)
+				.ok_or_else(invalid)?;
+			return Ok(Self::Subnet(network, prefix_length));
+		}
+		if let Ok(ip) = trimmed.parse::<Ipv4Addr>() {
+			return Ok(Self::Ip(ip));
+		}
+
+		Ok(Self::Name(trimmed.to_owned()))
+	}
+}
+
+/// Which of the network interfaces on this machine we should broadcast on.
 ///
-/// ## Errors
+/// An interface is used if it matches anything in the allow list (or the
+/// allow list is empty), and doesn't match anything in the deny list. The
+/// default filter allows every interface.
+#[derive(Clone, Debug, Default, Hash, PartialEq, Eq)]
+pub struct InterfaceFilter {
+	allow: Vec<InterfaceMatcher>,
+	deny: Vec<InterfaceMatcher>,
+}
+impl InterfaceFilter {
+	/// Create a new filter from an allow list, and a deny list.
+	#[must_use]
+	pub fn new(allow: Vec<InterfaceMatcher>, deny: Vec<InterfaceMatcher>) -> Self {
+		Self { allow, deny }
+	}
+
+	/// Create a filter that only allows the preferred interface configured in
+	/// the host state (`bridge_env.ini`).
+	///
+	/// If no preferred interface is configured every interface is allowed.
+	#[must_use]
+	pub fn from_host_state(host_state: &BridgeHostState) -> Self {
+		Self {
+			allow: host_state.get_preferred_interface().into_iter().collect(),
+			deny: Vec::with_capacity(0),
+		}
+	}
+
+	/// The interfaces that are allowed, if empty every interface is allowed.
+	#[must_use]
+	pub fn allow_list(&self) -> &[InterfaceMatcher] {
+		&self.allow
+	}
+
+	/// The interfaces that are never allowed.
+	#[must_use]
+	pub fn deny_list(&self) -> &[InterfaceMatcher] {
+		&self.deny
+	}
+
+	/// If an interface with a particular name, and IPv4 address should be
+	/// broadcast on.
+	#[must_use]
+	pub fn allows(&self, interface_name: &str, interface_ip: Ipv4Addr) -> bool {
+		(self.allow.is_empty()
+			|| self
+				.allow
+				.iter()
+				.any(|matcher| matcher.matches(interface_name, interface_ip)))
+			&& !self
+				.deny
+				.iter()
+				.any(|matcher| matcher.matches(interface_name, interface_ip))
+	}
+}
+
+/// The default amount of announcements we'll send per second when sweeping
+/// targets directly.
+pub const DEFAULT_SWEEP_PACKETS_PER_SECOND: u32 = 200;
+/// The most announcements we'll send per second when sweeping targets
+/// directly.
 ///
-/// - If we cannot list all the network interfaces present on the system.
-pub fn get_all_broadcast_addresses() -> Result<Vec<(Addr, Ipv4Addr)>, CatBridgeError> {
-	Ok(NetworkInterface::show()
-		.map_err(|cause| {
-			error!(?cause, "could not list network interfaces on this device");
-			CatBridgeError::NetworkError(NetworkError::ListInterfacesError)
-		})?
-		.into_iter()
-		.fold(Vec::<(Addr, Ipv4Addr)>::new(), |mut accum, iface| {
-			for local_address in &iface.addr {
-				let ip = match local_address.ip() {
-					IpAddr::V4(ref v4) => {
-						if !v4.is_private() && !v4.is_link_local() {
-							debug!(?iface, ?local_address, "will not broadcast to public ips");
-							continue;
-						}
+/// Anything faster than this would need to wait less than a microsecond
+/// between each packet, which no OS timer can actually do.
+pub const MAX_SWEEP_PACKETS_PER_SECOND: u32 = 1_000_000;
+/// The smallest CIDR prefix length we are willing to sweep one address at a
+/// time.
+const MIN_SWEEP_PREFIX_LENGTH: u8 = 16;
 
-						*v4
-					}
-					IpAddr::V6(_) => {
-						debug!(?iface, ?local_address, "cannot broadcast to IPv6 addresses");
-						continue;
-					}
-				};
+/// Somewhere to send announcements to directly, rather than broadcasting on
+/// each of our local interfaces.
+///
+/// These can be parsed from strings:
+///
+/// - `10.0.0.2` is a single [`DiscoveryTarget::Ip`].
+/// - `10.0.0.0/24` is a [`DiscoveryTarget::Cidr`] range.
+/// - `broadcast:10.0.0.255` is a [`DiscoveryTarget::DirectedBroadcast`].
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum DiscoveryTarget {
+	/// Send a single announcement to a single IP.
+	Ip(Ipv4Addr),
+	/// Send an announcement to every host address within a CIDR range.
+	///
+	/// The network, and broadcast addresses of the range are skipped (unless
+	/// the range is a `/31`, or `/32` which don't have them). Ranges larger
+	/// than a `/16` are not allowed.
+	Cidr(Ipv4Addr, u8),
+	/// Send a single announcement to the broadcast address of a subnet you
+	/// are not directly attached too.
+	///
+	/// This is a lot less traffic than sweeping a [`DiscoveryTarget::Cidr`],
+	/// but most routers will drop directed broadcasts by default.
+	DirectedBroadcast(Ipv4Addr),
+}
+impl DiscoveryTarget {
+	/// Create a CIDR range target, validating the prefix length.
+	///
+	/// ## Errors
+	///
+	/// - If the prefix length is greater than 32.
+	/// - If the prefix length is less than 16 (the range is too large to
+	///   sweep).
+	pub fn cidr(network: Ipv4Addr, prefix_length: u8) -> Result<Self, APIError> {
+		if prefix_length > 32 {
+			return Err(APIError::InvalidDiscoveryTarget(format!(
+				"{network}/{prefix_length}"
+			)));
+		}
+		if prefix_length < MIN_SWEEP_PREFIX_LENGTH {
+			return Err(APIError::DiscoveryRangeTooLarge(format!(
+				"{network}/{prefix_length}"
+			)));
+		}
 
-				accum.push((*local_address, ip));
+		Ok(Self::Cidr(network, prefix_length))
+	}
+
+	/// Every address we'll send an announcement to for this target.
+	#[must_use]
+	pub fn addresses(&self) -> Vec<Ipv4Addr> {
+		match *self {
+			Self::Ip(ip) | Self::DirectedBroadcast(ip) => vec![ip],
+			Self::Cidr(network, prefix_length) => {
+				let mask = prefix_mask(prefix_length);
+				let first = u32::from(network) & mask;
+				let last = first | !mask;
+
+				if prefix_length >= 31 {
+					(first..=last).map(Ipv4Addr::from).collect()
+				} else {
+					(first + 1..last).map(Ipv4Addr::from).collect()
+				}
 			}
+		}
+	}
+}
+impl Display for DiscoveryTarget {
+	fn fmt(&self, fmt: &mut Formatter<'_>) -> FmtResult {
+		match self {
+			Self::Ip(ip) => write!(fmt, "{ip}"),
+			Self::Cidr(network, prefix_length) => write!(fmt, "{network}/{prefix_length}"),
+			Self::DirectedBroadcast(ip) => write!(fmt, "broadcast:{ip}"),
+		}
+	}
+}
+impl TryFrom<&str> for DiscoveryTarget {
+	type Error = APIError;
 
-			accum
-		}))
+	fn try_from(value: &str) -> Result<Self, Self::Error> {
+		let trimmed = value.trim();
+		let invalid = || APIError::InvalidDiscoveryTarget(value.to_owned());
+
+		if let Some(broadcast) = trimmed.strip_prefix("broadcast:") {
+			return broadcast
+				.parse::<Ipv4Addr>()
+				.map(Self::DirectedBroadcast)
+				.map_err(|_| invalid());
+		}
+		if let Some((network, prefix_length)) = trimmed.split_once('/') {
+			let network = network.parse::<Ipv4Addr>().map_err(|_| invalid())?;
+			let prefix_length = prefix_length.parse::<u8>().map_err(|_| invalid())?;
+			return Self::cidr(network, prefix_length);
+		}
+
+		trimmed
+			.parse::<Ipv4Addr>()
+			.map(Self::Ip)
+			.map_err(|_| invalid())
+	}
 }
 
-/// Broadcast to all the MIONs on a particular network interface.
+/// Discover Cat-Dev Bridges by sending announcements directly to a series of
+/// targets, rather than broadcasting on our local interfaces.
 ///
-/// This doesn't actually read the values (we want to queue up all the reads
-/// so we can read from them all concurrently with a timeout that applies to
+/// This lets you find bridges that are on another subnet/VLAN as long as
+/// traffic is routed between you. Announcements are sent at most
+/// `packets_per_second` (or [`DEFAULT_SWEEP_PACKETS_PER_SECOND`]) so large
+/// ranges don't flood the network. We keep listening for responses for
+/// [`crate::mion::proto::MION_ANNOUNCE_TIMEOUT_SECONDS`] after the last
+/// announcement has been sent. To stop early simply close the receiving end
+/// of the channel.
+///
+/// *note: a bridge may respond more than once (e.g. if it is covered by
+/// multiple targets), you should handle any de-duping on your side!*
+///
+/// *note: you probably do not want to set `control_port`, we have not seen
+/// a mion respond on a separate port to this day, but certain tools do try
+/// to query other ports (We believe it's an unintentional bug, however, we
+/// expose it, just incase).
+///
+/// ## Errors
+///
+/// - If `packets_per_second` is above [`MAX_SWEEP_PACKETS_PER_SECOND`].
+/// - If we cannot bind to the control port to send announcements from.
+/// - If we cannot enable broadcasting on our socket (needed for directed
+///   broadcasts).
+/// - If we cannot spawn the task to send announcements in the background.
+pub async fn discover_bridges_at_targets(
+	targets: &[DiscoveryTarget],
+	fetch_detailed_info: bool,
+	override_control_port: Option<u16>,
+	packets_per_second: Option<NonZeroU32>,
+) -> Result<UnboundedReceiver<MionIdentity>, CatBridgeError> {
+	let port = override_control_port.unwrap_or(DEFAULT_MION_CONTROL_PORT);
+
+	sweep_targets(
+		SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, port),
+		port,
+		targets,
+		fetch_detailed_info,
+		packets_per_second,
+	)
+	.await
+}
+
+/// Send announcements to every address of every target from a local
+/// address, and listen for the responses.
+pub(crate) async fn sweep_targets(
+	local_address: SocketAddrV4,
+	target_port: u16,
+	targets: &[DiscoveryTarget],
+	fetch_detailed_info: bool,
+	packets_per_second: Option<NonZeroU32>,
+) -> Result<UnboundedReceiver<MionIdentity>, CatBridgeError> {
+	let addresses = targets
+		.iter()
+		.flat_map(DiscoveryTarget::addresses)
+		.collect::<Vec<_>>();
+	let packets_per_second =
+		packets_per_second.map_or(DEFAULT_SWEEP_PACKETS_PER_SECOND, NonZeroU32::get);
+	if packets_per_second > MAX_SWEEP_PACKETS_PER_SECOND {
+		return Err(APIError::DiscoverySweepRateTooHigh(packets_per_second).into());
+	}
+	let send_delay = Duration::from_secs(1) / packets_per_second;
+
+	let socket = UdpSocket::bind(local_address)
+		.await
+		.map_err(|_| NetworkError::BindAddressError)?;
+	socket
+		.set_broadcast(true)
+		.map_err(|_| NetworkError::SetBroadcastFailure)?;
+	let socket = Arc::new(socket);
+
+	let sweep_duration = send_delay * u32::try_from(addresses.len()).unwrap_or(u32::MAX);
+	TaskBuilder::new()
+		.name("cat_dev::discover_mion::sweep")
+		.spawn(send_sweep_announcements(
+			Arc::downgrade(&socket),
+			Bytes::from(MionIdentityAnnouncement::new(fetch_detailed_info)),
+			addresses,
+			target_port,
+			send_delay,
+		))
+		.map_err(|_| CatBridgeError::SpawnFailure)?;
+
+	Ok(listen_for_identities(
+		vec![socket],
+		sweep_duration + Duration::from_secs(MION_ANNOUNCE_TIMEOUT_SECONDS),
+	))
+}
+
+/// Send an announcement to each address, waiting `send_delay` in between each
+/// one.
+///
+/// We only hold onto a weak reference to the socket, once the listener goes
+/// away (because the receiver was closed) there's no reason to keep sending.
+async fn send_sweep_announcements(
+	socket: Weak<UdpSocket>,
+	announcement: Bytes,
+	addresses: Vec<Ipv4Addr>,
+	target_port: u16,
+	send_delay: Duration,
+) {
+	let mut ticker = interval(send_delay);
+	ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+	for address in addresses {
+		ticker.tick().await;
+		let Some(socket) = socket.upgrade() else {
+			debug!("stopping sweep, nothing is listening anymore");
+			return;
+		};
+		if let Err(cause) = socket
+			.send_to(&announcement, SocketAddrV4::new(address, target_port))
+			.await
+		{
+			debug!(?cause, %address, "failed to send announcement to target");
+		}
+	}
+}
+
+/// A field of a [`MionIdentity`] that can change while a bridge is being
+/// monitored.
+///
+/// The mac address is not included, as that is what we use to tell bridges
+/// apart from each other.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, Valuable)]
+pub enum MionIdentityField {
+	/// [`MionIdentity::name`]
+	Name,
+	/// [`MionIdentity::ip_address`]
+	IpAddress,
+	/// [`MionIdentity::raw_fpga_version`]
+	FpgaVersion,
+	/// [`MionIdentity::raw_firmware_version`]
+	FirmwareVersion,
+	/// [`MionIdentity::detailed_raw_sdk_version`]
+	SdkVersion,
+	/// [`MionIdentity::detailed_boot_type`]
+	BootType,
+	/// [`MionIdentity::detailed_is_cafe_on`]
+	CafePowerStatus,
+	/// [`MionIdentity::detailed_unknown_data`]
+	UnknownDetailedData,
+}
+impl MionIdentityField {
+	/// Get every field that is different between two identities.
+	#[must_use]
+	pub fn diff(previous: &MionIdentity, current: &MionIdentity) -> Vec<Self> {
+		let mut changed = Vec::new();
+		if previous.name() != current.name() {
+			changed.push(Self::Name);
+		}
+		if previous.ip_address() != current.ip_address() {
+			changed.push(Self::IpAddress);
+		}
+		if previous.raw_fpga_version() != current.raw_fpga_version() {
+			changed.push(Self::FpgaVersion);
+		}
+		if previous.raw_firmware_version() != current.raw_firmware_version() {
+			changed.push(Self::FirmwareVersion);
+		}
+		if previous.detailed_raw_sdk_version() != current.detailed_raw_sdk_version() {
+			changed.push(Self::SdkVersion);
+		}
+		if previous.detailed_boot_type() != current.detailed_boot_type() {
+			changed.push(Self::BootType);
+		}
+		if previous.detailed_is_cafe_on() != current.detailed_is_cafe_on() {
+			changed.push(Self::CafePowerStatus);
+		}
+		if previous.detailed_unknown_data() != current.detailed_unknown_data() {
+			changed.push(Self::UnknownDetailedData);
+		}
+		changed
+	}
+}
+
+/// Something that happened to a bridge while it was being monitored.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BridgeMonitorEvent {
+	/// A bridge we have not seen before (or since it was lost) responded.
+	Appeared(MionIdentity),
+	/// A bridge we already knew about responded with different information.
+	Updated {
+		previous: MionIdentity,
+		current: MionIdentity,
+		changed_fields: Vec<MionIdentityField>,
+	},
+	/// A bridge has not responded to any announcements within the lost
+	/// timeout.
+	Lost(MionIdentity),
+}
+
+/// How long to wait before receiving again, when receiving on a monitoring
+/// socket fails.
+const MONITOR_RECEIVE_ERROR_BACKOFF: Duration = Duration::from_millis(100);
+
+/// Continuously scan for bridges on the network, and get notified when they
+/// appear, change, or disappear.
+///
+/// Unlike [`discover_bridges`] this will re-broadcast an announcement every
+/// `rebroadcast_interval` forever, until this monitor is dropped. Bridges are
+/// identified by their mac address, so the same bridge responding on
+/// multiple interfaces is only ever reported once. A bridge is considered
+/// "lost" when it hasn't responded within `lost_after`, which should be a
+/// couple times longer than `rebroadcast_interval` so a single dropped
+/// packet doesn't cause it to be lost.
+///
+/// Events can be read with [`BridgeMonitor::next_event`], or by using the
+/// monitor as a [`Stream`].
+///
+/// *note: the interfaces to broadcast on are only looked up when the monitor
+/// is created. If your network interfaces change you will need to create a
+/// new monitor.*
+#[derive(Debug)]
+pub struct BridgeMonitor {
+	events: UnboundedReceiver<BridgeMonitorEvent>,
+	task: JoinHandle<()>,
+}
+impl BridgeMonitor {
+	/// Start monitoring for bridges on every interface we can broadcast on.
+	///
+	/// *note: you probably do not want to set `control_port`, we have not seen
+	/// a mion respond on a separate port to this day, but certain tools do try
+	/// to query other ports (We believe it's an unintentional bug, however, we
+	/// expose it, just incase).
+	///
+	/// ## Errors
+	///
+	/// - If we cannot list the network interfaces on this machine.
+	/// - If we cannot bind to, or enable broadcasting on any interface.
+	/// - If we cannot spawn the background task that does the monitoring.
+	pub async fn spawn(
+		fetch_detailed_info: bool,
+		override_control_port: Option<u16>,
+		rebroadcast_interval: Duration,
+		lost_after: Duration,
+	) -> Result<Self, CatBridgeError> {
+		Self::spawn_on_interfaces(
+			fetch_detailed_info,
+			override_control_port,
+			&InterfaceFilter::default(),
+			rebroadcast_interval,
+			lost_after,
+		)
+		.await
+	}
+
+	/// Start monitoring for bridges on only the interfaces allowed by a
+	/// filter.
+	///
+	/// *note: you probably do not want to set `control_port`, we have not seen
+	/// a mion respond on a separate port to this day, but certain tools do try
+	/// to query other ports (We believe it's an unintentional bug, however, we
+	/// expose it, just incase).
+	///
+	/// ## Errors
+	///
+	/// See the error notes for [`BridgeMonitor::spawn`].
+	pub async fn spawn_on_interfaces(
+		fetch_detailed_info: bool,
+		override_control_port: Option<u16>,
+		interface_filter: &InterfaceFilter,
+		rebroadcast_interval: Duration,
+		lost_after: Duration,
+	) -> Result<Self, CatBridgeError> {
+		let port = override_control_port.unwrap_or(DEFAULT_MION_CONTROL_PORT);
+		let mut targets = Vec::new();
+
+		for (interface_addr, interface_ipv4) in get_broadcast_addresses(interface_filter)? {
+			let Some(broadcast_address) = interface_addr.broadcast() else {
+				debug!(
+					?interface_addr,
+					?interface_ipv4,
+					"failed to get broadcast address"
+				);
+				continue;
+			};
+
+			let socket = UdpSocket::bind(SocketAddrV4::new(interface_ipv4, port))
+				.await
+				.map_err(|_| NetworkError::BindAddressError)?;
+			socket
+				.set_broadcast(true)
+				.map_err(|_| NetworkError::SetBroadcastFailure)?;
+			targets.push((socket, SocketAddr::new(broadcast_address, port)));
+		}
+
+		Self::spawn_with_targets(
+			fetch_detailed_info,
+			targets,
+			rebroadcast_interval,
+			lost_after,
+		)
+	}
+
+	/// Start monitoring by sending announcements from each socket, to the
+	/// address it's paired with.
+	///
+	/// ## Errors
+	///
+	/// - If we cannot spawn the background task that does the monitoring.
+	pub(crate) fn spawn_with_targets(
+		fetch_detailed_info: bool,
+		targets: Vec<(UdpSocket, SocketAddr)>,
+		rebroadcast_interval: Duration,
+		lost_after: Duration,
+	) -> Result<Self, CatBridgeError> {
+		let (send, events) = unbounded_channel();
+		let task = TaskBuilder::new()
+			.name("cat_dev::bridge_monitor")
+			.spawn(run_bridge_monitor(
+				Bytes::from(MionIdentityAnnouncement::new(fetch_detailed_info)),
+				targets,
+				(rebroadcast_interval, lost_after),
+				send,
+			))
+			.map_err(|_| CatBridgeError::SpawnFailure)?;
+
+		Ok(Self { events, task })
+	}
+
+	/// Wait for the next event to happen.
+	///
+	/// Returns `None` if the monitor can no longer receive from any interface.
+	pub async fn next_event(&mut self) -> Option<BridgeMonitorEvent> {
+		self.events.recv().await
+	}
+}
+impl Stream for BridgeMonitor {
+	type Item = BridgeMonitorEvent;
+
+	fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		self.events.poll_recv(cx)
+	}
+}
+impl Drop for BridgeMonitor {
+	fn drop(&mut self) {
+		self.task.abort();
+	}
+}
+
+/// All the bridges a [`BridgeMonitor`] currently knows about, and turns new
+/// identities into events.
+#[derive(Debug, Default)]
+struct BridgeMonitorState {
+	known: FnvHashMap<MacAddress, (MionIdentity, Instant)>,
+}
+impl BridgeMonitorState {
+	/// Record that we've seen an identity, returning an event if it's new, or
+	/// has changed.
+	fn observe(&mut self, identity: MionIdentity, now: Instant) -> Option<BridgeMonitorEvent> {
+		let Some((previous, last_seen)) = self.known.get_mut(&identity.mac_address()) else {
+			self.known
+				.insert(identity.mac_address(), (identity.clone(), now));
+			return Some(BridgeMonitorEvent::Appeared(identity));
+		};
+
+		*last_seen = now;
+		let changed_fields = MionIdentityField::diff(previous, &identity);
+		if changed_fields.is_empty() {
+			return None;
+		}
+
+		let previous = std::mem::replace(previous, identity.clone());
+		Some(BridgeMonitorEvent::Updated {
+			previous,
+			current: identity,
+			changed_fields,
+		})
+	}
+
+	/// Remove any bridges we haven't heard from in `lost_after`, returning a
+	/// lost event for each one.
+	fn expire(&mut self, now: Instant, lost_after: Duration) -> Vec<BridgeMonitorEvent> {
+		let lost_macs = self
+			.known
+			.iter()
+			.filter(|(_, (_, last_seen))| now.saturating_duration_since(*last_seen) >= lost_after)
+			.map(|(mac, _)| *mac)
+			.collect::<Vec<_>>();
+
+		lost_macs
+			.into_iter()
+			.filter_map(|mac| self.known.remove(&mac))
+			.map(|(identity, _)| BridgeMonitorEvent::Lost(identity))
+			.collect()
+	}
+}
+
+/// The background task for a [`BridgeMonitor`].
+async fn run_bridge_monitor(
+	announcement: Bytes,
+	targets: Vec<(UdpSocket, SocketAddr)>,
+	(rebroadcast_interval, lost_after): (Duration, Duration),
+	send: UnboundedSender<BridgeMonitorEvent>,
+) {
+	let mut our_addresses = FnvHashSet::default();
+	let mut sockets = Vec::with_capacity(targets.len());
+	for (socket, target) in targets {
+		if let Ok(our_addr) = socket.local_addr() {
+			our_addresses.insert(our_addr);
+		}
+		sockets.push((Arc::new(socket), target));
+	}
+
+	let mut single_stream = futures::stream::select_all(sockets.iter().map(|(socket, _)| {
+		Box::pin(unfold(socket.clone(), |sock| async move {
+			let mut buff = BytesMut::zeroed(1024);
+			loop {
+				match sock.recv_from(&mut buff).await {
+					Ok((len, addr)) => {
+						buff.truncate(len);
+						return Some(((addr, buff.freeze()), sock));
+					}
+					// Some OS's will report errors (like an ICMP port unreachable
+					// from a previous send) on the next receive, those shouldn't
+					// stop the monitor. Wait a little bit so a socket that is
+					// permanently broken doesn't spin.
+					Err(cause) => {
+						warn!(?cause, "failed to receive data from monitoring socket");
+						sleep(MONITOR_RECEIVE_ERROR_BACKOFF).await;
+					}
+				}
+			}
+		}))
+	}));
+	let mut ticker = interval(rebroadcast_interval);
+	ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+	let mut state = BridgeMonitorState::default();
+
+	loop {
+		let events = tokio::select! {
+			_ = ticker.tick() => {
+				for (socket, target) in &sockets {
+					if let Err(cause) = socket.send_to(&announcement, target).await {
+						warn!(?cause, %target, "failed to send announcement while monitoring");
+					}
+				}
+				state.expire(Instant::now(), lost_after)
+			}
+			opt = single_stream.next() => {
+				// Sockets keep receiving through errors, so this only happens when
+				// there were no sockets to monitor in the first place.
+				let Some((from, packet)) = opt else {
+					break;
+				};
+				if our_addresses.contains(&from) {
+					debug!("monitor saw our own message");
+					continue;
+				}
+				let Some(identity) = identity_from_packet(from, &packet) else {
+					continue;
+				};
+				state.observe(identity, Instant::now()).into_iter().collect()
+			}
+		};
+
+		for event in events {
+			if let Err(_closed) = send.send(event) {
+				return;
+			}
+		}
+	}
+}
+
+/// Get the network mask for a prefix length, e.g. `24` is `255.255.255.0`.
+fn prefix_mask(prefix_length: u8) -> u32 {
+	u32::MAX
+		.checked_shl(32_u32.saturating_sub(u32::from(prefix_length)))
+		.unwrap_or(0)
+}
+
+/// Get a list of all the network interfaces to actively scanning on.
+///
+/// NOTE: this doesn't actually fetch all the broadcast addresses, just the
+/// potential ones we MIGHT be able to scan on. This is specifically required
+/// for implementing the broken behavior of `findbridge`, which DOES NOT
+/// actually ensure that a broadcast address could be made at all.
+///
+/// Thanks `findbridge`.
+///
+/// ## Errors
+///
+/// - If we cannot list all the network interfaces present on the system.
+pub fn get_all_broadcast_addresses() -> Result<Vec<(Addr, Ipv4Addr)>, CatBridgeError> {
+	get_broadcast_addresses(&InterfaceFilter::default())
+}
+
+/// Get a list of the network interfaces to actively scan on, that are allowed
+/// by a filter.
+///
+/// See [`get_all_broadcast_addresses`] for the caveats of what we return.
+///
+/// ## Errors
+///
+/// - If we cannot list all the network interfaces present on the system.
+pub fn get_broadcast_addresses(
+	interface_filter: &InterfaceFilter,
+) -> Result<Vec<(Addr, Ipv4Addr)>, CatBridgeError> {
+	Ok(NetworkInterface::show()
+		.map_err(|cause| {
+			error!(?cause, "could not list network interfaces on this device");
+			CatBridgeError::NetworkError(NetworkError::ListInterfacesError)
+		})?
+		.into_iter()
+		.fold(Vec::<(Addr, Ipv4Addr)>::new(), |mut accum, iface| {
+			for local_address in &iface.addr {
+				let ip = match local_address.ip() {
+					IpAddr::V4(ref v4) => {
+						if !v4.is_private() && !v4.is_link_local() {
+							debug!(?iface, ?local_address, "will not broadcast to public ips");
+							continue;
+						}
+
+						*v4
+					}
+					IpAddr::V6(_) => {
+						debug!(?iface, ?local_address, "cannot broadcast to IPv6 addresses");
+						continue;
+					}
+				};
+				if !interface_filter.allows(&iface.name, ip) {
+					debug!(?iface, ?local_address, "interface is not allowed by filter");
+					continue;
+				}
+
+				accum.push((*local_address, ip));
+			}
+
+			accum
+		}))
+}
+
+/// Broadcast to all the MIONs on a particular network interface.
+///
+/// This doesn't actually read the values (we want to queue up all the reads
+/// so we can read from them all concurrently with a timeout that applies to
 /// all of them).
 async fn broadcast_to_mions_on_interface<InterfaceLoggingHook>(
 	override_control_port: Option<u16>,
@@ -635,12 +1450,136 @@ where
 	Ok(Some(local_socket))
 }
 
+/// Send an announcement to a single MION, and wait for it to respond.
+///
+/// This is what powers [`MIONFindBy::Ip`], the local address is passed in
+/// seperately so it doesn't have to match the port the MION is listening on.
+async fn query_mion_directly(
+	local_address: SocketAddrV4,
+	mion_address: SocketAddrV4,
+	find_detailed_info: bool,
+) -> Result<Option<MionIdentity>, CatBridgeError> {
+	let local_socket = UdpSocket::bind(local_address)
+		.await
+		.map_err(|_| NetworkError::BindAddressError)?;
+	local_socket
+		.connect(mion_address)
+		.await
+		.map_err(NetworkError::IOError)?;
+	local_socket
+		.send(&Bytes::from(MionIdentityAnnouncement::new(
+			find_detailed_info,
+		)))
+		.await
+		.map_err(NetworkError::IOError)?;
+
+	let mut buff = BytesMut::zeroed(8192);
+	tokio::select! {
+		result = local_socket.recv(&mut buff) => {
+			let actual_size = result.map_err(NetworkError::IOError)?;
+			buff.truncate(actual_size);
+		}
+		() = sleep(Duration::from_secs(MION_ANNOUNCE_TIMEOUT_SECONDS)) => {
+			return Ok(None);
+		}
+	}
+	Ok(Some(MionIdentity::try_from((
+		*mion_address.ip(),
+		buff.freeze(),
+	))?))
+}
+
+/// Listen on a series of sockets that have sent out announcements, and turn
+/// any responses into a stream of identities.
+///
+/// Listening stops after `listen_for`, or when the receiving side of the
+/// channel is closed.
+fn listen_for_identities(
+	listening_sockets: Vec<Arc<UdpSocket>>,
+	listen_for: Duration,
+) -> UnboundedReceiver<MionIdentity> {
+	let mut our_addresses = FnvHashSet::with_capacity_and_hasher(
+		listening_sockets.len(),
+		BuildHasherDefault::default(),
+	);
+	for sock in &listening_sockets {
+		if let Ok(our_addr) = sock.local_addr() {
+			our_addresses.insert(our_addr.ip());
+		}
+	}
+
+	let streams = listening_sockets
+		.into_iter()
+		.map(|socket| Box::pin(unfold(socket, unfold_socket)))
+		.collect::<Vec<_>>();
+	// Combine every single socket receive into a single receive stream.
+	let mut single_stream = futures::stream::select_all(streams);
+	let timeout_at = Instant::now() + listen_for;
+	let (send, recv) = unbounded_channel::<MionIdentity>();
+
+	tokio::task::spawn(async move {
+		loop {
+			tokio::select! {
+				opt = single_stream.next() => {
+					// Every socket has stopped being readable (or we never had any
+					// sockets to begin with), so there's nothing left to wait on.
+					let Some((read_data_len, from, mut buff)) = opt else {
+						break;
+					};
+					buff.truncate(read_data_len);
+					let frozen = buff.freeze();
+
+					if our_addresses.contains(&from.ip()) {
+						debug!("broadcast saw our own message");
+						continue;
+					}
+					let Some(identity) = identity_from_packet(from, &frozen) else {
+						continue;
+					};
+					if let Err(_closed) = send.send(identity) {
+						break;
+					}
+				}
+				() = tokio::time::sleep_until(timeout_at) => {
+					break;
+				}
+			}
+		}
+	});
+
+	recv
+}
+
+/// Parse a packet we received in response to an announcement.
+///
+/// Returns `None` (and logs) if the packet came from an IPv6 address, or was
+/// not an identity.
+fn identity_from_packet(from: SocketAddr, packet: &Bytes) -> Option<MionIdentity> {
+	let ip_address = match from.ip() {
+		IpAddr::V4(v4) => v4,
+		IpAddr::V6(v6) => {
+			debug!(%v6, "broadcast packet from IPv6, ignoring, can't be announcement");
+			return None;
+		}
+	};
+
+	match MionIdentity::try_from((ip_address, packet.clone())) {
+		Ok(identity) => Some(identity),
+		Err(_cause) => {
+			warn!(%from, packet = %format!("{packet:02x?}"), "could not parse packet from MION");
+			None
+		}
+	}
+}
+
 /// Unfold sockets goal is to turn reading from a socket over & over into a
 /// stream.
 ///
 /// When the stream has produced a value, and gets polled again,
 /// it queues up another read, and so on.
-async fn unfold_socket(sock: UdpSocket) -> Option<((usize, SocketAddr, BytesMut), UdpSocket)> {
+async fn unfold_socket(
+	sock: Arc<UdpSocket>,
+) -> Option<((usize, SocketAddr, BytesMut), Arc<UdpSocket>)> {
 	let mut buff = BytesMut::zeroed(1024);
 	let Ok((len, addr)) = sock.recv_from(&mut buff).await else {
 		warn!("failed to receive data from broadcast socket");
@@ -656,6 +1595,27 @@ fn noop_logger_interface(_: &Addr) {}
 #[cfg(test)]
 mod unit_tests {
 	use super::*;
+	use crate::mion::{
+		emulator::EmulatedMion,
+		proto::control::{DETAILED_CAFE_ON_OFFSET, DETAILED_DATA_SIZE},
+	};
+	use tokio::time::timeout;
+
+	fn test_identity(ip_address: Ipv4Addr, is_cafe_on: bool) -> MionIdentity {
+		let mut detailed = vec![0_u8; DETAILED_DATA_SIZE];
+		detailed[227..231].copy_from_slice(&[2, 12, 13, 0]);
+		detailed[DETAILED_CAFE_ON_OFFSET] = u8::from(is_cafe_on);
+
+		MionIdentity::new(
+			Some(Bytes::from(detailed)),
+			[0, 14, 80, 1],
+			[0x13, 0x05, 0x20, 0x71],
+			ip_address,
+			MacAddress::new([0x00, 0x25, 0x5c, 0xba, 0x5a, 0x00]),
+			"00-25-5C-BA-5A-00".to_owned(),
+		)
+		.expect("Failed to create test identity!")
+	}
 
 	#[test]
 	pub fn can_list_at_least_one_interface() {
@@ -700,4 +1660,282 @@ mod unit_tests {
 			"Somehow found a MION that can't exist?"
 		);
 	}
+
+	#[test]
+	pub fn bridge_monitor_state_events() {
+		let mut state = BridgeMonitorState::default();
+		let start = Instant::now();
+		let off = test_identity(Ipv4Addr::new(10, 0, 0, 2), false);
+		let on = test_identity(Ipv4Addr::new(10, 0, 0, 2), true);
+		let moved = test_identity(Ipv4Addr::new(10, 0, 0, 3), true);
+
+		assert_eq!(
+			state.observe(off.clone(), start),
+			Some(BridgeMonitorEvent::Appeared(off.clone())),
+		);
+		// Seeing the same identity again (e.g. on another interface) is not a
+		// change.
+		assert_eq!(state.observe(off.clone(), start), None);
+		assert_eq!(
+			state.observe(on.clone(), start + Duration::from_secs(1)),
+			Some(BridgeMonitorEvent::Updated {
+				previous: off,
+				current: on.clone(),
+				changed_fields: vec![MionIdentityField::CafePowerStatus],
+			}),
+		);
+		assert_eq!(
+			state.observe(moved.clone(), start + Duration::from_secs(2)),
+			Some(BridgeMonitorEvent::Updated {
+				previous: on,
+				current: moved.clone(),
+				changed_fields: vec![MionIdentityField::IpAddress],
+			}),
+		);
+
+		assert!(state
+			.expire(start + Duration::from_secs(3), Duration::from_secs(5))
+			.is_empty());
+		assert_eq!(
+			state.expire(start + Duration::from_secs(7), Duration::from_secs(5)),
+			vec![BridgeMonitorEvent::Lost(moved.clone())],
+		);
+		// Once lost, a bridge appears again.
+		assert_eq!(
+			state.observe(moved.clone(), start + Duration::from_secs(8)),
+			Some(BridgeMonitorEvent::Appeared(moved)),
+		);
+	}
+
+	#[tokio::test]
+	pub async fn bridge_monitor_follows_emulated_mion() {
+		let emulated = EmulatedMion::spawn(test_identity(Ipv4Addr::LOCALHOST, false))
+			.await
+			.expect("Failed to spawn emulated MION!");
+		let socket = UdpSocket::bind(SocketAddrV4::new(Ipv4Addr::LOCALHOST, 0))
+			.await
+			.expect("Failed to bind local socket!");
+		let mut monitor = BridgeMonitor::spawn_with_targets(
+			true,
+			vec![(socket, SocketAddr::V4(emulated.control_address()))],
+			Duration::from_millis(50),
+			Duration::from_millis(500),
+		)
+		.expect("Failed to spawn bridge monitor!");
+
+		let appeared = timeout(Duration::from_secs(5), monitor.next_event())
+			.await
+			.expect("Timed out waiting for emulated MION to appear!");
+		assert!(
+			matches!(appeared, Some(BridgeMonitorEvent::Appeared(ref identity)) if identity.detailed_is_cafe_on() == Some(false)),
+			"Expected emulated MION to appear, got: {appeared:?}",
+		);
+
+		emulated.set_powered_on(true);
+		let updated = timeout(Duration::from_secs(5), monitor.next_event())
+			.await
+			.expect("Timed out waiting for emulated MION to update!");
+		assert!(
+			matches!(updated, Some(BridgeMonitorEvent::Updated { ref changed_fields, .. }) if *changed_fields == vec![MionIdentityField::CafePowerStatus]),
+			"Expected emulated MION to power on, got: {updated:?}",
+		);
+
+		std::mem::drop(emulated);
+		let lost = timeout(Duration::from_secs(5), monitor.next_event())
+			.await
+			.expect("Timed out waiting for emulated MION to be lost!");
+		assert!(
+			matches!(lost, Some(BridgeMonitorEvent::Lost(_))),
+			"Expected emulated MION to be lost, got: {lost:?}",
+		);
+	}
+
+	#[test]
+	pub fn discovery_target_parsing() {
+		assert_eq!(
+			DiscoveryTarget::try_from("10.0.0.2"),
+			Ok(DiscoveryTarget::Ip(Ipv4Addr::new(10, 0, 0, 2))),
+		);
+		assert_eq!(
+			DiscoveryTarget::try_from("10.0.0.0/24"),
+			Ok(DiscoveryTarget::Cidr(Ipv4Addr::new(10, 0, 0, 0), 24)),
+		);
+		assert_eq!(
+			DiscoveryTarget::try_from("broadcast:10.0.1.255"),
+			Ok(DiscoveryTarget::DirectedBroadcast(Ipv4Addr::new(
+				10, 0, 1, 255
+			))),
+		);
+		for target in ["10.0.0.2", "10.0.0.0/24", "broadcast:10.0.1.255"] {
+			assert_eq!(
+				format!(
+					"{}",
+					DiscoveryTarget::try_from(target).expect("Failed to parse target!")
+				),
+				target,
+			);
+		}
+
+		assert_eq!(
+			DiscoveryTarget::try_from("10.0.0.0/8"),
+			Err(APIError::DiscoveryRangeTooLarge("10.0.0.0/8".to_owned())),
+		);
+		for invalid in ["10.0.0.0/33", "10.0.0", "broadcast:", "my-bridge"] {
+			assert_eq!(
+				DiscoveryTarget::try_from(invalid),
+				Err(APIError::InvalidDiscoveryTarget(invalid.to_owned())),
+			);
+		}
+	}
+
+	#[test]
+	pub fn discovery_target_addresses() {
+		let range = DiscoveryTarget::Cidr(Ipv4Addr::new(10, 0, 0, 77), 24).addresses();
+		assert_eq!(range.len(), 254);
+		assert_eq!(range.first(), Some(&Ipv4Addr::new(10, 0, 0, 1)));
+		assert_eq!(range.last(), Some(&Ipv4Addr::new(10, 0, 0, 254)));
+
+		assert_eq!(
+			DiscoveryTarget::Cidr(Ipv4Addr::new(10, 0, 0, 4), 31).addresses(),
+			vec![Ipv4Addr::new(10, 0, 0, 4), Ipv4Addr::new(10, 0, 0, 5)],
+		);
+		assert_eq!(
+			DiscoveryTarget::Cidr(Ipv4Addr::new(10, 0, 0, 4), 32).addresses(),
+			vec![Ipv4Addr::new(10, 0, 0, 4)],
+		);
+		assert_eq!(
+			DiscoveryTarget::DirectedBroadcast(Ipv4Addr::new(10, 0, 1, 255)).addresses(),
+			vec![Ipv4Addr::new(10, 0, 1, 255)],
+		);
+	}
+
+	#[test]
+	pub fn interface_matcher_parsing() {
+		assert_eq!(
+			InterfaceMatcher::try_from("eth0"),
+			Ok(InterfaceMatcher::Name("eth0".to_owned())),
+		);
+		assert_eq!(
+			InterfaceMatcher::try_from(" Ethernet 2 "),
+			Ok(InterfaceMatcher::Name("Ethernet 2".to_owned())),
+		);
+		assert_eq!(
+			InterfaceMatcher::try_from("192.168.7.2"),
+			Ok(InterfaceMatcher::Ip(Ipv4Addr::new(192, 168, 7, 2))),
+		);
+		assert_eq!(
+			InterfaceMatcher::try_from("192.168.7.0/24"),
+			Ok(InterfaceMatcher::Subnet(Ipv4Addr::new(192, 168, 7, 0), 24)),
+		);
+		assert!(InterfaceMatcher::try_from("").is_err());
+		assert!(InterfaceMatcher::try_from("192.168.7.0/33").is_err());
+		assert!(InterfaceMatcher::try_from("docker0/24").is_err());
+
+		for value in ["eth0", "192.168.7.2", "192.168.7.0/24"] {
+			assert_eq!(
+				format!(
+					"{}",
+					InterfaceMatcher::try_from(value).expect("Failed to parse interface!")
+				),
+				value,
+			);
+		}
+	}
+
+	#[test]
+	pub fn interface_filter_allows() {
+		let everything = InterfaceFilter::default();
+		assert!(everything.allows("docker0", Ipv4Addr::new(172, 17, 0, 1)));
+		assert!(everything.allows("eth0", Ipv4Addr::new(192, 168, 7, 2)));
+
+		let allow_lan = InterfaceFilter::new(
+			vec![InterfaceMatcher::Subnet(Ipv4Addr::new(192, 168, 7, 0), 24)],
+			Vec::new(),
+		);
+		assert!(allow_lan.allows("eth0", Ipv4Addr::new(192, 168, 7, 2)));
+		assert!(!allow_lan.allows("eth0", Ipv4Addr::new(192, 168, 8, 2)));
+		assert!(!allow_lan.allows("docker0", Ipv4Addr::new(172, 17, 0, 1)));
+
+		let deny_docker = InterfaceFilter::new(
+			Vec::new(),
+			vec![
+				InterfaceMatcher::Name("docker0".to_owned()),
+				InterfaceMatcher::Ip(Ipv4Addr::new(10, 8, 0, 2)),
+			],
+		);
+		assert!(deny_docker.allows("eth0", Ipv4Addr::new(192, 168, 7, 2)));
+		assert!(!deny_docker.allows("docker0", Ipv4Addr::new(172, 17, 0, 1)));
+		assert!(!deny_docker.allows("tun0", Ipv4Addr::new(10, 8, 0, 2)));
+
+		// Deny always wins over allow.
+		let both = InterfaceFilter::new(
+			vec![InterfaceMatcher::Subnet(Ipv4Addr::new(10, 0, 0, 0), 8)],
+			vec![InterfaceMatcher::Name("tun0".to_owned())],
+		);
+		assert!(both.allows("eth1", Ipv4Addr::new(10, 1, 0, 2)));
+		assert!(!both.allows("tun0", Ipv4Addr::new(10, 8, 0, 2)));
+	}
+
+	// Responses from our own address are ignored, so the emulator needs to live
+	// on a second loopback address, which only works on linux.
+	#[cfg(target_os = "linux")]
+	#[tokio::test]
+	pub async fn can_sweep_for_emulated_mion() {
+		let emulated_ip = Ipv4Addr::new(127, 0, 0, 2);
+		let emulated =
+			EmulatedMion::spawn_on(test_identity(emulated_ip, false), emulated_ip, 0, 0, 0)
+				.await
+				.expect("Failed to spawn emulated MION!");
+
+		let mut recv = sweep_targets(
+			SocketAddrV4::new(Ipv4Addr::LOCALHOST, 0),
+			emulated.control_address().port(),
+			&[DiscoveryTarget::Cidr(Ipv4Addr::new(127, 0, 0, 0), 29)],
+			true,
+			NonZeroU32::new(1_000),
+		)
+		.await
+		.expect("Failed to start sweeping for emulated MION!");
+		let identity = timeout(Duration::from_secs(5), recv.recv())
+			.await
+			.expect("Timed out waiting for emulated MION to respond!")
+			.expect("Sweep finished without finding emulated MION!");
+
+		assert_eq!(identity.ip_address(), emulated_ip);
+		assert_eq!(
+			identity.mac_address(),
+			MacAddress::new([0x00, 0x25, 0x5c, 0xba, 0x5a, 0x00])
+		);
+	}
+
+	#[tokio::test]
+	pub async fn sweep_rate_is_limited() {
+		let result = sweep_targets(
+			SocketAddrV4::new(Ipv4Addr::LOCALHOST, 0),
+			DEFAULT_MION_CONTROL_PORT,
+			&[DiscoveryTarget::Ip(Ipv4Addr::LOCALHOST)],
+			true,
+			NonZeroU32::new(u32::MAX),
+		)
+		.await;
+		assert!(
+			matches!(
+				result,
+				Err(CatBridgeError::ApiError(
+					APIError::DiscoverySweepRateTooHigh(u32::MAX)
+				))
+			),
+			"Sweeping faster than we can send should be refused, got: {result:?}",
+		);
+
+		sweep_targets(
+			SocketAddrV4::new(Ipv4Addr::LOCALHOST, 0),
+			DEFAULT_MION_CONTROL_PORT,
+			&[DiscoveryTarget::Ip(Ipv4Addr::LOCALHOST)],
+			true,
+			NonZeroU32::new(MAX_SWEEP_PACKETS_PER_SECOND),
+		)
+		.await
+		.expect("Sweeping at the maximum rate should be allowed!");
+	}
 }
@@ -0,0 +1,418 @@
+//! The emulated HTTP server, serving the CGI pages a MION has.
+
+use crate::mion::{
+	emulator::EmulatedMionState,
+	image::{HddImage, ImageHeader, IMAGE_HEADER_SIZE},
+	proto::cgis::{ControlOperation, IpMode, MionSetup, Signal},
+};
+use bytes::{Bytes, BytesMut};
+use fnv::FnvHashMap;
+use hyper::{
+	body::to_bytes as read_http_body_bytes,
+	server::conn::AddrIncoming,
+	service::{make_service_fn, service_fn},
+	Body, Method, Request, Response, Server, StatusCode,
+};
+use std::{convert::Infallible, fmt::Write, net::Ipv4Addr, sync::Arc};
+use tracing::warn;
+
+/// Serve all the CGI's forever.
+pub(super) async fn serve_cgis(incoming: AddrIncoming, state: Arc<EmulatedMionState>) {
+	let make_service = make_service_fn(move |_conn| {
+		let state = state.clone();
+		async move {
+			Ok::<_, Infallible>(service_fn(move |request| {
+				handle_cgi_request(request, state.clone())
+			}))
+		}
+	});
+
+	if let Err(cause) = Server::builder(incoming)
+		.http1_only(true)
+		.serve(make_service)
+		.await
+	{
+		warn!(?cause, "emulated mion http server stopped");
+	}
+}
+
+async fn handle_cgi_request(
+	request: Request<Body>,
+	state: Arc<EmulatedMionState>,
+) -> Result<Response<Body>, Infallible> {
+	let expected_authz = state.authorization();
+	let is_authorized = request
+		.headers()
+		.get("authorization")
+		.and_then(|value| value.to_str().ok())
+		.is_some_and(|value| value == expected_authz);
+	if !is_authorized {
+		return Ok(empty_response(StatusCode::UNAUTHORIZED));
+	}
+	let path = request.uri().path().to_owned();
+	let query = request.uri().query().unwrap_or_default().to_owned();
+	if request.method() == Method::GET && path == "/setup.cgi" {
+		return Ok(html_response(&render_setup(&state.setup(), "")));
+	}
+	if request.method() != Method::POST {
+		return Ok(empty_response(StatusCode::METHOD_NOT_ALLOWED));
+	}
+
+	let content_type = request
+		.headers()
+		.get("content-type")
+		.and_then(|value| value.to_str().ok())
+		.unwrap_or_default()
+		.to_owned();
+	let Ok(body) = read_http_body_bytes(request.into_body()).await else {
+		return Ok(empty_response(StatusCode::BAD_REQUEST));
+	};
+	if path == "/image.cgi" && content_type == "application/octet-stream" {
+		// Chunks of an image are the raw body, with the fields in the query.
+		let Ok(form) = serde_urlencoded::from_str::<FnvHashMap<String, String>>(&query) else {
+			return Ok(empty_response(StatusCode::BAD_REQUEST));
+		};
+		return Ok(html_response(&handle_image(&form, Some(&body), &state)));
+	}
+	let Ok(form) = serde_urlencoded::from_bytes::<Vec<(String, String)>>(&body) else {
+		return Ok(empty_response(StatusCode::BAD_REQUEST));
+	};
+
+	let page_body = match path.as_str() {
+		"/mion/control.cgi" => handle_control(&form, &state),
+		"/signal_get.cgi" => handle_signal_get(&form, &state),
+		"/setup.cgi" => handle_setup(form, &state),
+		"/image.cgi" => handle_image(&form.into_iter().collect(), None, &state),
+		_ => return Ok(empty_response(StatusCode::NOT_FOUND)),
+	};
+
+	Ok(html_response(&page_body))
+}
+
+fn html_response(page_body: &str) -> Response<Body> {
+	Response::new(Body::from(format!(
+		"<html>\n<head><title>MION</title></head>\n<body>{page_body}</body>\n</html>\n"
+	)))
+}
+
+fn handle_setup(form: Vec<(String, String)>, state: &EmulatedMionState) -> String {
+	let setup = match MionSetup::try_from(form.into_iter().collect::<FnvHashMap<_, _>>()) {
+		Ok(setup) => setup,
+		Err(cause) => return render_setup(&state.setup(), &format!("ERROR:{cause}<br>\n")),
+	};
+	if let Err(cause) = setup.validate() {
+		return render_setup(&state.setup(), &format!("ERROR:{cause}<br>\n"));
+	}
+
+	state.set_setup(setup.clone());
+	render_setup(&setup, "INFO:settings saved, restart to apply<br>\n")
+}
+
+/// Render the setup page as a form, like a real MION would.
+fn render_setup(setup: &MionSetup, messages: &str) -> String {
+	let mut page = format!("{messages}<form method=\"post\" action=\"setup.cgi\">\n");
+	for mode in [IpMode::Dhcp, IpMode::Static] {
+		_ = writeln!(
+			&mut page,
+			"<input type=\"radio\" name=\"ip_mode\" value=\"{mode}\"{}>",
+			if setup.ip_mode() == mode {
+				" checked"
+			} else {
+				""
+			},
+		);
+	}
+	for (name, value) in setup.to_form() {
+		if name == "ip_mode" {
+			continue;
+		}
+		_ = writeln!(
+			&mut page,
+			"<input type=\"text\" name=\"{name}\" value=\"{}\">",
+			value.replace('&', "&amp;").replace('"', "&quot;"),
+		);
+	}
+	page.push_str("<input type=\"submit\" value=\"Save\">\n</form>\n");
+	page
+}
+
+/// This accepts the made up `image.cgi` protocol from [`crate::mion::image`],
+/// we don't know what a real MION expects.
+fn handle_image(
+	fields: &FnvHashMap<String, String>,
+	chunk: Option<&Bytes>,
+	state: &EmulatedMionState,
+) -> String {
+	let name = fields.get("name").map(String::as_str).unwrap_or_default();
+	let number = |field: &str| {
+		fields
+			.get(field)
+			.and_then(|value| value.parse::<u64>().ok())
+			.ok_or_else(|| format!("missing, or invalid {field}"))
+	};
+	let mut hdd = state.hdd();
+
+	let result = match (fields.get("operation").map(String::as_str), chunk) {
+		(Some("list"), None) => {
+			let mut lines = String::new();
+			for image in &hdd.images {
+				_ = writeln!(&mut lines, "IMAGE:{image}<br>");
+			}
+			Ok(lines)
+		}
+		(Some("select"), None) => hdd
+			.select(name)
+			.map(|()| "INFO:image selected<br>\n".to_owned()),
+		(Some("delete"), None) => hdd
+			.delete(name)
+			.map(|()| "INFO:image deleted<br>\n".to_owned()),
+		(Some("begin"), None) => number("size")
+			.and_then(|size| hdd.begin(name, size))
+			.map(|received| format!("OFFSET:{received}<br>\n")),
+		(Some("chunk"), Some(chunk)) => number("offset")
+			.and_then(|offset| hdd.write_chunk(name, offset, chunk))
+			.map(|received| format!("OFFSET:{received}<br>\n")),
+		(Some("finish"), None) => hdd
+			.finish(name)
+			.map(|()| "INFO:image written<br>\n".to_owned()),
+		_ => Err("unknown operation".to_owned()),
+	};
+
+	match result {
+		Ok(lines) => format!("{lines}RESULT:OK<br>\n"),
+		Err(error) => format!("ERROR:{error}<br>\nRESULT:NG<br>\n"),
+	}
+}
+
+/// The images on the HDD of an emulated CAT-DEV.
+///
+/// Only the header of each upload is kept, so uploading a real image doesn't
+/// need gigabytes of memory.
+#[derive(Debug, Default)]
+pub(super) struct EmulatedHdd {
+	images: Vec<HddImage>,
+	uploads: FnvHashMap<String, PartialUpload>,
+}
+
+#[derive(Debug)]
+struct PartialUpload {
+	size: u64,
+	received: u64,
+	header: BytesMut,
+}
+
+impl EmulatedHdd {
+	pub(super) fn images(&self) -> Vec<HddImage> {
+		self.images.clone()
+	}
+
+	fn select(&mut self, name: &str) -> Result<(), String> {
+		if !self.images.iter().any(|image| image.name() == name) {
+			return Err(format!("no image named {name}"));
+		}
+		for image in &mut self.images {
+			*image = HddImage::new(image.name().to_owned(), image.size(), image.name() == name);
+		}
+		Ok(())
+	}
+
+	fn delete(&mut self, name: &str) -> Result<(), String> {
+		let images_before = self.images.len();
+		self.images.retain(|image| image.name() != name);
+		if self.images.len() == images_before {
+			Err(format!("no image named {name}"))
+		} else {
+			Ok(())
+		}
+	}
+
+	fn begin(&mut self, name: &str, size: u64) -> Result<u64, String> {
+		if self.images.iter().any(|image| image.name() == name) {
+			return Err(format!("an image named {name} already exists"));
+		}
+		let new_upload = || PartialUpload {
+			size,
+			received: 0,
+			header: BytesMut::with_capacity(IMAGE_HEADER_SIZE),
+		};
+		let upload = self
+			.uploads
+			.entry(name.to_owned())
+			.or_insert_with(new_upload);
+		// Resuming only makes sense for the same image.
+		if upload.size != size {
+			*upload = new_upload();
+		}
+		Ok(upload.received)
+	}
+
+	fn write_chunk(&mut self, name: &str, offset: u64, chunk: &[u8]) -> Result<u64, String> {
+		let Some(upload) = self.uploads.get_mut(name) else {
+			return Err(format!("no upload of {name} has been started"));
+		};
+		if offset != upload.received {
+			return Err(format!("expected a chunk at offset {}", upload.received));
+		}
+		if offset + chunk.len() as u64 > upload.size {
+			return Err("the chunk goes past the end of the image".to_owned());
+		}
+
+		let header_remaining = IMAGE_HEADER_SIZE.saturating_sub(upload.header.len());
+		upload
+			.header
+			.extend_from_slice(&chunk[..header_remaining.min(chunk.len())]);
+		upload.received += chunk.len() as u64;
+		Ok(upload.received)
+	}
+
+	fn finish(&mut self, name: &str) -> Result<(), String> {
+		let Some(upload) = self.uploads.get(name) else {
+			return Err(format!("no upload of {name} has been started"));
+		};
+		if upload.received != upload.size {
+			return Err(format!(
+				"only {} of {} bytes have been uploaded",
+				upload.received, upload.size,
+			));
+		}
+		let header = ImageHeader::parse(&upload.header).map_err(|cause| format!("{cause}"))?;
+		if header.image_size() != upload.size {
+			return Err(format!(
+				"the header says the image is {} bytes, but {} bytes were uploaded",
+				header.image_size(),
+				upload.size,
+			));
+		}
+
+		self.uploads.remove(name);
+		self.images
+			.push(HddImage::new(name.to_owned(), header.image_size(), false));
+		Ok(())
+	}
+}
+
+fn handle_control(form: &[(String, String)], state: &EmulatedMionState) -> String {
+	let fields = form.iter().cloned().collect::<FnvHashMap<String, String>>();
+	let Some(operation) = fields
+		.get("operation")
+		.and_then(|op| ControlOperation::try_from(op.as_str()).ok())
+	else {
+		return "ERROR:unknown operation<br>\nRESULT:NG<br>\n".to_owned();
+	};
+
+	match operation {
+		ControlOperation::GetInfo => {
+			let identity = state.identity_for(true);
+			let mut lines = String::new();
+			for (key, value) in [
+				("name", identity.name().to_owned()),
+				("mac_address", format!("{}", identity.mac_address())),
+				("fw_version", identity.firmware_version()),
+				("fpga_version", identity.detailed_fpga_version()),
+				(
+					"sdk_version",
+					identity.detailed_sdk_version().unwrap_or_default(),
+				),
+				(
+					"power",
+					if state.is_powered_on() { "on" } else { "off" }.to_owned(),
+				),
+				(
+					"host",
+					format!("{}", state.owner().unwrap_or(Ipv4Addr::UNSPECIFIED)),
+				),
+				(
+					"emulation",
+					if state.is_emulating() { "on" } else { "off" }.to_owned(),
+				),
+			] {
+				_ = writeln!(&mut lines, "{key}:{value}<br>");
+			}
+			if let Some(boot_type) = identity.detailed_boot_type() {
+				_ = writeln!(&mut lines, "boot_type:{boot_type}<br>");
+			}
+			if let Some(atapi_port) = state.set_params().get("atapi_port") {
+				_ = writeln!(&mut lines, "atapi_port:{atapi_port}<br>");
+			}
+			lines
+		}
+		ControlOperation::SetParam => {
+			for (key, value) in form {
+				if key != "operation" {
+					state.insert_set_param(key.clone(), value.clone());
+				}
+			}
+			"RESULT:OK<br>\n".to_owned()
+		}
+		ControlOperation::PowerOn | ControlOperation::PowerOnV2 => {
+			let host = requesting_host(&fields);
+			if let Some(in_use) = in_use_response(state, host) {
+				return in_use;
+			}
+			state.set_owner(host);
+			state.set_emulating(fields.get("emulation").is_some_and(|value| value == "on"));
+			state.set_powered_on(true);
+			"INFO:powering on<br>\nRESULT:OK<br>\n".to_owned()
+		}
+		ControlOperation::PowerOff => {
+			let host = requesting_host(&fields);
+			if let Some(in_use) = in_use_response(state, host) {
+				return in_use;
+			}
+			state.set_owner(None);
+			state.set_emulating(false);
+			state.set_powered_on(false);
+			"INFO:powering off<br>\nRESULT:OK<br>\n".to_owned()
+		}
+		ControlOperation::Reset => {
+			let host = requesting_host(&fields);
+			if let Some(in_use) = in_use_response(state, host) {
+				return in_use;
+			}
+			state.set_powered_on(true);
+			"INFO:resetting<br>\nRESULT:OK<br>\n".to_owned()
+		}
+	}
+}
+
+fn requesting_host(fields: &FnvHashMap<String, String>) -> Option<Ipv4Addr> {
+	fields
+		.get("host")
+		.and_then(|host| host.parse::<Ipv4Addr>().ok())
+}
+
+/// If someone other than `host` currently owns this MION, the response a
+/// real MION would give back.
+fn in_use_response(state: &EmulatedMionState, host: Option<Ipv4Addr>) -> Option<String> {
+	match state.owner() {
+		Some(owner) if Some(owner) != host => Some(format!(
+			"ERROR:MION is in use by host {owner}<br>\nRESULT:NG<br>\n"
+		)),
+		_ => None,
+	}
+}
+
+fn handle_signal_get(form: &[(String, String)], state: &EmulatedMionState) -> String {
+	let signal = form.iter().find_map(|(key, value)| {
+		if key == "sig" {
+			Some(value.as_str())
+		} else {
+			None
+		}
+	});
+
+	let Some(signal) = signal.and_then(|name| Signal::try_from(name).ok()) else {
+		return "ERROR:unknown signal".to_owned();
+	};
+	// We don't know what a real MION reports here, this is just something that
+	// changes with the power state.
+	match signal {
+		Signal::Vdd2 => if state.is_powered_on() { "1" } else { "0" }.to_owned(),
+	}
+}
+
+fn empty_response(status: StatusCode) -> Response<Body> {
+	let mut response = Response::new(Body::empty());
+	*response.status_mut() = status;
+	response
+}
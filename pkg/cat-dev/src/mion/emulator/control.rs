@@ -0,0 +1,44 @@
+//! The emulated UDP control port, which answers announcements.
+
+use crate::mion::{emulator::EmulatedMionState, proto::control::MionIdentityAnnouncement};
+use bytes::Bytes;
+use std::sync::Arc;
+use tokio::net::UdpSocket;
+use tracing::{debug, warn};
+
+/// Serve the control port forever, answering any announcements we receive.
+///
+/// Announcements are read from `listen_socket`, but are always answered from
+/// `reply_socket`, this way we can listen on a broadcast address (which can't
+/// be sent from), and still respond.
+pub(super) async fn serve_control_port(
+	listen_socket: Arc<UdpSocket>,
+	reply_socket: Arc<UdpSocket>,
+	state: Arc<EmulatedMionState>,
+) {
+	let mut buff = [0_u8; 1024];
+
+	loop {
+		let (read_size, from) = match listen_socket.recv_from(&mut buff).await {
+			Ok(data) => data,
+			Err(cause) => {
+				warn!(?cause, "emulated mion failed to receive on control port");
+				continue;
+			}
+		};
+
+		let packet = Bytes::copy_from_slice(&buff[..read_size]);
+		let announcement = match MionIdentityAnnouncement::try_from(packet) {
+			Ok(announcement) => announcement,
+			Err(cause) => {
+				debug!(?cause, %from, "emulated mion ignoring non-announcement packet");
+				continue;
+			}
+		};
+
+		let response = Bytes::from(state.identity_for(announcement.is_detailed()));
+		if let Err(cause) = reply_socket.send_to(&response, from).await {
+			warn!(?cause, %from, "emulated mion failed to respond to announcement");
+		}
+	}
+}
@@ -0,0 +1,1137 @@
+//! An in-process "fake" MION, that can be used to test against without
+//! needing to have a real CAT-DEV on your network.
+//!
+//! The emulator speaks the same protocols a real MION does:
+//!
+//! - UDP on the control port, answering [`MionIdentityAnnouncement`]'s with
+//!   a configurable [`MionIdentity`].
+//! - TCP on the parameter port, serving up a mutable 512 byte parameter
+//!   space.
+//! - HTTP serving `/mion/control.cgi`, `/signal_get.cgi`, `/setup.cgi`, and
+//!   `/image.cgi` (checking the same basic authorization header real MIONs
+//!   do).
+//!
+//! By default everything is bound to loopback with ports chosen by the OS,
+//! so many emulators can run at once. Because the HTTP APIs always talk to
+//! port 80 on the MION, you should use [`EmulatedMion::http_client`] which
+//! returns a client that always connects to the emulator no matter what
+//! address gets requested.
+//!
+//! *note: this is only available with the `emulator` feature enabled, it is
+//! not meant to be shipped in any real tool.*
+//!
+//! [`MionIdentityAnnouncement`]: crate::mion::proto::control::MionIdentityAnnouncement
+
+mod cgis;
+mod control;
+mod parameter;
+
+use crate::{
+	errors::{APIError, CatBridgeError, NetworkError},
+	mion::{
+		cgis::{basic_authorization, DEFAULT_MION_PASSWORD, DEFAULT_MION_USERNAME},
+		emulator::cgis::EmulatedHdd,
+		image::HddImage,
+		proto::{
+			cgis::{IpMode, MionSetup},
+			control::{MionIdentity, DETAILED_CAFE_ON_OFFSET},
+			DEFAULT_MION_CONTROL_PORT, DEFAULT_MION_PARAMETER_PORT,
+		},
+	},
+};
+use bytes::{Bytes, BytesMut};
+use fnv::FnvHashMap;
+use hyper::{client::HttpConnector, server::conn::AddrIncoming, service::Service, Client, Uri};
+use std::{
+	net::{Ipv4Addr, SocketAddr, SocketAddrV4},
+	sync::{
+		atomic::{AtomicBool, Ordering},
+		Arc, Mutex, MutexGuard,
+	},
+	task::{Context, Poll},
+};
+use tokio::{
+	net::{TcpListener, UdpSocket},
+	task::JoinSet,
+};
+
+/// A fake MION running in the background of the current tokio runtime.
+///
+/// All of the background tasks are stopped when this gets dropped.
+#[derive(Debug)]
+pub struct EmulatedMion {
+	control_address: SocketAddrV4,
+	control_socket: Arc<UdpSocket>,
+	http_address: SocketAddrV4,
+	parameter_address: SocketAddrV4,
+	state: Arc<EmulatedMionState>,
+	// Kept around so the tasks get aborted when we get dropped.
+	tasks: JoinSet<()>,
+}
+
+impl EmulatedMion {
+	/// Spawn a new emulated MION bound to loopback, with every port picked by
+	/// the OS.
+	///
+	/// The identity passed in is what will be announced, if it contains
+	/// detailed data the emulator will answer detailed announcements,
+	/// otherwise it acts like an old firmware that doesn't know about them.
+	///
+	/// ## Errors
+	///
+	/// - If we cannot bind any of the sockets the MION listens on.
+	pub async fn spawn(identity: MionIdentity) -> Result<Self, CatBridgeError> {
+		Self::spawn_on(identity, Ipv4Addr::LOCALHOST, 0, 0, 0).await
+	}
+
+	/// Spawn a new emulated MION bound to a specific address, and specific
+	/// ports.
+	///
+	/// Any port passed in as `0` will be picked by the OS.
+	///
+	/// ## Errors
+	///
+	/// - If we cannot bind any of the sockets the MION listens on.
+	pub async fn spawn_on(
+		identity: MionIdentity,
+		bind_address: Ipv4Addr,
+		control_port: u16,
+		parameter_port: u16,
+		http_port: u16,
+	) -> Result<Self, CatBridgeError> {
+		let control_socket = Arc::new(
+			UdpSocket::bind(SocketAddrV4::new(bind_address, control_port))
+				.await
+				.map_err(|_| NetworkError::BindAddressError)?,
+		);
+		let parameter_listener = TcpListener::bind(SocketAddrV4::new(bind_address, parameter_port))
+			.await
+			.map_err(|_| NetworkError::BindAddressError)?;
+		let http_listener = TcpListener::bind(SocketAddrV4::new(bind_address, http_port))
+			.await
+			.map_err(|_| NetworkError::BindAddressError)?;
+
+		let control_address =
+			v4_address(control_socket.local_addr().map_err(NetworkError::IOError)?)?;
+		let parameter_address = v4_address(
+			parameter_listener
+				.local_addr()
+				.map_err(NetworkError::IOError)?,
+		)?;
+		let http_address = v4_address(http_listener.local_addr().map_err(NetworkError::IOError)?)?;
+		let http_incoming = AddrIncoming::from_listener(http_listener)
+			.map_err(|_| NetworkError::BindAddressError)?;
+
+		let state = Arc::new(EmulatedMionState::new(identity));
+		let mut tasks = JoinSet::new();
+		tasks
+			.build_task()
+			.name(&format!("cat_dev::emulator::control::{control_address}"))
+			.spawn(control::serve_control_port(
+				control_socket.clone(),
+				control_socket.clone(),
+				state.clone(),
+			))
+			.map_err(|_| CatBridgeError::SpawnFailure)?;
+		tasks
+			.build_task()
+			.name(&format!(
+				"cat_dev::emulator::parameter::{parameter_address}"
+			))
+			.spawn(parameter::serve_parameter_port(
+				parameter_listener,
+				state.clone(),
+			))
+			.map_err(|_| CatBridgeError::SpawnFailure)?;
+		tasks
+			.build_task()
+			.name(&format!("cat_dev::emulator::cgis::{http_address}"))
+			.spawn(cgis::serve_cgis(http_incoming, state.clone()))
+			.map_err(|_| CatBridgeError::SpawnFailure)?;
+
+		Ok(Self {
+			control_address,
+			control_socket,
+			http_address,
+			parameter_address,
+			state,
+			tasks,
+		})
+	}
+
+	/// Also listen for announcements on another address, most commonly a
+	/// broadcast address.
+	///
+	/// Responses are always sent from the main control socket. This is what
+	/// allows running tools like `findbridge` on the same machine as the
+	/// emulator, as `findbridge` needs to bind to the port it broadcasts on
+	/// itself. So you can bind the main control port to `0`, and listen on
+	/// the broadcast address of an interface with the real control port.
+	///
+	/// *note: not every OS allows binding to a broadcast address, this is known
+	/// to work on linux.*
+	///
+	/// ## Errors
+	///
+	/// - If we cannot bind to the address.
+	/// - If we cannot spawn the task to listen in the background.
+	pub async fn listen_for_broadcasts(
+		&mut self,
+		address: SocketAddrV4,
+	) -> Result<(), CatBridgeError> {
+		let listen_socket = Arc::new(
+			UdpSocket::bind(address)
+				.await
+				.map_err(|_| NetworkError::BindAddressError)?,
+		);
+		self.tasks
+			.build_task()
+			.name(&format!("cat_dev::emulator::control::{address}"))
+			.spawn(control::serve_control_port(
+				listen_socket,
+				self.control_socket.clone(),
+				self.state.clone(),
+			))
+			.map_err(|_| CatBridgeError::SpawnFailure)?;
+
+		Ok(())
+	}
+
+	/// The address the UDP control port is listening on.
+	#[must_use]
+	pub const fn control_address(&self) -> SocketAddrV4 {
+		self.control_address
+	}
+
+	/// The address the TCP parameter port is listening on.
+	#[must_use]
+	pub const fn parameter_address(&self) -> SocketAddrV4 {
+		self.parameter_address
+	}
+
+	/// The address the HTTP server serving all the CGI's is listening on.
+	#[must_use]
+	pub const fn http_address(&self) -> SocketAddrV4 {
+		self.http_address
+	}
+
+	/// Get an HTTP client that will always connect to this emulated MION.
+	///
+	/// This can be passed to any of the `*_with_raw_client` functions in
+	/// [`crate::mion::cgis`], or wrapped in a
+	/// [`crate::mion::cgis::MionHttpClient`].
+	#[must_use]
+	pub fn http_client(&self) -> Client<EmulatedMionConnector> {
+		Client::builder().build(EmulatedMionConnector::new(self.http_address))
+	}
+
+	/// The identity as it would be announced right now to a detailed
+	/// announcement.
+	#[must_use]
+	pub fn identity(&self) -> MionIdentity {
+		self.state.identity_for(true)
+	}
+
+	/// Get a copy of the current 512 bytes of parameter space.
+	#[must_use]
+	pub fn parameters(&self) -> Bytes {
+		self.state.parameters()
+	}
+
+	/// Replace the entire parameter space of this MION.
+	///
+	/// ## Errors
+	///
+	/// - If the `parameters` argument is not exactly 512 bytes long.
+	pub fn replace_parameters(&self, parameters: &[u8]) -> Result<(), APIError> {
+		self.state.replace_parameters(parameters)
+	}
+
+	/// Set a single byte in the parameter space of this MION.
+	///
+	/// ## Errors
+	///
+	/// - If the index is not within the range of valid parameters.
+	pub fn set_parameter(&self, index: usize, value: u8) -> Result<(), APIError> {
+		self.state.set_parameter(index, value)
+	}
+
+	/// If cafe is currently "powered on".
+	#[must_use]
+	pub fn is_powered_on(&self) -> bool {
+		self.state.is_powered_on()
+	}
+
+	/// If cafe was powered on with disc emulation (e.g. to boot off of PCFS).
+	#[must_use]
+	pub fn is_emulating(&self) -> bool {
+		self.state.is_emulating()
+	}
+
+	/// Flip the power state of cafe, as if someone had pressed the button.
+	pub fn set_powered_on(&self, powered_on: bool) {
+		self.state.set_powered_on(powered_on);
+	}
+
+	/// The host that currently "owns" this MION, if any.
+	///
+	/// A host becomes the owner when it powers on cafe, and stops being the
+	/// owner when it powers cafe off. While owned any other host trying to
+	/// control power gets told the MION is in use.
+	#[must_use]
+	pub fn owner(&self) -> Option<Ipv4Addr> {
+		self.state.owner()
+	}
+
+	/// Change the host that currently "owns" this MION.
+	pub fn set_owner(&self, owner: Option<Ipv4Addr>) {
+		self.state.set_owner(owner);
+	}
+
+	/// Change the username, and password the CGI pages accept, like a bridge
+	/// that has had it's web password changed.
+	pub fn set_credentials(&self, username: &str, password: &str) {
+		self.state
+			.set_authorization(basic_authorization(username, password));
+	}
+
+	/// The current contents of the setup page.
+	///
+	/// Changing the setup page doesn't change the identity, or ports the
+	/// emulator is actually using, just like a real MION until it restarts.
+	#[must_use]
+	pub fn setup(&self) -> MionSetup {
+		self.state.setup()
+	}
+
+	/// Every value that has been set through `set_param` on `control.cgi`.
+	#[must_use]
+	pub fn set_params(&self) -> FnvHashMap<String, String> {
+		self.state.set_params()
+	}
+
+	/// The images that have been uploaded to the HDD through `image.cgi`.
+	#[must_use]
+	pub fn hdd_images(&self) -> Vec<HddImage> {
+		self.state.hdd().images()
+	}
+}
+
+/// A connector for [`hyper`] that connects to an emulated MION no matter what
+/// URI is actually requested.
+#[derive(Clone, Debug)]
+pub struct EmulatedMionConnector {
+	inner: HttpConnector,
+	target: Uri,
+}
+
+impl EmulatedMionConnector {
+	/// Create a new connector that will always connect to `http_address`.
+	#[must_use]
+	pub fn new(http_address: SocketAddrV4) -> Self {
+		Self {
+			inner: HttpConnector::new(),
+			// A socket address is always a valid authority.
+			target: Uri::builder()
+				.scheme("http")
+				.authority(format!("{http_address}"))
+				.path_and_query("/")
+				.build()
+				.unwrap_or_default(),
+		}
+	}
+}
+
+impl Service<Uri> for EmulatedMionConnector {
+	type Response = <HttpConnector as Service<Uri>>::Response;
+	type Error = <HttpConnector as Service<Uri>>::Error;
+	type Future = <HttpConnector as Service<Uri>>::Future;
+
+	fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+		self.inner.poll_ready(cx)
+	}
+
+	fn call(&mut self, _requested: Uri) -> Self::Future {
+		self.inner.call(self.target.clone())
+	}
+}
+
+/// All the state of an emulated MION, shared between each of the services.
+#[derive(Debug)]
+struct EmulatedMionState {
+	identity: MionIdentity,
+	parameters: Mutex<[u8; 512]>,
+	powered_on: AtomicBool,
+	emulating: AtomicBool,
+	owner: Mutex<Option<Ipv4Addr>>,
+	setup: Mutex<MionSetup>,
+	set_params: Mutex<FnvHashMap<String, String>>,
+	authorization: Mutex<String>,
+	hdd: Mutex<EmulatedHdd>,
+}
+
+impl EmulatedMionState {
+	fn new(identity: MionIdentity) -> Self {
+		let powered_on = identity.detailed_is_cafe_on().unwrap_or(false);
+		let setup = MionSetup::new(
+			IpMode::Dhcp,
+			identity.ip_address(),
+			Ipv4Addr::new(255, 255, 255, 0),
+			Ipv4Addr::UNSPECIFIED,
+			identity.name().to_owned(),
+			DEFAULT_MION_CONTROL_PORT + 1,
+			DEFAULT_MION_CONTROL_PORT + 2,
+			DEFAULT_MION_CONTROL_PORT + 3,
+			DEFAULT_MION_PARAMETER_PORT,
+		);
+
+		Self {
+			identity,
+			parameters: Mutex::new([0; 512]),
+			powered_on: AtomicBool::new(powered_on),
+			emulating: AtomicBool::new(false),
+			owner: Mutex::new(None),
+			hdd: Mutex::new(EmulatedHdd::default()),
+			authorization: Mutex::new(basic_authorization(
+				DEFAULT_MION_USERNAME,
+				DEFAULT_MION_PASSWORD,
+			)),
+			setup: Mutex::new(setup),
+			set_params: Mutex::new(FnvHashMap::default()),
+		}
+	}
+
+	/// Get the identity to respond with, keeping the detailed data in sync
+	/// with the current state.
+	fn identity_for(&self, detailed: bool) -> MionIdentity {
+		let detailed_data = if detailed {
+			self.identity.raw_detailed_data().map(|data| {
+				let mut updated = BytesMut::from(&data[..]);
+				updated[DETAILED_CAFE_ON_OFFSET] = u8::from(self.is_powered_on());
+				updated.freeze()
+			})
+		} else {
+			None
+		};
+
+		self.identity.with_detailed_data(detailed_data)
+	}
+
+	fn parameters(&self) -> Bytes {
+		let guard = self
+			.parameters
+			.lock()
+			.unwrap_or_else(std::sync::PoisonError::into_inner);
+		Bytes::copy_from_slice(&guard[..])
+	}
+
+	fn replace_parameters(&self, parameters: &[u8]) -> Result<(), APIError> {
+		if parameters.len() != 512 {
+			return Err(APIError::MIONParameterBodyNotCorrectLength(
+				parameters.len(),
+			));
+		}
+
+		self.parameters
+			.lock()
+			.unwrap_or_else(std::sync::PoisonError::into_inner)
+			.copy_from_slice(parameters);
+		Ok(())
+	}
+
+	fn set_parameter(&self, index: usize, value: u8) -> Result<(), APIError> {
+		if index > 511 {
+			return Err(APIError::MIONParameterNotInRage(index));
+		}
+
+		self.parameters
+			.lock()
+			.unwrap_or_else(std::sync::PoisonError::into_inner)[index] = value;
+		Ok(())
+	}
+
+	fn is_powered_on(&self) -> bool {
+		self.powered_on.load(Ordering::SeqCst)
+	}
+
+	fn set_powered_on(&self, powered_on: bool) {
+		self.powered_on.store(powered_on, Ordering::SeqCst);
+	}
+
+	fn is_emulating(&self) -> bool {
+		self.emulating.load(Ordering::SeqCst)
+	}
+
+	fn set_emulating(&self, emulating: bool) {
+		self.emulating.store(emulating, Ordering::SeqCst);
+	}
+
+	fn owner(&self) -> Option<Ipv4Addr> {
+		*self
+			.owner
+			.lock()
+			.unwrap_or_else(std::sync::PoisonError::into_inner)
+	}
+
+	fn set_owner(&self, owner: Option<Ipv4Addr>) {
+		*self
+			.owner
+			.lock()
+			.unwrap_or_else(std::sync::PoisonError::into_inner) = owner;
+	}
+
+	fn authorization(&self) -> String {
+		self.authorization
+			.lock()
+			.unwrap_or_else(std::sync::PoisonError::into_inner)
+			.clone()
+	}
+
+	fn set_authorization(&self, authorization: String) {
+		*self
+			.authorization
+			.lock()
+			.unwrap_or_else(std::sync::PoisonError::into_inner) = authorization;
+	}
+
+	fn setup(&self) -> MionSetup {
+		self.setup
+			.lock()
+			.unwrap_or_else(std::sync::PoisonError::into_inner)
+			.clone()
+	}
+
+	fn set_setup(&self, setup: MionSetup) {
+		*self
+			.setup
+			.lock()
+			.unwrap_or_else(std::sync::PoisonError::into_inner) = setup;
+	}
+
+	fn hdd(&self) -> MutexGuard<'_, EmulatedHdd> {
+		self.hdd
+			.lock()
+			.unwrap_or_else(std::sync::PoisonError::into_inner)
+	}
+
+	fn set_params(&self) -> FnvHashMap<String, String> {
+		self.set_params
+			.lock()
+			.unwrap_or_else(std::sync::PoisonError::into_inner)
+			.clone()
+	}
+
+	fn insert_set_param(&self, key: String, value: String) {
+		self.set_params
+			.lock()
+			.unwrap_or_else(std::sync::PoisonError::into_inner)
+			.insert(key, value);
+	}
+}
+
+/// We only ever bind to IPv4 addresses, so a local address should never be
+/// anything else.
+fn v4_address(address: SocketAddr) -> Result<SocketAddrV4, NetworkError> {
+	match address {
+		SocketAddr::V4(v4) => Ok(v4),
+		SocketAddr::V6(_) => Err(NetworkError::BindAddressError),
+	}
+}
+
+#[cfg(test)]
+mod unit_tests {
+	use super::*;
+	#[cfg(target_os = "linux")]
+	use crate::mion::discovery::{sweep_targets, DiscoveryTarget};
+	use crate::mion::{
+		cgis::{
+			do_raw_setup_request, get_info_with_raw_client, get_mion_info_with_raw_client,
+			get_power_state_with_raw_client, get_setup_with_raw_client,
+			get_signals_with_raw_client, get_vdd2_with_raw_client, power_off_with_raw_client,
+			power_on_with_raw_client, reset_with_raw_client,
+			set_experimental_param_with_raw_client, set_experimental_setup_with_raw_client,
+			set_param_with_raw_client, MionHttpClient, RetryPolicy,
+		},
+		image::{
+			delete_experimental_image_with_raw_client, list_images_with_raw_client,
+			select_experimental_image_with_raw_client, upload_experimental_image_with_raw_client,
+			ImageHeader, ImageKind, IMAGE_UPLOAD_CHUNK_SIZE,
+		},
+		parameter::{get_parameters, set_parameters},
+		proto::{
+			cgis::{
+				PowerOffRequest, PowerOnRequest, PowerState, ResetRequest, SetParameter, Signal,
+			},
+			parameter::well_known::ParameterLocationSpecification,
+		},
+	};
+	use mac_address::MacAddress;
+	use tokio::time::Duration;
+
+	fn test_identity() -> MionIdentity {
+		let mut detailed = vec![0_u8; 239];
+		detailed[227..231].copy_from_slice(&[2, 12, 13, 0]);
+		detailed[231] = 1;
+		detailed[232] = 2;
+
+		MionIdentity::new(
+			Some(Bytes::from(detailed)),
+			[0, 14, 80, 1],
+			[0x13, 0x05, 0x20, 0x71],
+			Ipv4Addr::LOCALHOST,
+			MacAddress::new([0x00, 0x25, 0x5c, 0xba, 0x5a, 0x00]),
+			"00-25-5C-BA-5A-00".to_owned(),
+		)
+		.expect("Failed to create test identity!")
+	}
+
+	/// Spawn an emulator that answers on a separate broadcast listener, on a port
+	/// that isn't being used by anything else.
+	///
+	/// Not every host has an interface we can broadcast on, so the listener sits
+	/// on its own loopback address, and gets sent announcements directly.
+	/// Discovery ignores responses from its own address, so the emulator has to
+	/// answer from yet another loopback address, which only works on linux.
+	#[cfg(target_os = "linux")]
+	async fn spawn_broadcast_emulator() -> (EmulatedMion, SocketAddrV4) {
+		let broadcast_address =
+			std::net::UdpSocket::bind(SocketAddrV4::new(Ipv4Addr::new(127, 0, 0, 3), 0))
+				.and_then(|socket| socket.local_addr())
+				.map(|address| v4_address(address).expect("Bound to a non IPv4 address!"))
+				.expect("Failed to find a free port!");
+		let mut emulator =
+			EmulatedMion::spawn_on(test_identity(), Ipv4Addr::new(127, 0, 0, 2), 0, 0, 0)
+				.await
+				.expect("Failed to spawn emulated MION!");
+		emulator
+			.listen_for_broadcasts(broadcast_address)
+			.await
+			.expect("Failed to listen for broadcasts!");
+
+		(emulator, broadcast_address)
+	}
+
+	/// Send announcements straight to the broadcast listener of an emulator.
+	#[cfg(target_os = "linux")]
+	async fn discover_at(
+		broadcast_address: SocketAddrV4,
+		fetch_detailed_info: bool,
+	) -> MionIdentity {
+		let mut recv = sweep_targets(
+			SocketAddrV4::new(Ipv4Addr::LOCALHOST, 0),
+			broadcast_address.port(),
+			&[DiscoveryTarget::Ip(*broadcast_address.ip())],
+			fetch_detailed_info,
+			None,
+		)
+		.await
+		.expect("Failed to start discovering bridges!");
+		tokio::time::timeout(Duration::from_secs(5), recv.recv())
+			.await
+			.expect("Timed out waiting for emulated MION to respond!")
+			.expect("Discovery stream closed without finding emulated MION!")
+	}
+
+	#[cfg(target_os = "linux")]
+	#[tokio::test]
+	pub async fn can_discover_emulated_mion() {
+		let (emulator, broadcast_address) = spawn_broadcast_emulator().await;
+		emulator.set_powered_on(true);
+
+		let identity = discover_at(broadcast_address, true).await;
+		assert_eq!(identity.ip_address(), Ipv4Addr::new(127, 0, 0, 2));
+		assert_eq!(identity.mac_address(), test_identity().mac_address());
+		assert_eq!(identity.detailed_sdk_version(), Some("2.12.13".to_owned()));
+		assert_eq!(identity.detailed_is_cafe_on(), Some(true));
+	}
+
+	#[cfg(target_os = "linux")]
+	#[tokio::test]
+	pub async fn can_discover_emulated_mion_without_detail() {
+		let (_emulator, broadcast_address) = spawn_broadcast_emulator().await;
+
+		let identity = discover_at(broadcast_address, false).await;
+		// Non detailed requests should get non-detailed responses.
+		assert!(!identity.is_detailed());
+		assert_eq!(identity.name(), "00-25-5C-BA-5A-00");
+	}
+
+	#[tokio::test]
+	pub async fn can_get_and_set_parameters() {
+		let emulator = EmulatedMion::spawn(test_identity())
+			.await
+			.expect("Failed to spawn emulated MION!");
+		emulator
+			.set_parameter(3, 2)
+			.expect("Failed to set parameter on emulator!");
+
+		let dumped = get_parameters(
+			*emulator.parameter_address().ip(),
+			Some(emulator.parameter_address().port()),
+			None,
+		)
+		.await
+		.expect("Failed to get parameters from emulated MION!");
+		assert_eq!(dumped.get_parameter_by_name("sdk-major"), Ok(2));
+		assert_eq!(dumped.get_parameter_by_index(4), Ok(0));
+
+		let response = set_parameters(
+			[
+				(ParameterLocationSpecification::Index(4), 12_u8),
+				(
+					ParameterLocationSpecification::NameLike("sdk-misc".to_owned()),
+					13_u8,
+				),
+			]
+			.into_iter(),
+			*emulator.parameter_address().ip(),
+			Some(emulator.parameter_address().port()),
+			None,
+		)
+		.await
+		.expect("Failed to set parameters on emulated MION!");
+		assert!(response.is_success());
+		assert_eq!(&emulator.parameters()[3..6], &[2, 12, 13]);
+	}
+
+	#[tokio::test]
+	pub async fn can_call_cgis() {
+		let emulator = EmulatedMion::spawn(test_identity())
+			.await
+			.expect("Failed to spawn emulated MION!");
+		let client = emulator.http_client();
+
+		let info = get_mion_info_with_raw_client(&client, Ipv4Addr::LOCALHOST, "test")
+			.await
+			.expect("Failed to get info from emulated MION!");
+		assert_eq!(info.name(), Some("00-25-5C-BA-5A-00"));
+		assert_eq!(info.firmware_version(), Some("0.0.14.80"));
+		assert_eq!(info.power(), Some(PowerState::Off));
+		assert_eq!(info.owner(), None);
+		assert_eq!(info.emulation(), Some(false));
+		assert_eq!(info.atapi_port(), None);
+		assert_eq!(
+			get_info_with_raw_client(&client, Ipv4Addr::LOCALHOST, "test")
+				.await
+				.expect("Failed to get raw info from emulated MION!")
+				.get("name")
+				.map(String::as_str),
+			Some("00-25-5C-BA-5A-00"),
+		);
+
+		assert!(set_param_with_raw_client(
+			&client,
+			Ipv4Addr::LOCALHOST,
+			SetParameter::AtapiPort(7975)
+		)
+		.await
+		.expect("Failed to set param on emulated MION!"));
+		assert_eq!(
+			emulator.set_params().get("atapi_port").map(String::as_str),
+			Some("7975"),
+		);
+		// Guessed settings have to be explicitly sent as experimental.
+		assert!(matches!(
+			set_param_with_raw_client(
+				&client,
+				Ipv4Addr::LOCALHOST,
+				SetParameter::SdioPrintfPort(7980),
+			)
+			.await,
+			Err(CatBridgeError::ApiError(
+				APIError::ExperimentalSetParameter("sdio_printf_port")
+			)),
+		));
+		assert!(!emulator.set_params().contains_key("sdio_printf_port"));
+		assert!(set_experimental_param_with_raw_client(
+			&client,
+			Ipv4Addr::LOCALHOST,
+			SetParameter::SdioPrintfPort(7980),
+		)
+		.await
+		.expect("Failed to set experimental param on emulated MION!"));
+		assert_eq!(
+			emulator
+				.set_params()
+				.get("sdio_printf_port")
+				.map(String::as_str),
+			Some("7980"),
+		);
+		assert_eq!(
+			get_mion_info_with_raw_client(&client, Ipv4Addr::LOCALHOST, "test")
+				.await
+				.expect("Failed to get info from emulated MION!")
+				.atapi_port(),
+			Some(7975),
+		);
+
+		assert_eq!(
+			get_vdd2_with_raw_client(&client, Ipv4Addr::LOCALHOST)
+				.await
+				.expect("Failed to get VDD2 from emulated MION!"),
+			"0",
+		);
+		emulator.set_powered_on(true);
+		assert_eq!(
+			get_vdd2_with_raw_client(&client, Ipv4Addr::LOCALHOST)
+				.await
+				.expect("Failed to get VDD2 from emulated MION!"),
+			"1",
+		);
+
+		let signals = get_signals_with_raw_client(&client, Ipv4Addr::LOCALHOST, &Signal::ALL)
+			.await
+			.expect("Failed to get signals from emulated MION!");
+		assert_eq!(signals, vec![(Signal::Vdd2, "1".to_owned())]);
+
+		// Requests without authorization are rejected.
+		let response = Client::builder()
+			.build::<_, hyper::Body>(EmulatedMionConnector::new(emulator.http_address()))
+			.request(
+				hyper::Request::post("http://127.0.0.1/mion/control.cgi")
+					.body(hyper::Body::from("operation=get_info"))
+					.expect("Failed to build request!"),
+			)
+			.await
+			.expect("Failed to send request to emulated MION!");
+		assert_eq!(response.status().as_u16(), 401);
+	}
+
+	#[tokio::test]
+	pub async fn can_manage_hdd_images() {
+		let emulator = EmulatedMion::spawn(test_identity())
+			.await
+			.expect("Failed to spawn emulated MION!");
+		let client = emulator.http_client();
+
+		let image_size = IMAGE_UPLOAD_CHUNK_SIZE as u64 * 2 + 1234;
+		let header = ImageHeader::new(
+			ImageKind::Wumad,
+			image_size,
+			0x0005_0000_1010_1000,
+			"WUP-P-ABCE",
+		)
+		.expect("Failed to create image header!");
+		let mut image = BytesMut::from(&header.to_bytes()[..]);
+		image.resize(usize::try_from(image_size).expect("Image too large!"), 0xAA);
+		let directory = tempfile::tempdir().expect("Failed to create temporary directory!");
+		let path = directory.path().join("game.wumad");
+		tokio::fs::write(&path, &image)
+			.await
+			.expect("Failed to write image!");
+
+		// Pretend an earlier upload only got through the first chunk.
+		let http_client = MionHttpClient::from_raw_client(client.clone());
+		assert_eq!(
+			http_client
+				.begin_experimental_image_upload(Ipv4Addr::LOCALHOST, "game", image_size)
+				.await
+				.expect("Failed to begin image upload!"),
+			0,
+		);
+		http_client
+			.upload_experimental_image_chunk(
+				Ipv4Addr::LOCALHOST,
+				"game",
+				image_size,
+				0,
+				image.split_to(IMAGE_UPLOAD_CHUNK_SIZE).freeze(),
+				|_, _| {},
+			)
+			.await
+			.expect("Failed to upload first chunk!");
+
+		let mut progress = Vec::new();
+		upload_experimental_image_with_raw_client(
+			&client,
+			Ipv4Addr::LOCALHOST,
+			&path,
+			"game",
+			|sent, total| {
+				progress.push((sent, total));
+			},
+		)
+		.await
+		.expect("Failed to upload image!");
+		assert_eq!(progress[0], (IMAGE_UPLOAD_CHUNK_SIZE as u64, image_size));
+		assert!(progress.windows(2).all(|pair| pair[0].0 <= pair[1].0));
+		assert_eq!(progress[progress.len() - 1], (image_size, image_size));
+		assert_eq!(
+			list_images_with_raw_client(&client, Ipv4Addr::LOCALHOST)
+				.await
+				.expect("Failed to list images!"),
+			vec![HddImage::new("game".to_owned(), image_size, false)],
+		);
+
+		// Names have to be unique, and have to exist to be selected.
+		assert!(matches!(
+			upload_experimental_image_with_raw_client(
+				&client,
+				Ipv4Addr::LOCALHOST,
+				&path,
+				"game",
+				|_, _| {}
+			)
+			.await,
+			Err(CatBridgeError::NetworkError(
+				NetworkError::ImageOperationRejected(_, _)
+			)),
+		));
+		assert!(matches!(
+			select_experimental_image_with_raw_client(&client, Ipv4Addr::LOCALHOST, "missing")
+				.await,
+			Err(CatBridgeError::NetworkError(
+				NetworkError::ImageOperationRejected(_, _)
+			)),
+		));
+
+		select_experimental_image_with_raw_client(&client, Ipv4Addr::LOCALHOST, "game")
+			.await
+			.expect("Failed to select image!");
+		assert_eq!(
+			emulator.hdd_images(),
+			vec![HddImage::new("game".to_owned(), image_size, true)],
+		);
+		delete_experimental_image_with_raw_client(&client, Ipv4Addr::LOCALHOST, "game")
+			.await
+			.expect("Failed to delete image!");
+		assert!(emulator.hdd_images().is_empty());
+
+		// Broken images never make it to the bridge.
+		tokio::fs::write(&path, b"not an image")
+			.await
+			.expect("Failed to write broken image!");
+		assert!(matches!(
+			upload_experimental_image_with_raw_client(
+				&client,
+				Ipv4Addr::LOCALHOST,
+				&path,
+				"broken",
+				|_, _| {}
+			)
+			.await,
+			Err(CatBridgeError::ApiError(APIError::InvalidDiscImage(_))),
+		));
+	}
+
+	#[tokio::test]
+	pub async fn can_use_custom_credentials() {
+		let emulator = EmulatedMion::spawn(test_identity())
+			.await
+			.expect("Failed to spawn emulated MION!");
+		emulator.set_credentials("mion", "hunter2");
+
+		let default_client = MionHttpClient::from_raw_client(emulator.http_client());
+		assert!(matches!(
+			default_client.get_power_state(Ipv4Addr::LOCALHOST).await,
+			Err(CatBridgeError::NetworkError(
+				NetworkError::MionRejectedCredentials
+			)),
+		));
+
+		let client = default_client.with_credentials("mion", "hunter2");
+		assert_eq!(
+			client
+				.get_power_state(Ipv4Addr::LOCALHOST)
+				.await
+				.expect("Failed to get power state with custom credentials!"),
+			PowerState::Off,
+		);
+
+		// A server that accepts connections, but never answers, times out even
+		// after retrying. Only the `GET` is retried, as the `POST` may have
+		// already been acted on.
+		let silent_listener = TcpListener::bind(SocketAddrV4::new(Ipv4Addr::LOCALHOST, 0))
+			.await
+			.expect("Failed to bind silent HTTP server!");
+		let SocketAddr::V4(silent_address) = silent_listener
+			.local_addr()
+			.expect("Failed to get silent HTTP server address!")
+		else {
+			panic!("Silent HTTP server bound to an IPv6 address!");
+		};
+		let (attempt_sender, mut attempt_receiver) = tokio::sync::mpsc::unbounded_channel();
+		let _silent_server = tokio::task::spawn(async move {
+			let mut connections = Vec::new();
+			while let Ok((connection, _)) = silent_listener.accept().await {
+				connections.push(connection);
+				_ = attempt_sender.send(());
+			}
+		});
+		let impatient_client = MionHttpClient::from_raw_client(
+			Client::builder().build(EmulatedMionConnector::new(silent_address)),
+		)
+		.with_request_timeout(Duration::from_millis(50))
+		.with_retry_policy(RetryPolicy::new(2, Duration::ZERO));
+		let mut count_attempts = || {
+			let mut attempts = 0;
+			while attempt_receiver.try_recv().is_ok() {
+				attempts += 1;
+			}
+			attempts
+		};
+
+		assert!(matches!(
+			impatient_client.get(Ipv4Addr::LOCALHOST, "/").await,
+			Err(CatBridgeError::NetworkError(NetworkError::TimeoutError)),
+		));
+		tokio::time::sleep(Duration::from_millis(50)).await;
+		assert_eq!(count_attempts(), 3);
+
+		assert!(matches!(
+			impatient_client.get_power_state(Ipv4Addr::LOCALHOST).await,
+			Err(CatBridgeError::NetworkError(NetworkError::TimeoutError)),
+		));
+		tokio::time::sleep(Duration::from_millis(50)).await;
+		assert_eq!(count_attempts(), 1);
+	}
+
+	#[tokio::test]
+	pub async fn can_control_power() {
+		let emulator = EmulatedMion::spawn(test_identity())
+			.await
+			.expect("Failed to spawn emulated MION!");
+		let client = emulator.http_client();
+		let us = Ipv4Addr::new(10, 0, 0, 1);
+		let them = Ipv4Addr::new(10, 0, 0, 2);
+
+		assert_eq!(
+			get_power_state_with_raw_client(&client, Ipv4Addr::LOCALHOST)
+				.await
+				.expect("Failed to get power state from emulated MION!"),
+			PowerState::Off,
+		);
+		let response = power_on_with_raw_client(
+			&client,
+			Ipv4Addr::LOCALHOST,
+			&PowerOnRequest::new().with_host(us),
+		)
+		.await
+		.expect("Failed to power on emulated MION!");
+		assert!(response.is_success());
+		assert_eq!(response.log_lines(), &["INFO:powering on".to_owned()]);
+		assert_eq!(emulator.owner(), Some(us));
+		let info = get_mion_info_with_raw_client(&client, Ipv4Addr::LOCALHOST, "test")
+			.await
+			.expect("Failed to get info from emulated MION!");
+		assert_eq!(info.owner(), Some(us));
+		assert_eq!(info.power(), Some(PowerState::On));
+		assert_eq!(
+			get_power_state_with_raw_client(&client, Ipv4Addr::LOCALHOST)
+				.await
+				.expect("Failed to get power state from emulated MION!"),
+			PowerState::On,
+		);
+
+		// Someone else can't take over while we own it.
+		for result in [
+			power_on_with_raw_client(
+				&client,
+				Ipv4Addr::LOCALHOST,
+				&PowerOnRequest::new().with_host(them),
+			)
+			.await,
+			power_off_with_raw_client(
+				&client,
+				Ipv4Addr::LOCALHOST,
+				&PowerOffRequest::new().with_host(them),
+			)
+			.await,
+			reset_with_raw_client(
+				&client,
+				Ipv4Addr::LOCALHOST,
+				&ResetRequest::new().with_host(them),
+			)
+			.await,
+		] {
+			assert!(matches!(
+				result,
+				Err(CatBridgeError::NetworkError(
+					NetworkError::MionInUseByAnotherHost(ref host)
+				)) if host == "10.0.0.1"
+			));
+		}
+		assert!(emulator.is_powered_on());
+
+		assert!(reset_with_raw_client(
+			&client,
+			Ipv4Addr::LOCALHOST,
+			&ResetRequest::new().with_host(us),
+		)
+		.await
+		.expect("Failed to reset emulated MION!")
+		.is_success());
+		assert!(power_off_with_raw_client(
+			&client,
+			Ipv4Addr::LOCALHOST,
+			&PowerOffRequest::new().with_host(us),
+		)
+		.await
+		.expect("Failed to power off emulated MION!")
+		.is_success());
+		assert_eq!(emulator.owner(), None);
+		assert!(!emulator.is_powered_on());
+	}
+
+	#[tokio::test]
+	pub async fn can_read_and_write_setup() {
+		let emulator = EmulatedMion::spawn(test_identity())
+			.await
+			.expect("Failed to spawn emulated MION!");
+		let client = emulator.http_client();
+
+		let setup = get_setup_with_raw_client(&client, Ipv4Addr::LOCALHOST)
+			.await
+			.expect("Failed to get setup from emulated MION!");
+		assert_eq!(setup.ip_mode(), IpMode::Dhcp);
+		assert_eq!(setup.name(), "00-25-5C-BA-5A-00");
+		assert_eq!(setup.parameter_port(), DEFAULT_MION_PARAMETER_PORT);
+
+		let updated = setup
+			.with_static_address(
+				Ipv4Addr::new(192, 168, 7, 40),
+				Ipv4Addr::new(255, 255, 255, 0),
+				Ipv4Addr::new(192, 168, 7, 1),
+			)
+			.with_name("provisioned".to_owned())
+			.with_parameter_port(7979);
+		set_experimental_setup_with_raw_client(&client, Ipv4Addr::LOCALHOST, &updated)
+			.await
+			.expect("Failed to set setup on emulated MION!");
+		assert_eq!(emulator.setup(), updated);
+		assert_eq!(
+			get_setup_with_raw_client(&client, Ipv4Addr::LOCALHOST)
+				.await
+				.expect("Failed to get setup from emulated MION!"),
+			updated,
+		);
+
+		// Invalid setups are caught before they're ever sent.
+		assert!(matches!(
+			set_experimental_setup_with_raw_client(
+				&client,
+				Ipv4Addr::LOCALHOST,
+				&updated.clone().with_atapi_port(0)
+			)
+			.await,
+			Err(CatBridgeError::ApiError(APIError::InvalidSetupValue(
+				"atapi_port",
+				_,
+				_
+			))),
+		));
+		// ... but the MION rejects them too.
+		let response = do_raw_setup_request(
+			&client,
+			Ipv4Addr::LOCALHOST,
+			Some("ip_mode=dhcp".to_owned()),
+		)
+		.await
+		.expect("Failed to send setup request to emulated MION!");
+		let body = hyper::body::to_bytes(response.into_body())
+			.await
+			.expect("Failed to read setup response!");
+		assert!(String::from_utf8_lossy(&body).contains("ERROR:"));
+		assert_eq!(emulator.setup(), updated);
+	}
+}
@@ -0,0 +1,64 @@
+//! The emulated TCP parameter port, which serves the 512 byte parameter
+//! space.
+
+use crate::{
+	errors::CatBridgeError,
+	mion::{
+		emulator::EmulatedMionState,
+		proto::parameter::{
+			codec::{MionParameterRequest, MionParameterServerCodec},
+			DumpedMionParameters, SetMionParametersResponse,
+		},
+	},
+};
+use futures::{SinkExt, StreamExt};
+use std::sync::Arc;
+use tokio::net::{TcpListener, TcpStream};
+use tokio_util::codec::Framed;
+use tracing::{debug, warn};
+
+/// Serve the parameter port forever, handling each connection in it's own
+/// task.
+pub(super) async fn serve_parameter_port(listener: TcpListener, state: Arc<EmulatedMionState>) {
+	loop {
+		let (stream, from) = match listener.accept().await {
+			Ok(data) => data,
+			Err(cause) => {
+				warn!(?cause, "emulated mion failed to accept on parameter port");
+				continue;
+			}
+		};
+
+		let cloned_state = state.clone();
+		tokio::task::spawn(async move {
+			if let Err(cause) = handle_parameter_connection(stream, cloned_state).await {
+				debug!(?cause, %from, "emulated mion closed parameter connection");
+			}
+		});
+	}
+}
+
+/// Handle a single connection, a client may send as many requests as it
+/// wants on a single connection.
+async fn handle_parameter_connection(
+	stream: TcpStream,
+	state: Arc<EmulatedMionState>,
+) -> Result<(), CatBridgeError> {
+	let mut framed = Framed::new(stream, MionParameterServerCodec);
+	// A client hanging up (`None`) is totally fine.
+	while let Some(request) = framed.next().await {
+		match request? {
+			MionParameterRequest::Dump(_) => {
+				framed
+					.send(DumpedMionParameters::new(state.parameters())?)
+					.await?;
+			}
+			MionParameterRequest::Set(request) => {
+				state.replace_parameters(request.get_raw_parameters())?;
+				framed.send(SetMionParametersResponse::new(0)).await?;
+			}
+		}
+	}
+
+	Ok(())
+}
@@ -0,0 +1,260 @@
+//! Comparing the firmware running on a MION to the firmware you want it to
+//! run.
+//!
+//! Versions are read from what a bridge announces when searching for bridges.
+//! Actually updating the firmware isn't supported, we've never had a real
+//! firmware image, or seen a real firmware update happen, so we don't know
+//! what a bridge expects to be uploaded.
+
+use crate::{errors::APIError, mion::proto::control::MionIdentity};
+use std::{
+	cmp::Ordering,
+	fmt::{Display, Formatter, Result as FmtResult},
+};
+
+/// The oldest firmware that responds with all of the detailed fields when
+/// searching for bridges.
+pub const MINIMUM_DETAILED_FIRMWARE_VERSION: FirmwareVersion = FirmwareVersion::new(0, 14, 63);
+
+/// The version of the firmware running on a MION.
+///
+/// These are always displayed with a leading `0.`, e.g. `0.0.14.80`.
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct FirmwareVersion {
+	major: u8,
+	minor: u8,
+	patch: u8,
+}
+
+impl FirmwareVersion {
+	#[must_use]
+	pub const fn new(major: u8, minor: u8, patch: u8) -> Self {
+		Self {
+			major,
+			minor,
+			patch,
+		}
+	}
+
+	/// Get the firmware version from the raw bytes in an announcement.
+	#[must_use]
+	pub const fn from_raw(raw: [u8; 4]) -> Self {
+		Self::new(raw[0], raw[1], raw[2])
+	}
+
+	/// The firmware version a MION announced.
+	#[must_use]
+	pub const fn from_identity(identity: &MionIdentity) -> Self {
+		Self::from_raw(identity.raw_firmware_version())
+	}
+
+	#[must_use]
+	pub const fn major(&self) -> u8 {
+		self.major
+	}
+
+	#[must_use]
+	pub const fn minor(&self) -> u8 {
+		self.minor
+	}
+
+	#[must_use]
+	pub const fn patch(&self) -> u8 {
+		self.patch
+	}
+
+	/// If a MION running this firmware responds with all of the detailed
+	/// fields when searching for bridges.
+	#[must_use]
+	pub fn supports_detailed_fields(&self) -> bool {
+		*self >= MINIMUM_DETAILED_FIRMWARE_VERSION
+	}
+}
+
+impl Display for FirmwareVersion {
+	fn fmt(&self, fmt: &mut Formatter<'_>) -> FmtResult {
+		write!(fmt, "0.{}.{}.{}", self.major, self.minor, self.patch)
+	}
+}
+
+impl TryFrom<&str> for FirmwareVersion {
+	type Error = APIError;
+
+	fn try_from(value: &str) -> Result<Self, Self::Error> {
+		let error = || APIError::InvalidFirmwareVersion(value.to_owned());
+		let parts = value
+			.trim()
+			.split('.')
+			.map(str::parse::<u8>)
+			.collect::<Result<Vec<_>, _>>()
+			.map_err(|_| error())?;
+		match parts.as_slice() {
+			[0, major, minor, patch] => Ok(Self::new(*major, *minor, *patch)),
+			_ => Err(error()),
+		}
+	}
+}
+
+/// What moving a MION to a different firmware version would do.
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub enum FirmwareChange {
+	/// The target is newer than what's installed.
+	Upgrade,
+	/// The target is the same version as what's installed.
+	Reinstall,
+	/// The target is older than what's installed.
+	Downgrade,
+}
+
+impl Display for FirmwareChange {
+	fn fmt(&self, fmt: &mut Formatter<'_>) -> FmtResult {
+		write!(
+			fmt,
+			"{}",
+			match self {
+				Self::Upgrade => "upgrade",
+				Self::Reinstall => "reinstall",
+				Self::Downgrade => "downgrade",
+			}
+		)
+	}
+}
+
+/// The result of comparing the firmware a MION is running to a target
+/// firmware, and FPGA version.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct FirmwareComparison {
+	installed_firmware: FirmwareVersion,
+	target_firmware: FirmwareVersion,
+	installed_fpga: String,
+	target_fpga: String,
+	change: FirmwareChange,
+}
+
+impl FirmwareComparison {
+	/// Compare what a MION announced it's running to a target firmware, and
+	/// FPGA version.
+	///
+	/// The FPGA version is rendered the same as
+	/// [`MionIdentity::detailed_fpga_version`].
+	#[must_use]
+	pub fn new(
+		identity: &MionIdentity,
+		target_firmware: FirmwareVersion,
+		target_fpga: &str,
+	) -> Self {
+		let installed_firmware = FirmwareVersion::from_identity(identity);
+		Self {
+			installed_firmware,
+			target_firmware,
+			installed_fpga: identity.detailed_fpga_version(),
+			target_fpga: target_fpga.to_owned(),
+			change: match target_firmware.cmp(&installed_firmware) {
+				Ordering::Greater => FirmwareChange::Upgrade,
+				Ordering::Equal => FirmwareChange::Reinstall,
+				Ordering::Less => FirmwareChange::Downgrade,
+			},
+		}
+	}
+
+	#[must_use]
+	pub const fn installed_firmware(&self) -> FirmwareVersion {
+		self.installed_firmware
+	}
+
+	#[must_use]
+	pub const fn target_firmware(&self) -> FirmwareVersion {
+		self.target_firmware
+	}
+
+	#[must_use]
+	pub fn installed_fpga(&self) -> &str {
+		&self.installed_fpga
+	}
+
+	#[must_use]
+	pub fn target_fpga(&self) -> &str {
+		&self.target_fpga
+	}
+
+	#[must_use]
+	pub const fn change(&self) -> FirmwareChange {
+		self.change
+	}
+
+	/// If the target has a different FPGA version than the MION.
+	#[must_use]
+	pub fn fpga_changes(&self) -> bool {
+		!self.installed_fpga.eq_ignore_ascii_case(&self.target_fpga)
+	}
+
+	/// If the MION is out of date compared to the target.
+	///
+	/// That is the target is newer, or has a different FPGA version with the
+	/// same firmware.
+	#[must_use]
+	pub fn needs_update(&self) -> bool {
+		self.change == FirmwareChange::Upgrade
+			|| (self.change == FirmwareChange::Reinstall && self.fpga_changes())
+	}
+}
+
+#[cfg(test)]
+mod unit_tests {
+	use super::*;
+	use std::net::Ipv4Addr;
+
+	#[test]
+	pub fn can_parse_firmware_versions() {
+		assert_eq!(
+			FirmwareVersion::try_from("0.0.14.80"),
+			Ok(FirmwareVersion::new(0, 14, 80)),
+		);
+		assert_eq!(
+			format!("{}", FirmwareVersion::from_raw([0, 14, 80, 1])),
+			"0.0.14.80",
+		);
+		assert!(FirmwareVersion::new(0, 14, 63) > FirmwareVersion::new(0, 14, 9));
+		assert!(FirmwareVersion::new(0, 14, 80).supports_detailed_fields());
+		assert!(!FirmwareVersion::new(0, 14, 62).supports_detailed_fields());
+
+		for invalid in ["1.0.14.80", "0.14.80", "0.0.14.800", "latest"] {
+			assert_eq!(
+				FirmwareVersion::try_from(invalid),
+				Err(APIError::InvalidFirmwareVersion(invalid.to_owned())),
+			);
+		}
+	}
+
+	#[test]
+	pub fn can_compare_versions_to_bridges() {
+		let identity = MionIdentity::new(
+			None,
+			[0, 14, 9, 1],
+			[0x71, 0x20, 0x05, 0x13],
+			Ipv4Addr::LOCALHOST,
+			mac_address::MacAddress::new([0x00, 0x25, 0x5c, 0xba, 0x5a, 0x00]),
+			"00-25-5C-BA-5A-00".to_owned(),
+		)
+		.expect("Failed to create identity!");
+
+		let newer = FirmwareComparison::new(&identity, FirmwareVersion::new(0, 14, 80), "13052071");
+		assert_eq!(newer.change(), FirmwareChange::Upgrade);
+		assert!(!newer.installed_firmware().supports_detailed_fields());
+		assert!(!newer.fpga_changes());
+		assert!(newer.needs_update());
+
+		let same = FirmwareComparison::new(&identity, FirmwareVersion::new(0, 14, 9), "13052071");
+		assert_eq!(same.change(), FirmwareChange::Reinstall);
+		assert!(!same.needs_update());
+
+		let new_fpga =
+			FirmwareComparison::new(&identity, FirmwareVersion::new(0, 14, 9), "13052072");
+		assert!(new_fpga.fpga_changes());
+		assert!(new_fpga.needs_update());
+
+		let older = FirmwareComparison::new(&identity, FirmwareVersion::new(0, 13, 9), "13052071");
+		assert_eq!(older.change(), FirmwareChange::Downgrade);
+		assert!(!older.needs_update());
+	}
+}
@@ -0,0 +1,959 @@
+//! Managing the mastered `WUM`, and `WUMAD` images stored on the internal HDD
+//! of a CAT-DEV.
+//!
+//! ***Unverified:** we've never seen a real `WUM`/`WUMAD` image, or a real
+//! upload happen. Both the `image.cgi` protocol, and the image header below
+//! are made up, and only match what our emulator accepts. A real bridge will
+//! most likely reject these requests, and real images will most likely fail
+//! the header checks.*
+//!
+//! Images are managed through `image.cgi` on the bridge. The HDD can hold
+//! several images at once, and the selected image is the one the CAT-DEV
+//! reads as it's disc. Every operation is a form posted to `image.cgi`:
+//!
+//! ```text
+//! operation=list                      `IMAGE:<size>,<selected>,<name>` for every image
+//! operation=select&name=              select an image
+//! operation=delete&name=              delete an image
+//! operation=begin&name=&size=         `OFFSET:<bytes already received>`
+//! ?operation=chunk&name=&offset=      `OFFSET:<bytes received>`
+//! operation=finish&name=              check the upload, and add it to the list
+//! ```
+//!
+//! Images are many gigabytes, so they're uploaded in chunks of
+//! [`IMAGE_UPLOAD_CHUNK_SIZE`], with the chunk as the raw body and the fields
+//! in the query string. The bridge remembers how much of an upload it has
+//! received, so starting an upload with the same name, and size picks up
+//! where the last one stopped.
+//!
+//! Every image starts with a small header, all numbers are big endian like
+//! everything cafe reads:
+//!
+//! ```text
+//! 0x00  4 bytes     magic: `WUM\0` for WUM images, `WUMA` for WUMAD images
+//! 0x04  4 bytes     header version, always 1
+//! 0x08  8 bytes     size of the whole image, header included
+//! 0x10  8 bytes     title id
+//! 0x18  0x20 bytes  product code in ASCII padded with NULs, e.g. `WUP-P-ABCE`
+//! 0x38  4 bytes     CRC-32 of the first 0x38 bytes of the header
+//! 0x3C  4 bytes     reserved
+//! ```
+//!
+//! Only the header is checked before uploading, the contents of an image are
+//! far too large to checksum up front.
+
+use crate::{
+	errors::{APIError, CatBridgeError, FSError, NetworkError, NetworkParseError},
+	mion::cgis::MionHttpClient,
+};
+use bytes::{BufMut, Bytes, BytesMut};
+use futures::stream;
+use hyper::client::{connect::Connect, Client};
+use std::{
+	fmt::{Display, Formatter, Result as FmtResult},
+	io::SeekFrom,
+	net::Ipv4Addr,
+	path::Path,
+};
+use tokio::{
+	fs::File,
+	io::{AsyncReadExt, AsyncSeekExt},
+	time::sleep,
+};
+use tracing::debug;
+
+/// The magic every `WUM` image starts with.
+///
+/// **Unverified**, see the module documentation.
+pub const WUM_IMAGE_MAGIC: [u8; 4] = *b"WUM\0";
+/// The magic every `WUMAD` image starts with.
+///
+/// **Unverified**, see the module documentation.
+pub const WUMAD_IMAGE_MAGIC: [u8; 4] = *b"WUMA";
+/// The size of the header at the start of every image.
+pub const IMAGE_HEADER_SIZE: usize = 0x40;
+/// The only version of the image header we know about.
+pub const IMAGE_HEADER_VERSION: u32 = 1;
+/// The longest name an image on the HDD can have.
+pub const MAX_IMAGE_NAME_LENGTH: usize = 64;
+/// How much of an image is sent in a single request, an interrupted upload
+/// only has to resend at most this much.
+pub const IMAGE_UPLOAD_CHUNK_SIZE: usize = 1024 * 1024;
+
+/// The path `image.cgi` is served on.
+const IMAGE_CGI_PATH: &str = "/image.cgi";
+/// Where the product code lives in the header, and how long it can be.
+const PRODUCT_CODE_OFFSET: usize = 0x18;
+const PRODUCT_CODE_LENGTH: usize = 0x20;
+/// Where the checksum of the header lives, it covers everything before it.
+const HEADER_CHECKSUM_OFFSET: usize = 0x38;
+
+/// The two kinds of mastered images a CAT-DEV can store.
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub enum ImageKind {
+	Wum,
+	Wumad,
+}
+
+impl ImageKind {
+	/// The magic images of this kind start with.
+	#[must_use]
+	pub const fn magic(self) -> [u8; 4] {
+		match self {
+			Self::Wum => WUM_IMAGE_MAGIC,
+			Self::Wumad => WUMAD_IMAGE_MAGIC,
+		}
+	}
+
+	/// The file extension images of this kind usually have.
+	#[must_use]
+	pub const fn extension(self) -> &'static str {
+		match self {
+			Self::Wum => "wum",
+			Self::Wumad => "wumad",
+		}
+	}
+}
+
+impl Display for ImageKind {
+	fn fmt(&self, fmt: &mut Formatter<'_>) -> FmtResult {
+		write!(
+			fmt,
+			"{}",
+			match self {
+				Self::Wum => "WUM",
+				Self::Wumad => "WUMAD",
+			}
+		)
+	}
+}
+
+/// The header of a `WUM`, or `WUMAD` image that has been checked.
+///
+/// **Unverified**, this is a format we made up, see the module documentation.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct ImageHeader {
+	kind: ImageKind,
+	image_size: u64,
+	title_id: u64,
+	product_code: String,
+}
+
+impl ImageHeader {
+	/// Create a new image header.
+	///
+	/// ## Errors
+	///
+	/// - If the image size is too small to fit the header.
+	/// - If the product code isn't printable ASCII, or is longer than 32
+	///   bytes.
+	pub fn new(
+		kind: ImageKind,
+		image_size: u64,
+		title_id: u64,
+		product_code: &str,
+	) -> Result<Self, APIError> {
+		if image_size < IMAGE_HEADER_SIZE as u64 {
+			return Err(APIError::InvalidDiscImage(format!(
+				"an image of {image_size} bytes is too small to fit a header"
+			)));
+		}
+		validate_product_code(product_code.as_bytes())?;
+
+		Ok(Self {
+			kind,
+			image_size,
+			title_id,
+			product_code: product_code.to_owned(),
+		})
+	}
+
+	/// Parse, and validate the header at the start of an image.
+	///
+	/// ## Errors
+	///
+	/// - If there aren't enough bytes for a header.
+	/// - If the header doesn't start with a magic we know about.
+	/// - If the header is a version we don't know about.
+	/// - If the checksum of the header doesn't match.
+	/// - If the size, or product code in the header aren't valid.
+	pub fn parse(header: &[u8]) -> Result<Self, APIError> {
+		if header.len() < IMAGE_HEADER_SIZE {
+			return Err(APIError::InvalidDiscImage(format!(
+				"it is only {} bytes long, which is too short to have a header",
+				header.len(),
+			)));
+		}
+		let kind = match [header[0], header[1], header[2], header[3]] {
+			WUM_IMAGE_MAGIC => ImageKind::Wum,
+			WUMAD_IMAGE_MAGIC => ImageKind::Wumad,
+			other => {
+				return Err(APIError::InvalidDiscImage(format!(
+					"it starts with {other:02x?} instead of the magic `WUM\\0`, or `WUMA`",
+				)));
+			}
+		};
+
+		let read_u32 = |offset: usize| {
+			u32::from_be_bytes([
+				header[offset],
+				header[offset + 1],
+				header[offset + 2],
+				header[offset + 3],
+			])
+		};
+		let read_u64 =
+			|offset: usize| (u64::from(read_u32(offset)) << 32) | u64::from(read_u32(offset + 4));
+		let version = read_u32(0x04);
+		if version != IMAGE_HEADER_VERSION {
+			return Err(APIError::InvalidDiscImage(format!(
+				"it has a header version of {version}, but we only know about version {IMAGE_HEADER_VERSION}",
+			)));
+		}
+		let expected_checksum = read_u32(HEADER_CHECKSUM_OFFSET);
+		let actual_checksum = crc32fast::hash(&header[..HEADER_CHECKSUM_OFFSET]);
+		if actual_checksum != expected_checksum {
+			return Err(APIError::InvalidDiscImage(format!(
+				"the header has a checksum of {actual_checksum:08x}, but it should be {expected_checksum:08x}, it may be corrupted",
+			)));
+		}
+
+		let product_code = &header[PRODUCT_CODE_OFFSET..PRODUCT_CODE_OFFSET + PRODUCT_CODE_LENGTH];
+		let product_code_length = product_code
+			.iter()
+			.position(|byte| *byte == 0)
+			.unwrap_or(PRODUCT_CODE_LENGTH);
+		Self::new(
+			kind,
+			read_u64(0x08),
+			read_u64(0x10),
+			&String::from_utf8_lossy(&product_code[..product_code_length]),
+		)
+	}
+
+	/// Read, and validate the header of an image on disk, making sure the file
+	/// is as large as the header says it should be.
+	///
+	/// ## Errors
+	///
+	/// - If we cannot read the file.
+	/// - If the file isn't the size the header says it is.
+	/// - See [`Self::parse`].
+	pub async fn load(path: &Path) -> Result<Self, CatBridgeError> {
+		let file = File::open(path).await.map_err(FSError::from)?;
+		let file_size = file.metadata().await.map_err(FSError::from)?.len();
+		let mut header = Vec::with_capacity(IMAGE_HEADER_SIZE);
+		file.take(IMAGE_HEADER_SIZE as u64)
+			.read_to_end(&mut header)
+			.await
+			.map_err(FSError::from)?;
+
+		let parsed = Self::parse(&header)?;
+		if parsed.image_size != file_size {
+			return Err(APIError::InvalidDiscImage(format!(
+				"the header says the image is {} bytes, but the file is {file_size} bytes, it may be truncated",
+				parsed.image_size,
+			))
+			.into());
+		}
+		Ok(parsed)
+	}
+
+	#[must_use]
+	pub const fn kind(&self) -> ImageKind {
+		self.kind
+	}
+
+	/// The size of the whole image, including this header.
+	#[must_use]
+	pub const fn image_size(&self) -> u64 {
+		self.image_size
+	}
+
+	#[must_use]
+	pub const fn title_id(&self) -> u64 {
+		self.title_id
+	}
+
+	#[must_use]
+	pub fn product_code(&self) -> &str {
+		&self.product_code
+	}
+
+	/// Serialize this header, with it's checksum.
+	#[must_use]
+	pub fn to_bytes(&self) -> Bytes {
+		let mut header = BytesMut::with_capacity(IMAGE_HEADER_SIZE);
+		header.extend_from_slice(&self.kind.magic());
+		header.put_u32(IMAGE_HEADER_VERSION);
+		header.put_u64(self.image_size);
+		header.put_u64(self.title_id);
+		header.extend_from_slice(self.product_code.as_bytes());
+		header.resize(HEADER_CHECKSUM_OFFSET, 0);
+		header.put_u32(crc32fast::hash(&header));
+		header.resize(IMAGE_HEADER_SIZE, 0);
+		header.freeze()
+	}
+}
+
+/// An image stored on the HDD of a CAT-DEV.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct HddImage {
+	name: String,
+	size: u64,
+	selected: bool,
+}
+
+impl HddImage {
+	#[must_use]
+	pub const fn new(name: String, size: u64, selected: bool) -> Self {
+		Self {
+			name,
+			size,
+			selected,
+		}
+	}
+
+	#[must_use]
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	#[must_use]
+	pub const fn size(&self) -> u64 {
+		self.size
+	}
+
+	/// If this is the image the CAT-DEV reads as it's disc.
+	#[must_use]
+	pub const fn is_selected(&self) -> bool {
+		self.selected
+	}
+}
+
+impl Display for HddImage {
+	fn fmt(&self, fmt: &mut Formatter<'_>) -> FmtResult {
+		write!(
+			fmt,
+			"{},{},{}",
+			self.size,
+			u8::from(self.selected),
+			self.name,
+		)
+	}
+}
+
+impl TryFrom<&str> for HddImage {
+	type Error = NetworkParseError;
+
+	/// Parse an image from a line of the list `image.cgi` responds with, the
+	/// `IMAGE:` prefix is optional.
+	fn try_from(value: &str) -> Result<Self, Self::Error> {
+		let line = value.trim();
+		let line = line.strip_prefix("IMAGE:").unwrap_or(line);
+		let error = || NetworkParseError::InvalidImageField("IMAGE", value.to_owned());
+		let mut fields = line.splitn(3, ',');
+		let size = fields
+			.next()
+			.and_then(|size| size.parse::<u64>().ok())
+			.ok_or_else(error)?;
+		let selected = match fields.next() {
+			Some("1") => true,
+			Some("0") => false,
+			_ => return Err(error()),
+		};
+		let name = fields
+			.next()
+			.filter(|name| !name.is_empty())
+			.ok_or_else(error)?;
+
+		Ok(Self::new(name.to_owned(), size, selected))
+	}
+}
+
+/// Check a name can be used for an image on the HDD.
+///
+/// ## Errors
+///
+/// - If the name is empty, or longer than [`MAX_IMAGE_NAME_LENGTH`].
+/// - If the name has anything besides printable ASCII, or contains `/`, `\`,
+///   or `,`.
+pub fn validate_image_name(name: &str) -> Result<(), APIError> {
+	let is_valid = !name.is_empty()
+		&& name.len() <= MAX_IMAGE_NAME_LENGTH
+		&& name.chars().all(|character| {
+			(character.is_ascii_graphic() || character == ' ')
+				&& !matches!(character, '/' | '\\' | ',')
+		});
+	if is_valid {
+		Ok(())
+	} else {
+		Err(APIError::InvalidImageName(name.to_owned()))
+	}
+}
+
+/// List the images stored on the HDD of a CAT-DEV.
+///
+/// ## Errors
+///
+/// - If we cannot make the HTTP request, or it times out.
+/// - If the server does not respond with a 200.
+/// - If the MION responds with an error:
+///   [`NetworkError::ImageOperationRejected`].
+/// - If any image in the list can't be parsed.
+pub async fn list_images(mion_ip: Ipv4Addr) -> Result<Vec<HddImage>, CatBridgeError> {
+	MionHttpClient::new().list_images(mion_ip).await
+}
+
+/// List the images stored on the HDD of a CAT-DEV, but with an already
+/// existing HTTP client.
+///
+/// ## Errors
+///
+/// See [`list_images`].
+pub async fn list_images_with_raw_client<ClientConnectorTy>(
+	client: &Client<ClientConnectorTy>,
+	mion_ip: Ipv4Addr,
+) -> Result<Vec<HddImage>, CatBridgeError>
+where
+	ClientConnectorTy: Clone + Connect + Send + Sync + 'static,
+{
+	MionHttpClient::from_raw_client(client.clone())
+		.list_images(mion_ip)
+		.await
+}
+
+/// Select the image the CAT-DEV reads as it's disc.
+///
+/// This is experimental, as `image.cgi` is made up (see the module
+/// documentation). A real MION will most likely reject the request.
+///
+/// ## Errors
+///
+/// - If the name isn't valid.
+/// - If we cannot make the HTTP request, or it times out.
+/// - If the server does not respond with a 200.
+/// - If the MION responds with an error, like there being no image with this
+///   name: [`NetworkError::ImageOperationRejected`].
+pub async fn select_experimental_image(
+	mion_ip: Ipv4Addr,
+	name: &str,
+) -> Result<(), CatBridgeError> {
+	MionHttpClient::new()
+		.select_experimental_image(mion_ip, name)
+		.await
+}
+
+/// Select the image the CAT-DEV reads as it's disc, but with an already
+/// existing HTTP client.
+///
+/// ## Errors
+///
+/// See [`select_experimental_image`].
+pub async fn select_experimental_image_with_raw_client<ClientConnectorTy>(
+	client: &Client<ClientConnectorTy>,
+	mion_ip: Ipv4Addr,
+	name: &str,
+) -> Result<(), CatBridgeError>
+where
+	ClientConnectorTy: Clone + Connect + Send + Sync + 'static,
+{
+	MionHttpClient::from_raw_client(client.clone())
+		.select_experimental_image(mion_ip, name)
+		.await
+}
+
+/// Delete an image from the HDD of a CAT-DEV.
+///
+/// This is experimental, see [`select_experimental_image`].
+///
+/// ## Errors
+///
+/// See [`select_experimental_image`].
+pub async fn delete_experimental_image(
+	mion_ip: Ipv4Addr,
+	name: &str,
+) -> Result<(), CatBridgeError> {
+	MionHttpClient::new()
+		.delete_experimental_image(mion_ip, name)
+		.await
+}
+
+/// Delete an image from the HDD of a CAT-DEV, but with an already existing
+/// HTTP client.
+///
+/// ## Errors
+///
+/// See [`select_experimental_image`].
+pub async fn delete_experimental_image_with_raw_client<ClientConnectorTy>(
+	client: &Client<ClientConnectorTy>,
+	mion_ip: Ipv4Addr,
+	name: &str,
+) -> Result<(), CatBridgeError>
+where
+	ClientConnectorTy: Clone + Connect + Send + Sync + 'static,
+{
+	MionHttpClient::from_raw_client(client.clone())
+		.delete_experimental_image(mion_ip, name)
+		.await
+}
+
+/// Upload an image on disk to the HDD of a CAT-DEV.
+///
+/// If an earlier upload with the same name, and size was interrupted, this
+/// continues where it stopped. `on_progress` is called with how many bytes of
+/// the image the bridge has, and how many there are in total.
+///
+/// This is experimental, as both `image.cgi`, and the image header are made
+/// up (see the module documentation). A real MION will most likely reject
+/// the upload.
+///
+/// ## Errors
+///
+/// - If the name isn't valid.
+/// - If we cannot read the image, or it's header isn't valid.
+/// - If we cannot make an HTTP request, or make no progress for the request
+///   timeout of the client.
+/// - If the server does not respond with a 200.
+/// - If the MION does not accept the image:
+///   [`NetworkError::ImageOperationRejected`].
+pub async fn upload_experimental_image<ProgressFnTy>(
+	mion_ip: Ipv4Addr,
+	path: &Path,
+	name: &str,
+	on_progress: ProgressFnTy,
+) -> Result<(), CatBridgeError>
+where
+	ProgressFnTy: FnMut(u64, u64),
+{
+	MionHttpClient::new()
+		.upload_experimental_image(mion_ip, path, name, on_progress)
+		.await
+}
+
+/// Upload an image on disk to the HDD of a CAT-DEV, but with an already
+/// existing HTTP client.
+///
+/// ## Errors
+///
+/// See [`upload_experimental_image`].
+pub async fn upload_experimental_image_with_raw_client<ClientConnectorTy, ProgressFnTy>(
+	client: &Client<ClientConnectorTy>,
+	mion_ip: Ipv4Addr,
+	path: &Path,
+	name: &str,
+	on_progress: ProgressFnTy,
+) -> Result<(), CatBridgeError>
+where
+	ClientConnectorTy: Clone + Connect + Send + Sync + 'static,
+	ProgressFnTy: FnMut(u64, u64),
+{
+	MionHttpClient::from_raw_client(client.clone())
+		.upload_experimental_image(mion_ip, path, name, on_progress)
+		.await
+}
+
+impl<ClientConnectorTy> MionHttpClient<ClientConnectorTy>
+where
+	ClientConnectorTy: Clone + Connect + Send + Sync + 'static,
+{
+	/// List the images stored on the HDD of a CAT-DEV with this client.
+	///
+	/// ## Errors
+	///
+	/// See [`list_images`].
+	pub async fn list_images(&self, mion_ip: Ipv4Addr) -> Result<Vec<HddImage>, CatBridgeError> {
+		let body_as_string = self
+			.post_form(mion_ip, IMAGE_CGI_PATH, &[("operation", "list")])
+			.await?;
+		let images = image_response_lines(&body_as_string, "list")?
+			.filter(|line| line.starts_with("IMAGE:"))
+			.map(HddImage::try_from)
+			.collect::<Result<Vec<_>, _>>()
+			.map_err(NetworkError::ParseError)?;
+		Ok(images)
+	}
+
+	/// Select the image the CAT-DEV reads as it's disc with this client.
+	///
+	/// ## Errors
+	///
+	/// See [`select_experimental_image`].
+	pub async fn select_experimental_image(
+		&self,
+		mion_ip: Ipv4Addr,
+		name: &str,
+	) -> Result<(), CatBridgeError> {
+		validate_image_name(name)?;
+		let body_as_string = self
+			.post_form(
+				mion_ip,
+				IMAGE_CGI_PATH,
+				&[("operation", "select"), ("name", name)],
+			)
+			.await?;
+		image_response_lines(&body_as_string, "select").map(|_lines| ())
+	}
+
+	/// Delete an image from the HDD of a CAT-DEV with this client.
+	///
+	/// ## Errors
+	///
+	/// See [`select_experimental_image`].
+	pub async fn delete_experimental_image(
+		&self,
+		mion_ip: Ipv4Addr,
+		name: &str,
+	) -> Result<(), CatBridgeError> {
+		validate_image_name(name)?;
+		let body_as_string = self
+			.post_form(
+				mion_ip,
+				IMAGE_CGI_PATH,
+				&[("operation", "delete"), ("name", name)],
+			)
+			.await?;
+		image_response_lines(&body_as_string, "delete").map(|_lines| ())
+	}
+
+	/// Upload an image on disk to the HDD of a CAT-DEV with this client.
+	///
+	/// If a chunk never gets a response, and the retry policy of this client
+	/// allows it, we ask the bridge how much it has received and carry on from
+	/// there.
+	///
+	/// ## Errors
+	///
+	/// See [`upload_experimental_image`].
+	pub async fn upload_experimental_image<ProgressFnTy>(
+		&self,
+		mion_ip: Ipv4Addr,
+		path: &Path,
+		name: &str,
+		mut on_progress: ProgressFnTy,
+	) -> Result<(), CatBridgeError>
+	where
+		ProgressFnTy: FnMut(u64, u64),
+	{
+		validate_image_name(name)?;
+		let total = ImageHeader::load(path).await?.image_size();
+		let mut file = File::open(path).await.map_err(FSError::from)?;
+		let mut offset = self
+			.begin_experimental_image_upload(mion_ip, name, total)
+			.await?;
+		on_progress(offset, total);
+
+		let mut buffer = vec![0_u8; IMAGE_UPLOAD_CHUNK_SIZE];
+		let mut failed_attempts = 0_u8;
+		while offset < total {
+			let length = usize::try_from(total - offset)
+				.unwrap_or(usize::MAX)
+				.min(IMAGE_UPLOAD_CHUNK_SIZE);
+			file.seek(SeekFrom::Start(offset))
+				.await
+				.map_err(FSError::from)?;
+			file.read_exact(&mut buffer[..length])
+				.await
+				.map_err(FSError::from)?;
+
+			let chunk_start = offset;
+			let result = self
+				.upload_experimental_image_chunk(
+					mion_ip,
+					name,
+					total,
+					offset,
+					Bytes::copy_from_slice(&buffer[..length]),
+					|sent, _| on_progress(chunk_start + sent, total),
+				)
+				.await;
+			match result {
+				Ok(new_offset) => {
+					offset = new_offset;
+					failed_attempts = 0;
+				}
+				Err(CatBridgeError::NetworkError(
+					NetworkError::HyperError(_) | NetworkError::TimeoutError,
+				)) if failed_attempts < self.retry_policy().retries() => {
+					failed_attempts += 1;
+					debug!(
+						%mion_ip,
+						name,
+						offset,
+						attempt = failed_attempts,
+						"chunk of an image upload got no response, resuming",
+					);
+					sleep(self.retry_policy().delay()).await;
+					// The bridge may have received some, or all of the chunk before
+					// we lost it, so ask rather than guessing.
+					offset = self
+						.begin_experimental_image_upload(mion_ip, name, total)
+						.await?;
+					on_progress(offset, total);
+				}
+				Err(cause) => return Err(cause),
+			}
+		}
+
+		self.finish_experimental_image_upload(mion_ip, name).await
+	}
+
+	/// Start uploading an image, returning how many bytes of it the bridge
+	/// already has from an earlier upload with the same name, and size.
+	///
+	/// ## Errors
+	///
+	/// - If the name isn't valid.
+	/// - If we cannot make the HTTP request, or it times out.
+	/// - If the server does not respond with a 200.
+	/// - If the MION responds with an error, like an image with this name
+	///   already existing: [`NetworkError::ImageOperationRejected`].
+	/// - If the MION responds with an offset we can't understand.
+	pub async fn begin_experimental_image_upload(
+		&self,
+		mion_ip: Ipv4Addr,
+		name: &str,
+		size: u64,
+	) -> Result<u64, CatBridgeError> {
+		validate_image_name(name)?;
+		let size_as_string = format!("{size}");
+		let body_as_string = self
+			.post_form(
+				mion_ip,
+				IMAGE_CGI_PATH,
+				&[
+					("operation", "begin"),
+					("name", name),
+					("size", &size_as_string),
+				],
+			)
+			.await?;
+		let offset = response_offset(&body_as_string, "begin uploading")?;
+		if offset > size {
+			return Err(
+				NetworkError::ParseError(NetworkParseError::InvalidImageField(
+					"OFFSET",
+					format!("{offset}"),
+				))
+				.into(),
+			);
+		}
+		Ok(offset)
+	}
+
+	/// Upload a single chunk of an image of `size` bytes that starts at
+	/// `offset`, returning how many bytes of the image the bridge has now.
+	///
+	/// `on_progress` is called with how much of this chunk has been sent, and
+	/// the size of the chunk.
+	///
+	/// ## Errors
+	///
+	/// - If we cannot encode the name.
+	/// - If we cannot make the HTTP request, or make no progress for the
+	///   request timeout of the client.
+	/// - If the server does not respond with a 200.
+	/// - If the MION responds with an error, like the offset not being where
+	///   the upload is at: [`NetworkError::ImageOperationRejected`].
+	/// - If the MION responds with an offset that isn't after this one, or is
+	///   past the end of this chunk, or the image.
+	pub async fn upload_experimental_image_chunk<ProgressFnTy>(
+		&self,
+		mion_ip: Ipv4Addr,
+		name: &str,
+		size: u64,
+		offset: u64,
+		chunk: Bytes,
+		on_progress: ProgressFnTy,
+	) -> Result<u64, CatBridgeError>
+	where
+		ProgressFnTy: FnMut(u64, u64),
+	{
+		let offset_as_string = format!("{offset}");
+		let chunk_end = offset.saturating_add(chunk.len() as u64);
+		let query = serde_urlencoded::to_string([
+			("operation", "chunk"),
+			("name", name),
+			("offset", &offset_as_string),
+		])
+		.map_err(NetworkParseError::FormDataEncodeError)
+		.map_err(NetworkError::ParseError)?;
+		let body_as_string = self
+			.upload(
+				mion_ip,
+				&format!("{IMAGE_CGI_PATH}?{query}"),
+				"application/octet-stream",
+				chunk.len() as u64,
+				stream::once(async move { Ok(chunk) }),
+				on_progress,
+			)
+			.await?;
+		let new_offset = response_offset(&body_as_string, "upload a chunk of")?;
+		if new_offset <= offset || new_offset > chunk_end || new_offset > size {
+			return Err(
+				NetworkError::ParseError(NetworkParseError::InvalidImageField(
+					"OFFSET",
+					format!("{new_offset}"),
+				))
+				.into(),
+			);
+		}
+		Ok(new_offset)
+	}
+
+	/// Finish uploading an image, the bridge checks it has the whole image
+	/// before adding it to the list of images.
+	///
+	/// ## Errors
+	///
+	/// See [`Self::select_experimental_image`].
+	pub async fn finish_experimental_image_upload(
+		&self,
+		mion_ip: Ipv4Addr,
+		name: &str,
+	) -> Result<(), CatBridgeError> {
+		validate_image_name(name)?;
+		let body_as_string = self
+			.post_form(
+				mion_ip,
+				IMAGE_CGI_PATH,
+				&[("operation", "finish"), ("name", name)],
+			)
+			.await?;
+		image_response_lines(&body_as_string, "finish uploading").map(|_lines| ())
+	}
+}
+
+fn validate_product_code(product_code: &[u8]) -> Result<(), APIError> {
+	if product_code.len() > PRODUCT_CODE_LENGTH {
+		return Err(APIError::InvalidDiscImage(format!(
+			"the product code can be at most {PRODUCT_CODE_LENGTH} bytes, but is {} bytes",
+			product_code.len(),
+		)));
+	}
+	if !product_code.iter().all(u8::is_ascii_graphic) {
+		return Err(APIError::InvalidDiscImage(format!(
+			"the product code {product_code:02x?} is not printable ASCII",
+		)));
+	}
+	Ok(())
+}
+
+/// Split the response from `image.cgi` into lines, erroring if the MION
+/// reported any errors.
+fn image_response_lines<'body>(
+	body_as_string: &'body str,
+	operation: &'static str,
+) -> Result<impl Iterator<Item = &'body str>, CatBridgeError> {
+	let lines = body_as_string
+		.split(['\n', '>', '<'])
+		.map(str::trim)
+		.filter(|line| !line.is_empty());
+	let errors = lines
+		.clone()
+		.filter(|line| line.starts_with("ERROR:"))
+		.map(ToOwned::to_owned)
+		.collect::<Vec<_>>();
+	if errors.is_empty() {
+		Ok(lines)
+	} else {
+		Err(NetworkError::ImageOperationRejected(operation, errors).into())
+	}
+}
+
+/// Get the `OFFSET:` a response from `image.cgi` has.
+fn response_offset(body_as_string: &str, operation: &'static str) -> Result<u64, CatBridgeError> {
+	let offset = image_response_lines(body_as_string, operation)?
+		.find_map(|line| line.strip_prefix("OFFSET:"))
+		.unwrap_or_default();
+	offset.parse::<u64>().map_err(|_| {
+		NetworkError::ParseError(NetworkParseError::InvalidImageField(
+			"OFFSET",
+			offset.to_owned(),
+		))
+		.into()
+	})
+}
+
+#[cfg(test)]
+mod unit_tests {
+	use super::*;
+
+	#[test]
+	pub fn can_round_trip_and_validate_headers() {
+		let header = ImageHeader::new(
+			ImageKind::Wumad,
+			0x1000,
+			0x0005_0000_1010_1000,
+			"WUP-P-ABCE",
+		)
+		.expect("Failed to create image header!");
+		let serialized = header.to_bytes();
+		assert_eq!(serialized.len(), IMAGE_HEADER_SIZE);
+		assert_eq!(&serialized[..4], b"WUMA");
+		assert_eq!(ImageHeader::parse(&serialized), Ok(header));
+
+		assert!(ImageHeader::parse(&serialized[..IMAGE_HEADER_SIZE - 1]).is_err());
+		let mut corrupted = BytesMut::from(&serialized[..]);
+		corrupted[0x09] ^= 0xFF;
+		assert!(ImageHeader::parse(&corrupted).is_err());
+		let mut wrong_magic = BytesMut::from(&serialized[..]);
+		wrong_magic[0] = b'X';
+		assert!(ImageHeader::parse(&wrong_magic).is_err());
+		let mut wrong_version = BytesMut::from(&serialized[..]);
+		wrong_version[0x07] = 2;
+		assert!(ImageHeader::parse(&wrong_version).is_err());
+
+		assert!(ImageHeader::new(ImageKind::Wum, 0x10, 0, "WUP-P-ABCE").is_err());
+		assert!(ImageHeader::new(ImageKind::Wum, 0x1000, 0, "WUP P ABCE").is_err());
+		assert!(ImageHeader::new(ImageKind::Wum, 0x1000, 0, &"A".repeat(33)).is_err());
+	}
+
+	#[tokio::test]
+	pub async fn can_load_headers_from_disk() {
+		let header = ImageHeader::new(ImageKind::Wum, 0x1000, 1, "WUP-P-ABCE")
+			.expect("Failed to create image header!");
+		let mut image = BytesMut::from(&header.to_bytes()[..]);
+		image.resize(0x1000, 0xAA);
+
+		let directory = tempfile::tempdir().expect("Failed to create temporary directory!");
+		let path = directory.path().join("game.wum");
+		tokio::fs::write(&path, &image)
+			.await
+			.expect("Failed to write image!");
+		assert_eq!(
+			ImageHeader::load(&path)
+				.await
+				.expect("Failed to load image!"),
+			header,
+		);
+
+		tokio::fs::write(&path, &image[..0x800])
+			.await
+			.expect("Failed to write truncated image!");
+		assert!(ImageHeader::load(&path).await.is_err());
+	}
+
+	#[test]
+	pub fn can_parse_image_listings_and_names() {
+		assert_eq!(
+			HddImage::try_from("IMAGE:4096,1,My Game (USA)"),
+			Ok(HddImage::new("My Game (USA)".to_owned(), 4096, true)),
+		);
+		let image = HddImage::new("game.wumad".to_owned(), 4096, false);
+		assert_eq!(HddImage::try_from(format!("{image}").as_str()), Ok(image));
+		assert!(HddImage::try_from("IMAGE:big,0,game").is_err());
+		assert!(HddImage::try_from("IMAGE:1,2,game").is_err());
+		assert!(HddImage::try_from("IMAGE:1,0,").is_err());
+
+		assert!(validate_image_name("game.wumad").is_ok());
+		assert!(validate_image_name("My Game (USA)").is_ok());
+		for invalid in ["", "a/b", "a\\b", "a,b", "tab\there", "ゲーム"] {
+			assert_eq!(
+				validate_image_name(invalid),
+				Err(APIError::InvalidImageName(invalid.to_owned())),
+			);
+		}
+		assert!(validate_image_name(&"a".repeat(MAX_IMAGE_NAME_LENGTH + 1)).is_err());
+	}
+}
@@ -9,5 +9,10 @@
 
 pub mod cgis;
 pub mod discovery;
+#[cfg(feature = "emulator")]
+pub mod emulator;
+pub mod firmware;
+pub mod image;
 pub mod parameter;
 pub mod proto;
+pub mod session;
@@ -0,0 +1,1534 @@
+//! Get parameters from the parameter space of a MION.
+
+mod snapshot;
+pub use snapshot::*;
+
+use crate::{
+	errors::{APIError, CatBridgeError, NetworkError, NetworkParseError},
+	mion::proto::{
+		parameter::{
+			codec::{MionParameterClientCodec, MionParameterResponse},
+			well_known::{
+				definition_from_parameter_name, ensure_index_is_writable,
+				index_from_parameter_name, ParameterLocationSpecification,
+			},
+			DumpedMionParameters, MionDumpParameters, PacketType, SetMionParameters,
+			SetMionParametersResponse,
+		},
+		DEFAULT_MION_PARAMETER_PORT, MION_PARAMETER_TIMEOUT_SECONDS,
+	},
+};
+use bytes::{Bytes, BytesMut};
+use fnv::FnvHashMap;
+use futures::{SinkExt, StreamExt};
+use std::{
+	io::{Error as IoError, ErrorKind as IoErrorKind},
+	net::Ipv4Addr,
+};
+use tokio::{
+	net::TcpStream,
+	time::{sleep, Duration},
+};
+use tokio_util::codec::Framed;
+use tracing::debug;
+
+/// A connection to the parameter port of a MION, that has already been
+/// framed into full packets.
+type ParameterConnection = Framed<TcpStream, MionParameterClientCodec>;
+
+/// Get parameters from the parameter space of a MION bridge.
+///
+/// These are the parameters you can access from the normal CLI tools:
+///
+/// - `mionps.exe`
+/// - `mionparameterspace.exe`
+///
+/// It's unclear what many of these parameters are, but we know it contains at
+/// least certain values like the SDK version, NAND Mode, etc.
+///
+/// ## Errors
+///
+/// - If we fail to connect, send, or receive data from the MION IP Address
+///   on the [`DEFAULT_MION_PARAMETER_PORT`].
+/// - If we do not get a response within [`MION_PARAMETER_TIMEOUT_SECONDS`].
+/// - If the MION responded with invalid data.
+pub async fn get_parameters(
+	mion_addr: Ipv4Addr,
+	parameter_port: Option<u16>,
+	timeout: Option<Duration>,
+) -> Result<DumpedMionParameters, CatBridgeError> {
+	get_parameters_with_logging_hooks(
+		mion_addr,
+		parameter_port,
+		timeout,
+		noop_tcp_session_made,
+		noop_connection_established,
+		noop_write_finished,
+		noop_read_finished,
+	)
+	.await
+}
+
+/// Get parameters from the parameter space of a MION bridge.
+///
+/// These are the parameters you can access from the normal CLI tools:
+///
+/// - `mionps.exe`
+/// - `mionparameterspace.exe`
+///
+/// It's unclear what many of these parameters are, but we know it contains at
+/// least certain values like the SDK version, NAND Mode, etc.
+///
+/// This is the function that allows you to specify EXTRA logging hooks (e.g.
+/// those that aren't written to [`tracing`], for like when you need to manually
+/// recreate a CLI with old hacky `println!`).
+///
+/// You probably want [`get_parameters`].
+///
+/// ## Errors
+///
+/// See [`get_parameters`].
+pub async fn get_parameters_with_logging_hooks<
+	TcpSessionHook,
+	ConnectionEstablishedHook,
+	WriteFinishedHook,
+	ReadFinishedHook,
+>(
+	mion_addr: Ipv4Addr,
+	parameter_port: Option<u16>,
+	timeout: Option<Duration>,
+	tcp_session_logging_hook: TcpSessionHook,
+	connection_established_logging_hook: ConnectionEstablishedHook,
+	write_finished_hook: WriteFinishedHook,
+	read_finished_hook: ReadFinishedHook,
+) -> Result<DumpedMionParameters, CatBridgeError>
+where
+	TcpSessionHook: Fn(u128) + Clone + Send + 'static,
+	ConnectionEstablishedHook: Fn(Ipv4Addr) + Clone + Send + 'static,
+	WriteFinishedHook: Fn(usize) + Clone + Send + 'static,
+	ReadFinishedHook: Fn(usize) + Clone + Send + 'static,
+{
+	let usable_timeout = timeout.unwrap_or(Duration::from_secs(MION_PARAMETER_TIMEOUT_SECONDS));
+	// The logging hook expects a millisecond timeout.
+	tcp_session_logging_hook(usable_timeout.as_millis());
+
+	MionParameterClient::new(mion_addr, parameter_port, Some(usable_timeout))
+		.dump_with_logging_hooks(
+			connection_established_logging_hook,
+			write_finished_hook,
+			read_finished_hook,
+		)
+		.await
+}
+
+/// Set one or more parameters for the parameter space of a MION bridge.
+///
+/// These are the parameters you can access from the normal CLI tools:
+///
+/// - `mionps.exe`
+/// - `mionparameterspace.exe`
+///
+/// It's unclear what many of these parameters are, but we know it contains at
+/// least certain values like the SDK version, NAND Mode, etc.
+///
+/// ## Errors
+///
+/// - If we fail to connect, send, or receive data from the MION IP Address
+///   on the [`DEFAULT_MION_PARAMETER_PORT`].
+/// - If we do not get a response within [`MION_PARAMETER_TIMEOUT_SECONDS`].
+/// - If the MION responded with invalid data.
+/// - If the MION responds with a non successful status code.
+pub async fn set_parameters<IterTy>(
+	parameters_to_set: IterTy,
+	mion_addr: Ipv4Addr,
+	parameter_port: Option<u16>,
+	timeout: Option<Duration>,
+) -> Result<SetMionParametersResponse, CatBridgeError>
+where
+	IterTy: Iterator<Item = (ParameterLocationSpecification, u8)>,
+{
+	set_parameters_with_logging_hooks(
+		parameters_to_set,
+		mion_addr,
+		parameter_port,
+		timeout,
+		noop_tcp_session_made,
+		noop_connection_established,
+		noop_write_finished,
+		noop_read_finished,
+		noop_set_value_hook,
+		noop_write_finished,
+	)
+	.await
+	.map(|(resp, _changed_values)| resp)
+}
+
+/// Set one or more parameters for the parameter space of a MION bridge.
+///
+/// These are the parameters you can access from the normal CLI tools:
+///
+/// - `mionps.exe`
+/// - `mionparameterspace.exe`
+///
+/// It's unclear what many of these parameters are, but we know it contains at
+/// least certain values like the SDK version, NAND Mode, etc.
+///
+/// This function is like set parameters, but it also returns a map of:
+/// `<location, old_value>` for each changed value, so you can print out
+/// what the value was changed from/to.
+///
+/// ## Errors
+///
+/// See [`set_parameters`].
+pub async fn set_parameters_and_get_changed_values<IterTy>(
+	parameters_to_set: IterTy,
+	mion_addr: Ipv4Addr,
+	parameter_port: Option<u16>,
+	timeout: Option<Duration>,
+) -> Result<(SetMionParametersResponse, FnvHashMap<usize, u8>), CatBridgeError>
+where
+	IterTy: Iterator<Item = (ParameterLocationSpecification, u8)>,
+{
+	set_parameters_with_logging_hooks(
+		parameters_to_set,
+		mion_addr,
+		parameter_port,
+		timeout,
+		noop_tcp_session_made,
+		noop_connection_established,
+		noop_write_finished,
+		noop_read_finished,
+		noop_set_value_hook,
+		noop_write_finished,
+	)
+	.await
+}
+
+/// Set one or more parameters for the parameter space of a MION bridge, and
+/// make sure they actually stuck.
+///
+/// This re-reads the parameter space after writing to verify every changed
+/// index, and if anything goes wrong writes the original values back. See
+/// [`MionParameterClient::set_many_transactionally`] for the full details.
+///
+/// ## Errors
+///
+/// - If the write failed, and was rolled back:
+///   [`APIError::MIONParameterWriteRolledBack`].
+/// - If the write failed, and could not be rolled back:
+///   [`APIError::MIONParameterRollbackFailed`].
+/// - See [`set_parameters`].
+pub async fn set_parameters_transactionally<IterTy>(
+	parameters_to_set: IterTy,
+	mion_addr: Ipv4Addr,
+	parameter_port: Option<u16>,
+	timeout: Option<Duration>,
+) -> Result<(SetMionParametersResponse, FnvHashMap<usize, u8>), CatBridgeError>
+where
+	IterTy: Iterator<Item = (ParameterLocationSpecification, u8)>,
+{
+	MionParameterClient::new(mion_addr, parameter_port, timeout)
+		.set_many_transactionally(parameters_to_set)
+		.await
+}
+
+/// Set one or more parameters for the parameter space of a MION bridge.
+///
+/// These are the parameters you can access from the normal CLI tools:
+///
+/// - `mionps.exe`
+/// - `mionparameterspace.exe`
+///
+/// It's unclear what many of these parameters are, but we know it contains at
+/// least certain values like the SDK version, NAND Mode, etc.
+///
+/// This is the function that allows you to specify EXTRA logging hooks (e.g.
+/// those that aren't written to [`tracing`], for like when you need to manually
+/// recreate a CLI with old hacky `println!`).
+///
+/// You probably want [`set_parameters`].
+///
+/// ## Errors
+///
+/// See [`set_parameters`].
+#[allow(
+	// Yes, clippy I KNOW THIS IS BAD. I HATE IT TOO.
+	clippy::too_many_arguments,
+)]
+pub async fn set_parameters_with_logging_hooks<
+	IterTy,
+	TcpSessionHook,
+	ConnectionEstablishedHook,
+	WriteFinishedHook,
+	ReadFinishedHook,
+	SetNewValueHook,
+	WriteSetFinishedHook,
+>(
+	parameters_to_set: IterTy,
+	mion_addr: Ipv4Addr,
+	parameter_port: Option<u16>,
+	timeout: Option<Duration>,
+	tcp_session_logging_hook: TcpSessionHook,
+	connection_established_logging_hook: ConnectionEstablishedHook,
+	write_finished_hook: WriteFinishedHook,
+	read_finished_hook: ReadFinishedHook,
+	set_new_value_hook: SetNewValueHook,
+	write_set_finished_hook: WriteSetFinishedHook,
+) -> Result<(SetMionParametersResponse, FnvHashMap<usize, u8>), CatBridgeError>
+where
+	IterTy: Iterator<Item = (ParameterLocationSpecification, u8)>,
+	TcpSessionHook: Fn(u128) + Clone + Send + 'static,
+	ConnectionEstablishedHook: Fn(Ipv4Addr) + Clone + Send + 'static,
+	WriteFinishedHook: Fn(usize) + Clone + Send + 'static,
+	ReadFinishedHook: Fn(usize) + Clone + Send + 'static,
+	SetNewValueHook: Fn(u8, u8, usize) + Clone + Send + 'static,
+	WriteSetFinishedHook: Fn(usize) + Clone + Send + 'static,
+{
+	let usable_timeout = timeout.unwrap_or(Duration::from_secs(MION_PARAMETER_TIMEOUT_SECONDS));
+	// The logging hook expects a millisecond timeout.
+	tcp_session_logging_hook(usable_timeout.as_millis());
+
+	MionParameterClient::new(mion_addr, parameter_port, Some(usable_timeout))
+		.set_many_with_logging_hooks(
+			parameters_to_set,
+			connection_established_logging_hook,
+			write_finished_hook,
+			read_finished_hook,
+			set_new_value_hook,
+			write_set_finished_hook,
+		)
+		.await
+}
+
+/// A client for the parameter port of a single MION, that keeps one TCP
+/// connection open across requests.
+///
+/// Each of the free functions in this module opens a brand new connection
+/// for every call, which is fine for one-off CLI invocations, but if you're
+/// polling parameters, or applying many edits you want to use a client
+/// instead so you're not constantly reconnecting to the bridge.
+///
+/// The connection is opened lazily on the first request. If the bridge drops
+/// the connection in between requests the client will reconnect, and retry
+/// reading the parameters once automatically. Sets are never retried, as
+/// the MION may have already applied them.
+#[derive(Debug)]
+pub struct MionParameterClient {
+	/// The IP address of the MION we're talking to.
+	mion_addr: Ipv4Addr,
+	/// The port the parameter space is being served on.
+	parameter_port: u16,
+	/// How long any single request (including connecting) is allowed to take.
+	timeout: Duration,
+	/// The currently open connection, if we have one.
+	connection: Option<ParameterConnection>,
+}
+
+impl MionParameterClient {
+	/// Create a new client for the parameter space of a MION.
+	///
+	/// This does not connect to the MION until the first request is made. If
+	/// no port is provided we use the [`DEFAULT_MION_PARAMETER_PORT`], and if
+	/// no timeout is provided we use [`MION_PARAMETER_TIMEOUT_SECONDS`].
+	#[must_use]
+	pub fn new(
+		mion_addr: Ipv4Addr,
+		parameter_port: Option<u16>,
+		timeout: Option<Duration>,
+	) -> Self {
+		Self {
+			mion_addr,
+			parameter_port: parameter_port.unwrap_or(DEFAULT_MION_PARAMETER_PORT),
+			timeout: timeout.unwrap_or(Duration::from_secs(MION_PARAMETER_TIMEOUT_SECONDS)),
+			connection: None,
+		}
+	}
+
+	/// Create a new client for the parameter space of a MION, and immediately
+	/// connect to it.
+	///
+	/// ## Errors
+	///
+	/// - If we fail to connect to the MION.
+	/// - If we do not connect within the timeout.
+	pub async fn connect(
+		mion_addr: Ipv4Addr,
+		parameter_port: Option<u16>,
+		timeout: Option<Duration>,
+	) -> Result<Self, CatBridgeError> {
+		let mut client = Self::new(mion_addr, parameter_port, timeout);
+		let usable_timeout = client.timeout;
+		tokio::select! {
+		  res = client.get_connection(noop_connection_established) => { res.map(|_| ()) }
+		  () = sleep(usable_timeout) => {
+			  Err(CatBridgeError::NetworkError(NetworkError::TimeoutError))
+		  }
+		}?;
+		Ok(client)
+	}
+
+	/// The IP address of the MION this client talks to.
+	#[must_use]
+	pub const fn get_mion_addr(&self) -> Ipv4Addr {
+		self.mion_addr
+	}
+
+	/// The port on the MION this client talks to.
+	#[must_use]
+	pub const fn get_parameter_port(&self) -> u16 {
+		self.parameter_port
+	}
+
+	/// How long any single request is allowed to take.
+	#[must_use]
+	pub const fn get_timeout(&self) -> Duration {
+		self.timeout
+	}
+
+	/// Update how long any single request is allowed to take.
+	pub fn set_timeout(&mut self, timeout: Duration) {
+		self.timeout = timeout;
+	}
+
+	/// If this client currently has an open connection.
+	///
+	/// Note: the MION may have closed the connection on it's side, we won't
+	/// know until we try to make another request.
+	#[must_use]
+	pub const fn is_connected(&self) -> bool {
+		self.connection.is_some()
+	}
+
+	/// Close the current connection if there is one.
+	///
+	/// The next request will open a new connection.
+	pub fn disconnect(&mut self) {
+		self.connection = None;
+	}
+
+	/// Dump all of the parameters from the parameter space.
+	///
+	/// ## Errors
+	///
+	/// - If we fail to connect, send, or receive data from the MION.
+	/// - If we do not get a response within the timeout.
+	/// - If the MION responded with invalid data.
+	pub async fn dump(&mut self) -> Result<DumpedMionParameters, CatBridgeError> {
+		self.dump_with_logging_hooks(
+			noop_connection_established,
+			noop_write_finished,
+			noop_read_finished,
+		)
+		.await
+	}
+
+	/// Dump all of the parameters from the parameter space.
+	///
+	/// This is the function that allows you to specify EXTRA logging hooks
+	/// (e.g. those that aren't written to [`tracing`], for like when you need
+	/// to manually recreate a CLI with old hacky `println!`).
+	///
+	/// You probably want [`MionParameterClient::dump`].
+	///
+	/// ## Errors
+	///
+	/// See [`MionParameterClient::dump`].
+	pub async fn dump_with_logging_hooks<
+		ConnectionEstablishedHook,
+		WriteFinishedHook,
+		ReadFinishedHook,
+	>(
+		&mut self,
+		connection_established_hook: ConnectionEstablishedHook,
+		write_finished_hook: WriteFinishedHook,
+		read_finished_hook: ReadFinishedHook,
+	) -> Result<DumpedMionParameters, CatBridgeError>
+	where
+		ConnectionEstablishedHook: Fn(Ipv4Addr) + Clone + Send + 'static,
+		WriteFinishedHook: Fn(usize) + Clone + Send + 'static,
+		ReadFinishedHook: Fn(usize) + Clone + Send + 'static,
+	{
+		let reused_connection = self.is_connected();
+		match self
+			.try_dump(
+				connection_established_hook.clone(),
+				write_finished_hook.clone(),
+				read_finished_hook.clone(),
+			)
+			.await
+		{
+			Err(cause) if reused_connection && is_connection_lost(&cause) => {
+				debug!(
+					?cause,
+					mion.addr = %self.mion_addr,
+					"parameter connection was dropped, reconnecting",
+				);
+				self.try_dump(
+					connection_established_hook,
+					write_finished_hook,
+					read_finished_hook,
+				)
+				.await
+			}
+			res => res,
+		}
+	}
+
+	/// Set one or more parameters in the parameter space.
+	///
+	/// This dumps the current parameters, and writes them back with your
+	/// changes applied. Returns the response from the MION, as well as a map
+	/// of: `<location, old_value>` for each changed value.
+	///
+	/// ## Errors
+	///
+	/// - If any of the parameters are not known, or out of range (nothing is
+	///   sent to the MION in this case).
+	/// - If we fail to connect, send, or receive data from the MION.
+	/// - If we do not get a response within the timeout.
+	/// - If the MION responded with invalid data.
+	pub async fn set_many<IterTy>(
+		&mut self,
+		parameters_to_set: IterTy,
+	) -> Result<(SetMionParametersResponse, FnvHashMap<usize, u8>), CatBridgeError>
+	where
+		IterTy: Iterator<Item = (ParameterLocationSpecification, u8)>,
+	{
+		self.set_many_with_logging_hooks(
+			parameters_to_set,
+			noop_connection_established,
+			noop_write_finished,
+			noop_read_finished,
+			noop_set_value_hook,
+			noop_write_finished,
+		)
+		.await
+	}
+
+	/// Set one or more parameters in the parameter space.
+	///
+	/// This is the function that allows you to specify EXTRA logging hooks
+	/// (e.g. those that aren't written to [`tracing`], for like when you need
+	/// to manually recreate a CLI with old hacky `println!`).
+	///
+	/// You probably want [`MionParameterClient::set_many`].
+	///
+	/// ## Errors
+	///
+	/// See [`MionParameterClient::set_many`].
+	pub async fn set_many_with_logging_hooks<
+		IterTy,
+		ConnectionEstablishedHook,
+		WriteFinishedHook,
+		ReadFinishedHook,
+		SetNewValueHook,
+		WriteSetFinishedHook,
+	>(
+		&mut self,
+		parameters_to_set: IterTy,
+		connection_established_hook: ConnectionEstablishedHook,
+		write_finished_hook: WriteFinishedHook,
+		read_finished_hook: ReadFinishedHook,
+		set_new_value_hook: SetNewValueHook,
+		write_set_finished_hook: WriteSetFinishedHook,
+	) -> Result<(SetMionParametersResponse, FnvHashMap<usize, u8>), CatBridgeError>
+	where
+		IterTy: Iterator<Item = (ParameterLocationSpecification, u8)>,
+		ConnectionEstablishedHook: Fn(Ipv4Addr) + Clone + Send + 'static,
+		WriteFinishedHook: Fn(usize) + Clone + Send + 'static,
+		ReadFinishedHook: Fn(usize) + Clone + Send + 'static,
+		SetNewValueHook: Fn(u8, u8, usize) + Clone + Send + 'static,
+		WriteSetFinishedHook: Fn(usize) + Clone + Send + 'static,
+	{
+		let mut changes = Vec::new();
+		for (location_spec, new_value) in parameters_to_set {
+			changes.extend(
+				resolve_location(location_spec, new_value)?
+					.into_iter()
+					.map(|(location, byte)| (location, None, byte)),
+			);
+		}
+
+		self.apply_changes(
+			&changes,
+			connection_established_hook,
+			write_finished_hook,
+			read_finished_hook,
+			set_new_value_hook,
+			write_set_finished_hook,
+		)
+		.await
+	}
+
+	/// Set one or more parameters in the parameter space, but only if every
+	/// parameter currently has the value you expect.
+	///
+	/// Each item is: `(location, expected_value, new_value)`. If any location
+	/// does not currently have it's expected value, nothing is written.
+	///
+	/// ## Errors
+	///
+	/// - If any location did not have it's expected value.
+	/// - See [`MionParameterClient::set_many`].
+	pub async fn compare_and_set<IterTy>(
+		&mut self,
+		parameters_to_set: IterTy,
+	) -> Result<SetMionParametersResponse, CatBridgeError>
+	where
+		IterTy: Iterator<Item = (ParameterLocationSpecification, u8, u8)>,
+	{
+		let mut changes = Vec::new();
+		for (location_spec, expected_value, new_value) in parameters_to_set {
+			let expected = resolve_location(location_spec.clone(), expected_value)?;
+			changes.extend(
+				resolve_location(location_spec, new_value)?
+					.into_iter()
+					.zip(expected)
+					.map(|((location, byte), (_, expected_byte))| {
+						(location, Some(expected_byte), byte)
+					}),
+			);
+		}
+
+		self.apply_changes(
+			&changes,
+			noop_connection_established,
+			noop_write_finished,
+			noop_read_finished,
+			noop_set_value_hook,
+			noop_write_finished,
+		)
+		.await
+		.map(|(response, _old_values)| response)
+	}
+
+	/// Set one or more parameters in the parameter space, verifying the write,
+	/// and rolling back if anything goes wrong.
+	///
+	/// This:
+	///
+	/// 1. Dumps the current parameters, remembering the original values.
+	/// 2. Writes the changes, but only if nothing changed since the dump.
+	/// 3. Dumps the parameters again, and checks every changed index has the
+	///    value we wrote.
+	///
+	/// If the MION reports a failure, the request errors out part way through,
+	/// or any index doesn't have the value we wrote, the original values are
+	/// written back, and the indexes that had to be restored are reported in
+	/// the error. On success this returns the same thing as
+	/// [`MionParameterClient::set_many`].
+	///
+	/// ## Errors
+	///
+	/// - If the write failed, and was rolled back:
+	///   [`APIError::MIONParameterWriteRolledBack`].
+	/// - If the write failed, and could not be rolled back:
+	///   [`APIError::MIONParameterRollbackFailed`].
+	/// - If the parameters changed in between reading, and writing them
+	///   (nothing is written in this case).
+	/// - See [`MionParameterClient::set_many`].
+	pub async fn set_many_transactionally<IterTy>(
+		&mut self,
+		parameters_to_set: IterTy,
+	) -> Result<(SetMionParametersResponse, FnvHashMap<usize, u8>), CatBridgeError>
+	where
+		IterTy: Iterator<Item = (ParameterLocationSpecification, u8)>,
+	{
+		let mut new_values = FnvHashMap::default();
+		for (location_spec, new_value) in parameters_to_set {
+			new_values.extend(resolve_location(location_spec, new_value)?);
+		}
+
+		let original = self.dump().await?;
+		let old_values = new_values
+			.keys()
+			.map(|location| (*location, original.get_raw_parameters()[*location]))
+			.collect::<FnvHashMap<_, _>>();
+		let changes = new_values
+			.iter()
+			.map(|(location, new_value)| (*location, Some(old_values[location]), *new_value))
+			.collect::<Vec<_>>();
+
+		let failure_reason =
+			match self
+				.apply_changes(
+					&changes,
+					noop_connection_established,
+					noop_write_finished,
+					noop_read_finished,
+					noop_set_value_hook,
+					noop_write_finished,
+				)
+				.await
+			{
+				// These only come back before the set is sent, so nothing was
+				// written, and there's nothing to roll back.
+				Err(CatBridgeError::ApiError(cause)) => return Err(cause.into()),
+				Err(cause) => format!("{cause}"),
+				Ok((response, _)) if response.is_error() => format!(
+					"MION responded with return code: {}",
+					response.get_return_code()
+				),
+				Ok((response, _)) => match self.dump().await {
+					Ok(written) => {
+						let mut mismatched = new_values
+							.iter()
+							.filter(|(location, new_value)| {
+								written.get_raw_parameters()[**location] != **new_value
+							})
+							.map(|(location, new_value)| {
+								(
+									*location,
+									*new_value,
+									written.get_raw_parameters()[*location],
+								)
+							})
+							.collect::<Vec<_>>();
+						if mismatched.is_empty() {
+							return Ok((response, old_values));
+						}
+						mismatched.sort_unstable();
+						mismatched
+							.into_iter()
+							.map(|(location, expected, actual)| {
+								format!("index {location} was {actual:#04x} after writing {expected:#04x}")
+							})
+							.collect::<Vec<_>>()
+							.join(", ")
+					}
+					Err(cause) => format!("could not verify the write: {cause}"),
+				},
+			};
+
+		debug!(
+			mion.addr = %self.mion_addr,
+			reason = %failure_reason,
+			"parameter write failed, rolling back",
+		);
+		Err(match self.roll_back(&old_values).await {
+			Ok(rolled_back) => APIError::MIONParameterWriteRolledBack(rolled_back, failure_reason),
+			Err(not_rolled_back) => {
+				APIError::MIONParameterRollbackFailed(not_rolled_back, failure_reason)
+			}
+		}
+		.into())
+	}
+
+	/// Set one or more named fields in the parameter space transactionally,
+	/// using the same values you'd see when reading them (e.g. `2.12.13` for
+	/// `sdk-version`).
+	///
+	/// Every byte of every field is written in the same request, and verified,
+	/// and rolled back just like [`MionParameterClient::set_many_transactionally`].
+	///
+	/// ## Errors
+	///
+	/// - If any name is not a field we know about.
+	/// - If any value is not valid for it's field.
+	/// - See [`MionParameterClient::set_many_transactionally`].
+	pub async fn set_named_transactionally<'value, IterTy>(
+		&mut self,
+		fields_to_set: IterTy,
+	) -> Result<(SetMionParametersResponse, FnvHashMap<usize, u8>), CatBridgeError>
+	where
+		IterTy: Iterator<Item = (&'value str, &'value str)>,
+	{
+		let mut locations = Vec::new();
+		for (name, value) in fields_to_set {
+			let definition = definition_from_parameter_name(name)
+				.ok_or_else(|| APIError::MIONParameterNameNotKnown(name.to_owned()))?;
+			for (location, byte) in definition.encode(value)? {
+				locations.push((
+					ParameterLocationSpecification::Index(
+						u16::try_from(location)
+							.map_err(|_| APIError::MIONParameterNotInRage(location))?,
+					),
+					byte,
+				));
+			}
+		}
+
+		self.set_many_transactionally(locations.into_iter()).await
+	}
+
+	/// Write back the original values of a failed write.
+	///
+	/// Returns the (sorted) indexes that actually had to be put back on
+	/// success, or the indexes we could not confirm were restored on failure.
+	async fn roll_back(
+		&mut self,
+		old_values: &FnvHashMap<usize, u8>,
+	) -> Result<Vec<usize>, Vec<usize>> {
+		let mut all_locations = old_values.keys().copied().collect::<Vec<_>>();
+		all_locations.sort_unstable();
+
+		let Ok(current) = self.dump().await else {
+			return Err(all_locations);
+		};
+		let to_restore = all_locations
+			.iter()
+			.copied()
+			.filter(|location| current.get_raw_parameters()[*location] != old_values[location])
+			.collect::<Vec<_>>();
+		if to_restore.is_empty() {
+			return Ok(to_restore);
+		}
+
+		let changes = to_restore
+			.iter()
+			.map(|location| (*location, None, old_values[location]))
+			.collect::<Vec<_>>();
+		match self
+			.apply_changes(
+				&changes,
+				noop_connection_established,
+				noop_write_finished,
+				noop_read_finished,
+				noop_set_value_hook,
+				noop_write_finished,
+			)
+			.await
+		{
+			Ok((response, _)) if response.is_success() => {}
+			_ => return Err(to_restore),
+		}
+
+		let Ok(restored) = self.dump().await else {
+			return Err(to_restore);
+		};
+		let still_wrong = to_restore
+			.iter()
+			.copied()
+			.filter(|location| restored.get_raw_parameters()[*location] != old_values[location])
+			.collect::<Vec<_>>();
+		if still_wrong.is_empty() {
+			Ok(to_restore)
+		} else {
+			Err(still_wrong)
+		}
+	}
+
+	/// Put the parameter space back to how it was when a snapshot was taken.
+	///
+	/// Only the bytes that differ from the snapshot are changed, and only if
+	/// they haven't changed again since we compared them. Returns the
+	/// differences that were applied (going from the bridge to the snapshot),
+	/// and the response from the MION if anything needed to be written.
+	///
+	/// ## Errors
+	///
+	/// - If the parameters changed while we were restoring.
+	/// - See [`MionParameterClient::set_many`].
+	pub async fn restore_snapshot(
+		&mut self,
+		snapshot: &ParameterSnapshot,
+	) -> Result<(Option<SetMionParametersResponse>, ParameterDiff), CatBridgeError> {
+		let current = self.dump().await?;
+		let diff = ParameterDiff::between(&current, snapshot.parameters());
+		if diff.is_empty() {
+			return Ok((None, diff));
+		}
+
+		let mut changes = Vec::with_capacity(diff.byte_differences().len());
+		for difference in diff.byte_differences() {
+			let location = usize::from(
+				u16::try_from(difference.index())
+					.map_err(|_| APIError::MIONParameterNotInRage(difference.index()))?,
+			);
+			if location > 511 {
+				return Err(APIError::MIONParameterNotInRage(location).into());
+			}
+			ensure_index_is_writable(location)?;
+			changes.push((
+				location,
+				Some(difference.old_value()),
+				difference.new_value(),
+			));
+		}
+		let (response, _old_values) = self
+			.apply_changes(
+				&changes,
+				noop_connection_established,
+				noop_write_finished,
+				noop_read_finished,
+				noop_set_value_hook,
+				noop_write_finished,
+			)
+			.await?;
+
+		Ok((Some(response), diff))
+	}
+
+	/// Apply a series of `(location, expected_value, new_value)` changes.
+	///
+	/// Only reading the current parameters is retried if the connection was
+	/// dropped. Once the set has been sent we can't know if the MION applied it,
+	/// so a failure after that point is always returned to the caller.
+	#[allow(
+		// Yes, clippy I KNOW THIS IS BAD. I HATE IT TOO.
+		clippy::too_many_arguments,
+	)]
+	async fn apply_changes<
+		ConnectionEstablishedHook,
+		WriteFinishedHook,
+		ReadFinishedHook,
+		SetNewValueHook,
+		WriteSetFinishedHook,
+	>(
+		&mut self,
+		changes: &[(usize, Option<u8>, u8)],
+		connection_established_hook: ConnectionEstablishedHook,
+		write_finished_hook: WriteFinishedHook,
+		read_finished_hook: ReadFinishedHook,
+		set_new_value_hook: SetNewValueHook,
+		write_set_finished_hook: WriteSetFinishedHook,
+	) -> Result<(SetMionParametersResponse, FnvHashMap<usize, u8>), CatBridgeError>
+	where
+		ConnectionEstablishedHook: Fn(Ipv4Addr) + Clone + Send + 'static,
+		WriteFinishedHook: Fn(usize) + Clone + Send + 'static,
+		ReadFinishedHook: Fn(usize) + Clone + Send + 'static,
+		SetNewValueHook: Fn(u8, u8, usize) + Clone + Send + 'static,
+		WriteSetFinishedHook: Fn(usize) + Clone + Send + 'static,
+	{
+		let got_parameters = self
+			.dump_with_logging_hooks(
+				connection_established_hook,
+				write_finished_hook,
+				read_finished_hook,
+			)
+			.await?;
+
+		let mut old_values_map = FnvHashMap::default();
+		let mut new_parameters = BytesMut::with_capacity(512);
+		new_parameters.extend_from_slice(got_parameters.get_raw_parameters());
+		for (location, expected_value, new_value) in changes.iter().copied() {
+			let orig_value = got_parameters.get_raw_parameters()[location];
+			if let Some(expected) = expected_value {
+				if expected != orig_value {
+					return Err(APIError::MIONParameterCompareFailed(
+						location, expected, orig_value,
+					)
+					.into());
+				}
+			}
+
+			set_new_value_hook(orig_value, new_value, location);
+			old_values_map.insert(location, orig_value);
+			new_parameters[location] = new_value;
+		}
+
+		let request = SetMionParameters::new(new_parameters.freeze())?;
+		let usable_timeout = self.timeout;
+		let result = tokio::select! {
+		  res = self.set_on_connection(request, write_set_finished_hook) => { res }
+		  () = sleep(usable_timeout) => {
+			  Err(CatBridgeError::NetworkError(NetworkError::TimeoutError))
+		  }
+		};
+		if result.is_err() {
+			// We have no idea what state the connection is in, start over.
+			self.disconnect();
+		}
+
+		result.map(|response| (response, old_values_map))
+	}
+
+	async fn try_dump<ConnectionEstablishedHook, WriteFinishedHook, ReadFinishedHook>(
+		&mut self,
+		connection_established_hook: ConnectionEstablishedHook,
+		write_finished_hook: WriteFinishedHook,
+		read_finished_hook: ReadFinishedHook,
+	) -> Result<DumpedMionParameters, CatBridgeError>
+	where
+		ConnectionEstablishedHook: Fn(Ipv4Addr) + Clone + Send + 'static,
+		WriteFinishedHook: Fn(usize) + Clone + Send + 'static,
+		ReadFinishedHook: Fn(usize) + Clone + Send + 'static,
+	{
+		let usable_timeout = self.timeout;
+		let result = tokio::select! {
+		  res = self.dump_on_connection(
+				connection_established_hook,
+				write_finished_hook,
+				read_finished_hook,
+			) => { res }
+		  () = sleep(usable_timeout) => {
+			  Err(CatBridgeError::NetworkError(NetworkError::TimeoutError))
+		  }
+		};
+		if result.is_err() {
+			// We have no idea what state the connection is in, start over.
+			self.disconnect();
+		}
+
+		result
+	}
+
+	async fn dump_on_connection<ConnectionEstablishedHook, WriteFinishedHook, ReadFinishedHook>(
+		&mut self,
+		connection_established_hook: ConnectionEstablishedHook,
+		write_finished_hook: WriteFinishedHook,
+		read_finished_hook: ReadFinishedHook,
+	) -> Result<DumpedMionParameters, CatBridgeError>
+	where
+		ConnectionEstablishedHook: Fn(Ipv4Addr) + Clone + Send + 'static,
+		WriteFinishedHook: Fn(usize) + Clone + Send + 'static,
+		ReadFinishedHook: Fn(usize) + Clone + Send + 'static,
+	{
+		let connection = self.get_connection(connection_established_hook).await?;
+		connection.send(MionDumpParameters::new()).await?;
+
+		let expected_bytes_to_read = 520;
+		write_finished_hook(expected_bytes_to_read);
+
+		match next_response(connection).await? {
+			MionParameterResponse::Dumped(parameters) => {
+				read_finished_hook(Bytes::from(&parameters).len());
+				Ok(parameters)
+			}
+			MionParameterResponse::Set(_) => Err(NetworkError::ParseError(
+				NetworkParseError::UnknownParamsPacketType(i32::from(PacketType::Write)),
+			)
+			.into()),
+		}
+	}
+
+	async fn set_on_connection<WriteFinishedHook>(
+		&mut self,
+		request: SetMionParameters,
+		write_finished_hook: WriteFinishedHook,
+	) -> Result<SetMionParametersResponse, CatBridgeError>
+	where
+		WriteFinishedHook: Fn(usize) + Clone + Send + 'static,
+	{
+		let connection = self.get_connection(noop_connection_established).await?;
+		connection.send(request).await?;
+
+		let expected_bytes_to_read = 12;
+		write_finished_hook(expected_bytes_to_read);
+
+		match next_response(connection).await? {
+			MionParameterResponse::Set(response) => Ok(response),
+			MionParameterResponse::Dumped(_) => Err(NetworkError::ParseError(
+				NetworkParseError::UnknownParamsPacketType(i32::from(PacketType::Read)),
+			)
+			.into()),
+		}
+	}
+
+	/// Get the currently open connection, or open a new one if we don't have
+	/// one.
+	async fn get_connection<ConnectionEstablishedHook>(
+		&mut self,
+		connection_established_hook: ConnectionEstablishedHook,
+	) -> Result<&mut ParameterConnection, CatBridgeError>
+	where
+		ConnectionEstablishedHook: Fn(Ipv4Addr) + Clone + Send + 'static,
+	{
+		let connection = if let Some(connection) = self.connection.take() {
+			connection
+		} else {
+			let stream = TcpStream::connect((self.mion_addr, self.parameter_port))
+				.await
+				.map_err(NetworkError::IOError)?;
+			connection_established_hook(self.mion_addr);
+			Framed::new(stream, MionParameterClientCodec)
+		};
+
+		Ok(self.connection.insert(connection))
+	}
+}
+
+/// Turn a location specification, and the value to write there into the
+/// actual `(index, byte)` pairs in the parameter space we need to write.
+///
+/// Names of fields that span multiple bytes resolve to every byte of that
+/// field, with the value encoded the same way the field is read (so `0x12`
+/// written to a two byte field becomes `[0x12, 0x00]`). Fields that can't be
+/// represented by a single number (like `sdk-version`) are refused, they need
+/// to be written byte by byte with [`ParameterDefinition::encode`].
+///
+/// [`ParameterDefinition::encode`]: crate::mion::proto::parameter::well_known::ParameterDefinition::encode
+fn resolve_location(
+	location_spec: ParameterLocationSpecification,
+	value: u8,
+) -> Result<Vec<(usize, u8)>, APIError> {
+	let locations = match location_spec {
+		ParameterLocationSpecification::Index(idx) => vec![(usize::from(idx), value)],
+		ParameterLocationSpecification::NameLike(name) => {
+			if let Some(definition) = definition_from_parameter_name(&name) {
+				definition.encode(&value.to_string())?
+			} else {
+				vec![(
+					index_from_parameter_name(&name)
+						.ok_or(APIError::MIONParameterNameNotKnown(name))?,
+					value,
+				)]
+			}
+		}
+	};
+	for (location, _) in &locations {
+		if *location > 511 {
+			return Err(APIError::MIONParameterNotInRage(*location));
+		}
+		ensure_index_is_writable(*location)?;
+	}
+
+	Ok(locations)
+}
+
+/// If an error means the MION closed the connection on us (as opposed to
+/// sending us bad data, or timing out).
+fn is_connection_lost(cause: &CatBridgeError) -> bool {
+	matches!(
+		cause,
+		CatBridgeError::NetworkError(NetworkError::IOError(io_error))
+			if matches!(
+				io_error.kind(),
+				IoErrorKind::UnexpectedEof
+					| IoErrorKind::ConnectionReset
+					| IoErrorKind::ConnectionAborted
+					| IoErrorKind::BrokenPipe
+					| IoErrorKind::NotConnected
+			)
+	)
+}
+
+/// Wait for the next full response packet to arrive on a connection.
+///
+/// ## Errors
+///
+/// - If the MION closed the connection before sending a full response.
+/// - If the MION sent us an invalid packet.
+async fn next_response(
+	connection: &mut ParameterConnection,
+) -> Result<MionParameterResponse, NetworkError> {
+	connection.next().await.unwrap_or_else(|| {
+		Err(NetworkError::IOError(IoError::new(
+			IoErrorKind::UnexpectedEof,
+			"MION closed the parameter connection before sending a full response",
+		)))
+	})
+}
+
+fn noop_tcp_session_made(_timeout: u128) {}
+
+fn noop_connection_established(_ip: Ipv4Addr) {}
+
+fn noop_write_finished(_expected_read: usize) {}
+
+fn noop_read_finished(_read_size: usize) {}
+
+fn noop_set_value_hook(_old_value: u8, _new_value: u8, _location: usize) {}
+
+#[cfg(test)]
+mod unit_tests {
+	use super::*;
+	use crate::mion::{
+		emulator::EmulatedMion,
+		proto::{
+			control::MionIdentity,
+			parameter::codec::{MionParameterRequest, MionParameterServerCodec},
+		},
+	};
+	use mac_address::MacAddress;
+	use tokio::net::TcpListener;
+
+	async fn spawn_emulator() -> EmulatedMion {
+		EmulatedMion::spawn(
+			MionIdentity::new(
+				None,
+				[0, 14, 80, 1],
+				[0x13, 0x05, 0x20, 0x71],
+				Ipv4Addr::LOCALHOST,
+				MacAddress::new([0x00, 0x25, 0x5c, 0xba, 0x5a, 0x00]),
+				"00-25-5C-BA-5A-00".to_owned(),
+			)
+			.expect("Failed to create test identity!"),
+		)
+		.await
+		.expect("Failed to spawn emulated MION!")
+	}
+
+	#[tokio::test]
+	pub async fn client_reuses_connection() {
+		let emulator = spawn_emulator().await;
+		let mut client = MionParameterClient::new(
+			*emulator.parameter_address().ip(),
+			Some(emulator.parameter_address().port()),
+			None,
+		);
+		assert!(!client.is_connected());
+
+		let (response, old_values) = client
+			.set_many([(ParameterLocationSpecification::Index(4), 12_u8)].into_iter())
+			.await
+			.expect("Failed to set parameters!");
+		assert!(response.is_success());
+		assert_eq!(old_values.get(&4), Some(&0));
+		assert!(client.is_connected());
+
+		let dumped = client.dump().await.expect("Failed to dump parameters!");
+		assert_eq!(dumped.get_parameter_by_index(4), Ok(12));
+
+		// Compare and set should refuse to write if the value doesn't match...
+		assert!(matches!(
+			client
+				.compare_and_set(
+					[(ParameterLocationSpecification::Index(4), 0_u8, 13_u8)].into_iter()
+				)
+				.await,
+			Err(CatBridgeError::ApiError(
+				APIError::MIONParameterCompareFailed(4, 0, 12)
+			)),
+		));
+		// ... but work if it does.
+		client
+			.compare_and_set([(ParameterLocationSpecification::Index(4), 12_u8, 13_u8)].into_iter())
+			.await
+			.expect("Failed to compare and set!");
+		assert_eq!(
+			client
+				.dump()
+				.await
+				.expect("Failed to dump parameters!")
+				.get_parameter_by_index(4),
+			Ok(13),
+		);
+	}
+
+	#[tokio::test]
+	pub async fn client_can_restore_snapshots() {
+		let emulator = spawn_emulator().await;
+		emulator
+			.set_parameter(3, 2)
+			.expect("Failed to set parameter on emulator!");
+		let mut client = MionParameterClient::new(
+			*emulator.parameter_address().ip(),
+			Some(emulator.parameter_address().port()),
+			None,
+		);
+
+		let snapshot = ParameterSnapshot::new(client.dump().await.expect("Failed to dump!"));
+		let (response, diff) = client
+			.restore_snapshot(&snapshot)
+			.await
+			.expect("Failed to restore unchanged snapshot!");
+		assert!(
+			response.is_none(),
+			"Nothing should be written if nothing changed!"
+		);
+		assert!(diff.is_empty());
+
+		emulator
+			.set_parameter(3, 9)
+			.expect("Failed to set parameter on emulator!");
+		emulator
+			.set_parameter(400, 1)
+			.expect("Failed to set parameter on emulator!");
+		let (response, diff) = client
+			.restore_snapshot(&snapshot)
+			.await
+			.expect("Failed to restore snapshot!");
+		assert!(response.expect("Restore should have written!").is_success());
+		assert_eq!(
+			diff.byte_differences()
+				.iter()
+				.map(|difference| (
+					difference.index(),
+					difference.old_value(),
+					difference.new_value()
+				))
+				.collect::<Vec<_>>(),
+			vec![(3, 9, 2), (400, 1, 0)],
+		);
+		assert_eq!(
+			emulator.parameters(),
+			snapshot.parameters().get_raw_parameters()
+		);
+	}
+
+	#[tokio::test]
+	pub async fn transactional_writes_are_verified() {
+		let emulator = spawn_emulator().await;
+		let mut client = MionParameterClient::new(
+			*emulator.parameter_address().ip(),
+			Some(emulator.parameter_address().port()),
+			None,
+		);
+
+		let (response, old_values) = client
+			.set_many_transactionally(
+				[
+					(ParameterLocationSpecification::Index(4), 12_u8),
+					(ParameterLocationSpecification::Index(100), 7_u8),
+				]
+				.into_iter(),
+			)
+			.await
+			.expect("Failed to transactionally set parameters!");
+		assert!(response.is_success());
+		assert_eq!(old_values.get(&4), Some(&0));
+		assert_eq!(old_values.get(&100), Some(&0));
+		assert_eq!(emulator.parameters()[4], 12);
+		assert_eq!(emulator.parameters()[100], 7);
+	}
+
+	#[tokio::test]
+	pub async fn read_only_parameters_are_refused() {
+		let emulator = spawn_emulator().await;
+		emulator
+			.set_parameter(0, 7)
+			.expect("Failed to set parameter on emulator!");
+		let mut client = MionParameterClient::new(
+			*emulator.parameter_address().ip(),
+			Some(emulator.parameter_address().port()),
+			None,
+		);
+
+		assert!(matches!(
+			client
+				.set_many([(ParameterLocationSpecification::Index(0), 1_u8)].into_iter())
+				.await,
+			Err(CatBridgeError::ApiError(APIError::MIONParameterReadOnly(
+				0, "header"
+			))),
+		));
+		assert!(matches!(
+			client
+				.set_many_transactionally(
+					[(
+						ParameterLocationSpecification::NameLike("header".to_owned()),
+						1_u8
+					)]
+					.into_iter()
+				)
+				.await,
+			Err(CatBridgeError::ApiError(APIError::MIONParameterReadOnly(
+				0, "header"
+			))),
+		));
+		assert_eq!(emulator.parameters()[0], 7);
+		assert!(
+			!client.is_connected(),
+			"Nothing should be sent for a refused write!"
+		);
+	}
+
+	#[tokio::test]
+	pub async fn names_write_every_byte_of_a_field() {
+		let emulator = spawn_emulator().await;
+		let mut client = MionParameterClient::new(
+			*emulator.parameter_address().ip(),
+			Some(emulator.parameter_address().port()),
+			None,
+		);
+
+		// A single number can't be a full version, so nothing should be written.
+		assert!(matches!(
+			client
+				.set_many(
+					[(
+						ParameterLocationSpecification::NameLike("sdk-version".to_owned()),
+						2_u8
+					)]
+					.into_iter()
+				)
+				.await,
+			Err(CatBridgeError::ApiError(
+				APIError::MIONParameterValueInvalid("sdk-version", _)
+			)),
+		));
+		assert_eq!(&emulator.parameters()[3..6], &[0, 0, 0]);
+
+		let (response, old_values) = client
+			.set_named_transactionally([("sdk-version", "2.12.13")].into_iter())
+			.await
+			.expect("Failed to set named parameters!");
+		assert!(response.is_success());
+		assert_eq!(old_values.len(), 3);
+		assert_eq!(&emulator.parameters()[3..6], &[2, 12, 13]);
+	}
+
+	#[tokio::test]
+	pub async fn transactional_writes_roll_back_on_mismatch() {
+		let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+			.await
+			.expect("Failed to bind listener!");
+		let port = listener
+			.local_addr()
+			.expect("Failed to get listener address!")
+			.port();
+		// A bridge whose first write "succeeds", but silently drops the change
+		// to index 5.
+		let server = tokio::task::spawn(async move {
+			let (stream, _) = listener.accept().await.expect("Failed to accept!");
+			let mut framed = Framed::new(stream, MionParameterServerCodec);
+			let mut parameters = vec![0_u8; 512];
+			let mut is_first_write = true;
+			while let Some(request) = framed.next().await {
+				match request.expect("Failed to read request!") {
+					MionParameterRequest::Dump(_) => framed
+						.send(
+							DumpedMionParameters::new(Bytes::from(parameters.clone()))
+								.expect("Failed to create parameters!"),
+						)
+						.await
+						.expect("Failed to send response!"),
+					MionParameterRequest::Set(set) => {
+						let old_value = parameters[5];
+						parameters.copy_from_slice(set.get_raw_parameters());
+						if is_first_write {
+							parameters[5] = old_value;
+							is_first_write = false;
+						}
+						framed
+							.send(SetMionParametersResponse::new(0))
+							.await
+							.expect("Failed to send response!");
+					}
+				}
+			}
+			parameters
+		});
+
+		let mut client = MionParameterClient::new(Ipv4Addr::LOCALHOST, Some(port), None);
+		let result = client
+			.set_many_transactionally(
+				[
+					(ParameterLocationSpecification::Index(4), 1_u8),
+					(ParameterLocationSpecification::Index(5), 2_u8),
+					(ParameterLocationSpecification::Index(6), 3_u8),
+				]
+				.into_iter(),
+			)
+			.await;
+		assert!(
+			matches!(
+				&result,
+				Err(CatBridgeError::ApiError(APIError::MIONParameterWriteRolledBack(indexes, _)))
+					if indexes == &vec![4, 6],
+			),
+			"Unexpected result: {result:?}",
+		);
+
+		client.disconnect();
+		let final_parameters = server.await.expect("Fake bridge failed!");
+		assert_eq!(final_parameters, vec![0_u8; 512]);
+	}
+
+	#[tokio::test]
+	pub async fn transactional_writes_roll_back_when_connection_drops_after_set() {
+		let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+			.await
+			.expect("Failed to bind listener!");
+		let port = listener
+			.local_addr()
+			.expect("Failed to get listener address!")
+			.port();
+		// A bridge that applies the first write, but hangs up before responding
+		// to it.
+		let server = tokio::task::spawn(async move {
+			let mut parameters = vec![0_u8; 512];
+			let mut is_first_write = true;
+			for _ in 0..2 {
+				let (stream, _) = listener.accept().await.expect("Failed to accept!");
+				let mut framed = Framed::new(stream, MionParameterServerCodec);
+				while let Some(request) = framed.next().await {
+					match request.expect("Failed to read request!") {
+						MionParameterRequest::Dump(_) => framed
+							.send(
+								DumpedMionParameters::new(Bytes::from(parameters.clone()))
+									.expect("Failed to create parameters!"),
+							)
+							.await
+							.expect("Failed to send response!"),
+						MionParameterRequest::Set(set) => {
+							parameters.copy_from_slice(set.get_raw_parameters());
+							if is_first_write {
+								is_first_write = false;
+								break;
+							}
+							framed
+								.send(SetMionParametersResponse::new(0))
+								.await
+								.expect("Failed to send response!");
+						}
+					}
+				}
+			}
+			parameters
+		});
+
+		let mut client = MionParameterClient::new(Ipv4Addr::LOCALHOST, Some(port), None);
+		let result = client
+			.set_many_transactionally(
+				[
+					(ParameterLocationSpecification::Index(4), 1_u8),
+					(ParameterLocationSpecification::Index(5), 2_u8),
+				]
+				.into_iter(),
+			)
+			.await;
+		// The write can't be retried (it was already applied), so it has to be
+		// rolled back.
+		assert!(
+			matches!(
+				&result,
+				Err(CatBridgeError::ApiError(APIError::MIONParameterWriteRolledBack(indexes, _)))
+					if indexes == &vec![4, 5],
+			),
+			"Unexpected result: {result:?}",
+		);
+
+		client.disconnect();
+		let final_parameters = server.await.expect("Fake bridge failed!");
+		assert_eq!(final_parameters, vec![0_u8; 512]);
+	}
+
+	#[tokio::test]
+	pub async fn client_reconnects_when_connection_is_dropped() {
+		let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+			.await
+			.expect("Failed to bind listener!");
+		let port = listener
+			.local_addr()
+			.expect("Failed to get listener address!")
+			.port();
+		// A bridge that answers exactly one request per connection, and then
+		// hangs up.
+		let server = tokio::task::spawn(async move {
+			for _ in 0..2 {
+				let (stream, _) = listener.accept().await.expect("Failed to accept!");
+				let mut framed = Framed::new(stream, MionParameterServerCodec);
+				let request = framed
+					.next()
+					.await
+					.expect("Client hung up!")
+					.expect("Failed to read request!");
+				assert_eq!(
+					request,
+					MionParameterRequest::Dump(MionDumpParameters::new())
+				);
+				framed
+					.send(
+						DumpedMionParameters::new(Bytes::from(vec![0_u8; 512]))
+							.expect("Failed to create parameters!"),
+					)
+					.await
+					.expect("Failed to send response!");
+			}
+		});
+
+		let mut client = MionParameterClient::connect(Ipv4Addr::LOCALHOST, Some(port), None)
+			.await
+			.expect("Failed to connect!");
+		client.dump().await.expect("Failed to dump first time!");
+		// Make sure the server has hung up.
+		tokio::time::sleep(Duration::from_millis(100)).await;
+		client
+			.dump()
+			.await
+			.expect("Failed to reconnect, and dump a second time!");
+		server.await.expect("Fake bridge failed!");
+	}
+}
@@ -0,0 +1,472 @@
+//! Snapshots of the parameter space, so a bridge can be put back into a
+//! known good state after an experiment.
+//!
+//! Snapshots are stored as small INI files (the same format as
+//! `bridge_env.ini`), with the parameters hex encoded 16 bytes per line, so
+//! they're still easy to read, and diff with normal tools:
+//!
+//! ```ini
+//! [SNAPSHOT]
+//! VERSION=1
+//! BRIDGE_NAME=00-25-5C-BA-5A-00
+//! MAC_ADDRESS=00:25:5C:BA:5A:00
+//! FIRMWARE_VERSION=0.0.14.80
+//! TAKEN_AT=1700000000
+//!
+//! [PARAMETERS]
+//! 000=00 00 00 02 0c 0d 00 00 00 00 00 00 00 00 00 00
+//! 010=...
+//! ```
+
+use crate::{
+	errors::{APIError, CatBridgeError, FSError},
+	mion::proto::{
+		control::MionIdentity,
+		parameter::{
+			well_known::{ParameterDefinition, ParameterValue, PARAMETER_SCHEMA},
+			DumpedMionParameters,
+		},
+	},
+};
+use bytes::BytesMut;
+use configparser::ini::Ini;
+use mac_address::MacAddress;
+use std::{
+	fmt::Write,
+	path::Path,
+	time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// The section containing all the metadata about a snapshot.
+const SNAPSHOT_SECTION: &str = "SNAPSHOT";
+/// The section containing the actual parameters.
+const PARAMETERS_SECTION: &str = "PARAMETERS";
+/// The version of the snapshot format we write out.
+const SNAPSHOT_FORMAT_VERSION: &str = "1";
+/// How many bytes of parameters we store on each line.
+const BYTES_PER_LINE: usize = 16;
+
+/// A saved copy of the parameter space of a bridge, along with some metadata
+/// about which bridge it came from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParameterSnapshot {
+	/// The name of the bridge this snapshot was taken from.
+	bridge_name: Option<String>,
+	/// The MAC Address of the bridge this snapshot was taken from.
+	mac_address: Option<MacAddress>,
+	/// The firmware version of the bridge this snapshot was taken from.
+	firmware_version: Option<String>,
+	/// When this snapshot was taken, in seconds since the unix epoch.
+	taken_at: u64,
+	/// The actual parameters.
+	parameters: DumpedMionParameters,
+}
+
+impl ParameterSnapshot {
+	/// Create a new snapshot of a set of parameters, taken right now.
+	///
+	/// This snapshot won't have any information about the bridge it came from,
+	/// see [`ParameterSnapshot::with_identity`].
+	#[must_use]
+	pub fn new(parameters: DumpedMionParameters) -> Self {
+		Self {
+			bridge_name: None,
+			mac_address: None,
+			firmware_version: None,
+			taken_at: SystemTime::now()
+				.duration_since(UNIX_EPOCH)
+				.map(|since| since.as_secs())
+				.unwrap_or_default(),
+			parameters,
+		}
+	}
+
+	/// Record which bridge this snapshot was taken from.
+	#[must_use]
+	pub fn with_identity(mut self, identity: &MionIdentity) -> Self {
+		self.bridge_name = Some(identity.name().to_owned());
+		self.mac_address = Some(identity.mac_address());
+		self.firmware_version = Some(identity.firmware_version());
+		self
+	}
+
+	/// The name of the bridge this snapshot was taken from, if known.
+	#[must_use]
+	pub fn bridge_name(&self) -> Option<&str> {
+		self.bridge_name.as_deref()
+	}
+
+	/// The MAC Address of the bridge this snapshot was taken from, if known.
+	#[must_use]
+	pub const fn mac_address(&self) -> Option<MacAddress> {
+		self.mac_address
+	}
+
+	/// The firmware version of the bridge this snapshot was taken from, if
+	/// known.
+	#[must_use]
+	pub fn firmware_version(&self) -> Option<&str> {
+		self.firmware_version.as_deref()
+	}
+
+	/// When this snapshot was taken.
+	#[must_use]
+	pub fn taken_at(&self) -> SystemTime {
+		UNIX_EPOCH + Duration::from_secs(self.taken_at)
+	}
+
+	/// The parameters that were saved.
+	#[must_use]
+	pub const fn parameters(&self) -> &DumpedMionParameters {
+		&self.parameters
+	}
+
+	/// Compare this snapshot to another, treating this snapshot as the "old"
+	/// state, and the other as the "new" state.
+	#[must_use]
+	pub fn diff(&self, other: &ParameterSnapshot) -> ParameterDiff {
+		ParameterDiff::between(&self.parameters, &other.parameters)
+	}
+
+	/// Serialize this snapshot into the INI format we store on disk.
+	///
+	/// We write this out by hand rather than through [`Ini`] so the keys are
+	/// always in the same order, which keeps snapshots readable, and friendly
+	/// to diff with other tools.
+	#[must_use]
+	pub fn serialize(&self) -> String {
+		let mut ini = String::new();
+		_ = writeln!(&mut ini, "[{SNAPSHOT_SECTION}]");
+		_ = writeln!(&mut ini, "VERSION={SNAPSHOT_FORMAT_VERSION}");
+		if let Some(name) = self.bridge_name.as_ref() {
+			_ = writeln!(&mut ini, "BRIDGE_NAME={name}");
+		}
+		if let Some(mac) = self.mac_address {
+			_ = writeln!(&mut ini, "MAC_ADDRESS={mac}");
+		}
+		if let Some(version) = self.firmware_version.as_ref() {
+			_ = writeln!(&mut ini, "FIRMWARE_VERSION={version}");
+		}
+		_ = writeln!(&mut ini, "TAKEN_AT={}", self.taken_at);
+
+		_ = writeln!(&mut ini, "\n[{PARAMETERS_SECTION}]");
+		for (line_idx, chunk) in self
+			.parameters
+			.get_raw_parameters()
+			.chunks(BYTES_PER_LINE)
+			.enumerate()
+		{
+			_ = write!(&mut ini, "{:03x}=", line_idx * BYTES_PER_LINE);
+			for (byte_idx, byte) in chunk.iter().enumerate() {
+				if byte_idx != 0 {
+					ini.push(' ');
+				}
+				_ = write!(&mut ini, "{byte:02x}");
+			}
+			ini.push('\n');
+		}
+
+		ini
+	}
+
+	/// Write this snapshot out to a file.
+	///
+	/// ## Errors
+	///
+	/// If we run into a system error when writing the file to the disk.
+	pub async fn write_to_file(&self, path: &Path) -> Result<(), FSError> {
+		if let Some(parent_dir) = path.parent() {
+			if !parent_dir.as_os_str().is_empty() {
+				tokio::fs::create_dir_all(parent_dir).await?;
+			}
+		}
+		tokio::fs::write(path, self.serialize().into_bytes()).await?;
+		Ok(())
+	}
+
+	/// Load a snapshot that was previously written to a file.
+	///
+	/// ## Errors
+	///
+	/// - If we cannot read from the file on the file system.
+	/// - If we cannot parse the data in the file as UTF8.
+	/// - If the file is not a valid snapshot.
+	pub async fn load_from_file(path: &Path) -> Result<Self, CatBridgeError> {
+		let as_bytes = tokio::fs::read(path).await.map_err(FSError::from)?;
+		let as_string = String::from_utf8(as_bytes).map_err(FSError::from)?;
+		Ok(Self::try_from(as_string.as_str())?)
+	}
+}
+
+impl TryFrom<&str> for ParameterSnapshot {
+	type Error = APIError;
+
+	fn try_from(value: &str) -> Result<Self, Self::Error> {
+		let mut ini = Ini::new_cs();
+		ini.read(value.to_owned()).map_err(|cause| {
+			APIError::InvalidParameterSnapshot(format!("not an INI file: {cause}"))
+		})?;
+
+		let version = ini.get(SNAPSHOT_SECTION, "VERSION");
+		if version.as_deref() != Some(SNAPSHOT_FORMAT_VERSION) {
+			return Err(APIError::InvalidParameterSnapshot(format!(
+				"unsupported snapshot version: {version:?}"
+			)));
+		}
+
+		let mac_address = ini
+			.get(SNAPSHOT_SECTION, "MAC_ADDRESS")
+			.map(|mac| {
+				mac.parse::<MacAddress>().map_err(|_| {
+					APIError::InvalidParameterSnapshot(format!("invalid mac address: {mac}"))
+				})
+			})
+			.transpose()?;
+		let taken_at = ini
+			.get(SNAPSHOT_SECTION, "TAKEN_AT")
+			.and_then(|time| time.parse::<u64>().ok())
+			.ok_or_else(|| {
+				APIError::InvalidParameterSnapshot("missing, or invalid TAKEN_AT".to_owned())
+			})?;
+
+		let mut parameters = BytesMut::with_capacity(512);
+		for offset in (0..512).step_by(BYTES_PER_LINE) {
+			let key = format!("{offset:03x}");
+			let line = ini.get(PARAMETERS_SECTION, &key).ok_or_else(|| {
+				APIError::InvalidParameterSnapshot(format!("missing parameters line: {key}"))
+			})?;
+			let mut bytes_on_line = 0;
+			for byte in line.split_whitespace() {
+				parameters.extend_from_slice(&[u8::from_str_radix(byte, 16).map_err(|_| {
+					APIError::InvalidParameterSnapshot(format!(
+						"invalid byte: {byte} on parameters line: {key}"
+					))
+				})?]);
+				bytes_on_line += 1;
+			}
+			if bytes_on_line != BYTES_PER_LINE {
+				return Err(APIError::InvalidParameterSnapshot(format!(
+					"parameters line: {key} has {bytes_on_line} bytes, expected {BYTES_PER_LINE}"
+				)));
+			}
+		}
+
+		Ok(Self {
+			bridge_name: ini.get(SNAPSHOT_SECTION, "BRIDGE_NAME"),
+			mac_address,
+			firmware_version: ini.get(SNAPSHOT_SECTION, "FIRMWARE_VERSION"),
+			taken_at,
+			parameters: DumpedMionParameters::new(parameters.freeze())?,
+		})
+	}
+}
+
+/// A single byte that differs between two sets of parameters.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct ByteDifference {
+	/// The index of this byte in the parameter space.
+	index: usize,
+	/// The value in the "old" parameters.
+	old_value: u8,
+	/// The value in the "new" parameters.
+	new_value: u8,
+}
+
+impl ByteDifference {
+	/// The index of this byte in the parameter space.
+	#[must_use]
+	pub const fn index(&self) -> usize {
+		self.index
+	}
+
+	/// The value in the "old" parameters.
+	#[must_use]
+	pub const fn old_value(&self) -> u8 {
+		self.old_value
+	}
+
+	/// The value in the "new" parameters.
+	#[must_use]
+	pub const fn new_value(&self) -> u8 {
+		self.new_value
+	}
+}
+
+/// A known field from the [`PARAMETER_SCHEMA`] that differs between two sets
+/// of parameters.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct FieldDifference {
+	/// The field that changed.
+	definition: &'static ParameterDefinition,
+	/// The value in the "old" parameters.
+	old_value: ParameterValue,
+	/// The value in the "new" parameters.
+	new_value: ParameterValue,
+}
+
+impl FieldDifference {
+	/// The field that changed.
+	#[must_use]
+	pub const fn definition(&self) -> &'static ParameterDefinition {
+		self.definition
+	}
+
+	/// The value in the "old" parameters.
+	#[must_use]
+	pub const fn old_value(&self) -> &ParameterValue {
+		&self.old_value
+	}
+
+	/// The value in the "new" parameters.
+	#[must_use]
+	pub const fn new_value(&self) -> &ParameterValue {
+		&self.new_value
+	}
+}
+
+/// All the differences between two sets of parameters, both byte by byte,
+/// and for every known field.
+#[derive(Clone, Debug, Default, Hash, PartialEq, Eq)]
+pub struct ParameterDiff {
+	/// Every byte that differs, in order of index.
+	bytes: Vec<ByteDifference>,
+	/// Every known field that differs, in the order of the schema.
+	fields: Vec<FieldDifference>,
+}
+
+impl ParameterDiff {
+	/// Compute the differences going from `old` to `new`.
+	#[must_use]
+	pub fn between(old: &DumpedMionParameters, new: &DumpedMionParameters) -> Self {
+		let old_bytes = old.get_raw_parameters();
+		let new_bytes = new.get_raw_parameters();
+
+		let bytes = old_bytes
+			.iter()
+			.zip(new_bytes.iter())
+			.enumerate()
+			.filter(|(_, (old_value, new_value))| old_value != new_value)
+			.map(|(index, (old_value, new_value))| ByteDifference {
+				index,
+				old_value: *old_value,
+				new_value: *new_value,
+			})
+			.collect();
+		let fields = PARAMETER_SCHEMA
+			.iter()
+			.filter_map(|definition| {
+				let old_value = definition.read(old_bytes).ok()?;
+				let new_value = definition.read(new_bytes).ok()?;
+				(old_value != new_value).then_some(FieldDifference {
+					definition,
+					old_value,
+					new_value,
+				})
+			})
+			.collect();
+
+		Self { bytes, fields }
+	}
+
+	/// If there are no differences at all.
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.bytes.is_empty()
+	}
+
+	/// Every byte that differs, in order of index.
+	#[must_use]
+	pub fn byte_differences(&self) -> &[ByteDifference] {
+		&self.bytes
+	}
+
+	/// Every known field that differs, in the order of the
+	/// [`PARAMETER_SCHEMA`].
+	#[must_use]
+	pub fn field_differences(&self) -> &[FieldDifference] {
+		&self.fields
+	}
+}
+
+#[cfg(test)]
+mod unit_tests {
+	use super::*;
+
+	fn test_parameters(sdk: [u8; 3]) -> DumpedMionParameters {
+		let mut parameters = BytesMut::zeroed(512);
+		parameters[3..6].copy_from_slice(&sdk);
+		parameters[511] = 0xFF;
+		DumpedMionParameters::new(parameters.freeze()).expect("Failed to create parameters!")
+	}
+
+	#[test]
+	pub fn snapshot_round_trips() {
+		let identity = MionIdentity::new(
+			None,
+			[0, 14, 80, 1],
+			[0x13, 0x05, 0x20, 0x71],
+			std::net::Ipv4Addr::LOCALHOST,
+			MacAddress::new([0x00, 0x25, 0x5c, 0xba, 0x5a, 0x00]),
+			"00-25-5C-BA-5A-00".to_owned(),
+		)
+		.expect("Failed to create identity!");
+		let snapshot =
+			ParameterSnapshot::new(test_parameters([2, 12, 13])).with_identity(&identity);
+		let serialized = snapshot.serialize();
+		assert!(serialized.contains("000=00 00 00 02 0c 0d 00 00 00 00 00 00 00 00 00 00"));
+
+		let parsed =
+			ParameterSnapshot::try_from(serialized.as_str()).expect("Failed to parse snapshot!");
+		assert_eq!(parsed, snapshot);
+		assert_eq!(parsed.bridge_name(), Some("00-25-5C-BA-5A-00"));
+		assert_eq!(parsed.firmware_version(), Some("0.0.14.80"));
+
+		// Snapshots without metadata should also work.
+		let bare = ParameterSnapshot::new(test_parameters([2, 11, 1]));
+		assert_eq!(
+			ParameterSnapshot::try_from(bare.serialize().as_str()),
+			Ok(bare),
+		);
+
+		assert!(ParameterSnapshot::try_from("[SNAPSHOT]\nVERSION=1\nTAKEN_AT=0\n").is_err());
+		assert!(
+			ParameterSnapshot::try_from(serialized.replace("000=00 00", "000=00 zz").as_str())
+				.is_err()
+		);
+		assert!(
+			ParameterSnapshot::try_from(serialized.replace("VERSION=1", "VERSION=2").as_str())
+				.is_err()
+		);
+	}
+
+	#[test]
+	pub fn can_diff_snapshots() {
+		let old = ParameterSnapshot::new(test_parameters([2, 12, 13]));
+		let new = ParameterSnapshot::new(test_parameters([2, 11, 13]));
+		assert!(old.diff(&old).is_empty());
+
+		let diff = old.diff(&new);
+		assert_eq!(
+			diff.byte_differences(),
+			&[ByteDifference {
+				index: 4,
+				old_value: 12,
+				new_value: 11,
+			}],
+		);
+		assert_eq!(
+			diff.field_differences()
+				.iter()
+				.map(|field| (
+					field.definition().name(),
+					format!("{}", field.old_value()),
+					format!("{}", field.new_value()),
+				))
+				.collect::<Vec<_>>(),
+			vec![
+				("sdk-minor", "12".to_owned(), "11".to_owned()),
+				("sdk-version", "2.12.13".to_owned(), "2.11.13".to_owned()),
+			],
+		);
+	}
+}
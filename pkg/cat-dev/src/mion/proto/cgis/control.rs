@@ -1,11 +1,22 @@
-use crate::errors::APIError;
-use std::fmt::{Display, Formatter, Result as FmtResult};
+use crate::{errors::APIError, mion::proto::control::MIONBootType};
+use fnv::FnvHashMap;
+use mac_address::MacAddress;
+use std::{
+	fmt::{Display, Formatter, Result as FmtResult},
+	net::Ipv4Addr,
+};
 
 /// The type of operations you can do on the `control.cgi` page.
 #[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
 pub enum ControlOperation {
 	PowerOn,
 	PowerOnV2,
+	/// **Unverified**: we've never seen the official tools send this, the
+	/// name is a guess based off of `power_on`.
+	PowerOff,
+	/// **Unverified**: we've never seen the official tools send this, the
+	/// name is a guess.
+	Reset,
 	GetInfo,
 	SetParam,
 }
@@ -21,6 +32,8 @@ impl From<&ControlOperation> for &str {
 		match *value {
 			ControlOperation::PowerOn => "power_on",
 			ControlOperation::PowerOnV2 => "power_on_v2",
+			ControlOperation::PowerOff => "power_off",
+			ControlOperation::Reset => "reset",
 			ControlOperation::GetInfo => "get_info",
 			ControlOperation::SetParam => "set_param",
 		}
@@ -40,6 +53,8 @@ impl TryFrom<&str> for ControlOperation {
 		match value {
 			"power_on" => Ok(Self::PowerOn),
 			"power_on_v2" => Ok(Self::PowerOnV2),
+			"power_off" => Ok(Self::PowerOff),
+			"reset" => Ok(Self::Reset),
 			"get_info" => Ok(Self::GetInfo),
 			"set_param" => Ok(Self::SetParam),
 			val => Err(APIError::UnknownControlOperation(val.to_owned())),
@@ -47,19 +62,227 @@ impl TryFrom<&str> for ControlOperation {
 	}
 }
 
-/// The type of parameters you can set on the control page.
+/// A value that can be changed with `set_param` on `control.cgi`.
+///
+/// Use [`SetParameter::validate`] (which `set_param` calls for you) to make
+/// sure the value is sane before sending it.
+///
+/// Only [`SetParameter::AtapiPort`] has been seen being sent by the official
+/// tools. Every other setting is **experimental**: the key names are guesses
+/// based off of the fields `get_info` reports, and a real MION may reject, or
+/// ignore them. These can only be sent through the `set_experimental_param`
+/// functions, see [`SetParameter::is_experimental`].
 #[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
 pub enum SetParameter {
 	/// Set the ATAPI Port to use.
 	AtapiPort(u16),
+	/// **Experimental**: Set the port SDIO printf output is served on.
+	SdioPrintfPort(u16),
+	/// **Experimental**: Set the port the SDIO block device is served on.
+	SdioBlockPort(u16),
+	/// **Experimental**: Set the port the parameter space is served on.
+	ParameterPort(u16),
+	/// **Experimental**: Set the IP of the host PC that serves files to cafe.
+	HostIp(Ipv4Addr),
+	/// **Experimental**: Set if the disc drive should be emulated by the host
+	/// PC.
+	Emulation(bool),
+	/// **Experimental**: Set where cafe should boot from.
+	BootType(MIONBootType),
+	/// **Experimental**: Set how many seconds the MION waits for the host PC
+	/// to respond when emulating before giving up.
+	EmulationTimeout(u16),
+	/// **Experimental**: Set how many seconds the MION waits for cafe to shut
+	/// down cleanly before cutting power.
+	ShutdownTimeout(u16),
 }
 
 impl SetParameter {
+	/// The names of every value that can be set, in the form `set_param`
+	/// expects them.
+	pub const NAMES: [&'static str; 9] = [
+		"atapi_port",
+		"sdio_printf_port",
+		"sdio_block_port",
+		"parameter_port",
+		"host_ip",
+		"emulation",
+		"boot_type",
+		"emulation_timeout",
+		"shutdown_timeout",
+	];
+	/// The names of the values we know a real MION accepts.
+	pub const VERIFIED_NAMES: [&'static str; 1] = ["atapi_port"];
+	/// The longest timeout we will send, in seconds.
+	///
+	/// This is our own sanity check, we don't know what limit (if any) a real
+	/// MION has.
+	pub const MAX_TIMEOUT_SECONDS: u16 = 3600;
+
+	/// If this is a setting we've never seen the official tools send, and
+	/// the key name is a guess.
+	#[must_use]
+	pub const fn is_experimental(&self) -> bool {
+		!matches!(self, Self::AtapiPort(_))
+	}
+
+	/// Parse a value to set from it's name, and a string value.
+	///
+	/// The value is validated before being returned.
+	///
+	/// ## Errors
+	///
+	/// - If the name isn't one of [`Self::NAMES`].
+	/// - If the value can't be parsed as the type the setting needs.
+	/// - If the value is not valid, see [`Self::validate`].
+	pub fn from_name_and_value(name: &str, value: &str) -> Result<Self, APIError> {
+		let trimmed_name = name.trim().to_ascii_lowercase().replace('-', "_");
+		let value = value.trim();
+		let Some(name) = Self::NAMES
+			.iter()
+			.find(|known| **known == trimmed_name)
+			.copied()
+		else {
+			return Err(APIError::UnknownSetParameter(
+				name.to_owned(),
+				Self::NAMES.join(", "),
+			));
+		};
+
+		let port = || {
+			value.parse::<u16>().map_err(|_| {
+				APIError::InvalidSetParameterValue(
+					name,
+					value.to_owned(),
+					"it must be a port number",
+				)
+			})
+		};
+		let seconds = || {
+			value.parse::<u16>().map_err(|_| {
+				APIError::InvalidSetParameterValue(
+					name,
+					value.to_owned(),
+					"it must be a number of seconds",
+				)
+			})
+		};
+		let parsed = match name {
+			"atapi_port" => Self::AtapiPort(port()?),
+			"sdio_printf_port" => Self::SdioPrintfPort(port()?),
+			"sdio_block_port" => Self::SdioBlockPort(port()?),
+			"parameter_port" => Self::ParameterPort(port()?),
+			"host_ip" => Self::HostIp(value.parse::<Ipv4Addr>().map_err(|_| {
+				APIError::InvalidSetParameterValue(
+					name,
+					value.to_owned(),
+					"it must be an IPv4 address",
+				)
+			})?),
+			"emulation" => Self::Emulation(
+				if ["on", "true", "1", "yes"]
+					.iter()
+					.any(|truthy| value.eq_ignore_ascii_case(truthy))
+				{
+					true
+				} else if ["off", "false", "0", "no"]
+					.iter()
+					.any(|falsy| value.eq_ignore_ascii_case(falsy))
+				{
+					false
+				} else {
+					return Err(APIError::InvalidSetParameterValue(
+						name,
+						value.to_owned(),
+						"it must be `on`, or `off`",
+					));
+				},
+			),
+			"boot_type" => Self::BootType(MIONBootType::try_from(value).map_err(|_| {
+				APIError::InvalidSetParameterValue(
+					name,
+					value.to_owned(),
+					"it must be one of: NAND, PCFS, DUAL, HDD",
+				)
+			})?),
+			"emulation_timeout" => Self::EmulationTimeout(seconds()?),
+			_ => Self::ShutdownTimeout(seconds()?),
+		};
+		parsed.validate()?;
+		Ok(parsed)
+	}
+
+	/// Make sure this is a value the MION will accept.
+	///
+	/// ## Errors
+	///
+	/// - If a port is `0`.
+	/// - If the host IP is not an IP a MION could actually talk to (e.g.
+	///   `0.0.0.0`, broadcast, or multicast).
+	/// - If the boot type is not one we know of.
+	/// - If a timeout is `0`, or over [`Self::MAX_TIMEOUT_SECONDS`].
+	pub fn validate(&self) -> Result<(), APIError> {
+		let name = Into::<&str>::into(self);
+		match *self {
+			Self::AtapiPort(port)
+			| Self::SdioPrintfPort(port)
+			| Self::SdioBlockPort(port)
+			| Self::ParameterPort(port) => {
+				if port == 0 {
+					return Err(APIError::InvalidSetParameterValue(
+						name,
+						self.get_value_as_string(),
+						"a port cannot be 0",
+					));
+				}
+			}
+			Self::HostIp(ip) => {
+				if ip.is_unspecified() || ip.is_broadcast() || ip.is_multicast() {
+					return Err(APIError::InvalidSetParameterValue(
+						name,
+						self.get_value_as_string(),
+						"it must be the IP of a single host",
+					));
+				}
+			}
+			Self::BootType(boot_type) => {
+				if matches!(boot_type, MIONBootType::Unk(_)) {
+					return Err(APIError::InvalidSetParameterValue(
+						name,
+						self.get_value_as_string(),
+						"it must be one of: NAND, PCFS, DUAL, HDD",
+					));
+				}
+			}
+			Self::EmulationTimeout(seconds) | Self::ShutdownTimeout(seconds) => {
+				if seconds == 0 || seconds > Self::MAX_TIMEOUT_SECONDS {
+					return Err(APIError::InvalidSetParameterValue(
+						name,
+						self.get_value_as_string(),
+						"it must be between 1, and 3600 seconds",
+					));
+				}
+			}
+			Self::Emulation(_) => {}
+		}
+
+		Ok(())
+	}
+
 	/// Get the value regardless of what it is as a string.
 	#[must_use]
 	pub fn get_value_as_string(&self) -> String {
 		match self {
-			Self::AtapiPort(ref port) => format!("{port}"),
+			Self::AtapiPort(ref port)
+			| Self::SdioPrintfPort(ref port)
+			| Self::SdioBlockPort(ref port)
+			| Self::ParameterPort(ref port) => format!("{port}"),
+			Self::HostIp(ref ip) => format!("{ip}"),
+			Self::Emulation(ref emulation) => if *emulation { "on" } else { "off" }.to_owned(),
+			Self::BootType(ref boot_type) => format!("{boot_type}"),
+			Self::EmulationTimeout(ref seconds) | Self::ShutdownTimeout(ref seconds) => {
+				format!("{seconds}")
+			}
 		}
 	}
 }
@@ -74,6 +297,14 @@ impl From<&SetParameter> for &str {
 	fn from(value: &SetParameter) -> Self {
 		match *value {
 			SetParameter::AtapiPort(_) => "atapi_port",
+			SetParameter::SdioPrintfPort(_) => "sdio_printf_port",
+			SetParameter::SdioBlockPort(_) => "sdio_block_port",
+			SetParameter::ParameterPort(_) => "parameter_port",
+			SetParameter::HostIp(_) => "host_ip",
+			SetParameter::Emulation(_) => "emulation",
+			SetParameter::BootType(_) => "boot_type",
+			SetParameter::EmulationTimeout(_) => "emulation_timeout",
+			SetParameter::ShutdownTimeout(_) => "shutdown_timeout",
 		}
 	}
 }
@@ -83,15 +314,476 @@ impl From<SetParameter> for &str {
 	}
 }
 
+/// A request to power on cafe through `control.cgi`.
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub struct PowerOnRequest {
+	/// If the MION should emulate the disc drive (PCFS) rather than booting
+	/// from NAND.
+	emulation: bool,
+	/// The host that is taking control of the MION, if not set this is the
+	/// local ip of this machine.
+	host: Option<Ipv4Addr>,
+}
+
+impl PowerOnRequest {
+	/// Create a new request to power on from NAND, from this machine.
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			emulation: false,
+			host: None,
+		}
+	}
+
+	/// Set if the MION should emulate the disc drive when powering on.
+	#[must_use]
+	pub const fn with_emulation(mut self, emulation: bool) -> Self {
+		self.emulation = emulation;
+		self
+	}
+
+	/// Set the host that is taking control of the MION.
+	#[must_use]
+	pub const fn with_host(mut self, host: Ipv4Addr) -> Self {
+		self.host = Some(host);
+		self
+	}
+
+	/// If the MION should emulate the disc drive when powering on.
+	#[must_use]
+	pub const fn emulation(&self) -> bool {
+		self.emulation
+	}
+
+	/// The host that is taking control of the MION, if one was set.
+	#[must_use]
+	pub const fn host(&self) -> Option<Ipv4Addr> {
+		self.host
+	}
+}
+
+impl Default for PowerOnRequest {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// A request to power off cafe through `control.cgi`.
+///
+/// **Unverified**: see [`ControlOperation::PowerOff`], a real bridge may
+/// not understand this request at all.
+#[derive(Copy, Clone, Debug, Default, Hash, PartialEq, Eq)]
+pub struct PowerOffRequest {
+	/// The host asking to power off, if not set this is the local ip of this
+	/// machine.
+	host: Option<Ipv4Addr>,
+}
+
+impl PowerOffRequest {
+	/// Create a new request to power off from this machine.
+	#[must_use]
+	pub const fn new() -> Self {
+		Self { host: None }
+	}
+
+	/// Set the host that is asking to power off.
+	#[must_use]
+	pub const fn with_host(mut self, host: Ipv4Addr) -> Self {
+		self.host = Some(host);
+		self
+	}
+
+	/// The host that is asking to power off, if one was set.
+	#[must_use]
+	pub const fn host(&self) -> Option<Ipv4Addr> {
+		self.host
+	}
+}
+
+/// A request to hard reset cafe through `control.cgi`.
+///
+/// **Unverified**: see [`ControlOperation::Reset`], a real bridge may not
+/// understand this request at all.
+#[derive(Copy, Clone, Debug, Default, Hash, PartialEq, Eq)]
+pub struct ResetRequest {
+	/// The host asking to reset, if not set this is the local ip of this
+	/// machine.
+	host: Option<Ipv4Addr>,
+}
+
+impl ResetRequest {
+	/// Create a new request to reset from this machine.
+	#[must_use]
+	pub const fn new() -> Self {
+		Self { host: None }
+	}
+
+	/// Set the host that is asking to reset.
+	#[must_use]
+	pub const fn with_host(mut self, host: Ipv4Addr) -> Self {
+		self.host = Some(host);
+		self
+	}
+
+	/// The host that is asking to reset, if one was set.
+	#[must_use]
+	pub const fn host(&self) -> Option<Ipv4Addr> {
+		self.host
+	}
+}
+
+/// The parsed result of an operation on `control.cgi`.
+///
+/// Operations respond with a series of lines, a `RESULT:` line (`OK` on
+/// success), and any number of `INFO:`/`WARN:`/`ERROR:` log lines.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct ControlResponse {
+	operation: ControlOperation,
+	result_code: String,
+	log_lines: Vec<String>,
+	extra_lines: Vec<String>,
+}
+
+impl ControlResponse {
+	/// Create a new response to an operation.
+	#[must_use]
+	pub const fn new(
+		operation: ControlOperation,
+		result_code: String,
+		log_lines: Vec<String>,
+		extra_lines: Vec<String>,
+	) -> Self {
+		Self {
+			operation,
+			result_code,
+			log_lines,
+			extra_lines,
+		}
+	}
+
+	/// The operation this is a response to.
+	#[must_use]
+	pub const fn operation(&self) -> ControlOperation {
+		self.operation
+	}
+
+	/// The value of the `RESULT:` line, empty if there wasn't one.
+	#[must_use]
+	pub fn result_code(&self) -> &str {
+		&self.result_code
+	}
+
+	/// Every `INFO:`/`WARN:`/`ERROR:` line in the response, including the
+	/// prefix.
+	#[must_use]
+	pub fn log_lines(&self) -> &[String] {
+		&self.log_lines
+	}
+
+	/// Any other lines we didn't recognize.
+	#[must_use]
+	pub fn extra_lines(&self) -> &[String] {
+		&self.extra_lines
+	}
+
+	/// If the MION reported this operation as successful.
+	#[must_use]
+	pub fn is_success(&self) -> bool {
+		self.result_code == "OK"
+	}
+
+	/// If the MION refused this operation because another host is using it.
+	///
+	/// The MION reports this with an `ERROR:` line saying it is "in use".
+	#[must_use]
+	pub fn is_in_use(&self) -> bool {
+		self.in_use_line().is_some()
+	}
+
+	/// The host that is using the MION, if it refused this operation because
+	/// of another host, and it told us who.
+	#[must_use]
+	pub fn in_use_by(&self) -> Option<Ipv4Addr> {
+		self.in_use_line().and_then(|line| {
+			line.split(|character: char| !(character.is_ascii_digit() || character == '.'))
+				.find_map(|potential_ip| potential_ip.parse::<Ipv4Addr>().ok())
+		})
+	}
+
+	fn in_use_line(&self) -> Option<&str> {
+		self.log_lines
+			.iter()
+			.filter_map(|line| line.strip_prefix("ERROR:"))
+			.find(|line| line.to_ascii_lowercase().contains("in use"))
+	}
+}
+
+/// If cafe is powered on, or off.
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub enum PowerState {
+	On,
+	Off,
+}
+
+impl Display for PowerState {
+	fn fmt(&self, fmt: &mut Formatter<'_>) -> FmtResult {
+		match self {
+			Self::On => write!(fmt, "on"),
+			Self::Off => write!(fmt, "off"),
+		}
+	}
+}
+
+/// Everything a MION reports about itself from `get_info` on `control.cgi`.
+///
+/// Every field is optional, as not every firmware reports every field, and a
+/// field with a value we couldn't understand is left empty rather than failing
+/// the whole request. Every value is still available as it was reported
+/// through [`Self::raw`], along with [`Self::unknown_fields`], and
+/// [`Self::unparsable_fields`].
+///
+/// **Unverified**: we have never captured a `get_info` response from a real
+/// bridge. The keys in [`Self::KNOWN_FIELDS`] (like `power`, `host`, and
+/// `boot_type`), and the formats of their values are guesses, so a real
+/// bridge may report every field as unknown.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MionInfo {
+	name: Option<String>,
+	mac_address: Option<MacAddress>,
+	firmware_version: Option<String>,
+	fpga_version: Option<String>,
+	sdk_version: Option<String>,
+	power: Option<PowerState>,
+	owner: Option<Ipv4Addr>,
+	emulation: Option<bool>,
+	boot_type: Option<MIONBootType>,
+	atapi_port: Option<u16>,
+	sdio_printf_port: Option<u16>,
+	sdio_block_port: Option<u16>,
+	parameter_port: Option<u16>,
+	unparsable_fields: Vec<&'static str>,
+	raw: FnvHashMap<String, String>,
+}
+
+impl MionInfo {
+	/// All the keys we know how to parse into a typed field.
+	///
+	/// **Unverified**: these are guesses, see [`MionInfo`].
+	pub const KNOWN_FIELDS: [&'static str; 13] = [
+		"name",
+		"mac_address",
+		"fw_version",
+		"fpga_version",
+		"sdk_version",
+		"power",
+		"host",
+		"emulation",
+		"boot_type",
+		"atapi_port",
+		"sdio_printf_port",
+		"sdio_block_port",
+		"parameter_port",
+	];
+
+	/// The name of the MION.
+	#[must_use]
+	pub fn name(&self) -> Option<&str> {
+		self.name.as_deref()
+	}
+
+	/// The MAC Address of the MION.
+	#[must_use]
+	pub const fn mac_address(&self) -> Option<MacAddress> {
+		self.mac_address
+	}
+
+	/// The firmware version of the MION itself.
+	#[must_use]
+	pub fn firmware_version(&self) -> Option<&str> {
+		self.firmware_version.as_deref()
+	}
+
+	/// The version of the FPGA on the MION.
+	#[must_use]
+	pub fn fpga_version(&self) -> Option<&str> {
+		self.fpga_version.as_deref()
+	}
+
+	/// The version of the SDK installed on cafe.
+	#[must_use]
+	pub fn sdk_version(&self) -> Option<&str> {
+		self.sdk_version.as_deref()
+	}
+
+	/// If cafe is currently powered on or off.
+	#[must_use]
+	pub const fn power(&self) -> Option<PowerState> {
+		self.power
+	}
+
+	/// The host that currently owns the MION, if any host does.
+	#[must_use]
+	pub const fn owner(&self) -> Option<Ipv4Addr> {
+		self.owner
+	}
+
+	/// If the disc drive is currently being emulated.
+	#[must_use]
+	pub const fn emulation(&self) -> Option<bool> {
+		self.emulation
+	}
+
+	/// The type of boot the MION will do.
+	#[must_use]
+	pub const fn boot_type(&self) -> Option<MIONBootType> {
+		self.boot_type
+	}
+
+	/// The port the ATAPI emulation is served on.
+	#[must_use]
+	pub const fn atapi_port(&self) -> Option<u16> {
+		self.atapi_port
+	}
+
+	/// The port the SDIO printf output is served on.
+	#[must_use]
+	pub const fn sdio_printf_port(&self) -> Option<u16> {
+		self.sdio_printf_port
+	}
+
+	/// The port the SDIO block device is served on.
+	#[must_use]
+	pub const fn sdio_block_port(&self) -> Option<u16> {
+		self.sdio_block_port
+	}
+
+	/// The port the parameter space is served on.
+	#[must_use]
+	pub const fn parameter_port(&self) -> Option<u16> {
+		self.parameter_port
+	}
+
+	/// Every field that was reported, including the ones we parsed.
+	#[must_use]
+	pub const fn raw(&self) -> &FnvHashMap<String, String> {
+		&self.raw
+	}
+
+	/// Every one of [`Self::KNOWN_FIELDS`] that was reported, but had a value
+	/// we couldn't understand (the value is still in [`Self::raw`]).
+	#[must_use]
+	pub fn unparsable_fields(&self) -> &[&'static str] {
+		&self.unparsable_fields
+	}
+
+	/// Every field that was reported that isn't one of [`Self::KNOWN_FIELDS`].
+	pub fn unknown_fields(&self) -> impl Iterator<Item = (&str, &str)> {
+		self.raw
+			.iter()
+			.filter(|(key, _)| !Self::KNOWN_FIELDS.contains(&key.as_str()))
+			.map(|(key, value)| (key.as_str(), value.as_str()))
+	}
+}
+
+impl From<FnvHashMap<String, String>> for MionInfo {
+	fn from(raw: FnvHashMap<String, String>) -> Self {
+		let mut unparsable_fields = Vec::new();
+		let text = |key: &str| {
+			raw.get(key)
+				.map(|value| value.trim())
+				.filter(|value| !value.is_empty())
+				.map(ToOwned::to_owned)
+		};
+
+		Self {
+			name: text("name"),
+			mac_address: parse_info_field(&raw, "mac_address", &mut unparsable_fields, |value| {
+				value.parse::<MacAddress>().ok()
+			}),
+			firmware_version: text("fw_version"),
+			fpga_version: text("fpga_version"),
+			sdk_version: text("sdk_version"),
+			power: parse_info_field(&raw, "power", &mut unparsable_fields, parse_on_off)
+				.map(|on| if on { PowerState::On } else { PowerState::Off }),
+			// A MION nobody owns reports it's owner as all zeroes.
+			owner: parse_info_field(&raw, "host", &mut unparsable_fields, |value| {
+				value.parse::<Ipv4Addr>().ok()
+			})
+			.filter(|host| !host.is_unspecified()),
+			emulation: parse_info_field(&raw, "emulation", &mut unparsable_fields, parse_on_off),
+			boot_type: parse_info_field(&raw, "boot_type", &mut unparsable_fields, |value| {
+				MIONBootType::try_from(value).ok()
+			}),
+			atapi_port: parse_info_field(&raw, "atapi_port", &mut unparsable_fields, parse_port),
+			sdio_printf_port: parse_info_field(
+				&raw,
+				"sdio_printf_port",
+				&mut unparsable_fields,
+				parse_port,
+			),
+			sdio_block_port: parse_info_field(
+				&raw,
+				"sdio_block_port",
+				&mut unparsable_fields,
+				parse_port,
+			),
+			parameter_port: parse_info_field(
+				&raw,
+				"parameter_port",
+				&mut unparsable_fields,
+				parse_port,
+			),
+			unparsable_fields,
+			raw,
+		}
+	}
+}
+
+/// Parse one of the known fields of [`MionInfo`], remembering the key if it
+/// was reported with a value we couldn't understand.
+fn parse_info_field<ValueTy>(
+	raw: &FnvHashMap<String, String>,
+	key: &'static str,
+	unparsable_fields: &mut Vec<&'static str>,
+	parser: impl FnOnce(&str) -> Option<ValueTy>,
+) -> Option<ValueTy> {
+	let value = raw.get(key).map(|value| value.trim())?;
+	if value.is_empty() {
+		return None;
+	}
+
+	let parsed = parser(value);
+	if parsed.is_none() {
+		unparsable_fields.push(key);
+	}
+	parsed
+}
+
+fn parse_on_off(value: &str) -> Option<bool> {
+	if value.eq_ignore_ascii_case("on") || value == "1" {
+		Some(true)
+	} else if value.eq_ignore_ascii_case("off") || value == "0" {
+		Some(false)
+	} else {
+		None
+	}
+}
+
+fn parse_port(value: &str) -> Option<u16> {
+	value.parse::<u16>().ok()
+}
+
 #[cfg(test)]
 mod unit_tests {
 	use super::*;
 
 	#[test]
 	pub fn round_trip_control_operation() {
-		for operation in vec![
+		for operation in [
 			ControlOperation::PowerOn,
 			ControlOperation::PowerOnV2,
+			ControlOperation::PowerOff,
+			ControlOperation::Reset,
 			ControlOperation::GetInfo,
 			ControlOperation::SetParam,
 		] {
@@ -102,4 +794,58 @@ mod unit_tests {
 			);
 		}
 	}
+
+	#[test]
+	pub fn can_parse_set_parameters() {
+		for name in SetParameter::NAMES {
+			let value = match name {
+				"host_ip" => "192.168.7.40",
+				"emulation" => "on",
+				"boot_type" => "pcfs",
+				"emulation_timeout" | "shutdown_timeout" => "30",
+				_ => "7975",
+			};
+			let parsed = SetParameter::from_name_and_value(name, value)
+				.expect("Failed to parse a known set parameter!");
+			assert_eq!(Into::<&str>::into(&parsed), name);
+		}
+
+		assert_eq!(
+			SetParameter::from_name_and_value("Parameter-Port", " 7979 "),
+			Ok(SetParameter::ParameterPort(7979)),
+		);
+		assert_eq!(
+			SetParameter::from_name_and_value("emulation", "OFF"),
+			Ok(SetParameter::Emulation(false)),
+		);
+		assert_eq!(
+			SetParameter::from_name_and_value("boot_type", "dual")
+				.map(|parsed| parsed.get_value_as_string()),
+			Ok("DUAL".to_owned()),
+		);
+
+		assert!(matches!(
+			SetParameter::from_name_and_value("not_a_setting", "1"),
+			Err(APIError::UnknownSetParameter(..)),
+		));
+		for (name, value) in [
+			("atapi_port", "0"),
+			("atapi_port", "65536"),
+			("host_ip", "0.0.0.0"),
+			("host_ip", "255.255.255.255"),
+			("host_ip", "not-an-ip"),
+			("emulation", "maybe"),
+			("boot_type", "7"),
+			("shutdown_timeout", "0"),
+			("emulation_timeout", "3601"),
+		] {
+			assert!(
+				matches!(
+					SetParameter::from_name_and_value(name, value),
+					Err(APIError::InvalidSetParameterValue(..)),
+				),
+				"{name}={value} should not be valid!",
+			);
+		}
+	}
 }
@@ -2,5 +2,9 @@
 //! board.
 
 mod control;
+mod setup;
+mod signal_get;
 
 pub use control::*;
+pub use setup::*;
+pub use signal_get::*;
@@ -0,0 +1,700 @@
+//! Types for the setup page of the MION: `setup.cgi`.
+//!
+//! Unlike `control.cgi` this page is a normal HTML form meant for a browser,
+//! so reading it means pulling the current values out of the form fields, and
+//! writing it means submitting the form back with the values changed.
+//!
+//! **Unverified**: the names of the form fields here are guesses that haven't
+//! been checked against a real bridge, see [`crate::mion::cgis::get_setup`].
+
+use crate::errors::{APIError, NetworkParseError};
+use fnv::FnvHashMap;
+use std::{
+	fmt::{Display, Formatter, Result as FmtResult},
+	net::Ipv4Addr,
+};
+
+/// How a MION gets it's IP address.
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub enum IpMode {
+	/// Ask a DHCP server for an address.
+	Dhcp,
+	/// Use the address, netmask, and gateway on the setup page.
+	Static,
+}
+
+impl Display for IpMode {
+	fn fmt(&self, fmt: &mut Formatter<'_>) -> FmtResult {
+		match self {
+			Self::Dhcp => write!(fmt, "dhcp"),
+			Self::Static => write!(fmt, "static"),
+		}
+	}
+}
+
+impl TryFrom<&str> for IpMode {
+	type Error = APIError;
+
+	fn try_from(value: &str) -> Result<Self, Self::Error> {
+		let trimmed = value.trim();
+		if trimmed.eq_ignore_ascii_case("dhcp") {
+			Ok(Self::Dhcp)
+		} else if trimmed.eq_ignore_ascii_case("static") {
+			Ok(Self::Static)
+		} else {
+			Err(APIError::InvalidSetupValue(
+				"ip_mode",
+				value.to_owned(),
+				"it must be `dhcp`, or `static`",
+			))
+		}
+	}
+}
+
+/// Everything on the setup page of a MION.
+///
+/// Any fields on the form we don't know about are kept around, and submitted
+/// back untouched so changing one field doesn't reset any of the others.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MionSetup {
+	ip_mode: IpMode,
+	ip_address: Ipv4Addr,
+	netmask: Ipv4Addr,
+	gateway: Ipv4Addr,
+	name: String,
+	atapi_port: u16,
+	sdio_printf_port: u16,
+	sdio_block_port: u16,
+	parameter_port: u16,
+	unknown_fields: FnvHashMap<String, String>,
+}
+
+impl MionSetup {
+	/// The names of the form fields we know how to parse into a typed field.
+	pub const KNOWN_FIELDS: [&'static str; 9] = [
+		"ip_mode",
+		"ip_address",
+		"netmask",
+		"gateway",
+		"name",
+		"atapi_port",
+		"sdio_printf_port",
+		"sdio_block_port",
+		"parameter_port",
+	];
+
+	/// Create a new setup page, with no other unknown fields.
+	#[allow(
+		// These are all the fields of the setup page, they're each a different
+		// thing to set.
+		clippy::too_many_arguments,
+	)]
+	#[must_use]
+	pub fn new(
+		ip_mode: IpMode,
+		ip_address: Ipv4Addr,
+		netmask: Ipv4Addr,
+		gateway: Ipv4Addr,
+		name: String,
+		atapi_port: u16,
+		sdio_printf_port: u16,
+		sdio_block_port: u16,
+		parameter_port: u16,
+	) -> Self {
+		Self {
+			ip_mode,
+			ip_address,
+			netmask,
+			gateway,
+			name,
+			atapi_port,
+			sdio_printf_port,
+			sdio_block_port,
+			parameter_port,
+			unknown_fields: FnvHashMap::default(),
+		}
+	}
+
+	/// Parse the setup page out of the HTML that `setup.cgi` responds with.
+	///
+	/// ## Errors
+	///
+	/// - If any of the fields in [`Self::KNOWN_FIELDS`] are missing.
+	/// - If any of the fields in [`Self::KNOWN_FIELDS`] have a value we can't
+	///   understand.
+	pub fn from_html(html: &str) -> Result<Self, NetworkParseError> {
+		Self::try_from(parse_form_fields(html))
+	}
+
+	/// How the MION gets it's IP address.
+	#[must_use]
+	pub const fn ip_mode(&self) -> IpMode {
+		self.ip_mode
+	}
+
+	/// The static IP address of the MION, when the IP mode is
+	/// [`IpMode::Dhcp`] this is whatever was last entered.
+	#[must_use]
+	pub const fn ip_address(&self) -> Ipv4Addr {
+		self.ip_address
+	}
+
+	/// The netmask used with the static IP address.
+	#[must_use]
+	pub const fn netmask(&self) -> Ipv4Addr {
+		self.netmask
+	}
+
+	/// The gateway used with the static IP address, `0.0.0.0` if there is no
+	/// gateway.
+	#[must_use]
+	pub const fn gateway(&self) -> Ipv4Addr {
+		self.gateway
+	}
+
+	/// The name of the MION, this is what gets announced during discovery.
+	#[must_use]
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	/// The port ATAPI emulation is served on.
+	#[must_use]
+	pub const fn atapi_port(&self) -> u16 {
+		self.atapi_port
+	}
+
+	/// The port SDIO printf output is served on.
+	#[must_use]
+	pub const fn sdio_printf_port(&self) -> u16 {
+		self.sdio_printf_port
+	}
+
+	/// The port the SDIO block device is served on.
+	#[must_use]
+	pub const fn sdio_block_port(&self) -> u16 {
+		self.sdio_block_port
+	}
+
+	/// The port the parameter space is served on.
+	#[must_use]
+	pub const fn parameter_port(&self) -> u16 {
+		self.parameter_port
+	}
+
+	/// Every field that was on the form when it was read, that isn't one of
+	/// [`Self::KNOWN_FIELDS`].
+	#[must_use]
+	pub const fn unknown_fields(&self) -> &FnvHashMap<String, String> {
+		&self.unknown_fields
+	}
+
+	/// Get an IP address from a DHCP server.
+	#[must_use]
+	pub const fn with_dhcp(mut self) -> Self {
+		self.ip_mode = IpMode::Dhcp;
+		self
+	}
+
+	/// Use a static IP address, pass `0.0.0.0` as the gateway for no gateway.
+	#[must_use]
+	pub const fn with_static_address(
+		mut self,
+		ip_address: Ipv4Addr,
+		netmask: Ipv4Addr,
+		gateway: Ipv4Addr,
+	) -> Self {
+		self.ip_mode = IpMode::Static;
+		self.ip_address = ip_address;
+		self.netmask = netmask;
+		self.gateway = gateway;
+		self
+	}
+
+	/// Change the name of the MION.
+	#[must_use]
+	pub fn with_name(mut self, name: String) -> Self {
+		self.name = name;
+		self
+	}
+
+	/// Change the port ATAPI emulation is served on.
+	#[must_use]
+	pub const fn with_atapi_port(mut self, port: u16) -> Self {
+		self.atapi_port = port;
+		self
+	}
+
+	/// Change the port SDIO printf output is served on.
+	#[must_use]
+	pub const fn with_sdio_printf_port(mut self, port: u16) -> Self {
+		self.sdio_printf_port = port;
+		self
+	}
+
+	/// Change the port the SDIO block device is served on.
+	#[must_use]
+	pub const fn with_sdio_block_port(mut self, port: u16) -> Self {
+		self.sdio_block_port = port;
+		self
+	}
+
+	/// Change the port the parameter space is served on.
+	#[must_use]
+	pub const fn with_parameter_port(mut self, port: u16) -> Self {
+		self.parameter_port = port;
+		self
+	}
+
+	/// Make sure this is a setup the MION will accept, and that won't make it
+	/// unreachable.
+	///
+	/// ## Errors
+	///
+	/// - If the name is empty, not ASCII, or longer than 255 bytes.
+	/// - If using a static address, and the address isn't one a single host
+	///   can use, the netmask isn't contiguous, or the gateway is outside of
+	///   the subnet.
+	/// - If any of the ports are `0`, or two services share a port.
+	pub fn validate(&self) -> Result<(), APIError> {
+		if !self.name.is_ascii() {
+			return Err(APIError::DeviceNameMustBeAscii);
+		}
+		if self.name.is_empty() {
+			return Err(APIError::DeviceNameCannotBeEmpty);
+		}
+		if self.name.len() > 255 {
+			return Err(APIError::DeviceNameTooLong(self.name.len()));
+		}
+
+		if self.ip_mode == IpMode::Static {
+			let ip = self.ip_address;
+			if ip.is_unspecified() || ip.is_broadcast() || ip.is_multicast() || ip.is_loopback() {
+				return Err(APIError::InvalidSetupValue(
+					"ip_address",
+					format!("{ip}"),
+					"it must be the IP of a single host",
+				));
+			}
+			let mask = u32::from(self.netmask);
+			if mask == 0 || mask.leading_ones() + mask.trailing_zeros() != 32 {
+				return Err(APIError::InvalidSetupValue(
+					"netmask",
+					format!("{}", self.netmask),
+					"it must be a contiguous netmask like 255.255.255.0",
+				));
+			}
+			if !self.gateway.is_unspecified()
+				&& u32::from(self.gateway) & mask != u32::from(ip) & mask
+			{
+				return Err(APIError::InvalidSetupValue(
+					"gateway",
+					format!("{}", self.gateway),
+					"it must be within the same subnet as the ip address",
+				));
+			}
+		}
+
+		let ports = [
+			("atapi_port", self.atapi_port),
+			("sdio_printf_port", self.sdio_printf_port),
+			("sdio_block_port", self.sdio_block_port),
+			("parameter_port", self.parameter_port),
+		];
+		for (idx, (name, port)) in ports.iter().enumerate() {
+			if *port == 0 {
+				return Err(APIError::InvalidSetupValue(
+					name,
+					format!("{port}"),
+					"a port cannot be 0",
+				));
+			}
+			if ports[..idx].iter().any(|(_, other)| other == port) {
+				return Err(APIError::InvalidSetupValue(
+					name,
+					format!("{port}"),
+					"each service needs it's own port",
+				));
+			}
+		}
+
+		Ok(())
+	}
+
+	/// The fields to submit back to `setup.cgi`.
+	///
+	/// Known fields come first, followed by every other field that was on the
+	/// form when it was read.
+	#[must_use]
+	pub fn to_form(&self) -> Vec<(String, String)> {
+		let mut form = vec![
+			("ip_mode".to_owned(), format!("{}", self.ip_mode)),
+			("ip_address".to_owned(), format!("{}", self.ip_address)),
+			("netmask".to_owned(), format!("{}", self.netmask)),
+			("gateway".to_owned(), format!("{}", self.gateway)),
+			("name".to_owned(), self.name.clone()),
+			("atapi_port".to_owned(), format!("{}", self.atapi_port)),
+			(
+				"sdio_printf_port".to_owned(),
+				format!("{}", self.sdio_printf_port),
+			),
+			(
+				"sdio_block_port".to_owned(),
+				format!("{}", self.sdio_block_port),
+			),
+			(
+				"parameter_port".to_owned(),
+				format!("{}", self.parameter_port),
+			),
+		];
+		let mut unknown = self
+			.unknown_fields
+			.iter()
+			.map(|(key, value)| (key.clone(), value.clone()))
+			.collect::<Vec<_>>();
+		unknown.sort();
+		form.extend(unknown);
+		form
+	}
+}
+
+impl TryFrom<FnvHashMap<String, String>> for MionSetup {
+	type Error = NetworkParseError;
+
+	fn try_from(raw: FnvHashMap<String, String>) -> Result<Self, Self::Error> {
+		let text = |key: &'static str| {
+			raw.get(key)
+				.map(|value| value.trim())
+				.ok_or(NetworkParseError::SetupFieldMissing(key))
+		};
+		let ip = |key: &'static str| {
+			let value = text(key)?;
+			// Browsers submit empty fields, which the MION treats as no address.
+			if value.is_empty() {
+				return Ok(Ipv4Addr::UNSPECIFIED);
+			}
+			value
+				.parse::<Ipv4Addr>()
+				.map_err(|_| NetworkParseError::InvalidSetupField(key, value.to_owned()))
+		};
+		let port = |key: &'static str| {
+			let value = text(key)?;
+			value
+				.parse::<u16>()
+				.map_err(|_| NetworkParseError::InvalidSetupField(key, value.to_owned()))
+		};
+
+		let ip_mode_value = text("ip_mode")?;
+		let ip_mode = IpMode::try_from(ip_mode_value).map_err(|_| {
+			NetworkParseError::InvalidSetupField("ip_mode", ip_mode_value.to_owned())
+		})?;
+
+		Ok(Self {
+			ip_mode,
+			ip_address: ip("ip_address")?,
+			netmask: ip("netmask")?,
+			gateway: ip("gateway")?,
+			name: text("name")?.to_owned(),
+			atapi_port: port("atapi_port")?,
+			sdio_printf_port: port("sdio_printf_port")?,
+			sdio_block_port: port("sdio_block_port")?,
+			parameter_port: port("parameter_port")?,
+			unknown_fields: raw
+				.iter()
+				.filter(|(key, _)| !Self::KNOWN_FIELDS.contains(&key.as_str()))
+				.map(|(key, value)| (key.clone(), value.clone()))
+				.collect(),
+		})
+	}
+}
+
+/// Pull the current value of every field out of an HTML form.
+///
+/// This only handles what the MION actually serves: `<input>`s (where radio
+/// buttons, and checkboxes only count when checked, and buttons are
+/// ignored), and `<select>`s (where the selected option, or the first option
+/// is used).
+#[must_use]
+pub fn parse_form_fields(html: &str) -> FnvHashMap<String, String> {
+	// Only ASCII characters are lowercased, so every byte offset is the same as
+	// the original.
+	let lowered = html.to_ascii_lowercase();
+	let mut fields = FnvHashMap::default();
+
+	let mut cursor = 0;
+	while let Some(relative_start) = lowered[cursor..].find('<') {
+		let start = cursor + relative_start + 1;
+		let Some(relative_end) = lowered[start..].find('>') else {
+			break;
+		};
+		let end = start + relative_end;
+		cursor = end + 1;
+
+		if let Some(attributes) = lowered[start..end]
+			.strip_prefix("input")
+			.map(|rest| &html[end - rest.len()..end])
+		{
+			let attributes = parse_attributes(attributes);
+			let Some(name) = attributes.get("name") else {
+				continue;
+			};
+			let input_type = attributes
+				.get("type")
+				.map_or_else(|| "text".to_owned(), |kind| kind.to_ascii_lowercase());
+			match input_type.as_str() {
+				"submit" | "button" | "reset" | "image" => continue,
+				"radio" | "checkbox" if !attributes.contains_key("checked") => continue,
+				_ => {}
+			}
+			fields.insert(
+				name.clone(),
+				attributes.get("value").cloned().unwrap_or_default(),
+			);
+		} else if let Some(attributes) = lowered[start..end]
+			.strip_prefix("select")
+			.map(|rest| &html[end - rest.len()..end])
+		{
+			let attributes = parse_attributes(attributes);
+			let options_end = lowered[cursor..]
+				.find("</select")
+				.map_or(html.len(), |relative| cursor + relative);
+			let options = &html[cursor..options_end];
+			cursor = options_end;
+
+			let Some(name) = attributes.get("name") else {
+				continue;
+			};
+			if let Some(value) = selected_option(options) {
+				fields.insert(name.clone(), value);
+			}
+		}
+	}
+
+	fields
+}
+
+/// Get the value of the selected option (or the first option if none are
+/// selected) from the inside of a `<select>`.
+fn selected_option(options: &str) -> Option<String> {
+	let lowered = options.to_ascii_lowercase();
+	let mut first = None;
+
+	let mut cursor = 0;
+	while let Some(relative_start) = lowered[cursor..].find("<option") {
+		let start = cursor + relative_start + "<option".len();
+		let Some(relative_end) = lowered[start..].find('>') else {
+			break;
+		};
+		let end = start + relative_end;
+		cursor = end + 1;
+
+		let attributes = parse_attributes(&options[start..end]);
+		let value = attributes.get("value").cloned().unwrap_or_else(|| {
+			let text_end = lowered[cursor..]
+				.find('<')
+				.map_or(options.len(), |relative| cursor + relative);
+			decode_entities(options[cursor..text_end].trim())
+		});
+		if attributes.contains_key("selected") {
+			return Some(value);
+		}
+		first.get_or_insert(value);
+	}
+
+	first
+}
+
+/// Parse the attributes of a tag, e.g. ` name="ip_mode" checked`.
+///
+/// Attribute names are lowercased, and attributes without a value (like
+/// `checked`) have an empty value.
+fn parse_attributes(tag: &str) -> FnvHashMap<String, String> {
+	let bytes = tag.as_bytes();
+	let mut attributes = FnvHashMap::default();
+
+	let mut idx = 0;
+	while idx < bytes.len() {
+		if bytes[idx].is_ascii_whitespace() || bytes[idx] == b'/' {
+			idx += 1;
+			continue;
+		}
+
+		let name_start = idx;
+		while idx < bytes.len()
+			&& !bytes[idx].is_ascii_whitespace()
+			&& bytes[idx] != b'='
+			&& bytes[idx] != b'/'
+		{
+			idx += 1;
+		}
+		let name = tag[name_start..idx].to_ascii_lowercase();
+		while idx < bytes.len() && bytes[idx].is_ascii_whitespace() {
+			idx += 1;
+		}
+
+		if idx >= bytes.len() || bytes[idx] != b'=' {
+			attributes.insert(name, String::new());
+			continue;
+		}
+		idx += 1;
+		while idx < bytes.len() && bytes[idx].is_ascii_whitespace() {
+			idx += 1;
+		}
+
+		let value = if idx < bytes.len() && (bytes[idx] == b'"' || bytes[idx] == b'\'') {
+			let quote = bytes[idx];
+			let value_start = idx + 1;
+			idx = value_start;
+			while idx < bytes.len() && bytes[idx] != quote {
+				idx += 1;
+			}
+			let value = &tag[value_start..idx];
+			// Skip the closing quote.
+			idx += 1;
+			value
+		} else {
+			let value_start = idx;
+			while idx < bytes.len() && !bytes[idx].is_ascii_whitespace() {
+				idx += 1;
+			}
+			&tag[value_start..idx]
+		};
+		attributes.insert(name, decode_entities(value));
+	}
+
+	attributes
+}
+
+/// Decode the few HTML entities that can show up in a form value.
+fn decode_entities(value: &str) -> String {
+	value
+		.replace("&lt;", "<")
+		.replace("&gt;", ">")
+		.replace("&quot;", "\"")
+		.replace("&#39;", "'")
+		.replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod unit_tests {
+	use super::*;
+
+	fn setup() -> MionSetup {
+		MionSetup::new(
+			IpMode::Static,
+			Ipv4Addr::new(192, 168, 7, 40),
+			Ipv4Addr::new(255, 255, 255, 0),
+			Ipv4Addr::new(192, 168, 7, 1),
+			"my-bridge".to_owned(),
+			7975,
+			7976,
+			7977,
+			7978,
+		)
+	}
+
+	#[test]
+	pub fn can_parse_form_fields() {
+		let fields = parse_form_fields(
+			r#"<form method="post" action="setup.cgi">
+			<INPUT TYPE="radio" NAME="ip_mode" VALUE="dhcp">
+			<input type="radio" name="ip_mode" value="static" checked>
+			<input type=text name=name value='a &amp; b'/>
+			<input type="hidden" name="token" value="1234">
+			<input type="checkbox" name="telnet" value="on">
+			<select name="speed"><option value="10">10</option><option selected>100</option></select>
+			<select name="duplex"><option value="full">Full</option></select>
+			<input type="submit" name="submit" value="Save">
+			</form>"#,
+		);
+
+		assert_eq!(fields.get("ip_mode").map(String::as_str), Some("static"));
+		assert_eq!(fields.get("name").map(String::as_str), Some("a & b"));
+		assert_eq!(fields.get("token").map(String::as_str), Some("1234"));
+		assert_eq!(fields.get("speed").map(String::as_str), Some("100"));
+		assert_eq!(fields.get("duplex").map(String::as_str), Some("full"));
+		assert!(!fields.contains_key("telnet"));
+		assert!(!fields.contains_key("submit"));
+	}
+
+	#[test]
+	pub fn validates_setup() {
+		assert!(setup().validate().is_ok());
+		assert!(setup()
+			.with_static_address(
+				Ipv4Addr::new(10, 0, 0, 5),
+				Ipv4Addr::new(255, 0, 0, 0),
+				Ipv4Addr::UNSPECIFIED,
+			)
+			.validate()
+			.is_ok());
+		// An invalid static address doesn't matter when using DHCP.
+		assert!(setup()
+			.with_static_address(
+				Ipv4Addr::UNSPECIFIED,
+				Ipv4Addr::UNSPECIFIED,
+				Ipv4Addr::UNSPECIFIED,
+			)
+			.with_dhcp()
+			.validate()
+			.is_ok());
+
+		assert_eq!(
+			setup().with_name(String::new()).validate(),
+			Err(APIError::DeviceNameCannotBeEmpty),
+		);
+		for (invalid, field) in [
+			(
+				setup().with_static_address(
+					Ipv4Addr::new(224, 0, 0, 1),
+					Ipv4Addr::new(255, 255, 255, 0),
+					Ipv4Addr::UNSPECIFIED,
+				),
+				"ip_address",
+			),
+			(
+				setup().with_static_address(
+					Ipv4Addr::new(192, 168, 7, 40),
+					Ipv4Addr::new(255, 0, 255, 0),
+					Ipv4Addr::UNSPECIFIED,
+				),
+				"netmask",
+			),
+			(
+				setup().with_static_address(
+					Ipv4Addr::new(192, 168, 7, 40),
+					Ipv4Addr::new(255, 255, 255, 0),
+					Ipv4Addr::new(192, 168, 8, 1),
+				),
+				"gateway",
+			),
+			(setup().with_atapi_port(0), "atapi_port"),
+			(setup().with_parameter_port(7975), "parameter_port"),
+		] {
+			assert!(
+				matches!(
+					invalid.validate(),
+					Err(APIError::InvalidSetupValue(name, _, _)) if name == field,
+				),
+				"Expected {field} to be invalid!",
+			);
+		}
+	}
+
+	#[test]
+	pub fn form_round_trips() {
+		let mut raw = setup()
+			.to_form()
+			.into_iter()
+			.collect::<FnvHashMap<String, String>>();
+		raw.insert("token".to_owned(), "1234".to_owned());
+		let parsed = MionSetup::try_from(raw).expect("Failed to parse setup form!");
+
+		assert_eq!(parsed.ip_mode(), IpMode::Static);
+		assert_eq!(parsed.gateway(), Ipv4Addr::new(192, 168, 7, 1));
+		assert_eq!(parsed.parameter_port(), 7978);
+		let form = parsed.with_parameter_port(7979).to_form();
+		assert!(form.contains(&("parameter_port".to_owned(), "7979".to_owned())));
+		assert_eq!(form.last(), Some(&("token".to_owned(), "1234".to_owned())));
+	}
+}
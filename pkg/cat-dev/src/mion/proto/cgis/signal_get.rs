@@ -0,0 +1,64 @@
+//! Types for the hardware signals that can be read from `signal_get.cgi`.
+
+use crate::errors::APIError;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+/// A hardware signal that can be read from the MION.
+///
+/// `VDD2` is the only signal we've seen the official tools ask for, so it's
+/// the only one we know `signal_get.cgi` accepts. We also don't know what it's
+/// values mean, so they're always passed through exactly as the MION sent
+/// them.
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub enum Signal {
+	/// The `VDD2` signal.
+	Vdd2,
+}
+
+impl Signal {
+	/// Every signal that can be read.
+	pub const ALL: [Self; 1] = [Self::Vdd2];
+
+	/// The name `signal_get.cgi` knows this signal by.
+	#[must_use]
+	pub const fn name(&self) -> &'static str {
+		match self {
+			Self::Vdd2 => "VDD2",
+		}
+	}
+}
+
+impl Display for Signal {
+	fn fmt(&self, fmt: &mut Formatter<'_>) -> FmtResult {
+		write!(fmt, "{}", self.name())
+	}
+}
+
+impl TryFrom<&str> for Signal {
+	type Error = APIError;
+
+	fn try_from(value: &str) -> Result<Self, Self::Error> {
+		let trimmed = value.trim();
+		Self::ALL
+			.into_iter()
+			.find(|signal| signal.name().eq_ignore_ascii_case(trimmed))
+			.ok_or_else(|| APIError::UnknownSignal(value.to_owned()))
+	}
+}
+
+#[cfg(test)]
+mod unit_tests {
+	use super::*;
+
+	#[test]
+	pub fn round_trip_signal_names() {
+		for signal in Signal::ALL {
+			assert_eq!(Signal::try_from(signal.name()), Ok(signal));
+		}
+		assert_eq!(Signal::try_from(" vdd2 "), Ok(Signal::Vdd2));
+		assert_eq!(
+			Signal::try_from("VDD1"),
+			Err(APIError::UnknownSignal("VDD1".to_owned())),
+		);
+	}
+}
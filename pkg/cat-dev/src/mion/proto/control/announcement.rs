@@ -16,6 +16,7 @@ use mac_address::MacAddress;
 use std::{
 	fmt::{Display, Formatter, Result as FmtResult},
 	net::Ipv4Addr,
+	ops::Range,
 };
 use valuable::{Fields, NamedField, NamedValues, StructDef, Structable, Valuable, Value, Visit};
 
@@ -24,6 +25,26 @@ const ANNOUNCEMENT_MESSAGE: &str = "MULTI_I/O_NETWORK_BOARD";
 /// The flag to encode into the packet to request more detailed information.
 const DETAIL_FLAG_MESSAGE: &str = "enumV1";
 
+/// The size of the extra data that gets sent back when asking for detailed
+/// information.
+pub const DETAILED_DATA_SIZE: usize = 239;
+/// The offset into the detailed data where the 4 byte SDK version is stored.
+pub const DETAILED_SDK_VERSION_OFFSET: usize = 227;
+/// The offset into the detailed data where the boot type is stored.
+pub const DETAILED_BOOT_TYPE_OFFSET: usize = 232;
+/// The offset into the detailed data where the power status of cafe is
+/// stored.
+pub const DETAILED_CAFE_ON_OFFSET: usize = 233;
+/// All the ranges of the detailed data we have not been able to identify yet.
+///
+/// Every device we've been able to capture sends all zeroes for these ranges,
+/// except for byte `231` which has always been `0x01`.
+pub const DETAILED_UNKNOWN_RANGES: [Range<usize>; 3] = [
+	0..DETAILED_SDK_VERSION_OFFSET,
+	DETAILED_SDK_VERSION_OFFSET + 4..DETAILED_BOOT_TYPE_OFFSET,
+	DETAILED_CAFE_ON_OFFSET + 1..DETAILED_DATA_SIZE,
+];
+
 /// An announcement to ask all MION's to identify themselves.
 ///
 /// Provide "detailed" to get more than the IP/Mac/Name/FPGA Version/FW Version
@@ -144,17 +165,44 @@ impl From<MionIdentityAnnouncement> for Bytes {
 }
 
 /// The boot type the MION is actively configured to boot into.
-#[derive(Clone, Debug, Hash, PartialEq, Eq, Valuable)]
+///
+/// The names here match what `findbridge -detail` displays in it's
+/// "Boot Mode" column.
+///
+/// Only [`MIONBootType::PCFS`] (`0x2`) has been seen in a captured
+/// announcement. The raw values for every other boot type are guesses based
+/// off of their order in the official tools, and have not been confirmed
+/// against a real device. If you see a device report a boot type as
+/// `Unk(_)`, or one that doesn't match how it's configured please reach out.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, Valuable)]
 pub enum MIONBootType {
+	/// Boot from the devices own internal NAND.
+	///
+	/// *note: the raw value `0x1` is a guess.*
+	NAND,
 	/// Boot from the PC rather than from it's own internal device nand.
 	PCFS,
+	/// Boot from both the internal NAND, and the PC. This is what gets used
+	/// when titles are installed on the device, but the disc is being
+	/// emulated from the PC.
+	///
+	/// *note: the raw value `0x3` is a guess.*
+	DUAL,
+	/// Boot from the HDD attached to the bridge, with the disc being emulated
+	/// from an image on that HDD.
+	///
+	/// *note: the raw value `0x4` is a guess.*
+	HDD,
 	/// An unknown boot type we don't know how to parse.
 	Unk(u8),
 }
 impl Display for MIONBootType {
 	fn fmt(&self, fmt: &mut Formatter<'_>) -> FmtResult {
 		match *self {
+			Self::NAND => write!(fmt, "NAND"),
 			Self::PCFS => write!(fmt, "PCFS"),
+			Self::DUAL => write!(fmt, "DUAL"),
+			Self::HDD => write!(fmt, "HDD"),
 			Self::Unk(val) => write!(fmt, "Unk({val})"),
 		}
 	}
@@ -162,11 +210,52 @@ impl Display for MIONBootType {
 impl From<u8> for MIONBootType {
 	fn from(value: u8) -> Self {
 		match value {
+			0x1 => MIONBootType::NAND,
 			0x2 => MIONBootType::PCFS,
+			0x3 => MIONBootType::DUAL,
+			0x4 => MIONBootType::HDD,
 			num => MIONBootType::Unk(num),
 		}
 	}
 }
+impl From<MIONBootType> for u8 {
+	fn from(value: MIONBootType) -> Self {
+		match value {
+			MIONBootType::NAND => 0x1,
+			MIONBootType::PCFS => 0x2,
+			MIONBootType::DUAL => 0x3,
+			MIONBootType::HDD => 0x4,
+			MIONBootType::Unk(num) => num,
+		}
+	}
+}
+impl TryFrom<&str> for MIONBootType {
+	// This type is an API Error, because we only ever parse it from users, the
+	// network always sends us a byte.
+	type Error = APIError;
+
+	fn try_from(value: &str) -> Result<Self, Self::Error> {
+		let trimmed = value.trim();
+		if trimmed.eq_ignore_ascii_case("nand") {
+			Ok(Self::NAND)
+		} else if trimmed.eq_ignore_ascii_case("pcfs") {
+			Ok(Self::PCFS)
+		} else if trimmed.eq_ignore_ascii_case("dual") {
+			Ok(Self::DUAL)
+		} else if trimmed.eq_ignore_ascii_case("hdd") {
+			Ok(Self::HDD)
+		} else if let Some(raw) = trimmed
+			.strip_prefix("Unk(")
+			.and_then(|rest| rest.strip_suffix(')'))
+			.or(Some(trimmed))
+			.and_then(|num| num.parse::<u8>().ok())
+		{
+			Ok(Self::from(raw))
+		} else {
+			Err(APIError::UnknownBootType(value.to_owned()))
+		}
+	}
+}
 
 /// An identity for a CAT-DEV that we received from the network.
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
@@ -188,6 +277,7 @@ impl MionIdentity {
 	/// - If the name is not ASCII.
 	/// - If the name is longer than 255 bytes.
 	/// - If the name is empty.
+	/// - If detailed data is present, but is not exactly 239 bytes long.
 	pub fn new(
 		detailed_data: Option<Bytes>,
 		firmware_version: [u8; 4],
@@ -205,6 +295,11 @@ impl MionIdentity {
 		if name.is_empty() {
 			return Err(APIError::DeviceNameCannotBeEmpty);
 		}
+		if let Some(detailed) = detailed_data.as_ref() {
+			if detailed.len() != DETAILED_DATA_SIZE {
+				return Err(APIError::DetailedDataNotCorrectLength(detailed.len()));
+			}
+		}
 
 		Ok(Self {
 			detailed_all: detailed_data,
@@ -216,6 +311,16 @@ impl MionIdentity {
 		})
 	}
 
+	/// Create a copy of this identity, but with a different set of detailed
+	/// data.
+	#[cfg(feature = "emulator")]
+	pub(crate) fn with_detailed_data(&self, detailed_data: Option<Bytes>) -> Self {
+		Self {
+			detailed_all: detailed_data,
+			..self.clone()
+		}
+	}
+
 	/// The firmware version of the current CAT-DEV, rendered as a string you'd
 	/// see displayed.
 	#[must_use]
@@ -301,16 +406,26 @@ impl MionIdentity {
 		self.detailed_all.is_some()
 	}
 
+	/// The raw 239 bytes of extra data that get sent when you ask for detailed
+	/// information.
+	///
+	/// Most of this data isn't understood yet, you probably want one of the
+	/// `detailed_*` accessors instead.
+	#[must_use]
+	pub const fn raw_detailed_data(&self) -> Option<&Bytes> {
+		self.detailed_all.as_ref()
+	}
+
 	/// If you've asked for, and received detailed information this will be the
 	/// SDK version that the current dev-kit is running.
 	#[must_use]
 	pub fn detailed_sdk_version(&self) -> Option<String> {
 		self.detailed_all.as_ref().map(|extra_data| {
 			let bytes = [
-				extra_data[227],
-				extra_data[228],
-				extra_data[229],
-				extra_data[230],
+				extra_data[DETAILED_SDK_VERSION_OFFSET],
+				extra_data[DETAILED_SDK_VERSION_OFFSET + 1],
+				extra_data[DETAILED_SDK_VERSION_OFFSET + 2],
+				extra_data[DETAILED_SDK_VERSION_OFFSET + 3],
 			];
 
 			// SDK versions may not display the fourth identifier.
@@ -331,10 +446,10 @@ impl MionIdentity {
 	pub fn detailed_raw_sdk_version(&self) -> Option<[u8; 4]> {
 		self.detailed_all.as_ref().map(|extra_data| {
 			[
-				extra_data[227],
-				extra_data[228],
-				extra_data[229],
-				extra_data[230],
+				extra_data[DETAILED_SDK_VERSION_OFFSET],
+				extra_data[DETAILED_SDK_VERSION_OFFSET + 1],
+				extra_data[DETAILED_SDK_VERSION_OFFSET + 2],
+				extra_data[DETAILED_SDK_VERSION_OFFSET + 3],
 			]
 		})
 	}
@@ -345,7 +460,7 @@ impl MionIdentity {
 	pub fn detailed_boot_type(&self) -> Option<MIONBootType> {
 		self.detailed_all
 			.as_ref()
-			.map(|extra_data| MIONBootType::from(extra_data[232]))
+			.map(|extra_data| MIONBootType::from(extra_data[DETAILED_BOOT_TYPE_OFFSET]))
 	}
 
 	/// If you've asked for, and received detailed information this will be the
@@ -354,7 +469,24 @@ impl MionIdentity {
 	pub fn detailed_is_cafe_on(&self) -> Option<bool> {
 		self.detailed_all
 			.as_ref()
-			.map(|extra_data| extra_data[233] > 0)
+			.map(|extra_data| extra_data[DETAILED_CAFE_ON_OFFSET] > 0)
+	}
+
+	/// If you've asked for, and received detailed information these are all
+	/// the parts of the detailed data we don't yet know the meaning of.
+	///
+	/// Each range is an offset into the detailed data (see:
+	/// [`DETAILED_UNKNOWN_RANGES`]), alongside the raw bytes at that range.
+	/// These are exposed so they can be used for diagnostics, and so they can
+	/// be compared across devices to figure out what they mean.
+	#[must_use]
+	pub fn detailed_unknown_data(&self) -> Option<Vec<(Range<usize>, Bytes)>> {
+		self.detailed_all.as_ref().map(|extra_data| {
+			DETAILED_UNKNOWN_RANGES
+				.iter()
+				.map(|range| (range.clone(), extra_data.slice(range.clone())))
+				.collect()
+		})
 	}
 }
 impl Display for MionIdentity {
@@ -374,12 +506,12 @@ impl Display for MionIdentity {
 				self.firmware_version[1],
 				self.firmware_version[2],
 				self.firmware_version[3],
-				detailed[227],
-				detailed[228],
-				detailed[229],
-				detailed[230],
-				MIONBootType::from(detailed[232]),
-				detailed[233],
+				detailed[DETAILED_SDK_VERSION_OFFSET],
+				detailed[DETAILED_SDK_VERSION_OFFSET + 1],
+				detailed[DETAILED_SDK_VERSION_OFFSET + 2],
+				detailed[DETAILED_SDK_VERSION_OFFSET + 3],
+				MIONBootType::from(detailed[DETAILED_BOOT_TYPE_OFFSET]),
+				detailed[DETAILED_CAFE_ON_OFFSET],
 			)
 		} else {
 			write!(
@@ -444,15 +576,16 @@ impl TryFrom<(Ipv4Addr, Bytes)> for MionIdentity {
 				),
 			));
 		}
-		if packet.len() > 16 + name_length + 239 {
+		if packet.len() > 16 + name_length + DETAILED_DATA_SIZE {
 			return Err(NetworkError::ParseError(
 				NetworkParseError::UnexpectedTrailer(
 					"MionIdentity",
-					packet.slice(16 + name_length + 239..),
+					packet.slice(16 + name_length + DETAILED_DATA_SIZE..),
 				),
 			));
 		}
-		if packet.len() != 16 + name_length && packet.len() != 16 + name_length + 239 {
+		if packet.len() != 16 + name_length && packet.len() != 16 + name_length + DETAILED_DATA_SIZE
+		{
 			return Err(NetworkError::ParseError(
 				NetworkParseError::UnexpectedTrailer(
 					"MionIdentity",
@@ -496,7 +629,9 @@ impl TryFrom<(Ipv4Addr, Bytes)> for MionIdentity {
 }
 impl From<&MionIdentity> for Bytes {
 	fn from(value: &MionIdentity) -> Self {
-		let mut buff = BytesMut::with_capacity(16 + value.name.len());
+		let mut buff = BytesMut::with_capacity(
+			16 + value.name.len() + value.detailed_all.as_ref().map_or(0, Bytes::len),
+		);
 		buff.put_u8(u8::from(MionCommandByte::AcknowledgeAnnouncement));
 		buff.extend_from_slice(&value.mac.bytes());
 		buff.put_u8(u8::try_from(value.name.len()).unwrap_or(u8::MAX));
@@ -513,6 +648,9 @@ impl From<&MionIdentity> for Bytes {
 			value.firmware_version[3],
 		]);
 		buff.extend_from_slice(value.name.as_bytes());
+		if let Some(detailed) = value.detailed_all.as_ref() {
+			buff.extend_from_slice(detailed);
+		}
 		buff.freeze()
 	}
 }
@@ -531,6 +669,7 @@ const MION_IDENTITY_FIELDS: &[NamedField<'static>] = &[
 	NamedField::new("detailed_sdk_version"),
 	NamedField::new("detailed_boot_mode"),
 	NamedField::new("detailed_power_status"),
+	NamedField::new("detailed_unknown_data"),
 ];
 impl Structable for MionIdentity {
 	fn definition(&self) -> StructDef<'_> {
@@ -550,8 +689,22 @@ impl Valuable for MionIdentity {
 			.detailed_boot_type()
 			.map_or("<missing data>".to_owned(), |bt| format!("{bt}"));
 		let detailed_power_status = self
-			.detailed_sdk_version()
-			.unwrap_or("<missing data>".to_owned());
+			.detailed_is_cafe_on()
+			.map_or("<missing data>", |is_on| if is_on { "ON" } else { "OFF" });
+		// Most of the unknown data is zeroed, so only render the bytes that
+		// aren't to keep logs readable.
+		let detailed_unknown_data = self
+			.detailed_unknown_data()
+			.unwrap_or_default()
+			.into_iter()
+			.flat_map(|(range, data)| {
+				range
+					.zip(data)
+					.filter(|(_, byte)| *byte != 0)
+					.map(|(offset, byte)| format!("{offset}=0x{byte:02x}"))
+					.collect::<Vec<_>>()
+			})
+			.collect::<Vec<String>>();
 
 		visitor.visit_named_fields(&NamedValues::new(
 			MION_IDENTITY_FIELDS,
@@ -564,6 +717,7 @@ impl Valuable for MionIdentity {
 				Valuable::as_value(&detailed_sdk_version),
 				Valuable::as_value(&detailed_boot_mode),
 				Valuable::as_value(&detailed_power_status),
+				Valuable::as_value(&detailed_unknown_data),
 			],
 		));
 	}
@@ -573,9 +727,54 @@ impl Valuable for MionIdentity {
 mod unit_tests {
 	use super::*;
 
+	/// A real detailed announcement, captured from a CAT-DEV with cafe off.
+	const OFF_ANNOUNCEMENT: [u8; 272] = [
+		0x20, 0x00, 0x25, 0x5c, 0xba, 0x5a, 0x00, 0x11, 0x71, 0x20, 0x05, 0x13, 0x00, 0x0e, 0x50,
+		0x01, 0x30, 0x30, 0x2d, 0x32, 0x35, 0x2d, 0x35, 0x43, 0x2d, 0x42, 0x41, 0x2d, 0x35, 0x41,
+		0x2d, 0x30, 0x30, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x0c, 0x0d, 0x00, 0x01, 0x02, 0x00, 0x00, 0x00, 0x00,
+		0x00, 0x00,
+	];
+	/// A real detailed announcement, captured from a CAT-DEV with cafe on.
+	const ON_ANNOUNCEMENT: [u8; 272] = [
+		0x20, 0x00, 0x25, 0x5c, 0xba, 0x5a, 0x00, 0x11, 0x71, 0x20, 0x05, 0x13, 0x00, 0x0e, 0x50,
+		0x01, 0x30, 0x30, 0x2d, 0x32, 0x35, 0x2d, 0x35, 0x43, 0x2d, 0x42, 0x41, 0x2d, 0x35, 0x41,
+		0x2d, 0x30, 0x30, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x0c, 0x0d, 0x00, 0x01, 0x02, 0x01, 0x00, 0x00, 0x00,
+		0x00, 0x00,
+	];
+
 	#[test]
 	pub fn mion_command_byte_conversions() {
-		for command_byte in vec![
+		for command_byte in [
 			MionCommandByte::Search,
 			MionCommandByte::Broadcast,
 			MionCommandByte::AnnounceYourselves,
@@ -629,6 +828,18 @@ mod unit_tests {
 			),
 			Err(APIError::DeviceNameCannotBeEmpty),
 		);
+		assert_eq!(
+			MionIdentity::new(
+				// Detailed data has to be exactly 239 bytes.
+				Some(Bytes::from(vec![0; 12])),
+				[0, 0, 0, 0],
+				[0, 0, 0, 0],
+				Ipv4Addr::LOCALHOST,
+				MacAddress::new([0, 0, 0, 0, 0, 0]),
+				"00-00-00-00-00-00".to_owned(),
+			),
+			Err(APIError::DetailedDataNotCorrectLength(12)),
+		);
 		// Success!
 		assert!(MionIdentity::new(
 			None,
@@ -898,7 +1109,7 @@ mod unit_tests {
 					value,
 					NetworkParseError::UnexpectedTrailer(
 						"MionIdentity",
-						Bytes::from(b"abcd".iter().cloned().collect::<Vec<u8>>())
+						Bytes::from(b"abcd".to_vec())
 					)
 				);
 			}
@@ -946,7 +1157,7 @@ mod unit_tests {
 					value,
 					NetworkParseError::UnexpectedTrailer(
 						"MionIdentity",
-						Bytes::from(b"abcd".iter().cloned().collect::<Vec<u8>>())
+						Bytes::from(b"abcd".to_vec())
 					)
 				);
 			}
@@ -955,51 +1166,6 @@ mod unit_tests {
 
 	#[test]
 	pub fn test_real_life_detailed_announcements() {
-		const OFF_ANNOUNCEMENT: [u8; 272] = [
-			0x20, 0x00, 0x25, 0x5c, 0xba, 0x5a, 0x00, 0x11, 0x71, 0x20, 0x05, 0x13, 0x00, 0x0e,
-			0x50, 0x01, 0x30, 0x30, 0x2d, 0x32, 0x35, 0x2d, 0x35, 0x43, 0x2d, 0x42, 0x41, 0x2d,
-			0x35, 0x41, 0x2d, 0x30, 0x30, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-			0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-			0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-			0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-			0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-			0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-			0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-			0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-			0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-			0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-			0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-			0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-			0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-			0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-			0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-			0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-			0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x0c, 0x0d, 0x00, 0x01, 0x02,
-			0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-		];
-		const ON_ANNOUNCEMENT: [u8; 272] = [
-			0x20, 0x00, 0x25, 0x5c, 0xba, 0x5a, 0x00, 0x11, 0x71, 0x20, 0x05, 0x13, 0x00, 0x0e,
-			0x50, 0x01, 0x30, 0x30, 0x2d, 0x32, 0x35, 0x2d, 0x35, 0x43, 0x2d, 0x42, 0x41, 0x2d,
-			0x35, 0x41, 0x2d, 0x30, 0x30, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-			0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-			0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-			0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-			0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-			0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-			0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-			0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-			0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-			0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-			0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-			0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-			0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-			0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-			0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-			0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-			0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x0c, 0x0d, 0x00, 0x01, 0x02,
-			0x01, 0x00, 0x00, 0x00, 0x00, 0x00,
-		];
-
 		let off_identity = MionIdentity::try_from((
 			Ipv4Addr::LOCALHOST,
 			Bytes::from(Vec::from(OFF_ANNOUNCEMENT)),
@@ -1024,6 +1190,100 @@ mod unit_tests {
 		);
 		assert_eq!(on_identity.detailed_boot_type(), Some(MIONBootType::PCFS));
 		assert_eq!(on_identity.detailed_is_cafe_on(), Some(true));
+
+		assert_eq!(
+			Bytes::from(&off_identity),
+			Bytes::from(Vec::from(OFF_ANNOUNCEMENT)),
+			"Detailed identity did not serialize back into the same packet!",
+		);
+		assert_eq!(
+			Bytes::from(&on_identity),
+			Bytes::from(Vec::from(ON_ANNOUNCEMENT)),
+			"Detailed identity did not serialize back into the same packet!",
+		);
+
+		let unknown_data = on_identity
+			.detailed_unknown_data()
+			.expect("Detailed identity did not have unknown data?");
+		assert_eq!(
+			unknown_data
+				.iter()
+				.map(|(range, _)| range.clone())
+				.collect::<Vec<_>>(),
+			Vec::from(DETAILED_UNKNOWN_RANGES),
+		);
+		assert_eq!(
+			unknown_data
+				.iter()
+				.map(|(_, data)| data.len())
+				.sum::<usize>(),
+			DETAILED_DATA_SIZE - 6,
+			"Unknown data should cover everything except the sdk version, boot type, and power status!",
+		);
+		assert!(unknown_data[0].1.iter().all(|byte| *byte == 0));
+		assert_eq!(unknown_data[1].1, Bytes::from_static(&[0x01]));
+		assert!(unknown_data[2].1.iter().all(|byte| *byte == 0));
+	}
+
+	#[test]
+	pub fn mion_boot_type_conversions() {
+		for (raw, boot_type, displayed) in [
+			(0x1, MIONBootType::NAND, "NAND"),
+			(0x2, MIONBootType::PCFS, "PCFS"),
+			(0x3, MIONBootType::DUAL, "DUAL"),
+			(0x4, MIONBootType::HDD, "HDD"),
+			(0x0, MIONBootType::Unk(0), "Unk(0)"),
+			(0xFF, MIONBootType::Unk(0xFF), "Unk(255)"),
+		] {
+			assert_eq!(MIONBootType::from(raw), boot_type);
+			assert_eq!(u8::from(boot_type), raw);
+			assert_eq!(format!("{boot_type}"), displayed);
+			assert_eq!(MIONBootType::try_from(displayed), Ok(boot_type));
+		}
+
+		assert_eq!(MIONBootType::try_from("pcfs"), Ok(MIONBootType::PCFS));
+		assert_eq!(MIONBootType::try_from(" 1 "), Ok(MIONBootType::NAND));
+		assert_eq!(MIONBootType::try_from("hdd"), Ok(MIONBootType::HDD));
+		assert_eq!(
+			MIONBootType::try_from("FLOPPY"),
+			Err(APIError::UnknownBootType("FLOPPY".to_owned())),
+		);
+	}
+
+	#[test]
+	pub fn detailed_announcement_boot_types() {
+		// The boot type is stored after the header (16 bytes), the name (17
+		// bytes), and 232 bytes of detailed data.
+		const BOOT_TYPE_OFFSET: usize = 16 + 17 + DETAILED_BOOT_TYPE_OFFSET;
+
+		// The only boot type we have a real capture of.
+		assert_eq!(OFF_ANNOUNCEMENT[BOOT_TYPE_OFFSET], 0x2);
+
+		// We don't have captures from devices in any other boot mode, so these
+		// are synthesized from the PCFS capture by swapping out the boot type
+		// byte. This only checks the byte lands in the right place, not that
+		// the raw values for these boot types are right.
+		for boot_type in [
+			MIONBootType::NAND,
+			MIONBootType::PCFS,
+			MIONBootType::DUAL,
+			MIONBootType::HDD,
+			MIONBootType::Unk(5),
+		] {
+			let mut packet = OFF_ANNOUNCEMENT;
+			packet[BOOT_TYPE_OFFSET] = u8::from(boot_type);
+			let identity =
+				MionIdentity::try_from((Ipv4Addr::LOCALHOST, Bytes::from(Vec::from(packet))))
+					.expect("Failed to parse announcement with modified boot type!");
+
+			assert_eq!(identity.detailed_boot_type(), Some(boot_type));
+			assert_eq!(identity.detailed_sdk_version(), Some("2.12.13".to_owned()));
+			assert_eq!(
+				Bytes::from(&identity),
+				Bytes::from(Vec::from(packet)),
+				"Boot type did not round trip through serialization!",
+			);
+		}
 	}
 
 	#[test]
@@ -62,7 +62,7 @@ mod unit_tests {
 
 	#[test]
 	pub fn ser_and_deser() {
-		for command_byte in vec![
+		for command_byte in [
 			MionCommandByte::Search,
 			MionCommandByte::Broadcast,
 			MionCommandByte::AnnounceYourselves,
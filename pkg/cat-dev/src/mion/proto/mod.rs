@@ -20,7 +20,8 @@
 //!   for communicating on that port under: [`crate::mion::proto::parameter`].
 //!   The official tools don't have a way of specifying this port, but it is
 //!   actually configurable in `http://<mionip>/setup.cgi`. Specifically you
-//!   can change it under "Parameter Space".
+//!   can change it under "Parameter Space", or with
+//!   [`crate::mion::cgis::set_experimental_setup`].
 
 pub mod cgis;
 pub mod control;
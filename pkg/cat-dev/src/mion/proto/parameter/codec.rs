@@ -0,0 +1,342 @@
+//! Encoders, and decoders for framing packets on the parameter port.
+//!
+//! Every packet on the parameter port starts with the same 8 byte header:
+//!
+//! - 4 bytes: [`PacketType`] (little endian `i32`).
+//! - 4 bytes: the size of the body that follows, or a negative status code
+//!   if the request failed (little endian `i32`).
+//!
+//! The parameter port is a TCP stream, so a single read may only contain part
+//! of a packet (or more than one packet!). These codecs buffer until a full
+//! packet has arrived, and can be used with [`tokio_util::codec::Framed`] to
+//! turn a [`tokio::net::TcpStream`] into a stream of packets.
+//!
+//! There are two codecs, one for each side of the connection:
+//!
+//! - [`MionParameterClientCodec`] sends requests, and receives responses.
+//! - [`MionParameterServerCodec`] receives requests, and sends responses.
+
+use crate::{
+	errors::{NetworkError, NetworkParseError},
+	mion::proto::parameter::{
+		DumpedMionParameters, MionDumpParameters, PacketType, SetMionParameters,
+		SetMionParametersResponse,
+	},
+};
+use bytes::{Bytes, BytesMut};
+use tokio_util::codec::{Decoder, Encoder};
+
+/// The size of the header on every parameter port packet.
+pub const PARAMETER_HEADER_SIZE: usize = 8;
+/// The largest body any parameter port packet we know about has (the full
+/// parameter space).
+pub const MAX_PARAMETER_BODY_SIZE: usize = 512;
+
+/// A response that can be received from the parameter port.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub enum MionParameterResponse {
+	/// The response to a [`MionDumpParameters`] request.
+	Dumped(DumpedMionParameters),
+	/// The response to a [`SetMionParameters`] request.
+	Set(SetMionParametersResponse),
+}
+
+/// A request that can be sent to the parameter port.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub enum MionParameterRequest {
+	/// A request to dump all of the parameters.
+	Dump(MionDumpParameters),
+	/// A request to overwrite all of the parameters.
+	Set(SetMionParameters),
+}
+
+/// The codec to use when talking to a MION's parameter port.
+///
+/// Encodes [`MionDumpParameters`], and [`SetMionParameters`], and decodes
+/// [`MionParameterResponse`]s.
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq)]
+pub struct MionParameterClientCodec;
+
+impl Decoder for MionParameterClientCodec {
+	type Item = MionParameterResponse;
+	type Error = NetworkError;
+
+	fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+		let Some((packet_type, frame)) = split_frame(src)? else {
+			return Ok(None);
+		};
+
+		Ok(Some(match packet_type {
+			PacketType::Read => {
+				MionParameterResponse::Dumped(DumpedMionParameters::try_from(frame)?)
+			}
+			PacketType::Write => {
+				MionParameterResponse::Set(SetMionParametersResponse::try_from(frame)?)
+			}
+		}))
+	}
+}
+impl Encoder<MionDumpParameters> for MionParameterClientCodec {
+	type Error = NetworkError;
+
+	fn encode(&mut self, item: MionDumpParameters, dst: &mut BytesMut) -> Result<(), Self::Error> {
+		dst.extend_from_slice(&Bytes::from(item));
+		Ok(())
+	}
+}
+impl Encoder<SetMionParameters> for MionParameterClientCodec {
+	type Error = NetworkError;
+
+	fn encode(&mut self, item: SetMionParameters, dst: &mut BytesMut) -> Result<(), Self::Error> {
+		dst.extend_from_slice(&Bytes::from(item));
+		Ok(())
+	}
+}
+
+/// The codec to use when serving a parameter port (e.g. pretending to be a
+/// MION).
+///
+/// Decodes [`MionParameterRequest`]s, and encodes [`DumpedMionParameters`],
+/// and [`SetMionParametersResponse`].
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq)]
+pub struct MionParameterServerCodec;
+
+impl Decoder for MionParameterServerCodec {
+	type Item = MionParameterRequest;
+	type Error = NetworkError;
+
+	fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+		let Some((packet_type, frame)) = split_frame(src)? else {
+			return Ok(None);
+		};
+
+		Ok(Some(match packet_type {
+			PacketType::Read => MionParameterRequest::Dump(MionDumpParameters::try_from(frame)?),
+			PacketType::Write => MionParameterRequest::Set(SetMionParameters::try_from(frame)?),
+		}))
+	}
+}
+impl Encoder<DumpedMionParameters> for MionParameterServerCodec {
+	type Error = NetworkError;
+
+	fn encode(
+		&mut self,
+		item: DumpedMionParameters,
+		dst: &mut BytesMut,
+	) -> Result<(), Self::Error> {
+		dst.extend_from_slice(&Bytes::from(item));
+		Ok(())
+	}
+}
+impl Encoder<SetMionParametersResponse> for MionParameterServerCodec {
+	type Error = NetworkError;
+
+	fn encode(
+		&mut self,
+		item: SetMionParametersResponse,
+		dst: &mut BytesMut,
+	) -> Result<(), Self::Error> {
+		dst.extend_from_slice(&Bytes::from(item));
+		Ok(())
+	}
+}
+
+/// Split a single full packet off the front of the buffer, if one has fully
+/// arrived.
+///
+/// ## Errors
+///
+/// - If the packet type is not one we know about.
+/// - If the header says the body is larger than [`MAX_PARAMETER_BODY_SIZE`].
+/// - If the header contains a negative status code (there is no body in this
+///   case, so the header is consumed before erroring).
+fn split_frame(src: &mut BytesMut) -> Result<Option<(PacketType, Bytes)>, NetworkError> {
+	if src.len() < PARAMETER_HEADER_SIZE {
+		src.reserve(PARAMETER_HEADER_SIZE - src.len());
+		return Ok(None);
+	}
+
+	let packet_type = PacketType::try_from(i32::from_le_bytes([src[0], src[1], src[2], src[3]]))?;
+	let size_or_status = i32::from_le_bytes([src[4], src[5], src[6], src[7]]);
+	let Ok(body_size) = usize::try_from(size_or_status) else {
+		_ = src.split_to(PARAMETER_HEADER_SIZE);
+		return Err(NetworkParseError::ParamsPacketErrorCode(size_or_status).into());
+	};
+	if body_size > MAX_PARAMETER_BODY_SIZE {
+		return Err(
+			NetworkParseError::ParamsFrameTooLarge(body_size, MAX_PARAMETER_BODY_SIZE).into(),
+		);
+	}
+
+	let frame_size = PARAMETER_HEADER_SIZE + body_size;
+	if src.len() < frame_size {
+		src.reserve(frame_size - src.len());
+		return Ok(None);
+	}
+
+	Ok(Some((packet_type, src.split_to(frame_size).freeze())))
+}
+
+#[cfg(test)]
+mod unit_tests {
+	use super::*;
+
+	/// Feed a packet into a decoder one byte at a time, ensuring nothing is
+	/// decoded until the very last byte arrives.
+	fn decode_byte_by_byte<DecoderTy: Decoder>(
+		decoder: &mut DecoderTy,
+		packet: &[u8],
+	) -> DecoderTy::Item
+	where
+		DecoderTy::Error: std::fmt::Debug,
+	{
+		let mut buff = BytesMut::new();
+		let (last, rest) = packet.split_last().expect("Packet cannot be empty!");
+		for byte in rest {
+			buff.extend_from_slice(&[*byte]);
+			assert!(
+				decoder
+					.decode(&mut buff)
+					.expect("Failed to decode partial packet!")
+					.is_none(),
+				"Decoded a packet before all of the bytes arrived!",
+			);
+		}
+
+		buff.extend_from_slice(&[*last]);
+		let item = decoder
+			.decode(&mut buff)
+			.expect("Failed to decode full packet!")
+			.expect("Did not decode a packet after all bytes arrived!");
+		assert!(buff.is_empty(), "Decoder left bytes behind!");
+		item
+	}
+
+	fn test_parameters() -> Bytes {
+		Bytes::from((0..=255_u8).cycle().take(512).collect::<Vec<_>>())
+	}
+
+	#[test]
+	pub fn client_reassembles_fragmented_responses() {
+		let dumped =
+			DumpedMionParameters::new(test_parameters()).expect("Failed to create dumped params!");
+		assert_eq!(
+			decode_byte_by_byte(&mut MionParameterClientCodec, &Bytes::from(dumped.clone())),
+			MionParameterResponse::Dumped(dumped),
+		);
+
+		let set_response = SetMionParametersResponse::new(0);
+		assert_eq!(
+			decode_byte_by_byte(
+				&mut MionParameterClientCodec,
+				&Bytes::from(set_response.clone())
+			),
+			MionParameterResponse::Set(set_response),
+		);
+	}
+
+	#[test]
+	pub fn server_reassembles_fragmented_requests() {
+		assert_eq!(
+			decode_byte_by_byte(
+				&mut MionParameterServerCodec,
+				&Bytes::from(MionDumpParameters::new()),
+			),
+			MionParameterRequest::Dump(MionDumpParameters::new()),
+		);
+
+		let set = SetMionParameters::new(test_parameters()).expect("Failed to create set params!");
+		assert_eq!(
+			decode_byte_by_byte(&mut MionParameterServerCodec, &Bytes::from(set.clone())),
+			MionParameterRequest::Set(set),
+		);
+	}
+
+	#[test]
+	pub fn decodes_multiple_packets_in_one_read() {
+		let mut buff = BytesMut::new();
+		buff.extend_from_slice(&Bytes::from(MionDumpParameters::new()));
+		buff.extend_from_slice(&Bytes::from(MionDumpParameters::new()));
+		// Start of a third packet that hasn't fully arrived.
+		buff.extend_from_slice(&[0x1, 0x0]);
+
+		let mut codec = MionParameterServerCodec;
+		for _ in 0..2 {
+			assert_eq!(
+				codec.decode(&mut buff).expect("Failed to decode packet!"),
+				Some(MionParameterRequest::Dump(MionDumpParameters::new())),
+			);
+		}
+		assert_eq!(
+			codec
+				.decode(&mut buff)
+				.expect("Failed to decode partial packet!"),
+			None,
+		);
+		assert_eq!(
+			buff.len(),
+			2,
+			"Partial packet should be left in the buffer!"
+		);
+	}
+
+	#[test]
+	pub fn rejects_bad_frames() {
+		// A body larger than any we know about.
+		let mut oversized = BytesMut::from(&[0x1_u8, 0x0, 0x0, 0x0, 0x01, 0x02, 0x0, 0x0][..]);
+		match MionParameterServerCodec.decode(&mut oversized) {
+			Err(NetworkError::ParseError(cause)) => {
+				assert_eq!(cause, NetworkParseError::ParamsFrameTooLarge(513, 512));
+			}
+			val => panic!("Oversized frame did not error correctly:\n\n {val:?}"),
+		}
+
+		// An error status, which has no body.
+		let mut errored = BytesMut::from(&[0x0_u8, 0x0, 0x0, 0x0, 0xFF, 0xFF, 0xFF, 0xFF][..]);
+		match MionParameterClientCodec.decode(&mut errored) {
+			Err(NetworkError::ParseError(cause)) => {
+				assert_eq!(cause, NetworkParseError::ParamsPacketErrorCode(-1));
+			}
+			val => panic!("Error status did not error correctly:\n\n {val:?}"),
+		}
+		assert!(
+			errored.is_empty(),
+			"Error status header should be consumed!"
+		);
+
+		// A packet type we don't know about, before the body has even arrived.
+		let mut unknown = BytesMut::from(&[0x11_u8, 0x0, 0x0, 0x0, 0x0, 0x2, 0x0, 0x0][..]);
+		match MionParameterClientCodec.decode(&mut unknown) {
+			Err(NetworkError::ParseError(cause)) => {
+				assert_eq!(cause, NetworkParseError::UnknownParamsPacketType(0x11));
+			}
+			val => panic!("Unknown packet type did not error correctly:\n\n {val:?}"),
+		}
+	}
+
+	#[test]
+	pub fn encodes_packets() {
+		let mut buff = BytesMut::new();
+		MionParameterClientCodec
+			.encode(MionDumpParameters::new(), &mut buff)
+			.expect("Failed to encode dump request!");
+		let set = SetMionParameters::new(test_parameters()).expect("Failed to create set params!");
+		MionParameterClientCodec
+			.encode(set.clone(), &mut buff)
+			.expect("Failed to encode set request!");
+		assert_eq!(
+			buff.len(),
+			PARAMETER_HEADER_SIZE * 2 + MAX_PARAMETER_BODY_SIZE
+		);
+
+		let mut server = MionParameterServerCodec;
+		assert_eq!(
+			server.decode(&mut buff).expect("Failed to decode dump!"),
+			Some(MionParameterRequest::Dump(MionDumpParameters::new())),
+		);
+		assert_eq!(
+			server.decode(&mut buff).expect("Failed to decode set!"),
+			Some(MionParameterRequest::Set(set)),
+		);
+	}
+}
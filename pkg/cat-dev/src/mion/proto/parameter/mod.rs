@@ -4,11 +4,15 @@
 //! of parameters. These values are usually things reachable from other parts
 //! of the MION interface, but are available through the MION too.
 
+pub mod codec;
 pub mod well_known;
 
 use crate::{
 	errors::{APIError, NetworkError, NetworkParseError},
-	mion::proto::parameter::well_known::{index_from_parameter_name, ValuableParameterDump},
+	mion::proto::parameter::well_known::{
+		definition_from_parameter_name, index_from_parameter_name, ParameterValue,
+		ValuableParameterDump,
+	},
 };
 use bytes::{BufMut, Bytes, BytesMut};
 use std::fmt::{Display, Formatter, Result as FmtResult};
@@ -129,6 +133,21 @@ pub struct DumpedMionParameters {
 }
 
 impl DumpedMionParameters {
+	/// Create a new response containing a full set of parameters.
+	///
+	/// ## Errors
+	///
+	/// - If the `parameters` argument is not exactly 512 bytes long.
+	pub fn new(parameters: Bytes) -> Result<Self, APIError> {
+		if parameters.len() != 512 {
+			return Err(APIError::MIONParameterBodyNotCorrectLength(
+				parameters.len(),
+			));
+		}
+
+		Ok(Self { parameters })
+	}
+
 	/// Get the entire set of parameters for you to mess around with.
 	#[must_use]
 	pub const fn get_raw_parameters(&self) -> &Bytes {
@@ -157,6 +176,18 @@ impl DumpedMionParameters {
 		}
 		Ok(self.parameters[index])
 	}
+
+	/// Get the value of a field from the [`well_known::PARAMETER_SCHEMA`] by
+	/// it's name, interpreting all of the bytes it spans.
+	///
+	/// ## Errors
+	///
+	/// - If the name of this field is not known.
+	pub fn get_field(&self, name: &str) -> Result<ParameterValue, APIError> {
+		definition_from_parameter_name(name)
+			.ok_or_else(|| APIError::MIONParameterNameNotKnown(name.to_owned()))?
+			.read(&self.parameters)
+	}
 }
 
 impl TryFrom<Bytes> for DumpedMionParameters {
@@ -358,6 +389,14 @@ pub struct SetMionParametersResponse {
 }
 
 impl SetMionParametersResponse {
+	/// Create a new response to a set parameters request.
+	///
+	/// A `return_code` of 0 is used to indicate success.
+	#[must_use]
+	pub const fn new(return_code: i32) -> Self {
+		Self { return_code }
+	}
+
 	/// Get the return code of this request.
 	///
 	/// 0 is used to indicate success, anything else is considered a failure.
@@ -709,6 +748,10 @@ mod unit_tests {
 			parsed_packet.get_parameter_by_name("512"),
 			Err(APIError::MIONParameterNameNotKnown("512".to_owned())),
 		);
+		assert_eq!(
+			parsed_packet.get_field("sdk-version"),
+			Ok(ParameterValue::DottedVersion(vec![0x02, 0x0C, 0x0D])),
+		);
 
 		assert_eq!(parsed_packet.get_parameter_by_index(511), Ok(0xFF));
 		assert_eq!(
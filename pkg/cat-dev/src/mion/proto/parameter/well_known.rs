@@ -3,6 +3,7 @@
 
 use crate::errors::APIError;
 use bytes::Bytes;
+use std::fmt::{Display, Formatter, Result as FmtResult};
 use valuable::{Fields, NamedField, NamedValues, StructDef, Structable, Valuable, Value, Visit};
 
 /// Ways to specify what parameter to update.
@@ -52,7 +53,411 @@ impl TryFrom<u16> for ParameterLocationSpecification {
 	}
 }
 
+/// How the bytes of a parameter should be interpreted.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum ParameterValueType {
+	/// A plain unsigned number, if it spans multiple bytes it's stored little
+	/// endian.
+	Unsigned,
+	/// Each byte is one component of a version, displayed separated by `.`
+	/// (e.g. `2.12.13`).
+	DottedVersion,
+	/// A number where specific values have a known name.
+	///
+	/// Values that aren't in the list are still allowed, they'll just be
+	/// displayed as a number.
+	Enumeration(&'static [(u32, &'static str)]),
+}
+
+/// How risky it is to write to a part of the parameter space.
+///
+/// These are ordered from least to most risky, so the safety of a byte
+/// covered by multiple fields is the most risky of them.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ParameterSafety {
+	/// Writing this is not known to cause any problems.
+	Safe,
+	/// Writing this can cause problems, but they are easy to recover from.
+	Caution,
+	/// Writing this can leave the bridge, or console in a state that needs to
+	/// be fixed by hand (e.g. unable to boot).
+	Dangerous,
+	/// This should never be written, and `cat_dev` will refuse to do so.
+	ReadOnly,
+}
+
+impl ParameterSafety {
+	/// If writing to this needs someone to explicitly opt in.
+	#[must_use]
+	pub const fn requires_force(self) -> bool {
+		matches!(self, Self::Caution | Self::Dangerous)
+	}
+}
+
+impl Display for ParameterSafety {
+	fn fmt(&self, fmt: &mut Formatter<'_>) -> FmtResult {
+		match self {
+			Self::Safe => write!(fmt, "safe"),
+			Self::Caution => write!(fmt, "caution"),
+			Self::Dangerous => write!(fmt, "dangerous"),
+			Self::ReadOnly => write!(fmt, "read-only"),
+		}
+	}
+}
+
+/// A value read out of the parameter space, interpreted using it's
+/// [`ParameterValueType`].
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub enum ParameterValue {
+	/// See [`ParameterValueType::Unsigned`].
+	Unsigned(u32),
+	/// See [`ParameterValueType::DottedVersion`].
+	DottedVersion(Vec<u8>),
+	/// See [`ParameterValueType::Enumeration`], the name is present if the
+	/// value is one we know about.
+	Enumeration(u32, Option<&'static str>),
+}
+
+impl Display for ParameterValue {
+	fn fmt(&self, fmt: &mut Formatter<'_>) -> FmtResult {
+		match self {
+			Self::Unsigned(value) | Self::Enumeration(value, None) => write!(fmt, "{value}"),
+			Self::DottedVersion(components) => write!(
+				fmt,
+				"{}",
+				components
+					.iter()
+					.map(ToString::to_string)
+					.collect::<Vec<_>>()
+					.join("."),
+			),
+			Self::Enumeration(_, Some(name)) => write!(fmt, "{name}"),
+		}
+	}
+}
+
+/// The definition of a named field within the 512 byte parameter space.
+///
+/// All the fields we know about live in [`PARAMETER_SCHEMA`]. Fields are
+/// allowed to overlap, for example `sdk-version` covers the same bytes as
+/// `sdk-major`, `sdk-minor`, and `sdk-misc`.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct ParameterDefinition {
+	/// The canonical name of this field.
+	name: &'static str,
+	/// Other names that can be used to refer to this field.
+	aliases: &'static [&'static str],
+	/// The name of this field when logged with [`valuable`].
+	field_name: &'static str,
+	/// The index of the first byte of this field.
+	index: usize,
+	/// How many bytes this field spans.
+	width: usize,
+	/// How to interpret the bytes of this field.
+	value_type: ParameterValueType,
+	/// A human readable description of this field.
+	description: &'static str,
+	/// How risky it is to write to this field.
+	safety: ParameterSafety,
+	/// What is known to happen when this field is written, if anything.
+	consequences: Option<&'static str>,
+}
+
+impl ParameterDefinition {
+	/// The canonical name of this field.
+	#[must_use]
+	pub const fn name(&self) -> &'static str {
+		self.name
+	}
+
+	/// Other names that can be used to refer to this field.
+	#[must_use]
+	pub const fn aliases(&self) -> &'static [&'static str] {
+		self.aliases
+	}
+
+	/// The index of the first byte of this field.
+	#[must_use]
+	pub const fn index(&self) -> usize {
+		self.index
+	}
+
+	/// How many bytes this field spans.
+	#[must_use]
+	pub const fn width(&self) -> usize {
+		self.width
+	}
+
+	/// How the bytes of this field should be interpreted.
+	#[must_use]
+	pub const fn value_type(&self) -> ParameterValueType {
+		self.value_type
+	}
+
+	/// A human readable description of this field.
+	#[must_use]
+	pub const fn description(&self) -> &'static str {
+		self.description
+	}
+
+	/// How risky it is to write to this field.
+	#[must_use]
+	pub const fn safety(&self) -> ParameterSafety {
+		self.safety
+	}
+
+	/// What is known to happen when this field is written, if anything.
+	#[must_use]
+	pub const fn consequences(&self) -> Option<&'static str> {
+		self.consequences
+	}
+
+	/// If this field covers a particular index in the parameter space.
+	#[must_use]
+	pub const fn covers_index(&self, index: usize) -> bool {
+		index >= self.index && index < self.index + self.width
+	}
+
+	/// If this field can be referred to by this name.
+	///
+	/// Names are compared ignoring case, and treating `-`, `_`, and ` ` as the
+	/// same character.
+	#[must_use]
+	pub fn is_named(&self, name: &str) -> bool {
+		let normalized = normalize_name(name);
+		std::iter::once(&self.name)
+			.chain(self.aliases.iter())
+			.any(|potential| normalize_name(potential) == normalized)
+	}
+
+	/// Read the value of this field out of a full parameter space.
+	///
+	/// ## Errors
+	///
+	/// - If the parameter space is not exactly 512 bytes long.
+	pub fn read(&self, parameters: &[u8]) -> Result<ParameterValue, APIError> {
+		if parameters.len() != 512 {
+			return Err(APIError::MIONParameterBodyNotCorrectLength(
+				parameters.len(),
+			));
+		}
+		let bytes = &parameters[self.index..self.index + self.width];
+
+		Ok(match self.value_type {
+			ParameterValueType::Unsigned => ParameterValue::Unsigned(le_bytes_to_u32(bytes)),
+			ParameterValueType::DottedVersion => ParameterValue::DottedVersion(bytes.to_vec()),
+			ParameterValueType::Enumeration(values) => {
+				let value = le_bytes_to_u32(bytes);
+				ParameterValue::Enumeration(
+					value,
+					values
+						.iter()
+						.find(|(known, _)| *known == value)
+						.map(|(_, name)| *name),
+				)
+			}
+		})
+	}
+
+	/// Parse a user provided value into the bytes that should be written for
+	/// this field.
+	///
+	/// Returns `(index, byte)` for every byte this field spans, which should
+	/// all be written at the same time.
+	///
+	/// ## Errors
+	///
+	/// - If the value could not be parsed for this type of field.
+	/// - If the value does not fit in the width of this field.
+	pub fn encode(&self, value: &str) -> Result<Vec<(usize, u8)>, APIError> {
+		let trimmed = value.trim();
+		let bytes = match self.value_type {
+			ParameterValueType::Unsigned => self.encode_number(trimmed)?,
+			ParameterValueType::DottedVersion => {
+				let components = trimmed
+					.split('.')
+					.map(|component| parse_number(component).and_then(|num| u8::try_from(num).ok()))
+					.collect::<Option<Vec<u8>>>()
+					.ok_or_else(|| self.invalid_value(value))?;
+				if components.len() != self.width {
+					return Err(self.invalid_value(value));
+				}
+				components
+			}
+			ParameterValueType::Enumeration(values) => {
+				if let Some((known, _)) = values
+					.iter()
+					.find(|(_, name)| name.eq_ignore_ascii_case(trimmed))
+				{
+					known.to_le_bytes()[..self.width].to_vec()
+				} else {
+					self.encode_number(trimmed)?
+				}
+			}
+		};
+
+		Ok(bytes
+			.into_iter()
+			.enumerate()
+			.map(|(offset, byte)| (self.index + offset, byte))
+			.collect())
+	}
+
+	fn encode_number(&self, value: &str) -> Result<Vec<u8>, APIError> {
+		let number = parse_number(value).ok_or_else(|| self.invalid_value(value))?;
+		let bytes = number.to_le_bytes();
+		if bytes[self.width..].iter().any(|byte| *byte != 0) {
+			return Err(self.invalid_value(value));
+		}
+		Ok(bytes[..self.width].to_vec())
+	}
+
+	fn invalid_value(&self, value: &str) -> APIError {
+		APIError::MIONParameterValueInvalid(self.name, value.to_owned())
+	}
+}
+
+const SDK_VERSION_CONSEQUENCES: &str = "Tools use this to check the bridge matches the SDK you are using, if it doesn't match booting may fail, or tools may refuse to run.";
+
+/// Every field in the parameter space we know the meaning of.
+///
+/// There are many more bytes we don't understand yet, these will always be
+/// accessible by index.
+pub const PARAMETER_SCHEMA: [ParameterDefinition; 6] = [
+	ParameterDefinition {
+		name: "header",
+		aliases: &["reserved"],
+		field_name: "Header",
+		index: 0,
+		width: 2,
+		value_type: ParameterValueType::Unsigned,
+		description: "The first two bytes of the parameter space, we don't know what these hold yet.",
+		// Nobody has confirmed these are read-only on a real bridge. They come
+		// before every field we do know about though, so until someone works
+		// out what they are we refuse to write them rather than risk
+		// overwriting whatever the firmware keeps here.
+		safety: ParameterSafety::ReadOnly,
+		consequences: None,
+	},
+	ParameterDefinition {
+		name: "nand-mode",
+		aliases: &["nandmode"],
+		field_name: "NandMode",
+		index: 2,
+		width: 1,
+		value_type: ParameterValueType::Unsigned,
+		description: "The mode the NAND is in.",
+		safety: ParameterSafety::Dangerous,
+		consequences: Some(
+			"Changing the NAND mode can leave the console unable to boot until it is set back by hand.",
+		),
+	},
+	ParameterDefinition {
+		name: "sdk-major",
+		aliases: &["sdk-major-version", "major-version", "major"],
+		field_name: "SdkMajor",
+		index: 3,
+		width: 1,
+		value_type: ParameterValueType::Unsigned,
+		description: "The major version of the SDK installed on the bridge.",
+		safety: ParameterSafety::Caution,
+		consequences: Some(SDK_VERSION_CONSEQUENCES),
+	},
+	ParameterDefinition {
+		name: "sdk-minor",
+		aliases: &["sdk-minor-version", "minor-version", "minor"],
+		field_name: "SdkMinor",
+		index: 4,
+		width: 1,
+		value_type: ParameterValueType::Unsigned,
+		description: "The minor version of the SDK installed on the bridge.",
+		safety: ParameterSafety::Caution,
+		consequences: Some(SDK_VERSION_CONSEQUENCES),
+	},
+	ParameterDefinition {
+		name: "sdk-misc",
+		aliases: &["sdk-misc-version", "misc-version", "misc"],
+		field_name: "SdkMisc",
+		index: 5,
+		width: 1,
+		value_type: ParameterValueType::Unsigned,
+		description: "The misc (patch) version of the SDK installed on the bridge.",
+		safety: ParameterSafety::Caution,
+		consequences: Some(SDK_VERSION_CONSEQUENCES),
+	},
+	ParameterDefinition {
+		name: "sdk-version",
+		aliases: &["sdk"],
+		field_name: "SdkVersion",
+		index: 3,
+		width: 3,
+		value_type: ParameterValueType::DottedVersion,
+		description: "The full version of the SDK installed on the bridge, e.g. `2.12.13`.",
+		safety: ParameterSafety::Caution,
+		consequences: Some(SDK_VERSION_CONSEQUENCES),
+	},
+];
+
+/// Find the definition of a field in the parameter space by it's name, or
+/// one of it's aliases.
+#[must_use]
+pub fn definition_from_parameter_name(name: &str) -> Option<&'static ParameterDefinition> {
+	PARAMETER_SCHEMA
+		.iter()
+		.find(|definition| definition.is_named(name))
+}
+
+/// Get every field that covers a particular index in the parameter space.
+pub fn definitions_at_index(index: usize) -> impl Iterator<Item = &'static ParameterDefinition> {
+	PARAMETER_SCHEMA
+		.iter()
+		.filter(move |definition| definition.covers_index(index))
+}
+
+/// How risky it is to write to a particular index in the parameter space.
+///
+/// If multiple fields cover the same index, this is the most risky of them.
+/// Indexes we don't know the meaning of are considered safe, which is how
+/// they've always been treated.
+#[must_use]
+pub fn safety_of_index(index: usize) -> ParameterSafety {
+	safety_of_index_in(&PARAMETER_SCHEMA, index)
+}
+
+/// Make sure an index in the parameter space is allowed to be written at all.
+///
+/// ## Errors
+///
+/// - If the index is covered by a [`ParameterSafety::ReadOnly`] field.
+pub fn ensure_index_is_writable(index: usize) -> Result<(), APIError> {
+	ensure_index_is_writable_in(&PARAMETER_SCHEMA, index)
+}
+
+fn safety_of_index_in(schema: &[ParameterDefinition], index: usize) -> ParameterSafety {
+	schema
+		.iter()
+		.filter(|definition| definition.covers_index(index))
+		.map(ParameterDefinition::safety)
+		.max()
+		.unwrap_or(ParameterSafety::Safe)
+}
+
+fn ensure_index_is_writable_in(
+	schema: &[ParameterDefinition],
+	index: usize,
+) -> Result<(), APIError> {
+	if let Some(definition) = schema.iter().find(|definition| {
+		definition.covers_index(index) && definition.safety == ParameterSafety::ReadOnly
+	}) {
+		return Err(APIError::MIONParameterReadOnly(index, definition.name));
+	}
+
+	Ok(())
+}
+
 /// Attempt to get the index of a marater based on a name
+///
+/// For fields that span multiple bytes this is the index of the first byte.
 #[must_use]
 pub fn index_from_parameter_name(name: &str) -> Option<usize> {
 	if let Ok(number) = name.parse::<usize>() {
@@ -61,32 +466,49 @@ pub fn index_from_parameter_name(name: &str) -> Option<usize> {
 		}
 	}
 
-	match name {
-		"nand-mode" | "nand_mode" | "nandmode" | "nand mode" => Some(2),
-		"sdk-major" | "sdk_major" | "sdk major" | "sdk-major-version" | "sdk_major_version"
-		| "sdk major version" | "major-version" | "major_version" | "major version" | "major" => Some(3),
-		"sdk-minor" | "sdk_minor" | "sdk minor" | "sdk-minor-version" | "sdk_minor_version"
-		| "sdk minor version" | "minor-version" | "minor_version" | "minor version" | "minor" => Some(4),
-		"sdk-misc" | "sdk_misc" | "sdk misc" | "sdk-misc-version" | "sdk_misc_version"
-		| "sdk misc version" | "misc-version" | "misc_version" | "misc version" | "misc" => Some(5),
-		_ => None,
+	definition_from_parameter_name(name).map(ParameterDefinition::index)
+}
+
+fn normalize_name(name: &str) -> String {
+	name.trim()
+		.chars()
+		.map(|character| match character {
+			'_' | ' ' => '-',
+			other => other.to_ascii_lowercase(),
+		})
+		.collect()
+}
+
+fn parse_number(value: &str) -> Option<u32> {
+	if let Some(hex_value) = value.strip_prefix("0x") {
+		u32::from_str_radix(hex_value, 16).ok()
+	} else {
+		value.parse::<u32>().ok()
 	}
 }
 
-const PARAMETER_DUMP_FIELDS: &[NamedField<'static>] = &[
-	NamedField::new("NandMode"),
-	NamedField::new("SdkMajor"),
-	NamedField::new("SdkMinor"),
-	NamedField::new("SdkMisc"),
-	NamedField::new("UnknownParameters"),
-];
-const KNOWN_INDEXES: &[usize] = &[2_usize, 3_usize, 4_usize, 5_usize];
+fn le_bytes_to_u32(bytes: &[u8]) -> u32 {
+	let mut buff = [0_u8; 4];
+	buff[..bytes.len()].copy_from_slice(bytes);
+	u32::from_le_bytes(buff)
+}
+
+const PARAMETER_DUMP_FIELDS: [NamedField<'static>; PARAMETER_SCHEMA.len() + 1] = dump_fields();
+const fn dump_fields() -> [NamedField<'static>; PARAMETER_SCHEMA.len() + 1] {
+	let mut fields = [NamedField::new("UnknownParameters"); PARAMETER_SCHEMA.len() + 1];
+	let mut idx = 0;
+	while idx < PARAMETER_SCHEMA.len() {
+		fields[idx] = NamedField::new(PARAMETER_SCHEMA[idx].field_name);
+		idx += 1;
+	}
+	fields
+}
 pub struct ValuableParameterDump<'value>(pub &'value Bytes);
 impl<'value> Structable for ValuableParameterDump<'value> {
 	fn definition(&self) -> StructDef<'_> {
 		StructDef::new_static(
 			"ValuableParameterDump",
-			Fields::Named(PARAMETER_DUMP_FIELDS),
+			Fields::Named(&PARAMETER_DUMP_FIELDS),
 		)
 	}
 }
@@ -96,24 +518,47 @@ impl<'value> Valuable for ValuableParameterDump<'value> {
 	}
 
 	fn visit(&self, visitor: &mut dyn Visit) {
-		let mut unknown_params = Vec::with_capacity(self.0.len() - KNOWN_INDEXES.len());
+		let mut unknown_params = Vec::with_capacity(self.0.len());
 		for (idx, byte) in self.0.iter().enumerate() {
-			if KNOWN_INDEXES.contains(&idx) {
+			if PARAMETER_SCHEMA.iter().any(|definition| {
+				(definition.index..definition.index + definition.width).contains(&idx)
+			}) {
 				continue;
 			}
 			unknown_params.push((idx, *byte));
 		}
 
-		visitor.visit_named_fields(&NamedValues::new(
-			PARAMETER_DUMP_FIELDS,
-			&[
-				Valuable::as_value(&self.0[KNOWN_INDEXES[0]]),
-				Valuable::as_value(&self.0[KNOWN_INDEXES[1]]),
-				Valuable::as_value(&self.0[KNOWN_INDEXES[2]]),
-				Valuable::as_value(&self.0[KNOWN_INDEXES[3]]),
-				Valuable::as_value(&unknown_params),
-			],
-		));
+		let known_values = PARAMETER_SCHEMA
+			.iter()
+			.map(|definition| definition.read(self.0))
+			.collect::<Result<Vec<_>, _>>()
+			.unwrap_or_default();
+		let displayed_values = known_values
+			.iter()
+			.map(ToString::to_string)
+			.collect::<Vec<_>>();
+		let mut values = known_values
+			.iter()
+			.zip(displayed_values.iter())
+			.zip(PARAMETER_SCHEMA.iter())
+			.map(|((value, displayed), definition)| match value {
+				// Single byte fields have always been logged as a `u8`.
+				ParameterValue::Unsigned(_) | ParameterValue::Enumeration(_, None)
+					if definition.width == 1 =>
+				{
+					Value::U8(self.0[definition.index])
+				}
+				ParameterValue::Unsigned(number) | ParameterValue::Enumeration(number, None) => {
+					Value::U32(*number)
+				}
+				ParameterValue::DottedVersion(_) | ParameterValue::Enumeration(_, Some(_)) => {
+					Value::String(displayed)
+				}
+			})
+			.collect::<Vec<_>>();
+		values.push(Valuable::as_value(&unknown_params));
+
+		visitor.visit_named_fields(&NamedValues::new(&PARAMETER_DUMP_FIELDS, &values));
 	}
 }
 
@@ -123,6 +568,41 @@ mod unit_tests {
 	use bytes::BytesMut;
 	use valuable::Visit;
 
+	#[test]
+	pub fn can_classify_parameter_safety() {
+		assert_eq!(safety_of_index(2), ParameterSafety::Dangerous);
+		assert_eq!(safety_of_index(3), ParameterSafety::Caution);
+		assert_eq!(safety_of_index(5), ParameterSafety::Caution);
+		assert_eq!(safety_of_index(100), ParameterSafety::Safe);
+		assert!(ensure_index_is_writable(2).is_ok());
+		assert_eq!(safety_of_index(1), ParameterSafety::ReadOnly);
+		assert_eq!(
+			ensure_index_is_writable(0),
+			Err(APIError::MIONParameterReadOnly(0, "header")),
+		);
+
+		let read_only_schema = [ParameterDefinition {
+			name: "test-read-only",
+			aliases: &[],
+			field_name: "TestReadOnly",
+			index: 10,
+			width: 2,
+			value_type: ParameterValueType::Unsigned,
+			description: "",
+			safety: ParameterSafety::ReadOnly,
+			consequences: None,
+		}];
+		assert_eq!(
+			safety_of_index_in(&read_only_schema, 11),
+			ParameterSafety::ReadOnly,
+		);
+		assert_eq!(
+			ensure_index_is_writable_in(&read_only_schema, 11),
+			Err(APIError::MIONParameterReadOnly(11, "test-read-only")),
+		);
+		assert!(ensure_index_is_writable_in(&read_only_schema, 12).is_ok());
+	}
+
 	#[test]
 	pub fn can_map_parameter_name_to_index() {
 		for (name, expected_index) in vec![
@@ -189,12 +669,100 @@ mod unit_tests {
 		}
 	}
 
+	#[test]
+	pub fn schema_is_well_formed() {
+		for (idx, definition) in PARAMETER_SCHEMA.iter().enumerate() {
+			assert!(
+				definition.index() + definition.width() <= 512,
+				"Parameter: {} extends past the end of the parameter space!",
+				definition.name(),
+			);
+			if definition.value_type() != ParameterValueType::DottedVersion {
+				assert!(
+					definition.width() <= 4,
+					"Numeric parameter: {} is wider than a u32!",
+					definition.name(),
+				);
+			}
+			for other in &PARAMETER_SCHEMA[idx + 1..] {
+				assert_ne!(definition.field_name, other.field_name);
+				for name in std::iter::once(&definition.name()).chain(definition.aliases()) {
+					assert!(
+						!other.is_named(name),
+						"Parameter name: {name} is ambiguous between: {}, and: {}",
+						definition.name(),
+						other.name(),
+					);
+				}
+			}
+			assert_eq!(PARAMETER_DUMP_FIELDS[idx].name(), definition.field_name);
+		}
+	}
+
+	#[test]
+	pub fn can_read_and_encode_fields() {
+		let mut parameters = BytesMut::zeroed(512);
+		parameters[3] = 2;
+		parameters[4] = 12;
+		parameters[5] = 13;
+
+		let version = definition_from_parameter_name("SDK Version").expect("Unknown field!");
+		assert_eq!(
+			version.read(&parameters),
+			Ok(ParameterValue::DottedVersion(vec![2, 12, 13])),
+		);
+		assert_eq!(
+			format!("{}", version.read(&parameters).expect("Failed to read!")),
+			"2.12.13",
+		);
+		// Multi-byte fields always write every byte they span.
+		assert_eq!(
+			version.encode("2.11.0x10"),
+			Ok(vec![(3, 2), (4, 11), (5, 16)]),
+		);
+		assert!(version.encode("2.11").is_err());
+		assert!(version.encode("2.11.256").is_err());
+		assert!(version.read(&parameters[..511]).is_err());
+
+		let major = definition_from_parameter_name("major").expect("Unknown field!");
+		assert_eq!(major.read(&parameters), Ok(ParameterValue::Unsigned(2)));
+		assert_eq!(major.encode(" 255 "), Ok(vec![(3, 255)]));
+		assert!(major.encode("256").is_err());
+		assert!(major.encode("two").is_err());
+
+		let wide_enum = ParameterDefinition {
+			name: "test",
+			aliases: &[],
+			field_name: "Test",
+			index: 510,
+			width: 2,
+			value_type: ParameterValueType::Enumeration(&[(1, "One"), (0x0102, "Big")]),
+			description: "",
+			safety: ParameterSafety::Safe,
+			consequences: None,
+		};
+		assert_eq!(wide_enum.encode("big"), Ok(vec![(510, 0x02), (511, 0x01)]));
+		assert_eq!(wide_enum.encode("3"), Ok(vec![(510, 3), (511, 0)]));
+		assert!(wide_enum.encode("0x10000").is_err());
+		parameters[510] = 0x02;
+		parameters[511] = 0x01;
+		assert_eq!(
+			wide_enum.read(&parameters),
+			Ok(ParameterValue::Enumeration(0x0102, Some("Big"))),
+		);
+		parameters[511] = 0x00;
+		assert_eq!(
+			format!("{}", wide_enum.read(&parameters).expect("Failed to read!")),
+			"2",
+		);
+	}
+
 	#[test]
 	pub fn properly_parses_name_fields() {
 		struct AssertableVisitor;
 		impl Visit for AssertableVisitor {
 			fn visit_named_fields(&mut self, named_values: &NamedValues<'_>) {
-				for name in PARAMETER_DUMP_FIELDS {
+				for name in &PARAMETER_DUMP_FIELDS {
 					assert!(
 						named_values.get_by_name(name.name()).is_some(),
 						"Parameter visitor did not pass a visit that had a required named field!"
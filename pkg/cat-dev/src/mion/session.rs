@@ -0,0 +1,469 @@
+//! Who is currently using a MION, and taking control of one.
+//!
+//! ***Unverified:** we've never seen what `SessionManagerUtil` actually sends,
+//! the ownership model below is our best guess, and is only known to match
+//! what our emulator does. The only part we've seen from the official tools is
+//! the `host` sent along with a power on. How a real MION reports its owner
+//! through `get_info`, and if powering off clears it (the `power_off`
+//! operation has never been seen either) is made up, so [`release`], and
+//! [`force_take`] may not work on a real bridge.*
+//!
+//! This is our equivalent of `SessionManagerUtil`. A MION only tracks a
+//! single "owner", which is the `host` that was sent along with the last
+//! power on through `control.cgi`. While a MION is owned, any other host that
+//! tries to control power gets told the MION is in use, and powering off
+//! clears the owner again. So on a MION:
+//!
+//! - [`claim`] powers on cafe as this host, which the MION refuses if
+//!   anybody else owns it.
+//! - [`release`] powers off cafe as this host, if this host owns it.
+//! - [`force_take`] powers off cafe as whoever currently owns it, and then
+//!   claims it. The MION trusts whatever `host` it's sent, so this always
+//!   works, but will pull the device out from under someone else.
+//!
+//! *note: there is no way to own a MION without cafe being powered on, so
+//! claiming a bridge boots it from whatever it was last set to boot from.*
+
+use crate::{
+	errors::{CatBridgeError, NetworkError},
+	mion::{
+		cgis::MionHttpClient,
+		proto::cgis::{PowerOffRequest, PowerOnRequest},
+	},
+};
+use hyper::client::{connect::Connect, Client};
+use local_ip_address::{local_ip, Error as LocalIpError};
+use std::{
+	fmt::{Display, Formatter, Result as FmtResult},
+	net::{IpAddr, Ipv4Addr},
+};
+use tracing::warn;
+
+/// Who currently owns a MION.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum SessionOwner {
+	/// Nobody owns the MION, anyone can claim it.
+	Nobody,
+	/// The host we asked as owns the MION.
+	ThisHost(Ipv4Addr),
+	/// Another host owns the MION.
+	OtherHost(Ipv4Addr),
+}
+
+impl SessionOwner {
+	/// The host that owns the MION, if any does.
+	#[must_use]
+	pub const fn host(&self) -> Option<Ipv4Addr> {
+		match self {
+			Self::Nobody => None,
+			Self::ThisHost(host) | Self::OtherHost(host) => Some(*host),
+		}
+	}
+}
+
+impl Display for SessionOwner {
+	fn fmt(&self, fmt: &mut Formatter<'_>) -> FmtResult {
+		match self {
+			Self::Nobody => write!(fmt, "nobody"),
+			Self::ThisHost(host) => write!(fmt, "{host} (this host)"),
+			Self::OtherHost(host) => write!(fmt, "{host}"),
+		}
+	}
+}
+
+/// Get who currently owns a MION.
+///
+/// `host` is who we are, if not set this is the local ip of this machine.
+///
+/// **Unverified**: this is based off of the `host` field `get_info` reports,
+/// which is a guess, see [`crate::mion::proto::cgis::MionInfo`].
+///
+/// ## Errors
+///
+/// - If we cannot figure out the local ip of this machine.
+/// - If we cannot make the HTTP request, or parse the HTML response.
+pub async fn get_owner(
+	mion_ip: Ipv4Addr,
+	host: Option<Ipv4Addr>,
+) -> Result<SessionOwner, CatBridgeError> {
+	MionHttpClient::new().get_owner(mion_ip, host).await
+}
+
+/// Get who currently owns a MION, but with an already existing HTTP client.
+///
+/// ## Errors
+///
+/// See [`get_owner`].
+pub async fn get_owner_with_raw_client<ClientConnectorTy>(
+	client: &Client<ClientConnectorTy>,
+	mion_ip: Ipv4Addr,
+	host: Option<Ipv4Addr>,
+) -> Result<SessionOwner, CatBridgeError>
+where
+	ClientConnectorTy: Clone + Connect + Send + Sync + 'static,
+{
+	MionHttpClient::from_raw_client(client.clone())
+		.get_owner(mion_ip, host)
+		.await
+}
+
+/// Claim a MION for this host, claiming a MION this host already owns powers
+/// it on again as this host.
+///
+/// ## Errors
+///
+/// - If we cannot figure out the local ip of this machine.
+/// - If we cannot make the HTTP request, or parse the HTML response.
+/// - If the MION is in use by another host:
+///   [`NetworkError::MionInUseByAnotherHost`].
+pub async fn claim(mion_ip: Ipv4Addr, host: Option<Ipv4Addr>) -> Result<(), CatBridgeError> {
+	MionHttpClient::new().claim(mion_ip, host).await
+}
+
+/// Claim a MION for this host, but with an already existing HTTP client.
+///
+/// ## Errors
+///
+/// See [`claim`].
+pub async fn claim_with_raw_client<ClientConnectorTy>(
+	client: &Client<ClientConnectorTy>,
+	mion_ip: Ipv4Addr,
+	host: Option<Ipv4Addr>,
+) -> Result<(), CatBridgeError>
+where
+	ClientConnectorTy: Clone + Connect + Send + Sync + 'static,
+{
+	MionHttpClient::from_raw_client(client.clone())
+		.claim(mion_ip, host)
+		.await
+}
+
+/// Release a MION this host owns, releasing a MION nobody owns does nothing.
+///
+/// ## Errors
+///
+/// - If we cannot figure out the local ip of this machine.
+/// - If we cannot make the HTTP request, or parse the HTML response.
+/// - If the MION is in use by another host:
+///   [`NetworkError::MionInUseByAnotherHost`].
+pub async fn release(mion_ip: Ipv4Addr, host: Option<Ipv4Addr>) -> Result<(), CatBridgeError> {
+	MionHttpClient::new().release(mion_ip, host).await
+}
+
+/// Release a MION this host owns, but with an already existing HTTP client.
+///
+/// ## Errors
+///
+/// See [`release`].
+pub async fn release_with_raw_client<ClientConnectorTy>(
+	client: &Client<ClientConnectorTy>,
+	mion_ip: Ipv4Addr,
+	host: Option<Ipv4Addr>,
+) -> Result<(), CatBridgeError>
+where
+	ClientConnectorTy: Clone + Connect + Send + Sync + 'static,
+{
+	MionHttpClient::from_raw_client(client.clone())
+		.release(mion_ip, host)
+		.await
+}
+
+/// Take a MION for this host, even if another host currently owns it.
+///
+/// Returns the host it was taken from, if it was taken from another host.
+/// This powers off cafe out from under whoever is using it, so should only
+/// be used when you know the other host is done with it.
+///
+/// **This impersonates the other host.** The power off is sent with the
+/// `host` of whoever currently owns the MION, not this host, so as far as
+/// the MION can tell the owner powered off cafe themselves. Nothing on the
+/// MION records that it was taken by someone else, only the warning we log.
+///
+/// **Unverified**, see the module documentation.
+///
+/// ## Errors
+///
+/// - If we cannot figure out the local ip of this machine.
+/// - If we cannot make the HTTP request, or parse the HTML response.
+/// - If the MION reports powering off, or on failed:
+///   [`NetworkError::ControlOperationFailed`].
+pub async fn force_take(
+	mion_ip: Ipv4Addr,
+	host: Option<Ipv4Addr>,
+) -> Result<Option<Ipv4Addr>, CatBridgeError> {
+	MionHttpClient::new().force_take(mion_ip, host).await
+}
+
+/// Take a MION for this host, even if another host currently owns it, but
+/// with an already existing HTTP client.
+///
+/// ## Errors
+///
+/// See [`force_take`].
+pub async fn force_take_with_raw_client<ClientConnectorTy>(
+	client: &Client<ClientConnectorTy>,
+	mion_ip: Ipv4Addr,
+	host: Option<Ipv4Addr>,
+) -> Result<Option<Ipv4Addr>, CatBridgeError>
+where
+	ClientConnectorTy: Clone + Connect + Send + Sync + 'static,
+{
+	MionHttpClient::from_raw_client(client.clone())
+		.force_take(mion_ip, host)
+		.await
+}
+
+impl<ClientConnectorTy> MionHttpClient<ClientConnectorTy>
+where
+	ClientConnectorTy: Clone + Connect + Send + Sync + 'static,
+{
+	/// Get who currently owns a MION with this client.
+	///
+	/// ## Errors
+	///
+	/// See [`get_owner`].
+	pub async fn get_owner(
+		&self,
+		mion_ip: Ipv4Addr,
+		host: Option<Ipv4Addr>,
+	) -> Result<SessionOwner, CatBridgeError> {
+		let this_host = this_host(host)?;
+		// Nothing we have seen depends on the name, so we do not send one.
+		Ok(match self.get_mion_info(mion_ip, "").await?.owner() {
+			None => SessionOwner::Nobody,
+			Some(owner) if owner == this_host => SessionOwner::ThisHost(owner),
+			Some(owner) => SessionOwner::OtherHost(owner),
+		})
+	}
+
+	/// Claim a MION for this host with this client.
+	///
+	/// ## Errors
+	///
+	/// See [`claim`].
+	pub async fn claim(
+		&self,
+		mion_ip: Ipv4Addr,
+		host: Option<Ipv4Addr>,
+	) -> Result<(), CatBridgeError> {
+		let this_host = this_host(host)?;
+		// Only the MION knows for sure who owns it, anything we read before
+		// powering on may already be out of date. So always power on, and let
+		// the MION refuse if another host got to it first.
+		match self
+			.power_on(mion_ip, &PowerOnRequest::new().with_host(this_host))
+			.await
+		{
+			Ok(_) => Ok(()),
+			Err(CatBridgeError::NetworkError(NetworkError::MionInUseByAnotherHost(owner))) => {
+				// A refusal doesn't always say who the MION is in use by, so look
+				// it up to give a more useful error.
+				let owner = match self.get_owner(mion_ip, Some(this_host)).await {
+					Ok(SessionOwner::OtherHost(current)) => format!("{current}"),
+					_ => owner,
+				};
+				Err(NetworkError::MionInUseByAnotherHost(owner).into())
+			}
+			Err(cause) => Err(cause),
+		}
+	}
+
+	/// Release a MION this host owns with this client.
+	///
+	/// ## Errors
+	///
+	/// See [`release`].
+	pub async fn release(
+		&self,
+		mion_ip: Ipv4Addr,
+		host: Option<Ipv4Addr>,
+	) -> Result<(), CatBridgeError> {
+		let this_host = this_host(host)?;
+		match self.get_owner(mion_ip, Some(this_host)).await? {
+			SessionOwner::Nobody => Ok(()),
+			SessionOwner::OtherHost(owner) => {
+				Err(NetworkError::MionInUseByAnotherHost(format!("{owner}")).into())
+			}
+			SessionOwner::ThisHost(_) => self
+				.power_off(mion_ip, &PowerOffRequest::new().with_host(this_host))
+				.await
+				.map(|_| ()),
+		}
+	}
+
+	/// Take a MION for this host, even if another host owns it, with this
+	/// client.
+	///
+	/// ## Errors
+	///
+	/// See [`force_take`].
+	pub async fn force_take(
+		&self,
+		mion_ip: Ipv4Addr,
+		host: Option<Ipv4Addr>,
+	) -> Result<Option<Ipv4Addr>, CatBridgeError> {
+		let this_host = this_host(host)?;
+		let previous_owner = match self.get_owner(mion_ip, Some(this_host)).await? {
+			SessionOwner::ThisHost(_) => return Ok(None),
+			SessionOwner::Nobody => None,
+			SessionOwner::OtherHost(owner) => {
+				warn!(
+					bridge.ip = %mion_ip,
+					bridge.owner = %owner,
+					"Forcibly taking a MION from another host, it's device is being powered off!",
+				);
+				self.power_off(mion_ip, &PowerOffRequest::new().with_host(owner))
+					.await?;
+				Some(owner)
+			}
+		};
+
+		self.power_on(mion_ip, &PowerOnRequest::new().with_host(this_host))
+			.await?;
+		Ok(previous_owner)
+	}
+}
+
+/// Use the host the caller asked for, or the local ip of this machine.
+fn this_host(host: Option<Ipv4Addr>) -> Result<Ipv4Addr, NetworkError> {
+	if let Some(host) = host {
+		return Ok(host);
+	}
+
+	match local_ip().map_err(NetworkError::LocalIpError)? {
+		IpAddr::V4(host) => Ok(host),
+		IpAddr::V6(_) => Err(NetworkError::LocalIpError(
+			LocalIpError::LocalIpAddressNotFound,
+		)),
+	}
+}
+
+#[cfg(test)]
+mod unit_tests {
+	use super::*;
+	use crate::mion::{emulator::EmulatedMion, proto::control::MionIdentity};
+	use mac_address::MacAddress;
+
+	const FIRST_HOST: Ipv4Addr = Ipv4Addr::new(192, 168, 7, 10);
+	const SECOND_HOST: Ipv4Addr = Ipv4Addr::new(192, 168, 7, 20);
+
+	async fn emulator() -> EmulatedMion {
+		EmulatedMion::spawn(
+			MionIdentity::new(
+				None,
+				[0, 14, 80, 1],
+				[0x71, 0x20, 0x05, 0x13],
+				Ipv4Addr::LOCALHOST,
+				MacAddress::new([0x00, 0x25, 0x5c, 0xba, 0x5a, 0x00]),
+				"00-25-5C-BA-5A-00".to_owned(),
+			)
+			.expect("Failed to create identity!"),
+		)
+		.await
+		.expect("Failed to spawn emulated MION!")
+	}
+
+	#[tokio::test]
+	pub async fn can_claim_and_release() {
+		let mion = emulator().await;
+		let client = MionHttpClient::from_raw_client(mion.http_client());
+
+		assert_eq!(
+			client
+				.get_owner(Ipv4Addr::LOCALHOST, Some(FIRST_HOST))
+				.await
+				.expect("Failed to get owner!"),
+			SessionOwner::Nobody,
+		);
+		for _ in 0..2 {
+			client
+				.claim(Ipv4Addr::LOCALHOST, Some(FIRST_HOST))
+				.await
+				.expect("Failed to claim MION!");
+		}
+		assert_eq!(mion.owner(), Some(FIRST_HOST));
+		assert!(mion.is_powered_on());
+		assert_eq!(
+			client
+				.get_owner(Ipv4Addr::LOCALHOST, Some(FIRST_HOST))
+				.await
+				.expect("Failed to get owner!"),
+			SessionOwner::ThisHost(FIRST_HOST),
+		);
+		assert_eq!(
+			client
+				.get_owner(Ipv4Addr::LOCALHOST, Some(SECOND_HOST))
+				.await
+				.expect("Failed to get owner!"),
+			SessionOwner::OtherHost(FIRST_HOST),
+		);
+
+		for _ in 0..2 {
+			client
+				.release(Ipv4Addr::LOCALHOST, Some(FIRST_HOST))
+				.await
+				.expect("Failed to release MION!");
+		}
+		assert_eq!(mion.owner(), None);
+		assert!(!mion.is_powered_on());
+	}
+
+	#[tokio::test]
+	pub async fn refuses_to_touch_another_hosts_session() {
+		let mion = emulator().await;
+		mion.set_owner(Some(FIRST_HOST));
+		mion.set_powered_on(true);
+		let client = MionHttpClient::from_raw_client(mion.http_client());
+
+		for result in [
+			client.claim(Ipv4Addr::LOCALHOST, Some(SECOND_HOST)).await,
+			client.release(Ipv4Addr::LOCALHOST, Some(SECOND_HOST)).await,
+		] {
+			match result {
+				Err(CatBridgeError::NetworkError(NetworkError::MionInUseByAnotherHost(host))) => {
+					assert_eq!(host, format!("{FIRST_HOST}"));
+				}
+				val => panic!("Touching another hosts session did not fail correctly:\n\n {val:?}"),
+			}
+		}
+		assert_eq!(mion.owner(), Some(FIRST_HOST));
+		assert!(mion.is_powered_on());
+	}
+
+	#[tokio::test]
+	pub async fn can_force_take() {
+		let mion = emulator().await;
+		mion.set_owner(Some(FIRST_HOST));
+		mion.set_powered_on(true);
+		let client = MionHttpClient::from_raw_client(mion.http_client());
+
+		assert_eq!(
+			client
+				.force_take(Ipv4Addr::LOCALHOST, Some(SECOND_HOST))
+				.await
+				.expect("Failed to force take MION!"),
+			Some(FIRST_HOST),
+		);
+		assert_eq!(mion.owner(), Some(SECOND_HOST));
+		assert!(mion.is_powered_on());
+
+		// Taking a MION we already own, or that nobody owns, takes it from nobody.
+		assert_eq!(
+			client
+				.force_take(Ipv4Addr::LOCALHOST, Some(SECOND_HOST))
+				.await
+				.expect("Failed to force take MION!"),
+			None,
+		);
+		mion.set_owner(None);
+		mion.set_powered_on(false);
+		assert_eq!(
+			client
+				.force_take(Ipv4Addr::LOCALHOST, Some(FIRST_HOST))
+				.await
+				.expect("Failed to force take MION!"),
+			None,
+		);
+		assert_eq!(mion.owner(), Some(FIRST_HOST));
+	}
+}